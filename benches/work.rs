@@ -0,0 +1,83 @@
+//! Benchmarks of algorithmic work, i.e., how many priorities are relabeled per insertion.
+//!
+//! Unlike wall-time benchmarks, these are deterministic, so they fail outright when the work done
+//! by a canonical workload exceeds its bound. Run with `cargo bench --features stats --bench work`.
+use order_maintenance::stats::RelabelStats;
+use order_maintenance::{list_range, tag_range, MaintainedOrd};
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+
+/// Number of insertions performed by each workload.
+const INSERTS: usize = 100_000;
+
+/// Priorities that expose the relabel statistics of their arena.
+trait Counted: MaintainedOrd {
+    fn relabel_stats(&self) -> RelabelStats;
+}
+
+impl Counted for tag_range::Priority {
+    fn relabel_stats(&self) -> RelabelStats {
+        self.relabel_stats()
+    }
+}
+
+impl Counted for list_range::Priority {
+    fn relabel_stats(&self) -> RelabelStats {
+        self.relabel_stats()
+    }
+}
+
+/// A canonical workload, which chooses where to insert given the priorities inserted so far.
+struct Workload {
+    name: &'static str,
+    next_index: fn(&mut StdRng, usize) -> usize,
+}
+
+const WORKLOADS: [Workload; 3] = [
+    Workload {
+        name: "append",
+        next_index: |_, len| len - 1,
+    },
+    Workload {
+        name: "insert-front",
+        next_index: |_, _| 0,
+    },
+    Workload {
+        name: "random",
+        next_index: |rng, len| rng.gen_range(0..len),
+    },
+];
+
+/// Run `workload`, returning the number of priorities relabeled per insertion.
+fn relabels_per_insert<P: Counted>(workload: &Workload) -> f64 {
+    let mut rng = StdRng::seed_from_u64(42);
+    let mut ps = vec![P::new()];
+    for _ in 0..INSERTS {
+        let i = (workload.next_index)(&mut rng, ps.len());
+        let p = ps[i].insert();
+        ps.push(p);
+    }
+    ps[0].relabel_stats().relabeled() as f64 / INSERTS as f64
+}
+
+/// Run every workload, checking each against its bound on relabels per insertion.
+fn check<P: Counted>(algo: &str, bounds: [f64; WORKLOADS.len()]) -> bool {
+    let mut ok = true;
+    for (workload, bound) in WORKLOADS.iter().zip(bounds) {
+        let ratio = relabels_per_insert::<P>(workload);
+        let verdict = if ratio <= bound { "ok" } else { "REGRESSED" };
+        println!(
+            "{algo:>10} {:>12}: {ratio:>8.3} relabels/insert (bound {bound}) {verdict}",
+            workload.name
+        );
+        ok &= ratio <= bound;
+    }
+    ok
+}
+
+fn main() {
+    // Bounds leave some headroom over the measured ratios, in the order of `WORKLOADS`.
+    let ok = check::<tag_range::Priority>("tag-range", [10.0, 10.0, 0.01])
+        & check::<list_range::Priority>("list-range", [0.1, 20.0, 0.01]);
+    assert!(ok, "relabels per insertion regressed beyond their bounds");
+}
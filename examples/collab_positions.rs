@@ -0,0 +1,133 @@
+//! Positions of characters in a shared document, as edited by several authors.
+//!
+//! Every character holds a priority, so an author inserts text by allocating priorities right
+//! after the character at their cursor, and deletes text by dropping them; no other character
+//! moves. Each frame, a renderer sorts the characters by a snapshot of their order, which stays
+//! consistent even if the document is edited while the frame is drawn, and shows runs of text by
+//! author. Run with `cargo run --example collab_positions`.
+use order_maintenance::frozen::ReadSnapshot;
+use order_maintenance::id::Identified;
+use order_maintenance::tag_range::Priority;
+use order_maintenance::types::Rank;
+use order_maintenance::MaintainedOrd;
+
+/// A character of the document.
+struct Char {
+    ch: char,
+    author: &'static str,
+    position: Priority,
+}
+
+/// A document, as a set of characters in no particular order.
+struct Document {
+    /// A priority before every character, which a cursor at the start of the document is at.
+    start: Priority,
+    chars: Vec<Char>,
+}
+
+impl Document {
+    fn new() -> Self {
+        Self {
+            start: Priority::new(),
+            chars: Vec::new(),
+        }
+    }
+
+    /// Type `text` as `author` after the character at `cursor`; returns the position of the last
+    /// character typed, which the cursor moves to.
+    fn type_after(&mut self, cursor: &Priority, author: &'static str, text: &str) -> Priority {
+        let mut at = cursor.clone();
+        for ch in text.chars() {
+            at = at.insert();
+            self.chars.push(Char {
+                ch,
+                author,
+                position: at.clone(),
+            });
+        }
+        at
+    }
+
+    /// Delete the characters from `from` to `to`, inclusive.
+    fn delete(&mut self, from: &Priority, to: &Priority) {
+        self.chars
+            .retain(|c| c.position < *from || *to < c.position);
+    }
+
+    /// The characters of the document, in order.
+    fn ordered(&self) -> Vec<&Char> {
+        let positions: Vec<Priority> = self.chars.iter().map(|c| c.position.clone()).collect();
+        Priority::order_indices(&positions)
+            .into_iter()
+            .map(|i| &self.chars[i])
+            .collect()
+    }
+
+    /// The positions of the first and last characters of the first occurrence of `text`.
+    fn find(&self, text: &str) -> Option<(Priority, Priority)> {
+        let ordered = self.ordered();
+        let chars: Vec<char> = text.chars().collect();
+        let start = ordered
+            .windows(chars.len())
+            .position(|w| w.iter().zip(&chars).all(|(c, ch)| c.ch == *ch))?;
+        let end = start + chars.len() - 1;
+        Some((
+            ordered[start].position.clone(),
+            ordered[end].position.clone(),
+        ))
+    }
+}
+
+/// Draw one frame of the document from `snapshot`: its text, and who wrote which runs of it.
+fn render(doc: &Document, snapshot: &ReadSnapshot) {
+    let mut frame: Vec<(Rank, &Char)> = doc
+        .chars
+        .iter()
+        .filter_map(|c| {
+            let frozen = snapshot.get(c.position.id())?;
+            Some((snapshot.order().rank(frozen)?, c))
+        })
+        .collect();
+    frame.sort_by_key(|(rank, _)| *rank);
+
+    let text: String = frame.iter().map(|(_, c)| c.ch).collect();
+    println!("{text:?}");
+    let mut runs: Vec<(&str, String)> = Vec::new();
+    for (_, c) in frame {
+        match runs.last_mut() {
+            Some((author, run)) if *author == c.author => run.push(c.ch),
+            _ => runs.push((c.author, c.ch.to_string())),
+        }
+    }
+    for (author, run) in runs {
+        println!("  {author:>5}: {run:?}");
+    }
+}
+
+fn main() {
+    let mut doc = Document::new();
+    let start = doc.start.clone();
+    let alice = doc.type_after(&start, "alice", "Hello world");
+    render(&doc, &doc.start.read_snapshot());
+
+    // Bob inserts in the middle of Alice's text, while Alice keeps typing at the end.
+    let (_, o) = doc.find("Hello").unwrap();
+    let bob = doc.type_after(&o, "bob", ",");
+    doc.type_after(&bob, "bob", " wonderful");
+    doc.type_after(&alice, "alice", "!");
+    render(&doc, &doc.start.read_snapshot());
+
+    // Bob deletes his word again, while Carol fixes up Alice's.
+    let (from, to) = doc.find(" wonderful").unwrap();
+    doc.delete(&from, &to);
+    let (_, world) = doc.find("world").unwrap();
+    doc.type_after(&world, "carol", "s");
+    render(&doc, &doc.start.read_snapshot());
+
+    // Every character is in order of its position, however it was typed.
+    let ordered = doc.ordered();
+    assert_eq!(
+        ordered.iter().map(|c| c.ch).collect::<String>(),
+        "Hello, worlds!"
+    );
+}
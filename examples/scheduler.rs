@@ -0,0 +1,123 @@
+//! A dependency scheduler, which keeps a run order of tasks as they are submitted.
+//!
+//! Each task is inserted into a tag-range order right after the latest of its dependencies, which
+//! keeps the order topological, and runs each task as early as its dependencies allow. Whether a
+//! task may run before another is then a single comparison, and the run order of any set of tasks
+//! is a sort of their labels. Run with `cargo run --example scheduler`.
+use order_maintenance::bulk::BulkLoader;
+use order_maintenance::capability::InsertFront;
+use order_maintenance::id::Identified;
+use order_maintenance::tag_range::Priority;
+use order_maintenance::MaintainedOrd;
+use std::collections::HashMap;
+
+/// A submitted task.
+struct Task {
+    name: &'static str,
+    dependencies: Vec<usize>,
+    slot: Priority,
+}
+
+/// Tasks in a topological order of their dependencies.
+struct Scheduler {
+    /// A priority before every task, after which tasks without dependencies are inserted.
+    start: Priority,
+    tasks: Vec<Task>,
+}
+
+impl Scheduler {
+    /// A scheduler that starts with `tasks` without dependencies, loaded in order of their keys.
+    fn with_initial(tasks: impl IntoIterator<Item = (u32, &'static str)>) -> Self {
+        let mut loader = BulkLoader::new();
+        loader.extend(tasks);
+        let loaded: Vec<(u32, &'static str, Priority)> = loader.finish();
+        let start = match loaded.first() {
+            Some((_, _, first)) => first.insert_front(),
+            None => Priority::new(),
+        };
+        let tasks = loaded
+            .into_iter()
+            .map(|(_, name, slot)| Task {
+                name,
+                dependencies: Vec::new(),
+                slot,
+            })
+            .collect();
+        Self { start, tasks }
+    }
+
+    /// Submit a task that depends on the tasks with the given indices; returns its index.
+    fn submit(&mut self, name: &'static str, dependencies: &[usize]) -> usize {
+        let latest = dependencies
+            .iter()
+            .map(|&d| &self.tasks[d].slot)
+            .max_by(|a, b| a.partial_cmp(b).unwrap())
+            .unwrap_or(&self.start);
+        let slot = latest.insert();
+        self.tasks.push(Task {
+            name,
+            dependencies: dependencies.to_vec(),
+            slot,
+        });
+        self.tasks.len() - 1
+    }
+
+    /// The index of the task named `name`.
+    fn index(&self, name: &str) -> usize {
+        self.tasks.iter().position(|t| t.name == name).unwrap()
+    }
+
+    /// Whether task `a` is scheduled to run before task `b`.
+    fn runs_before(&self, a: usize, b: usize) -> bool {
+        self.tasks[a].slot < self.tasks[b].slot
+    }
+
+    /// The indices of every task, in the order they run.
+    fn run_order(&self) -> Vec<usize> {
+        let slots: Vec<Priority> = self.tasks.iter().map(|t| t.slot.clone()).collect();
+        Priority::order_indices(&slots)
+    }
+}
+
+fn main() {
+    let mut scheduler = Scheduler::with_initial([(2, "fetch sources"), (1, "install toolchain")]);
+    let fetch = scheduler.index("fetch sources");
+    let toolchain = scheduler.index("install toolchain");
+    let configure = scheduler.submit("configure", &[fetch]);
+    let build = scheduler.submit("build", &[configure, toolchain]);
+    let docs = scheduler.submit("build docs", &[configure]);
+    let test = scheduler.submit("test", &[build]);
+    let package = scheduler.submit("package", &[build, docs]);
+    let lint = scheduler.submit("lint", &[fetch]);
+    scheduler.submit("publish", &[package, test, lint]);
+
+    let order = scheduler.run_order();
+    println!("run order:");
+    for (step, &task) in order.iter().enumerate() {
+        let task = &scheduler.tasks[task];
+        let after: Vec<_> = task
+            .dependencies
+            .iter()
+            .map(|&d| scheduler.tasks[d].name)
+            .collect();
+        println!("  {:>2}. {:<18} after {after:?}", step + 1, task.name);
+    }
+
+    // Every task runs after its dependencies.
+    for (i, task) in scheduler.tasks.iter().enumerate() {
+        assert!(task
+            .dependencies
+            .iter()
+            .all(|&d| scheduler.runs_before(d, i)));
+    }
+
+    // Checking many pairs at once borrows the order only once.
+    let ids: HashMap<_, _> = scheduler
+        .tasks
+        .iter()
+        .map(|t| (t.name, t.slot.id()))
+        .collect();
+    let by_id = scheduler.start.id_order();
+    let lint_first = by_id.compare(ids["lint"], ids["build"]).unwrap().is_lt();
+    println!("lint runs before build: {lint_first}");
+}
@@ -0,0 +1,149 @@
+//! A reorderable TODO list, driven by commands on standard input.
+//!
+//! Each item holds a list-range priority, and the list is shown in order of those priorities.
+//! Moving an item only allocates a new priority for it, between its new neighbors, so no other
+//! item is touched, however long the list. Run with `cargo run --example todo_list`, then type
+//! `help`; or pipe a script of commands into it, e.g.:
+//!
+//! ```sh
+//! printf 'add milk\nadd eggs\nadd bread\nmove 3 1\ndone 2\nlist\n' | cargo run --example todo_list
+//! ```
+use order_maintenance::capability::InsertFront;
+use order_maintenance::list_range::Priority;
+use order_maintenance::MaintainedOrd;
+use std::io::{BufRead, IsTerminal, Write};
+
+/// An item on the list.
+struct Item {
+    text: String,
+    done: bool,
+    position: Priority,
+}
+
+/// The items on the list, kept sorted by position.
+struct TodoList {
+    /// A priority before every item, which anchors insertions at the end of an empty list.
+    head: Priority,
+    items: Vec<Item>,
+}
+
+impl TodoList {
+    fn new() -> Self {
+        Self {
+            head: Priority::new(),
+            items: Vec::new(),
+        }
+    }
+
+    /// Add an item at the end of the list.
+    fn add(&mut self, text: &str) {
+        let last = self.items.last().map_or(&self.head, |item| &item.position);
+        let position = last.insert();
+        self.items.push(Item {
+            text: text.to_string(),
+            done: false,
+            position,
+        });
+    }
+
+    /// Move the item at index `from` to index `to`, as if dragged there.
+    fn move_item(&mut self, from: usize, to: usize) -> Result<(), String> {
+        if from >= self.items.len() || to >= self.items.len() {
+            return Err(format!("no such item: {}", from.max(to) + 1));
+        }
+        let mut item = self.items.remove(from);
+        item.position = match to.checked_sub(1) {
+            Some(before) => self.items[before].position.insert(),
+            None => self.head.insert_front(),
+        };
+        self.items.insert(to, item);
+        debug_assert!(self.items.windows(2).all(|w| w[0].position < w[1].position));
+        Ok(())
+    }
+
+    /// Toggle whether the item at index `index` is done.
+    fn toggle(&mut self, index: usize) -> Result<(), String> {
+        let item = self
+            .items
+            .get_mut(index)
+            .ok_or_else(|| format!("no such item: {}", index + 1))?;
+        item.done = !item.done;
+        Ok(())
+    }
+
+    /// Remove every item that is done.
+    fn clear(&mut self) {
+        self.items.retain(|item| !item.done);
+    }
+
+    fn show(&self, out: &mut impl Write) -> std::io::Result<()> {
+        if self.items.is_empty() {
+            writeln!(out, "  (nothing to do)")?;
+        }
+        for (i, item) in self.items.iter().enumerate() {
+            let mark = if item.done { 'x' } else { ' ' };
+            writeln!(out, "  {:>2}. [{mark}] {}", i + 1, item.text)?;
+        }
+        Ok(())
+    }
+
+    /// Run one command; returns whether to keep going.
+    fn run(&mut self, line: &str, out: &mut impl Write) -> std::io::Result<bool> {
+        let (command, rest) = line.trim().split_once(' ').unwrap_or((line.trim(), ""));
+        // Items are numbered from 1 on screen.
+        let index = |arg: Option<&str>| -> Result<usize, String> {
+            match arg.and_then(|arg| arg.parse::<usize>().ok()) {
+                Some(n) if n > 0 => Ok(n - 1),
+                _ => Err("expected an item number".to_string()),
+            }
+        };
+        let mut args = rest.split_whitespace();
+        let result = match command {
+            "" => Ok(()),
+            "add" if !rest.is_empty() => {
+                self.add(rest.trim());
+                Ok(())
+            }
+            "move" => index(args.next())
+                .and_then(|from| Ok((from, index(args.next())?)))
+                .and_then(|(from, to)| self.move_item(from, to)),
+            "done" => index(args.next()).and_then(|i| self.toggle(i)),
+            "clear" => {
+                self.clear();
+                Ok(())
+            }
+            "list" => return self.show(out).map(|()| true),
+            "quit" => return Ok(false),
+            _ => Err("commands: add TEXT, move FROM TO, done N, clear, list, quit".to_string()),
+        };
+        match result {
+            Ok(()) => self.show(out)?,
+            Err(message) => writeln!(out, "  {message}")?,
+        }
+        Ok(true)
+    }
+}
+
+fn main() -> std::io::Result<()> {
+    let stdin = std::io::stdin();
+    let interactive = stdin.is_terminal();
+    let mut out = std::io::stdout();
+    let mut list = TodoList::new();
+    let mut lines = stdin.lock().lines();
+    loop {
+        if interactive {
+            write!(out, "> ")?;
+            out.flush()?;
+        }
+        let Some(line) = lines.next().transpose()? else {
+            break;
+        };
+        if !interactive {
+            writeln!(out, "> {line}")?;
+        }
+        if !list.run(&line, &mut out)? {
+            break;
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,253 @@
+//! Delegation of tests and benchmarks to generic implementations.
+use proc_macro2::{Ident, TokenStream};
+use quote::{format_ident, quote, ToTokens};
+use syn::{
+    bracketed,
+    parse::{Parse, ParseStream},
+    punctuated::Punctuated,
+    LitStr, Path, Token, Type,
+};
+
+/// A priority type to delegate to, with an optional label, e.g., `Priority as "tag-range"`.
+struct Target {
+    ty: Type,
+    label: Option<LitStr>,
+}
+
+impl Parse for Target {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let ty = input.parse()?;
+        let label = if input.peek(Token![as]) {
+            let _as: Token![as] = input.parse()?;
+            Some(input.parse()?)
+        } else {
+            None
+        };
+        Ok(Self { ty, label })
+    }
+}
+
+impl Target {
+    /// The label of this target, defaulting to its type.
+    fn label(&self) -> String {
+        match &self.label {
+            Some(label) => label.value(),
+            None => self.ty.to_token_stream().to_string().replace(' ', ""),
+        }
+    }
+}
+
+/// What a section delegates.
+enum Kind {
+    Tests,
+    Benches,
+}
+
+/// A section of things to delegate, e.g., `tests in common::tests [compare_two, insertion]`.
+struct Section {
+    kind: Kind,
+    path: Path,
+    names: Punctuated<Ident, Token![,]>,
+}
+
+impl Parse for Section {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let key: Ident = input.parse()?;
+        let (kind, default_path) = if key == "tests" {
+            (Kind::Tests, "common::tests")
+        } else if key == "benches" {
+            (Kind::Benches, "common::benches")
+        } else {
+            return Err(syn::Error::new(
+                key.span(),
+                "unknown section; expected `tests` or `benches`",
+            ));
+        };
+        let path = if input.peek(Token![in]) {
+            let _in: Token![in] = input.parse()?;
+            input.parse()?
+        } else {
+            let mut path: Path = syn::parse_str(default_path)?;
+            for segment in &mut path.segments {
+                segment.ident.set_span(key.span());
+            }
+            path
+        };
+        let content;
+        bracketed!(content in input);
+        Ok(Self {
+            kind,
+            path,
+            names: content.parse_terminated(Ident::parse, Token![,])?,
+        })
+    }
+}
+
+/// Definition of delegated tests and benchmarks.
+///
+/// Looks like this:
+///
+/// ```no_compile
+/// {type} {as {label}}?, ... => {tests|benches} {in {path}}? [{name}, ...], ...
+/// ```
+pub(crate) struct Delegate {
+    targets: Punctuated<Target, Token![,]>,
+    _arrow: Token![=>],
+    sections: Punctuated<Section, Token![,]>,
+}
+
+impl Parse for Delegate {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let mut targets = Punctuated::new();
+        loop {
+            targets.push_value(input.parse()?);
+            if input.peek(Token![=>]) {
+                break;
+            }
+            targets.push_punct(input.parse()?);
+        }
+        Ok(Self {
+            targets,
+            _arrow: input.parse()?,
+            sections: Punctuated::parse_terminated(input)?,
+        })
+    }
+}
+
+impl Delegate {
+    pub(crate) fn generate(&self) -> syn::Result<TokenStream> {
+        let mut out = TokenStream::new();
+        for section in &self.sections {
+            out.extend(match section.kind {
+                Kind::Tests => self.generate_tests(section)?,
+                Kind::Benches => self.generate_benches(section),
+            });
+        }
+        Ok(out)
+    }
+
+    /// Generate a `#[test]` for each name and target.
+    ///
+    /// With several targets, each target's tests are generated in a module named after its label.
+    fn generate_tests(&self, section: &Section) -> syn::Result<TokenStream> {
+        let path = &section.path;
+        let names = section.names.iter();
+
+        if self.targets.len() == 1 {
+            let ty = &self.targets[0].ty;
+            return Ok(quote! {
+                #(
+                    #[test]
+                    fn #names() {
+                        #path::#names::<#ty>();
+                    }
+                )*
+            });
+        }
+
+        let mut out = TokenStream::new();
+        for target in &self.targets {
+            let Some(label) = &target.label else {
+                return Err(syn::Error::new_spanned(
+                    &target.ty,
+                    "delegating tests to several types requires a label for each, e.g., `Priority as \"priority\"`",
+                ));
+            };
+            let module = format_ident!(
+                "{}",
+                label
+                    .value()
+                    .replace(|c: char| !c.is_ascii_alphanumeric(), "_"),
+                span = label.span()
+            );
+            let ty = &target.ty;
+            let names = names.clone();
+            out.extend(quote! {
+                mod #module {
+                    #[allow(unused_imports)]
+                    use super::*;
+
+                    #(
+                        #[test]
+                        fn #names() {
+                            #path::#names::<#ty>();
+                        }
+                    )*
+                }
+            });
+        }
+        Ok(out)
+    }
+
+    /// Generate a `benchmark` function that runs each name as a group, over all targets.
+    fn generate_benches(&self, section: &Section) -> TokenStream {
+        let path = &section.path;
+        let groups = section.names.iter().map(|name| {
+            let group = name.to_string();
+            let runs = self.targets.iter().map(|target| {
+                let ty = &target.ty;
+                let label = target.label();
+                quote! { #path::#name::<#ty>(&mut group, #label); }
+            });
+            quote! {
+                {
+                    let mut group = c.benchmark_group(#group);
+                    #( #runs )*
+                    group.finish();
+                }
+            }
+        });
+        quote! {
+            pub fn benchmark(c: &mut ::criterion::Criterion) {
+                #( #groups )*
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn generate(input: &str) -> syn::Result<String> {
+        syn::parse_str::<Delegate>(input)?
+            .generate()
+            .map(|out| out.to_string())
+    }
+
+    #[test]
+    fn delegates_tests() {
+        let out = generate("Priority => tests [compare_two, insertion]").unwrap();
+        assert!(out
+            .contains("fn compare_two () { common :: tests :: compare_two :: < Priority > () ; }"));
+        assert!(out.contains("fn insertion ()"));
+        assert!(!out.contains("mod"));
+
+        let out =
+            generate("A as \"tag-range\", B as \"list-range\" => tests in my::tests [t]").unwrap();
+        assert!(out.contains("mod tag_range"));
+        assert!(out.contains("mod list_range"));
+        assert!(out.contains("my :: tests :: t :: < B > ()"));
+    }
+
+    #[test]
+    fn delegates_benches() {
+        let out = generate("A as \"a\", B => benches [sort]").unwrap();
+        assert!(out.contains("pub fn benchmark (c : & mut :: criterion :: Criterion)"));
+        assert!(out.contains("c . benchmark_group (\"sort\")"));
+        assert!(out.contains("common :: benches :: sort :: < A > (& mut group , \"a\") ;"));
+        assert!(out.contains("common :: benches :: sort :: < B > (& mut group , \"B\") ;"));
+    }
+
+    #[test]
+    fn reject_invalid_delegations() {
+        assert_eq!(
+            generate("A, B => tests [t]").unwrap_err().to_string(),
+            "delegating tests to several types requires a label for each, e.g., `Priority as \"priority\"`"
+        );
+        assert_eq!(
+            generate("A => docs [t]").unwrap_err().to_string(),
+            "unknown section; expected `tests` or `benches`"
+        );
+    }
+}
@@ -0,0 +1,174 @@
+//! Derivation of orderings for types that carry a priority.
+use proc_macro2::{Span, TokenStream};
+use quote::{format_ident, quote};
+use syn::{parse_quote, Data, DeriveInput, Fields, Generics, Index, Member};
+
+/// Generate the `MaintainedOrder` implementations for `input`.
+pub(crate) fn derive_maintained_order(input: &DeriveInput) -> syn::Result<TokenStream> {
+    let Data::Struct(data) = &input.data else {
+        return Err(syn::Error::new(
+            Span::call_site(),
+            "`MaintainedOrder` can only be derived for structs",
+        ));
+    };
+
+    // Each field, as (member, type, whether it is marked `#[order]`).
+    let fields: Vec<_> = data
+        .fields
+        .iter()
+        .enumerate()
+        .map(|(i, field)| {
+            let member = match &field.ident {
+                Some(ident) => Member::Named(ident.clone()),
+                None => Member::Unnamed(Index::from(i)),
+            };
+            let marked = field.attrs.iter().any(|a| a.path().is_ident("order"));
+            (member, &field.ty, marked)
+        })
+        .collect();
+
+    let marked: Vec<_> = fields.iter().filter(|(_, _, marked)| *marked).collect();
+    let (order, order_ty, _) = match (marked.as_slice(), fields.as_slice()) {
+        ([field], _) => *field,
+        ([], [field]) => field,
+        _ => {
+            return Err(syn::Error::new(
+                Span::call_site(),
+                "expected exactly one field marked `#[order]`",
+            ))
+        }
+    };
+
+    let name = &input.ident;
+    let vis = &input.vis;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+
+    // Comparisons delegate to the order field, so its type must support them, which does not
+    // follow from the struct's own bounds when that type is generic.
+    let bounded = |bound: TokenStream| {
+        let mut generics: Generics = input.generics.clone();
+        generics
+            .make_where_clause()
+            .predicates
+            .push(parse_quote!(#order_ty: #bound));
+        generics.split_for_impl().2.cloned()
+    };
+    let eq_where_clause = bounded(quote!(::core::cmp::PartialEq));
+    let ord_where_clause = bounded(quote!(::core::cmp::PartialOrd));
+
+    // The remaining fields become arguments of `insert_after()`, in order.
+    let rest: Vec<_> = fields.iter().filter(|(m, _, _)| m != order).collect();
+    let args: Vec<_> = rest
+        .iter()
+        .map(|(member, _, _)| match member {
+            Member::Named(ident) => ident.clone(),
+            Member::Unnamed(index) => format_ident!("field{}", index.index),
+        })
+        .collect();
+    let arg_tys = rest.iter().map(|(_, ty, _)| ty);
+    let arg_members = rest.iter().map(|(member, _, _)| member);
+
+    let construct = match data.fields {
+        Fields::Named(_) => quote! {
+            Self { #order: ::order_maintenance::MaintainedOrd::insert(&self.#order), #( #arg_members: #args ),* }
+        },
+        _ => {
+            let values = fields.iter().map(|(member, _, _)| {
+                if member == order {
+                    quote! { ::order_maintenance::MaintainedOrd::insert(&self.#order) }
+                } else {
+                    let i = rest.iter().position(|(m, _, _)| m == member).unwrap();
+                    let arg = &args[i];
+                    quote! { #arg }
+                }
+            });
+            quote! { Self(#( #values ),*) }
+        }
+    };
+
+    Ok(quote! {
+        impl #impl_generics ::core::cmp::PartialEq for #name #ty_generics #eq_where_clause {
+            fn eq(&self, other: &Self) -> bool {
+                ::core::cmp::PartialEq::eq(&self.#order, &other.#order)
+            }
+        }
+
+        impl #impl_generics ::core::cmp::PartialOrd for #name #ty_generics #ord_where_clause {
+            fn partial_cmp(&self, other: &Self) -> ::core::option::Option<::core::cmp::Ordering> {
+                ::core::cmp::PartialOrd::partial_cmp(&self.#order, &other.#order)
+            }
+        }
+
+        impl #impl_generics #name #ty_generics #where_clause {
+            /// Construct a value ordered immediately after `self`, with the given remaining fields.
+            #[allow(dead_code)]
+            #vis fn insert_after(&self, #( #args: #arg_tys ),*) -> Self
+            where
+                #order_ty: ::order_maintenance::MaintainedOrd,
+            {
+                #construct
+            }
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn derive(input: &str) -> syn::Result<String> {
+        let input: DeriveInput = syn::parse_str(input)?;
+        derive_maintained_order(&input).map(|out| out.to_string())
+    }
+
+    #[test]
+    fn delegates_to_marked_field() {
+        let out = derive("struct Task { name: String, #[order] prio: Priority }").unwrap();
+        assert!(out.contains("PartialEq :: eq (& self . prio , & other . prio)"));
+        assert!(out.contains("PartialOrd :: partial_cmp (& self . prio , & other . prio)"));
+        assert!(out.contains("fn insert_after (& self , name : String) -> Self"));
+
+        let out = derive("struct Wrapper(u32, #[order] Priority);").unwrap();
+        assert!(out.contains("& self . 1"));
+        assert!(out.contains("fn insert_after (& self , field0 : u32) -> Self"));
+
+        let out = derive("struct Only(Priority);").unwrap();
+        assert!(out.contains("& self . 0"));
+    }
+
+    #[test]
+    fn bounds_generic_order_field() {
+        let out = derive("struct Wrapper<P: Clone>(P) where P: Default;").unwrap();
+        assert!(out.contains(
+            "PartialEq for Wrapper < P > where P : Default , P : :: core :: cmp :: PartialEq"
+        ));
+        assert!(out.contains(
+            "PartialOrd for Wrapper < P > where P : Default , P : :: core :: cmp :: PartialOrd"
+        ));
+
+        let out = derive("struct Task<T> { item: T, #[order] prio: Priority }").unwrap();
+        assert!(
+            out.contains("PartialEq for Task < T > where Priority : :: core :: cmp :: PartialEq")
+        );
+    }
+
+    #[test]
+    fn reject_invalid_inputs() {
+        assert_eq!(
+            derive("struct Task { name: String, prio: Priority }")
+                .unwrap_err()
+                .to_string(),
+            "expected exactly one field marked `#[order]`"
+        );
+        assert_eq!(
+            derive("struct Task { #[order] a: Priority, #[order] b: Priority }")
+                .unwrap_err()
+                .to_string(),
+            "expected exactly one field marked `#[order]`"
+        );
+        assert_eq!(
+            derive("enum Task { A }").unwrap_err().to_string(),
+            "`MaintainedOrder` can only be derived for structs"
+        );
+    }
+}
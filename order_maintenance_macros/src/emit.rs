@@ -0,0 +1,207 @@
+//! Alternative representations for tables of capacities.
+use proc_macro2::{Ident, Literal, TokenStream};
+use quote::{format_ident, quote};
+use syn::{Attribute, LitByteStr, Visibility};
+
+/// How a table of capacities is emitted, selected by an `#[emit(...)]` attribute.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Emit {
+    /// A `const` array, computed at compile time. This is the default.
+    Const,
+    /// A `static` `LazyLock` array, computed at first use, i.e., `#[emit(lazy)]`.
+    Lazy,
+    /// A `const` table of delta-encoded rows, decoded on lookup, i.e., `#[emit(delta)]`.
+    Delta,
+}
+
+impl Emit {
+    /// Remove the `#[emit(...)]` attribute from `attrs`, if any, and parse it.
+    pub(crate) fn take_from(attrs: &mut Vec<Attribute>) -> syn::Result<Self> {
+        let mut emit = None;
+        let mut rest = Vec::with_capacity(attrs.len());
+        for attr in attrs.drain(..) {
+            if !attr.path().is_ident("emit") {
+                rest.push(attr);
+                continue;
+            }
+            let mode: Ident = attr.parse_args()?;
+            let parsed = if mode == "lazy" {
+                Emit::Lazy
+            } else if mode == "delta" {
+                Emit::Delta
+            } else {
+                return Err(syn::Error::new(
+                    mode.span(),
+                    "unknown emit mode; expected `lazy` or `delta`",
+                ));
+            };
+            if emit.replace(parsed).is_some() {
+                return Err(syn::Error::new(
+                    mode.span(),
+                    "emit mode specified more than once",
+                ));
+            }
+        }
+        *attrs = rest;
+        Ok(emit.unwrap_or(Emit::Const))
+    }
+}
+
+/// Encode a row of non-decreasing capacities as the LEB128 varints of their successive deltas.
+pub(crate) fn delta_encode(row: &[u128]) -> Vec<u8> {
+    let mut bytes = Vec::new();
+    let mut prev = 0;
+    for &capa in row {
+        let mut delta = capa - prev;
+        prev = capa;
+        loop {
+            let byte = (delta & 0x7f) as u8;
+            delta >>= 7;
+            if delta == 0 {
+                bytes.push(byte);
+                break;
+            }
+            bytes.push(byte | 0x80);
+        }
+    }
+    bytes
+}
+
+/// Name of the type of a delta-encoded table named `name`, e.g., `CapasTable` for `CAPAS`.
+pub(crate) fn delta_table_type(name: &Ident) -> Ident {
+    let camel: String = name
+        .to_string()
+        .split('_')
+        .filter(|word| !word.is_empty())
+        .map(|word| {
+            let first = word.chars().next().map_or(0, char::len_utf8);
+            let (first, rest) = word.split_at(first);
+            first.to_uppercase() + &rest.to_lowercase()
+        })
+        .collect();
+    format_ident!("{}Table", camel, span = name.span())
+}
+
+/// Generate the type of a delta-encoded table with `count` rows of `ty` capacities.
+///
+/// The type does not depend on the number of bits, so it only needs to be generated once.
+pub(crate) fn delta_table_def(
+    vis: &Visibility,
+    name: &Ident,
+    ty: &Ident,
+    count: usize,
+) -> TokenStream {
+    let table_ty = delta_table_type(name);
+    let doc = format!("Delta-encoded table of capacities, the type of [`{name}`].");
+    quote! {
+        #[doc = #doc]
+        #[derive(Debug, Clone, Copy)]
+        #vis struct #table_ty {
+            rows: [&'static [u8]; #count],
+        }
+
+        #[allow(dead_code)]
+        impl #table_ty {
+            /// The capacity at `level` in the row for the threshold with index `threshold`.
+            ///
+            /// This decodes the row up to `level`, so it takes time linear in `level`.
+            #vis const fn get(&self, threshold: usize, level: usize) -> #ty {
+                let row = self.rows[threshold];
+                let mut i = 0;
+                let mut capa: #ty = 0;
+                let mut l = 0;
+                loop {
+                    let mut delta: #ty = 0;
+                    let mut shift = 0;
+                    loop {
+                        let byte = row[i];
+                        i += 1;
+                        delta |= ((byte & 0x7f) as #ty) << shift;
+                        shift += 7;
+                        if byte & 0x80 == 0 {
+                            break;
+                        }
+                    }
+                    capa += delta;
+                    if l == level {
+                        return capa;
+                    }
+                    l += 1;
+                }
+            }
+
+            /// The number of rows in this table, i.e., the number of thresholds.
+            #vis const fn len(&self) -> usize {
+                #count
+            }
+        }
+    }
+}
+
+/// Generate the value of a delta-encoded table named `name`, from its rows of capacities.
+pub(crate) fn delta_table_value(name: &Ident, capas: &[Vec<u128>]) -> TokenStream {
+    let table_ty = delta_table_type(name);
+    let rows = capas
+        .iter()
+        .map(|row| LitByteStr::new(&delta_encode(row), name.span()));
+    quote! { #table_ty { rows: [#(#rows),*] } }
+}
+
+/// Generate the initializer of a lazily computed table, from its thresholds.
+///
+/// This computes capacities the same way as [`capacities_for_threshold()`](crate::capacities_for_threshold),
+/// but at runtime.
+pub(crate) fn lazy_table_value(ty: &Ident, bits: usize, thresholds: &[f64]) -> TokenStream {
+    let count = thresholds.len();
+    let thresholds = thresholds.iter().copied().map(Literal::f64_suffixed);
+    quote! {
+        ::std::sync::LazyLock::new(|| {
+            const THRESHOLDS: [f64; #count] = [#(#thresholds),*];
+            let mut capas = [[0; #bits]; #count];
+            for (row, t) in capas.iter_mut().zip(THRESHOLDS) {
+                for (level, capa) in row.iter_mut().enumerate() {
+                    *capa = (2.0f64 / t).powi(level as i32).floor() as #ty;
+                }
+            }
+            capas
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use proc_macro2::Span;
+
+    #[test]
+    fn delta_encoding() {
+        assert_eq!(delta_encode(&[1, 1, 3, 6, 10]), vec![1, 0, 2, 3, 4]);
+        assert_eq!(delta_encode(&[1, 200]), vec![1, 0xc7, 0x01]);
+    }
+
+    #[test]
+    fn delta_table_names() {
+        let name = Ident::new("CAPACITIES", Span::call_site());
+        assert_eq!(delta_table_type(&name), "CapacitiesTable");
+        let name = Ident::new("MY_CAPAS", Span::call_site());
+        assert_eq!(delta_table_type(&name), "MyCapasTable");
+        let name = Ident::new("ÉCART_CAPAS", Span::call_site());
+        assert_eq!(delta_table_type(&name), "ÉcartCapasTable");
+    }
+
+    #[test]
+    fn parse_emit_attrs() {
+        let mut attrs: Vec<Attribute> = vec![
+            syn::parse_quote!(#[doc = "docs"]),
+            syn::parse_quote!(#[emit(lazy)]),
+        ];
+        assert_eq!(Emit::take_from(&mut attrs).unwrap(), Emit::Lazy);
+        assert_eq!(attrs.len(), 1);
+
+        let mut attrs: Vec<Attribute> = vec![syn::parse_quote!(#[emit(eager)])];
+        assert_eq!(
+            Emit::take_from(&mut attrs).unwrap_err().to_string(),
+            "unknown emit mode; expected `lazy` or `delta`"
+        );
+    }
+}
@@ -1,90 +1,394 @@
-use proc_macro2::{Ident, TokenStream};
-use quote::quote;
+mod delegate;
+mod derive;
+mod emit;
+mod priority;
+mod threshold;
+
+use emit::Emit;
+use proc_macro2::{Ident, Literal, Span, TokenStream};
+use quote::{format_ident, quote};
 use syn::{
     bracketed,
     parse::{Parse, ParseStream},
     parse_macro_input,
+    punctuated::Punctuated,
     token::Bracket,
-    Attribute, LitFloat, LitInt, Token, Visibility,
+    Attribute, DeriveInput, LitFloat, LitInt, RangeLimits, Token, Visibility,
 };
+use threshold::{parse_threshold_list, parse_threshold_range, Threshold};
+
+/// Pointer widths that a `usize` table is generated for.
+const POINTER_WIDTHS: [usize; 3] = [16, 32, 64];
+
+/// Unsigned integer types that a table can be generated for, along with their number of bits.
+const UINT_WIDTHS: [(&str, usize); 5] = [
+    ("u8", 8),
+    ("u16", 16),
+    ("u32", 32),
+    ("u64", 64),
+    ("u128", 128),
+];
+
+/// Largest number of bits supported in a row of capacities given as a literal, since they are
+/// stored as `usize`.
+const MAX_BITS: usize = 64;
+
+/// Number of bits in each row of capacities.
+enum Bits {
+    /// Fixed number of bits, e.g., `64`, with capacities stored as `usize`.
+    Lit(LitInt),
+    /// The number of bits in a `usize` on the target, i.e., `usize`.
+    Usize(Ident),
+    /// The number of bits in an unsigned integer type, e.g., `u128`, with capacities stored as
+    /// that type.
+    Uint(Ident, usize),
+}
+
+impl Parse for Bits {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        if input.peek(LitInt) {
+            return Ok(Self::Lit(input.parse()?));
+        }
+        let ident: Ident = input.parse()?;
+        if ident == "usize" {
+            return Ok(Self::Usize(ident));
+        }
+        match UINT_WIDTHS.iter().find(|(ty, _)| ident == ty) {
+            Some(&(_, bits)) => Ok(Self::Uint(ident, bits)),
+            None => Err(syn::Error::new(
+                ident.span(),
+                "expected number of bits, `usize`, or an unsigned integer type",
+            )),
+        }
+    }
+}
+
+impl Bits {
+    /// Generate an item for each number of bits this stands for.
+    ///
+    /// For `usize`, an item is generated for each supported pointer width, each guarded by the
+    /// corresponding `#[cfg(target_pointer_width = "...")]`.
+    fn expand(&self, item: impl Fn(usize) -> TokenStream) -> syn::Result<TokenStream> {
+        match self {
+            Bits::Lit(lit) => Ok(item(parse_bits(lit)?)),
+            Bits::Uint(_, bits) => Ok(item(*bits)),
+            Bits::Usize(_) => {
+                let items = POINTER_WIDTHS.iter().map(|&bits| {
+                    let width = bits.to_string();
+                    let item = item(bits);
+                    quote! {
+                        #[cfg(target_pointer_width = #width)]
+                        #item
+                    }
+                });
+                Ok(quote! { #( #items )* })
+            }
+        }
+    }
+
+    /// The type that capacities are stored as.
+    fn elem_ty(&self) -> Ident {
+        match self {
+            Bits::Lit(lit) => Ident::new("usize", lit.span()),
+            Bits::Usize(ident) | Bits::Uint(ident, _) => ident.clone(),
+        }
+    }
+}
+
+/// Parse a number of bits, which must be between 1 and [`MAX_BITS`].
+fn parse_bits(lit: &LitInt) -> syn::Result<usize> {
+    let bits: usize = lit.base10_parse()?;
+    if bits == 0 || bits > MAX_BITS {
+        return Err(syn::Error::new(
+            lit.span(),
+            format!("number of bits must be between 1 and {MAX_BITS}"),
+        ));
+    }
+    Ok(bits)
+}
+
+/// Thresholds that a table of capacities is declared for.
+enum ThresholdSpec {
+    /// A range of `count` thresholds, e.g., `[[1.1..=1.9; 64]; 17]`.
+    Range {
+        begin: LitFloat,
+        limits: RangeLimits,
+        end: LitFloat,
+        count: LitInt,
+    },
+    /// An explicit list of thresholds, e.g., `[1.1, 1.25, 1.5; 64]`.
+    List(Punctuated<LitFloat, Token![,]>, Span),
+}
 
-/// Declaration for threshold range.
+/// Declaration for a table of capacities.
 ///
-/// Looks like this:
+/// Looks like one of these:
 ///
 /// ```no_compile
 /// {vis?} const {name}: [[{begin}..={end}; {bits}]; {count}];
+/// {vis?} const {name}: [[{begin}..{end}; {bits}]; {count}];
+/// {vis?} const {name}: [{threshold}, ...; {bits}];
 /// ```
-struct ThresholdRange {
+///
+/// where `{bits}` is either an integer literal, `usize`, or an unsigned integer type. It may be
+/// preceded by an `#[emit(...)]` attribute, which selects how the table is emitted.
+struct CapacitiesDecl {
     attrs: Vec<Attribute>,
+    emit: Emit,
     vis: Visibility,
     _const: Token![const],
     name: Ident,
     _colon: Token![:],
-    _bracket1: Bracket,
-    _bracket2: Bracket,
-    begin: LitFloat,
-    _dotdoteq: Token![..=],
-    end: LitFloat,
-    _semi2: Token![;],
-    bits: LitInt,
-    _semi1: Token![;],
-    count: LitInt,
+    thresholds: ThresholdSpec,
+    bits: Bits,
     _semi: Token![;],
 }
 
-impl Parse for ThresholdRange {
+impl Parse for CapacitiesDecl {
     fn parse(input: ParseStream) -> syn::Result<Self> {
+        let mut attrs = input.call(Attribute::parse_outer)?;
+        let emit = Emit::take_from(&mut attrs)?;
+        let vis = input.parse()?;
+        let _const = input.parse()?;
+        let name = input.parse()?;
+        let _colon = input.parse()?;
+
         let content1;
-        let content2;
+        let bracket1 = bracketed!(content1 in input);
+        let (thresholds, bits) = if content1.peek(Bracket) {
+            let content2;
+            bracketed!(content2 in content1);
+            let begin = content2.parse()?;
+            let limits = content2.parse()?;
+            let end = content2.parse()?;
+            let _semi2: Token![;] = content2.parse()?;
+            let bits = content2.parse()?;
+            let _semi1: Token![;] = content1.parse()?;
+            let count = content1.parse()?;
+            let range = ThresholdSpec::Range {
+                begin,
+                limits,
+                end,
+                count,
+            };
+            (range, bits)
+        } else {
+            let mut list = Punctuated::new();
+            while !content1.peek(Token![;]) {
+                list.push_value(content1.parse()?);
+                if content1.peek(Token![;]) {
+                    break;
+                }
+                list.push_punct(content1.parse()?);
+            }
+            let _semi1: Token![;] = content1.parse()?;
+            let bits = content1.parse()?;
+            (ThresholdSpec::List(list, bracket1.span.join()), bits)
+        };
 
         Ok(Self {
-            attrs: input.call(Attribute::parse_outer)?,
-            vis: input.parse()?,
-            _const: input.parse()?,
-            name: input.parse()?,
-            _colon: input.parse()?,
-            _bracket1: bracketed!(content1 in input),
-            _bracket2: bracketed!(content2 in content1),
-            begin: content2.parse()?,
-            _dotdoteq: content2.parse()?,
-            end: content2.parse()?,
-            _semi2: content2.parse()?,
-            bits: content2.parse()?,
-            _semi1: content1.parse()?,
-            count: content1.parse()?,
+            attrs,
+            emit,
+            vis,
+            _const,
+            name,
+            _colon,
+            thresholds,
+            bits,
             _semi: input.parse()?,
         })
     }
 }
 
-impl ThresholdRange {
+impl CapacitiesDecl {
     fn generate(&self) -> syn::Result<TokenStream> {
         let attrs = &self.attrs;
         let vis = &self.vis;
         let name = &self.name;
 
-        let begin: f64 = self.begin.base10_parse()?;
-        let end: f64 = self.end.base10_parse()?;
-        let bits: usize = self.bits.base10_parse()?;
-        let count: usize = self.count.base10_parse()?;
+        // Lint and `cfg` attributes also apply to the items generated alongside the table.
+        let lints: Vec<&Attribute> = attrs
+            .iter()
+            .filter(|attr| {
+                [
+                    "allow", "warn", "deny", "forbid", "expect", "cfg", "cfg_attr",
+                ]
+                .iter()
+                .any(|lint| attr.path().is_ident(lint))
+            })
+            .collect();
+
+        let thresholds = match &self.thresholds {
+            ThresholdSpec::Range {
+                begin,
+                limits,
+                end,
+                count,
+            } => parse_threshold_range(begin, limits, end, count)?,
+            ThresholdSpec::List(list, span) => parse_threshold_list(list, *span)?,
+        };
+        let count = thresholds.len();
 
-        // TODO: warn if bits is not 32/64/a reasonable value?
+        let ty = self.bits.elem_ty();
+        let capas = |bits| -> Vec<Vec<u128>> {
+            thresholds
+                .iter()
+                .map(|t| capacities_for_threshold(t.value, bits))
+                .collect()
+        };
 
-        let gap = (end - begin) / (count as f64);
+        let table = match self.emit {
+            Emit::Const => self.bits.expand(|bits| {
+                let capas = capas(bits)
+                    .into_iter()
+                    .map(|row| row.into_iter().map(Literal::u128_unsuffixed));
+                let rows = capas.map(|row| quote! { [#(#row),*] });
+                quote! {
+                    #( #attrs )*
+                    #vis const #name: [[#ty; #bits]; #count] = [#(#rows),*];
+                }
+            })?,
+            Emit::Lazy => {
+                let values: Vec<f64> = thresholds.iter().map(|t| t.value).collect();
+                self.bits.expand(|bits| {
+                    let value = emit::lazy_table_value(&ty, bits, &values);
+                    quote! {
+                        #( #attrs )*
+                        #vis static #name: ::std::sync::LazyLock<[[#ty; #bits]; #count]> = #value;
+                    }
+                })?
+            }
+            Emit::Delta => {
+                let table_ty = emit::delta_table_type(name);
+                let def = emit::delta_table_def(vis, name, &ty, count);
+                let def = quote! { #( #lints )* #def };
+                let consts = self.bits.expand(|bits| {
+                    let value = emit::delta_table_value(name, &capas(bits));
+                    quote! {
+                        #( #attrs )*
+                        #vis const #name: #table_ty = #value;
+                    }
+                })?;
+                quote! { #def #consts }
+            }
+        };
 
-        let capas: Vec<Vec<usize>> = (0..count)
-            .map(|i| capacities_for_threshold(begin + (i as f64) * gap, bits))
-            .collect();
+        let max_name = format_ident!("{}_MAX", name);
+        let max_doc = format!("The greatest capacity in each row of [`{name}`].");
+        let max_table = self.bits.expand(|bits| {
+            let max = capas(bits)
+                .into_iter()
+                .map(|c| Literal::u128_unsuffixed(c[bits - 1]));
+            quote! {
+                #[doc = #max_doc]
+                #[allow(dead_code)]
+                #( #lints )*
+                #vis const #max_name: [#ty; #count] = [#(#max),*];
+            }
+        })?;
+
+        let breakpoints_name = format_ident!("{}_BREAKPOINTS", name);
+        let breakpoints_doc = format!(
+            "The row of [`{name}`] for each threshold can be used while fewer than this many \
+             priorities have been inserted. These are non-increasing, so the highest usable \
+             threshold can be found by binary search."
+        );
+        let breakpoints = self.bits.expand(|bits| {
+            let breakpoints = capas(bits)
+                .into_iter()
+                .map(|c| Literal::u128_unsuffixed(c[bits - 1].saturating_sub(1)));
+            quote! {
+                #[doc = #breakpoints_doc]
+                #[allow(dead_code)]
+                #( #lints )*
+                #vis const #breakpoints_name: [#ty; #count] = [#(#breakpoints),*];
+            }
+        })?;
+
+        // Sanity check that capacities fit in the table's type, grow with each level, and shrink
+        // with each threshold, and that the metadata agrees with the table. Rows of lazy tables
+        // are not available at compile time, and rows of delta-encoded tables are non-decreasing
+        // by construction, so only the rows of `const` tables are checked.
+        let checks = self.bits.expand(|bits| {
+            let rows_check = match self.emit {
+                Emit::Const => quote! {
+                    let mut l = 1;
+                    while l < #bits {
+                        assert!(
+                            #name[t][l - 1] <= #name[t][l],
+                            "capacities must be non-decreasing within each row"
+                        );
+                        l += 1;
+                    }
+                    assert!(
+                        #name[t][#bits - 1] == #max_name[t],
+                        "greatest capacities must match the last column of the table"
+                    );
+                },
+                Emit::Delta => quote! {
+                    assert!(
+                        #name.get(t, #bits - 1) == #max_name[t],
+                        "greatest capacities must match the last column of the table"
+                    );
+                },
+                Emit::Lazy => quote! {},
+            };
+            quote! {
+                #( #lints )*
+                const _: () = {
+                    assert!(
+                        #bits <= #ty::BITS as usize,
+                        "capacities must fit in the type of the table on this target"
+                    );
+                    let mut t = 0;
+                    while t < #count {
+                        #rows_check
+                        assert!(
+                            t == 0 || #max_name[t - 1] >= #max_name[t],
+                            "capacities must be non-increasing across thresholds"
+                        );
+                        assert!(
+                            #breakpoints_name[t] == #max_name[t].saturating_sub(1),
+                            "breakpoints must be one less than the greatest capacities"
+                        );
+                        t += 1;
+                    }
+                };
+            }
+        })?;
+
+        let values = thresholds.iter().map(|t| t.value);
+        let values_name = format_ident!("{}_THRESHOLDS", name);
+        let values_doc = format!("The threshold for each row of [`{name}`].");
+
+        let ratios = thresholds
+            .iter()
+            .map(|&Threshold { num, den, .. }| quote! { (#num, #den) });
+        let ratios_name = format_ident!("{}_RATIOS", name);
+        let ratios_doc = format!(
+            "The threshold for each row of [`{name}`], as a `(numerator, denominator)` pair."
+        );
 
         Ok(quote! {
-            #( #attrs )*
-            #vis const #name: [[usize; #bits]; #count] = [#( [#(#capas),*] ),*];
+            #table
+            #max_table
+            #breakpoints
+            #checks
+
+            #[doc = #values_doc]
+            #[allow(dead_code)]
+            #( #lints )*
+            #vis const #values_name: [f64; #count] = [#(#values),*];
+
+            #[doc = #ratios_doc]
+            #[allow(dead_code)]
+            #( #lints )*
+            #vis const #ratios_name: [(u64, u64); #count] = [#(#ratios),*];
         })
     }
 }
 
-/// Generate the capacities for a range of thresholds.
+/// Generate the capacities for a range or list of thresholds.
 ///
 /// Example:
 ///
@@ -96,19 +400,209 @@ impl ThresholdRange {
 /// }
 /// ```
 ///
+/// Using `usize` as the number of bits generates a table for each supported pointer width, each
+/// guarded by the corresponding `#[cfg(target_pointer_width = "...")]`:
+///
+/// ```
+/// # use order_maintenance_macros::*;
+/// generate_capacities! {
+///     /// Capacities for 17 thresholds in the range `(1.1..=1.9)` (inclusive) with `usize` tags.
+///     const CAPAS: [[1.1..=1.9; usize]; 17];
+/// }
+/// assert_eq!(CAPAS[0].len(), usize::BITS as usize);
+/// ```
+///
+/// Using an unsigned integer type as the number of bits generates a table of that type, with as
+/// many bits as the type:
+///
+/// ```
+/// # use order_maintenance_macros::*;
+/// generate_capacities! {
+///     /// Capacities for 17 thresholds in the range `(1.1..=1.9)` (inclusive) with `u128` tags.
+///     const CAPAS: [[1.1..=1.9; u128]; 17];
+/// }
+/// let capas: [[u128; 128]; 17] = CAPAS;
+/// assert!(capas[0][127] > u64::MAX as u128);
+/// ```
+///
+/// An exclusive range stops one step short of its end, i.e., these 4 thresholds are `1.1`, `1.3`,
+/// `1.5`, and `1.7`:
+///
+/// ```
+/// # use order_maintenance_macros::*;
+/// generate_capacities! {
+///     const CAPAS: [[1.1..1.9; 64]; 4];
+/// }
+/// ```
+///
+/// Thresholds can also be listed explicitly, in increasing order:
+///
+/// ```
+/// # use order_maintenance_macros::*;
+/// generate_capacities! {
+///     const CAPAS: [1.1, 1.25, 1.5; 64];
+/// }
+/// assert_eq!(CAPAS.len(), 3);
+/// ```
+///
+/// Attributes on the table, such as doc comments, are passed through to it; lint attributes (e.g.,
+/// `#[allow(...)]`) and `#[cfg(...)]` also apply to the items generated alongside it:
+///
+/// ```
+/// # use order_maintenance_macros::*;
+/// mod capas {
+///     # use order_maintenance_macros::*;
+///     generate_capacities! {
+///         /// Capacities that are only visible to the enclosing crate.
+///         #[allow(clippy::unreadable_literal)]
+///         pub(crate) const CAPAS: [[1.1..1.9; 64]; 17];
+///     }
+/// }
+/// assert_eq!(capas::CAPAS_MAX.len(), 17);
+/// ```
+///
+/// The generated code also checks at compile time that capacities fit in the type of the table on
+/// the target, are non-decreasing within each row, and non-increasing across thresholds, and that
+/// the metadata below agrees with the table.
+///
+/// By default, the table is a `const` array computed at compile time. For large tables, an
+/// `#[emit(...)]` attribute selects a representation that takes up less space in the binary:
+///
+/// -   `#[emit(lazy)]` emits a `static` `LazyLock` array instead, computed at first use.
+/// -   `#[emit(delta)]` emits a `const` table whose rows are delta-encoded, with a generated type
+///     named after the table (e.g., `CapasTable` for `CAPAS`). Capacities are looked up with
+///     `CAPAS.get(threshold, level)`, which decodes the row up to `level`.
+///
+/// ```
+/// # use order_maintenance_macros::*;
+/// generate_capacities! {
+///     #[emit(lazy)]
+///     const LAZY: [[1.1..1.9; 64]; 17];
+/// }
+/// generate_capacities! {
+///     #[emit(delta)]
+///     const DELTA: [[1.1..1.9; 64]; 17];
+/// }
+/// assert_eq!(LAZY[3][20], DELTA.get(3, 20));
+/// assert_eq!(DELTA.len(), 17);
+/// ```
+///
+/// Alongside the table, this also generates metadata about each of its rows, named after it:
+///
+/// ```
+/// # use order_maintenance_macros::*;
+/// generate_capacities! {
+///     const CAPAS: [[1.2..1.6; 8]; 2];
+/// }
+/// assert_eq!(CAPAS_THRESHOLDS, [1.2, 1.4]);
+/// assert_eq!(CAPAS_RATIOS, [(6, 5), (7, 5)]); // 1.2 is 6/5, 1.4 is 7/5
+/// assert_eq!(CAPAS_MAX, [CAPAS[0][7], CAPAS[1][7]]);
+/// assert_eq!(CAPAS_BREAKPOINTS, [CAPAS_MAX[0] - 1, CAPAS_MAX[1] - 1]);
+/// ```
 #[proc_macro]
 pub fn generate_capacities(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
-    parse_macro_input!(input as ThresholdRange)
+    parse_macro_input!(input as CapacitiesDecl)
+        .generate()
+        .unwrap_or_else(|e| e.into_compile_error())
+        .into()
+}
+
+/// Define a priority type with custom parameters.
+///
+/// Looks like this:
+///
+/// ```ignore
+/// define_priority! {
+///     /// Doc comment for the priority type.
+///     pub MyPrio: tag_range(threshold = 1.3, bits = 32)
+/// }
+/// ```
+///
+/// This defines `MyPrio` as an alias of the algorithm's `GenericPriority`, along with a
+/// `MyPrioParams` struct implementing the parameter traits it requires. The algorithm is one of:
+///
+/// -   `tag_range`, which accepts a single `threshold = 1.3`, a list of thresholds
+///     `threshold = [1.1, 1.25, 1.5]`, or a range of thresholds `threshold = 1.1..=1.9` (or
+///     exclusive, `1.1..1.9`) with an optional `count = 17` (defaulting to 17). Without a
+///     `threshold`, it uses the same thresholds as `tag_range::Priority`.
+/// -   `list_range`, which does not use thresholds.
+///
+/// Both accept `bits = 32` (or equivalently, `bits = u32`) to restrict labels to fewer bits,
+/// defaulting to `bits = usize`.
+///
+/// The generated code refers to the `order_maintenance` crate by name, so this macro should be
+/// used through its re-export, `order_maintenance::define_priority!`.
+#[proc_macro]
+pub fn define_priority(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
+    parse_macro_input!(input as priority::PriorityDef)
         .generate()
         .unwrap_or_else(|e| e.into_compile_error())
         .into()
 }
 
+/// Delegate tests and benchmarks to generic implementations, instantiated at priority types.
+///
+/// Looks like this:
+///
+/// ```ignore
+/// om_delegate! {
+///     Priority => tests [compare_two, insertion], benches [insert_random, sort]
+/// }
+/// ```
+///
+/// Each name in `tests [...]` generates a `#[test]` that calls `common::tests::{name}::<Priority>()`.
+/// Several types can be given, each with a label (e.g., `Priority as "tag-range"`), in which case
+/// each type's tests are generated in a module named after its label.
+///
+/// The names in `benches [...]` generate a `benchmark(c: &mut criterion::Criterion)` function,
+/// which runs each name as a benchmark group, calling `common::benches::{name}::<Priority>(&mut
+/// group, label)` for each type. A type's label defaults to the type itself.
+///
+/// The paths that names are looked up in can be changed with, e.g., `tests in my::tests [...]`.
+#[proc_macro]
+pub fn om_delegate(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
+    parse_macro_input!(input as delegate::Delegate)
+        .generate()
+        .unwrap_or_else(|e| e.into_compile_error())
+        .into()
+}
+
+/// Derive `PartialEq` and `PartialOrd` for a struct carrying a priority, by delegating to it.
+///
+/// Looks like this:
+///
+/// ```ignore
+/// #[derive(MaintainedOrder)]
+/// struct Task {
+///     name: String,
+///     #[order]
+///     priority: Priority,
+/// }
+/// ```
+///
+/// The priority field is marked `#[order]`, which may be omitted if it is the only field. This
+/// also generates an `insert_after(&self, ...)` constructor, which takes the remaining fields in
+/// order and constructs a value whose priority is inserted immediately after `self`'s.
+///
+/// The generated code refers to the `order_maintenance` crate by name, so this macro should be
+/// used through its re-export, `order_maintenance::MaintainedOrder`.
+#[proc_macro_derive(MaintainedOrder, attributes(order))]
+pub fn derive_maintained_order(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
+    derive::derive_maintained_order(&parse_macro_input!(input as DeriveInput))
+        .unwrap_or_else(|e| e.into_compile_error())
+        .into()
+}
+
 /// Compute the capacities for a particular threshold.
-fn capacities_for_threshold(t: f64, bits: usize) -> Vec<usize> {
-    // TODO: assert that `t` is between 1.0 and 2.0?
+///
+/// The threshold `t` must be strictly between 1.0 and 2.0, which
+/// [`parse_threshold()`](threshold::parse_threshold) checks before calling this.
+///
+/// The capacity at each level is less than `2^level`, so it fits in any type with `bits` bits.
+fn capacities_for_threshold(t: f64, bits: usize) -> Vec<u128> {
+    debug_assert!(t > 1.0 && t < 2.0);
     (0..bits)
-        .map(|b| ((2.0f64 / t).powi(b as i32).floor()) as usize)
+        .map(|b| ((2.0f64 / t).powi(b as i32).floor()) as u128)
         .collect()
 }
 
@@ -116,9 +610,144 @@ fn capacities_for_threshold(t: f64, bits: usize) -> Vec<usize> {
 mod tests {
     use super::*;
 
+    #[test]
+    fn usize_bits_per_pointer_width() {
+        let range: CapacitiesDecl = syn::parse_str("const C: [[1.1..=1.9; usize]; 17];").unwrap();
+        let out = range.generate().unwrap().to_string();
+        for bits in POINTER_WIDTHS {
+            assert!(out.contains(&format!("target_pointer_width = \"{bits}\"")));
+            assert!(out.contains(&format!("[[usize ; {bits}usize] ; 17usize]")));
+        }
+    }
+
+    #[test]
+    fn uint_bits() {
+        let range: CapacitiesDecl = syn::parse_str("const C: [[1.1..=1.9; u128]; 17];").unwrap();
+        let out = range.generate().unwrap().to_string();
+        assert!(out.contains("[[u128 ; 128usize] ; 17usize]"));
+        assert!(out.contains("const C_MAX : [u128 ; 17usize]"));
+        assert!(!out.contains("target_pointer_width"));
+
+        let range: CapacitiesDecl = syn::parse_str("const C: [[1.1..=1.9; u32]; 17];").unwrap();
+        let out = range.generate().unwrap().to_string();
+        assert!(out.contains("[[u32 ; 32usize] ; 17usize]"));
+    }
+
+    #[test]
+    fn threshold_metadata() {
+        let range: CapacitiesDecl = syn::parse_str("const C: [[1.1..1.5; 8]; 2];").unwrap();
+        let out = range.generate().unwrap().to_string();
+        assert!(out.contains("const C_THRESHOLDS : [f64 ; 2usize] = [1.1f64 , 1.3f64]"));
+        assert!(out.contains(
+            "const C_RATIOS : [(u64 , u64) ; 2usize] = [(11u64 , 10u64) , (13u64 , 10u64)]"
+        ));
+        assert!(out.contains("const C_MAX : [usize ; 2usize] = [65 , 20]"));
+        assert!(out.contains("const C_BREAKPOINTS : [usize ; 2usize] = [64 , 19]"));
+        // The metadata is checked against the table at compile time.
+        assert!(out.contains("C [t] [8usize - 1] == C_MAX [t]"));
+        assert!(out.contains("C_BREAKPOINTS [t] == C_MAX [t] . saturating_sub (1)"));
+    }
+
+    #[test]
+    fn lint_attributes() {
+        let decl: CapacitiesDecl = syn::parse_str(
+            "/// Docs.\n#[allow(clippy::unreadable_literal)] pub(crate) const C: [[1.1..1.9; 64]; 17];",
+        )
+        .unwrap();
+        let out = decl.generate().unwrap().to_string();
+        assert_eq!(out.matches("Docs.").count(), 1);
+        // The table, the check, and each of the four arrays of metadata.
+        assert_eq!(
+            out.matches("allow (clippy :: unreadable_literal)").count(),
+            6
+        );
+        assert!(out.contains("pub (crate) const C_MAX"));
+        assert!(out.contains("const _ : () ="));
+    }
+
+    #[test]
+    fn threshold_lists() {
+        let decl: CapacitiesDecl = syn::parse_str("const C: [1.1, 1.25, 1.5; 8];").unwrap();
+        let out = decl.generate().unwrap().to_string();
+        assert!(out.contains("const C : [[usize ; 8usize] ; 3usize]"));
+        assert!(out.contains("[(11u64 , 10u64) , (5u64 , 4u64) , (3u64 , 2u64)]"));
+    }
+
+    #[test]
+    fn emit_modes() {
+        let decl: CapacitiesDecl =
+            syn::parse_str("#[emit(lazy)] const C: [[1.1..1.5; 8]; 2];").unwrap();
+        let out = decl.generate().unwrap().to_string();
+        assert!(
+            out.contains("static C : :: std :: sync :: LazyLock < [[usize ; 8usize] ; 2usize] >")
+        );
+        assert!(!out.contains("emit"));
+
+        let decl: CapacitiesDecl =
+            syn::parse_str("#[emit(delta)] const C: [[1.1..1.5; usize]; 2];").unwrap();
+        let out = decl.generate().unwrap().to_string();
+        assert_eq!(out.matches("struct CTable").count(), 1);
+        for bits in POINTER_WIDTHS {
+            assert!(out.contains(&format!("target_pointer_width = \"{bits}\"")));
+        }
+        assert!(out.contains("const C : CTable = CTable"));
+    }
+
+    fn generate_err(decl: &str) -> String {
+        let range: CapacitiesDecl = syn::parse_str(decl).unwrap();
+        range.generate().unwrap_err().to_string()
+    }
+
+    #[test]
+    fn reject_invalid_ranges() {
+        assert_eq!(
+            generate_err("const C: [[1.0..=1.9; 64]; 17];"),
+            "threshold must be strictly between 1.0 and 2.0"
+        );
+        assert_eq!(
+            generate_err("const C: [[1.1..=2.5; 64]; 17];"),
+            "threshold must be strictly between 1.0 and 2.0"
+        );
+        assert_eq!(
+            generate_err("const C: [[1.9..=1.1; 64]; 17];"),
+            "threshold range is reversed: 1.1 is less than 1.9"
+        );
+        assert_eq!(
+            generate_err("const C: [[1.5..1.5; 64]; 17];"),
+            "exclusive threshold range is empty"
+        );
+        assert_eq!(
+            generate_err("const C: [1.5, 1.3; 64];"),
+            "thresholds must be in strictly increasing order"
+        );
+        assert_eq!(
+            generate_err("const C: [; 64];"),
+            "number of thresholds must be at least 1"
+        );
+        assert_eq!(
+            generate_err("const C: [[1.1..=1.9; 64]; 0];"),
+            "number of thresholds must be at least 1"
+        );
+        assert_eq!(
+            generate_err("const C: [[1.1..=1.9; 1000]; 17];"),
+            "number of bits must be between 1 and 64"
+        );
+        assert_eq!(
+            generate_err("const C: [[1.1..=1.9; 0]; 17];"),
+            "number of bits must be between 1 and 64"
+        );
+        assert_eq!(
+            syn::parse_str::<CapacitiesDecl>("const C: [[1.1..=1.9; i32]; 17];")
+                .err()
+                .unwrap()
+                .to_string(),
+            "expected number of bits, `usize`, or an unsigned integer type"
+        );
+    }
+
     #[test]
     fn check_t1_1() {
-        let t1_1: [usize; 64] = [
+        let t1_1: [u128; 64] = [
             1,
             1,
             3,
@@ -190,7 +819,7 @@ mod tests {
 
     #[test]
     fn test_t1_2() {
-        let t1_2: [usize; 64] = [
+        let t1_2: [u128; 64] = [
             1,
             1,
             2,
@@ -261,7 +890,7 @@ mod tests {
 
     #[test]
     fn check_t1_25() {
-        let t1_25: [usize; 64] = [
+        let t1_25: [u128; 64] = [
             1,
             1,
             2,
@@ -332,7 +961,7 @@ mod tests {
 
     #[test]
     fn check_t1_4() {
-        let t1_4: [usize; 64] = [
+        let t1_4: [u128; 64] = [
             1, 1, 2, 2, 4, 5, 8, 12, 17, 24, 35, 50, 72, 103, 147, 210, 300, 429, 614, 877, 1253,
             1790, 2557, 3653, 5219, 7456, 10652, 15217, 21739, 31056, 44366, 63381, 90544, 129349,
             184784, 263978, 377112, 538731, 769616, 1099452, 1570646, 2243780, 3205400, 4579143,
@@ -345,7 +974,7 @@ mod tests {
 
     #[test]
     fn check_t1_8() {
-        let t1_8: [usize; 64] = [
+        let t1_8: [u128; 64] = [
             1, 1, 1, 1, 1, 1, 1, 2, 2, 2, 2, 3, 3, 3, 4, 4, 5, 5, 6, 7, 8, 9, 10, 11, 12, 13, 15,
             17, 19, 21, 23, 26, 29, 32, 35, 39, 44, 49, 54, 60, 67, 75, 83, 92, 103, 114, 127, 141,
             157, 174, 194, 215, 239, 266, 295, 328, 365, 405, 450, 500, 556, 618, 687, 763,
@@ -355,7 +984,7 @@ mod tests {
 
     #[test]
     fn check_t1_85() {
-        let t1_85: [usize; 64] = [
+        let t1_85: [u128; 64] = [
             1, 1, 1, 1, 1, 1, 1, 1, 1, 2, 2, 2, 2, 2, 2, 3, 3, 3, 4, 4, 4, 5, 5, 6, 6, 7, 7, 8, 8,
             9, 10, 11, 12, 13, 14, 15, 16, 17, 19, 20, 22, 24, 26, 28, 30, 33, 36, 39, 42, 45, 49,
             53, 57, 62, 67, 72, 78, 85, 91, 99, 107, 116, 125, 135,
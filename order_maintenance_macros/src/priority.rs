@@ -0,0 +1,401 @@
+//! Definitions of priority types with custom parameters.
+use crate::{
+    capacities_for_threshold,
+    threshold::{parse_threshold, parse_threshold_list, parse_threshold_range, Threshold},
+    Bits, MAX_BITS,
+};
+use proc_macro2::{Ident, Literal, Span, TokenStream};
+use quote::{format_ident, quote};
+use syn::{
+    bracketed, parenthesized,
+    parse::{Parse, ParseStream},
+    punctuated::Punctuated,
+    token::{Bracket, Paren},
+    Attribute, LitFloat, LitInt, RangeLimits, Token, Visibility,
+};
+
+/// Thresholds used when none are specified, matching `tag_range::DefaultParams`, i.e., the
+/// exclusive range `1.1..1.9` with a count of 17.
+const DEFAULT_THRESHOLDS: (&str, &str, usize) = ("1.1", "1.9", 17);
+
+/// The value of a `threshold = ...` parameter.
+enum ThresholdParam {
+    /// A single threshold, e.g., `1.3`.
+    Single(LitFloat),
+    /// A range of thresholds, e.g., `1.1..=1.9` or `1.1..1.9`.
+    Range(LitFloat, RangeLimits, LitFloat),
+    /// An explicit list of thresholds, e.g., `[1.1, 1.25, 1.5]`.
+    List(Punctuated<LitFloat, Token![,]>, Span),
+}
+
+/// A `key = value` parameter.
+enum Param {
+    Threshold(Ident, ThresholdParam),
+    Count(Ident, LitInt),
+    Bits(Ident, Bits),
+}
+
+impl Parse for Param {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let key: Ident = input.parse()?;
+        let _eq: Token![=] = input.parse()?;
+        if key == "threshold" {
+            let threshold = if input.peek(Bracket) {
+                let content;
+                let bracket = bracketed!(content in input);
+                let list = content.parse_terminated(LitFloat::parse, Token![,])?;
+                ThresholdParam::List(list, bracket.span.join())
+            } else {
+                let begin = input.parse()?;
+                if input.peek(Token![..]) {
+                    ThresholdParam::Range(begin, input.parse()?, input.parse()?)
+                } else {
+                    ThresholdParam::Single(begin)
+                }
+            };
+            Ok(Self::Threshold(key, threshold))
+        } else if key == "count" {
+            Ok(Self::Count(key, input.parse()?))
+        } else if key == "bits" {
+            Ok(Self::Bits(key, input.parse()?))
+        } else {
+            Err(syn::Error::new(
+                key.span(),
+                "unknown parameter; expected `threshold`, `count`, or `bits`",
+            ))
+        }
+    }
+}
+
+impl Param {
+    fn key(&self) -> &Ident {
+        match self {
+            Param::Threshold(key, _) | Param::Count(key, _) | Param::Bits(key, _) => key,
+        }
+    }
+}
+
+/// Definition of a priority type.
+///
+/// Looks like this:
+///
+/// ```no_compile
+/// {vis?} {name}: {algorithm}({key} = {value}, ...)
+/// ```
+pub(crate) struct PriorityDef {
+    attrs: Vec<Attribute>,
+    vis: Visibility,
+    name: Ident,
+    _colon: Token![:],
+    algorithm: Ident,
+    _paren: Paren,
+    params: Punctuated<Param, Token![,]>,
+    _semi: Option<Token![;]>,
+}
+
+impl Parse for PriorityDef {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let content;
+        Ok(Self {
+            attrs: input.call(Attribute::parse_outer)?,
+            vis: input.parse()?,
+            name: input.parse()?,
+            _colon: input.parse()?,
+            algorithm: input.parse()?,
+            _paren: parenthesized!(content in input),
+            params: content.parse_terminated(Param::parse, Token![,])?,
+            _semi: input.parse()?,
+        })
+    }
+}
+
+impl PriorityDef {
+    pub(crate) fn generate(&self) -> syn::Result<TokenStream> {
+        let mut threshold = None;
+        let mut count = None;
+        let mut bits = None;
+
+        for param in &self.params {
+            let duplicate = match param {
+                Param::Threshold(_, t) => threshold.replace(t).is_some(),
+                Param::Count(_, c) => count.replace(c).is_some(),
+                Param::Bits(_, b) => bits.replace(b).is_some(),
+            };
+            if duplicate {
+                return Err(syn::Error::new(
+                    param.key().span(),
+                    "parameter specified more than once",
+                ));
+            }
+        }
+
+        let default_bits = Bits::Usize(Ident::new("usize", self.algorithm.span()));
+        let bits = bits.unwrap_or(&default_bits);
+        if let Bits::Uint(ident, n) = bits {
+            if *n > MAX_BITS {
+                return Err(syn::Error::new(
+                    ident.span(),
+                    format!("number of bits must be between 1 and {MAX_BITS}"),
+                ));
+            }
+        }
+
+        if self.algorithm == "tag_range" {
+            let thresholds = match (threshold, count) {
+                (Some(ThresholdParam::Single(t)), None) => vec![parse_threshold(t)?],
+                (Some(ThresholdParam::List(list, span)), None) => {
+                    parse_threshold_list(list, *span)?
+                }
+                (Some(ThresholdParam::Single(_) | ThresholdParam::List(..)), Some(c)) => {
+                    return Err(syn::Error::new(
+                        c.span(),
+                        "`count` requires a range of thresholds",
+                    ))
+                }
+                (Some(ThresholdParam::Range(begin, limits, end)), Some(c)) => {
+                    parse_threshold_range(begin, limits, end, c)?
+                }
+                (Some(ThresholdParam::Range(begin, limits, end)), None) => {
+                    let c = LitInt::new(&DEFAULT_THRESHOLDS.2.to_string(), end.span());
+                    parse_threshold_range(begin, limits, end, &c)?
+                }
+                (None, Some(c)) => {
+                    return Err(syn::Error::new(
+                        c.span(),
+                        "`count` requires a range of thresholds",
+                    ))
+                }
+                (None, None) => {
+                    let (begin, end, n) = DEFAULT_THRESHOLDS;
+                    let span = self.algorithm.span();
+                    parse_threshold_range(
+                        &LitFloat::new(begin, span),
+                        &RangeLimits::HalfOpen(Token![..](span)),
+                        &LitFloat::new(end, span),
+                        &LitInt::new(&n.to_string(), span),
+                    )?
+                }
+            };
+            self.generate_tag_range(&thresholds, bits)
+        } else if self.algorithm == "list_range" {
+            if let Some(param) = self
+                .params
+                .iter()
+                .find(|p| matches!(p, Param::Threshold(..) | Param::Count(..)))
+            {
+                return Err(syn::Error::new(
+                    param.key().span(),
+                    "list_range does not use thresholds",
+                ));
+            }
+            self.generate_list_range(bits)
+        } else {
+            Err(syn::Error::new(
+                self.algorithm.span(),
+                "unknown algorithm; expected `tag_range` or `list_range`",
+            ))
+        }
+    }
+
+    /// Generate the type alias and the (so far empty) parameters struct.
+    fn generate_type(&self, module: &str) -> TokenStream {
+        let attrs = &self.attrs;
+        let vis = &self.vis;
+        let name = &self.name;
+        let params = self.params_name();
+        let module = Ident::new(module, self.algorithm.span());
+        let doc = format!("Parameters for [`{name}`].");
+
+        quote! {
+            #( #attrs )*
+            #vis type #name = ::order_maintenance::#module::GenericPriority<#params>;
+
+            #[doc = #doc]
+            #[derive(Debug)]
+            #vis struct #params;
+        }
+    }
+
+    fn params_name(&self) -> Ident {
+        format_ident!("{}Params", self.name)
+    }
+
+    fn generate_tag_range(
+        &self,
+        thresholds: &[Threshold],
+        bits: &Bits,
+    ) -> syn::Result<TokenStream> {
+        let ty = self.generate_type("tag_range");
+        let params = self.params_name();
+        let count = thresholds.len();
+        let ratios: Vec<TokenStream> = thresholds
+            .iter()
+            .map(|&Threshold { num, den, .. }| quote! { (#num, #den) })
+            .collect();
+
+        let impls = bits.expand(|bits| {
+            let rows: Vec<Vec<u128>> = thresholds
+                .iter()
+                .map(|t| capacities_for_threshold(t.value, bits))
+                .collect();
+            let breakpoints = rows
+                .iter()
+                .map(|row| Literal::u128_unsuffixed(row[bits - 1].saturating_sub(1)));
+            let capas = rows.iter().map(|row| {
+                let row = row.iter().copied().map(Literal::u128_unsuffixed);
+                quote! { [#(#row),*] }
+            });
+            quote! {
+                const _: () = {
+                    const CAPACITIES: [[usize; #bits]; #count] = [#(#capas),*];
+                    const BREAKPOINTS: [usize; #count] = [#(#breakpoints),*];
+                    const RATIOS: [(u64, u64); #count] = [#(#ratios),*];
+
+                    // Sanity check the generated tables, so that a wrong table fails to compile
+                    // instead of panicking deep in relabeling.
+                    const _: () = {
+                        assert!(
+                            #bits <= usize::BITS as usize,
+                            "labels must fit in usize on this target"
+                        );
+                        assert!(
+                            <#params as ::order_maintenance::params::Capacities>::THRESHOLDS
+                                == CAPACITIES.len(),
+                            "number of thresholds must match the table of capacities"
+                        );
+                        let mut t = 0;
+                        while t < #count {
+                            let mut l = 1;
+                            while l < #bits {
+                                assert!(
+                                    CAPACITIES[t][l - 1] <= CAPACITIES[t][l],
+                                    "capacities must be non-decreasing within each row"
+                                );
+                                l += 1;
+                            }
+                            assert!(
+                                t == 0 || CAPACITIES[t - 1][#bits - 1] >= CAPACITIES[t][#bits - 1],
+                                "capacities must be non-increasing across thresholds"
+                            );
+                            assert!(
+                                BREAKPOINTS[t] == CAPACITIES[t][#bits - 1].saturating_sub(1),
+                                "breakpoints must be one less than the greatest capacities"
+                            );
+                            t += 1;
+                        }
+                    };
+
+                    impl ::order_maintenance::params::Params for #params {
+                        const BITS: usize = #bits;
+                    }
+
+                    impl ::order_maintenance::params::Capacities for #params {
+                        const THRESHOLDS: usize = #count;
+
+                        fn capacity(threshold: usize, level: usize) -> usize {
+                            CAPACITIES[threshold][level]
+                        }
+
+                        fn breakpoint(threshold: usize) -> usize {
+                            BREAKPOINTS[threshold]
+                        }
+
+                        fn threshold(threshold: usize) -> (u64, u64) {
+                            RATIOS[threshold]
+                        }
+                    }
+                };
+            }
+        })?;
+
+        Ok(quote! { #ty #impls })
+    }
+
+    fn generate_list_range(&self, bits: &Bits) -> syn::Result<TokenStream> {
+        let ty = self.generate_type("list_range");
+        let params = self.params_name();
+
+        let impls = bits.expand(|bits| {
+            quote! {
+                impl ::order_maintenance::params::Params for #params {
+                    const BITS: usize = #bits;
+                }
+            }
+        })?;
+
+        Ok(quote! { #ty #impls })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn generate_err(def: &str) -> String {
+        let def: PriorityDef = syn::parse_str(def).unwrap();
+        def.generate().unwrap_err().to_string()
+    }
+
+    #[test]
+    fn generates_alias_and_params() {
+        let def: PriorityDef =
+            syn::parse_str("pub MyPrio: tag_range(threshold = 1.3, bits = 32)").unwrap();
+        let out = def.generate().unwrap().to_string();
+        assert!(out.contains("pub type MyPrio = :: order_maintenance :: tag_range :: GenericPriority < MyPrioParams >"));
+        assert!(out.contains("pub struct MyPrioParams"));
+        assert!(out.contains("const BITS : usize = 32usize"));
+        assert!(out.contains("const THRESHOLDS : usize = 1usize"));
+        assert!(out.contains("[(13u64 , 10u64)]"));
+
+        let def: PriorityDef =
+            syn::parse_str("MyPrio: tag_range(threshold = [1.1, 1.25, 1.5])").unwrap();
+        let out = def.generate().unwrap().to_string();
+        assert!(out.contains("const THRESHOLDS : usize = 3usize"));
+    }
+
+    #[test]
+    fn checks_tables() {
+        let def: PriorityDef = syn::parse_str("P: tag_range(threshold = 1.3, bits = 64)").unwrap();
+        let out = def.generate().unwrap().to_string();
+        assert!(out.contains("const CAPACITIES : [[usize ; 64usize] ; 1usize]"));
+        assert!(out.contains("64usize <= usize :: BITS as usize"));
+        assert!(out.contains("\"capacities must be non-decreasing within each row\""));
+        assert!(out.contains("\"number of thresholds must match the table of capacities\""));
+
+        let def: PriorityDef = syn::parse_str("P: list_range(bits = 64)").unwrap();
+        let out = def.generate().unwrap().to_string();
+        assert!(!out.contains("CAPACITIES"));
+    }
+
+    #[test]
+    fn reject_invalid_definitions() {
+        assert_eq!(
+            generate_err("P: tag_range(threshold = 2.3)"),
+            "threshold must be strictly between 1.0 and 2.0"
+        );
+        assert_eq!(
+            generate_err("P: tag_range(bits = 32, bits = 16)"),
+            "parameter specified more than once"
+        );
+        assert_eq!(
+            generate_err("P: tag_range(threshold = 1.3, count = 4)"),
+            "`count` requires a range of thresholds"
+        );
+        assert_eq!(
+            generate_err("P: tag_range(threshold = [1.1, 1.3], count = 4)"),
+            "`count` requires a range of thresholds"
+        );
+        assert_eq!(
+            generate_err("P: list_range(threshold = 1.3)"),
+            "list_range does not use thresholds"
+        );
+        assert_eq!(
+            generate_err("P: list_range(bits = u128)"),
+            "number of bits must be between 1 and 64"
+        );
+        assert_eq!(
+            generate_err("P: skip_list()"),
+            "unknown algorithm; expected `tag_range` or `list_range`"
+        );
+    }
+}
@@ -0,0 +1,234 @@
+//! Parsing and validation of density thresholds.
+use proc_macro2::Span;
+use syn::{punctuated::Punctuated, LitFloat, LitInt, RangeLimits, Token};
+
+/// A density threshold, strictly between 1.0 and 2.0.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) struct Threshold {
+    /// The threshold as a float, used to compute capacities.
+    pub(crate) value: f64,
+    /// Numerator of the threshold as an exact ratio, in lowest terms.
+    pub(crate) num: u64,
+    /// Denominator of the threshold as an exact ratio, in lowest terms.
+    pub(crate) den: u64,
+}
+
+fn gcd(a: u128, b: u128) -> u128 {
+    if b == 0 {
+        a
+    } else {
+        gcd(b, a % b)
+    }
+}
+
+/// Construct a ratio in lowest terms, which must fit in a `u64`.
+fn ratio(num: u128, den: u128, lit: &LitFloat) -> syn::Result<(u64, u64)> {
+    let d = gcd(num, den);
+    match (u64::try_from(num / d), u64::try_from(den / d)) {
+        (Ok(num), Ok(den)) => Ok((num, den)),
+        _ => Err(syn::Error::new(
+            lit.span(),
+            "threshold has too many digits to be represented exactly",
+        )),
+    }
+}
+
+/// Parse the exact ratio that a decimal float literal denotes, e.g., `1.25` is `5/4`.
+fn parse_ratio(lit: &LitFloat) -> syn::Result<(u128, u128)> {
+    let too_long = || {
+        syn::Error::new(
+            lit.span(),
+            "threshold has too many digits to be represented exactly",
+        )
+    };
+
+    let digits = lit.base10_digits();
+    let (mantissa, exp) = match digits.split_once(['e', 'E']) {
+        Some((mantissa, exp)) => (mantissa, exp.parse::<i32>().map_err(|_| too_long())?),
+        None => (digits, 0),
+    };
+    let (int, frac) = mantissa.split_once('.').unwrap_or((mantissa, ""));
+
+    let mut num: u128 = format!("{int}{frac}").parse().map_err(|_| too_long())?;
+    let mut den: u128 = 10u128.checked_pow(frac.len() as u32).ok_or_else(too_long)?;
+    let scale = 10u128
+        .checked_pow(exp.unsigned_abs())
+        .ok_or_else(too_long)?;
+    if exp >= 0 {
+        num = num.checked_mul(scale).ok_or_else(too_long)?;
+    } else {
+        den = den.checked_mul(scale).ok_or_else(too_long)?;
+    }
+
+    let d = gcd(num, den);
+    Ok((num / d, den / d))
+}
+
+/// Parse a threshold, which must be strictly between 1.0 and 2.0.
+pub(crate) fn parse_threshold(lit: &LitFloat) -> syn::Result<Threshold> {
+    let value: f64 = lit.base10_parse()?;
+    if value <= 1.0 || value >= 2.0 {
+        return Err(syn::Error::new(
+            lit.span(),
+            "threshold must be strictly between 1.0 and 2.0",
+        ));
+    }
+    let (num, den) = parse_ratio(lit)?;
+    let (num, den) = ratio(num, den, lit)?;
+    Ok(Threshold { value, num, den })
+}
+
+/// Parse a range of `count` thresholds from `begin` to `end`, spaced evenly apart.
+///
+/// The range includes `end` if it is inclusive (`..=`), and stops one step short of `end` if it is
+/// exclusive (`..`).
+pub(crate) fn parse_threshold_range(
+    begin: &LitFloat,
+    limits: &RangeLimits,
+    end: &LitFloat,
+    count: &LitInt,
+) -> syn::Result<Vec<Threshold>> {
+    let (begin_t, end_t) = (parse_threshold(begin)?, parse_threshold(end)?);
+    if begin_t.value > end_t.value {
+        return Err(syn::Error::new(
+            end.span(),
+            format!(
+                "threshold range is reversed: {} is less than {}",
+                end_t.value, begin_t.value
+            ),
+        ));
+    }
+    if begin_t.value == end_t.value && matches!(limits, RangeLimits::HalfOpen(_)) {
+        return Err(syn::Error::new(
+            end.span(),
+            "exclusive threshold range is empty",
+        ));
+    }
+
+    let n: usize = count.base10_parse()?;
+    if n == 0 {
+        return Err(syn::Error::new(
+            count.span(),
+            "number of thresholds must be at least 1",
+        ));
+    }
+
+    // Number of gaps between begin and end; an inclusive range has one fewer than its count.
+    let steps = match limits {
+        RangeLimits::HalfOpen(_) => n,
+        RangeLimits::Closed(_) => (n - 1).max(1),
+    };
+
+    // Exactly, the ith threshold is begin + i * (end - begin) / steps; over a common denominator
+    // d, that is (b * steps + i * (e - b)) / (d * steps).
+    let (bn, bd) = (begin_t.num as u128, begin_t.den as u128);
+    let (en, ed) = (end_t.num as u128, end_t.den as u128);
+    let d = bd / gcd(bd, ed) * ed;
+    let (b, e) = (bn * (d / bd), en * (d / ed));
+    let steps128 = steps as u128;
+
+    let gap = (end_t.value - begin_t.value) / (steps as f64);
+    (0..n)
+        .map(|i| {
+            let (num, den) = ratio(b * steps128 + i as u128 * (e - b), d * steps128, end)?;
+            let value = if i == steps {
+                end_t.value
+            } else {
+                begin_t.value + (i as f64) * gap
+            };
+            Ok(Threshold { value, num, den })
+        })
+        .collect()
+}
+
+/// Parse an explicit list of thresholds, which must be non-empty and strictly increasing.
+///
+/// `span` is used to report an empty list.
+pub(crate) fn parse_threshold_list(
+    lits: &Punctuated<LitFloat, Token![,]>,
+    span: Span,
+) -> syn::Result<Vec<Threshold>> {
+    if lits.is_empty() {
+        return Err(syn::Error::new(
+            span,
+            "number of thresholds must be at least 1",
+        ));
+    }
+
+    let mut thresholds: Vec<Threshold> = Vec::with_capacity(lits.len());
+    for lit in lits {
+        let t = parse_threshold(lit)?;
+        if thresholds.last().is_some_and(|last| last.value >= t.value) {
+            return Err(syn::Error::new(
+                lit.span(),
+                "thresholds must be in strictly increasing order",
+            ));
+        }
+        thresholds.push(t);
+    }
+    Ok(thresholds)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn lit(s: &str) -> LitFloat {
+        syn::parse_str(s).unwrap()
+    }
+
+    #[test]
+    fn exact_ratios() {
+        assert_eq!(parse_ratio(&lit("1.25")).unwrap(), (5, 4));
+        assert_eq!(parse_ratio(&lit("1.1")).unwrap(), (11, 10));
+        assert_eq!(parse_ratio(&lit("125e-2")).unwrap(), (5, 4));
+    }
+
+    fn range(begin: &str, limits: &str, end: &str, count: &str) -> Vec<(f64, u64, u64)> {
+        let limits: RangeLimits = syn::parse_str(limits).unwrap();
+        let count: LitInt = syn::parse_str(count).unwrap();
+        parse_threshold_range(&lit(begin), &limits, &lit(end), &count)
+            .unwrap()
+            .into_iter()
+            .map(|t| (t.value, t.num, t.den))
+            .collect()
+    }
+
+    #[test]
+    fn range_ratios() {
+        let ratios: Vec<(u64, u64)> = range("1.1", "..", "1.9", "4")
+            .into_iter()
+            .map(|(_, num, den)| (num, den))
+            .collect();
+        assert_eq!(ratios, vec![(11, 10), (13, 10), (3, 2), (17, 10)]);
+    }
+
+    #[test]
+    fn inclusive_range_ends_at_end() {
+        let ts = range("1.1", "..=", "1.9", "5");
+        assert_eq!(ts.first().unwrap(), &(1.1, 11, 10));
+        assert_eq!(ts.last().unwrap(), &(1.9, 19, 10));
+        assert_eq!(ts[2].1, 3);
+        assert_eq!(ts[2].2, 2);
+
+        assert_eq!(range("1.3", "..=", "1.3", "1"), vec![(1.3, 13, 10)]);
+    }
+
+    #[test]
+    fn threshold_lists() {
+        let lits: Punctuated<LitFloat, Token![,]> =
+            syn::parse::Parser::parse_str(Punctuated::parse_terminated, "1.1, 1.25, 1.5").unwrap();
+        let ts = parse_threshold_list(&lits, Span::call_site()).unwrap();
+        let ratios: Vec<(u64, u64)> = ts.iter().map(|t| (t.num, t.den)).collect();
+        assert_eq!(ratios, vec![(11, 10), (5, 4), (3, 2)]);
+
+        let lits: Punctuated<LitFloat, Token![,]> =
+            syn::parse::Parser::parse_str(Punctuated::parse_terminated, "1.1, 1.5, 1.5").unwrap();
+        assert_eq!(
+            parse_threshold_list(&lits, Span::call_site())
+                .unwrap_err()
+                .to_string(),
+            "thresholds must be in strictly increasing order"
+        );
+    }
+}
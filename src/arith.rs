@@ -0,0 +1,227 @@
+//! Pure label arithmetic, separate from arenas so that it can be verified in isolation.
+//!
+//! Every function here is free of side effects and works on plain integers or slices of labels,
+//! which makes them amenable to model checking with [Kani](https://github.com/model-checking/kani):
+//! `cargo kani` checks the proof harnesses at the end of this module.
+
+/// Number of labels from `this` up to `next`, in a label space whose greatest label is `max`.
+///
+/// If `next` wraps around to the beginning of the label space, it is treated as if it were at
+/// `max + 1`. That may not fit in a `usize`, so the gap is computed as a `u128`.
+pub(crate) fn gap_to_next(this: usize, next: usize, max: usize) -> u128 {
+    if next <= this {
+        max as u128 + 1 - this as u128
+    } else {
+        (next - this) as u128
+    }
+}
+
+/// The label halfway from `this` up to `next`, as measured by [`gap_to_next()`].
+///
+/// This is strictly between them if the gap is greater than 1.
+pub(crate) fn midpoint(this: usize, next: usize, max: usize) -> usize {
+    this + (gap_to_next(this, next, max) / 2) as usize
+}
+
+/// The label halfway from `this` up to `next`, for labels relative to a base that wrap around
+/// within `mask`.
+pub(crate) fn masked_midpoint(this: usize, next: usize, mask: usize) -> usize {
+    let gap = next.wrapping_sub(this) & mask;
+    this.wrapping_add(gap / 2) & mask
+}
+
+/// The window of labels around `label` at `level`, i.e., the inclusive range of labels that only
+/// differ from it in their lowest `level` bits.
+///
+/// The window holds `2^level` labels; `level` must be less than `usize::BITS`.
+pub(crate) fn window(label: usize, level: usize) -> (usize, usize) {
+    let prefix = label >> level;
+    (prefix << level, !(!prefix << level))
+}
+
+/// The `k`th of `count` labels spread evenly across the `size` labels starting from `min`.
+///
+/// Gaps between consecutive labels differ by at most 1, with the larger gaps first.
+pub(crate) fn spread_label(min: usize, size: usize, count: usize, k: usize) -> usize {
+    let gap = size / count;
+    let rem = size % count;
+    min + k * gap + k.min(rem)
+}
+
+/// Spread `labels` evenly across the `size` labels starting from `min`, like [`spread_label()`].
+#[cfg_attr(not(test), allow(dead_code))]
+pub(crate) fn spread(labels: &mut [usize], min: usize, size: usize) {
+    let count = labels.len();
+    for (k, label) in labels.iter_mut().enumerate() {
+        *label = spread_label(min, size, count, k);
+    }
+}
+
+/// The permutation that stably sorts `keys`, i.e., the indices of `keys` in increasing order of
+/// key, with equal keys in their original order.
+///
+/// Sorts by least-significant-digit radix sort, one byte per pass, skipping bytes that are the
+/// same in every key.
+pub(crate) fn sort_indices(keys: &[usize]) -> Vec<usize> {
+    let mut order: Vec<usize> = (0..keys.len()).collect();
+    let mut scratch = vec![0; keys.len()];
+    let varying = keys.iter().fold(0, |acc, &k| acc | (k ^ keys[0]));
+    for shift in (0..usize::BITS).step_by(8) {
+        if (varying >> shift) & 0xff == 0 {
+            continue;
+        }
+        let digit = |i: usize| (keys[i] >> shift) & 0xff;
+        let mut starts = [0; 257];
+        for &i in &order {
+            starts[digit(i) + 1] += 1;
+        }
+        for d in 0..256 {
+            starts[d + 1] += starts[d];
+        }
+        for &i in &order {
+            let d = digit(i);
+            scratch[starts[d]] = i;
+            starts[d] += 1;
+        }
+        std::mem::swap(&mut order, &mut scratch);
+    }
+    order
+}
+
+/// Reorder `items` in place so that the `i`th item is the one that was at index `order[i]`, for a
+/// permutation `order`, e.g., one computed by [`sort_indices()`].
+pub(crate) fn permute<T>(items: &mut [T], mut order: Vec<usize>) {
+    for start in 0..items.len() {
+        // Follow the cycle through `start`, marking each index as done once it is in place.
+        let mut at = start;
+        while order[at] != at {
+            let from = order[at];
+            order[at] = at;
+            if from == start {
+                break;
+            }
+            items.swap(at, from);
+            at = from;
+        }
+    }
+}
+
+/// The ranges of the maximal runs of adjacent `items` that have equal keys under `key`.
+pub(crate) fn adjacent_runs<T, K: PartialEq>(
+    items: &[T],
+    mut key: impl FnMut(&T) -> K,
+) -> Vec<std::ops::Range<usize>> {
+    let mut runs = Vec::new();
+    let mut keys = items.iter().map(&mut key).enumerate();
+    let Some((_, mut current)) = keys.next() else {
+        return runs;
+    };
+    let mut start = 0;
+    for (i, k) in keys {
+        if k != current {
+            runs.push(start..i);
+            start = i;
+            current = k;
+        }
+    }
+    runs.push(start..items.len());
+    runs
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn midpoints() {
+        assert_eq!(midpoint(4, 8, 15), 6);
+        // The next label wraps around, so it is treated as 16.
+        assert_eq!(midpoint(12, 0, 15), 14);
+        assert_eq!(midpoint(0, 0, usize::MAX), 1 << (usize::BITS - 1));
+        assert_eq!(masked_midpoint(12, 2, 15), 15);
+        assert_eq!(masked_midpoint(12, 4, 15), 0);
+    }
+
+    #[test]
+    fn windows() {
+        assert_eq!(window(0b1011, 0), (0b1011, 0b1011));
+        assert_eq!(window(0b1011, 2), (0b1000, 0b1011));
+        assert_eq!(
+            window(usize::MAX, usize::BITS as usize - 1),
+            (1 << (usize::BITS - 1), usize::MAX)
+        );
+    }
+
+    #[test]
+    fn sorted_indices() {
+        assert!(sort_indices(&[]).is_empty());
+        assert_eq!(sort_indices(&[7, 7, 7]), vec![0, 1, 2]);
+        let keys = [0x300, 5, usize::MAX, 0x2ff, 5, 0];
+        assert_eq!(sort_indices(&keys), vec![5, 1, 4, 3, 0, 2]);
+    }
+
+    #[test]
+    fn permuted_in_place() {
+        let keys = [0x300, 5, usize::MAX, 0x2ff, 5, 0];
+        let mut items = keys;
+        permute(&mut items, sort_indices(&keys));
+        assert_eq!(items, [0, 5, 5, 0x2ff, 0x300, usize::MAX]);
+    }
+
+    #[test]
+    fn runs_of_equal_keys() {
+        assert!(adjacent_runs(&[0; 0], |&x| x).is_empty());
+        let items = [1, 3, 2, 4, 6, 5];
+        assert_eq!(adjacent_runs(&items, |x| x % 2), vec![0..2, 2..5, 5..6]);
+    }
+
+    #[test]
+    fn spread_within_windows() {
+        // Exhaustively check every dense enough window of narrow labels.
+        for level in 0..8 {
+            for label in 0..256 {
+                let (lo, hi) = window(label, level);
+                for count in 1..=1 << level {
+                    let mut labels = vec![0; count];
+                    spread(&mut labels, lo, hi - lo + 1);
+                    assert_eq!(labels[0], lo);
+                    assert!(labels[count - 1] <= hi);
+                    assert!(labels.windows(2).all(|w| w[0] < w[1]));
+                }
+            }
+        }
+    }
+}
+
+#[cfg(kani)]
+mod proofs {
+    use super::*;
+
+    /// Midpoints are strictly between labels with room between them, and within the label space.
+    #[kani::proof]
+    fn midpoint_between() {
+        let (this, next, max): (usize, usize, usize) = (kani::any(), kani::any(), kani::any());
+        kani::assume(this <= max && next <= max);
+        kani::assume(gap_to_next(this, next, max) > 1);
+        let mid = midpoint(this, next, max);
+        assert!(this < mid && mid <= max);
+        assert!(next <= this || mid < next);
+    }
+
+    /// Spreading at most one label per slot of a window keeps labels increasing, within it.
+    #[kani::proof]
+    fn spread_within_window() {
+        let (label, level, count, k): (usize, usize, usize, usize) =
+            (kani::any(), kani::any(), kani::any(), kani::any());
+        kani::assume(level < usize::BITS as usize);
+        kani::assume(0 < count && count <= 1 << level);
+        kani::assume(k < count - 1);
+        let (lo, hi) = window(label, level);
+        let size = 1 << level;
+        let (this, next) = (
+            spread_label(lo, size, count, k),
+            spread_label(lo, size, count, k + 1),
+        );
+        assert!(lo <= this && this < next && next <= hi);
+    }
+}
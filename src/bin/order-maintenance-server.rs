@@ -0,0 +1,22 @@
+//! Serves one maintained order to other processes, over a Unix socket; see the `ipc` module of
+//! the library for the protocol.
+//!
+//! Usage: `order-maintenance-server SOCKET`
+//!
+//! Clients cannot stop the server, so it serves until its process is killed.
+
+#[cfg(unix)]
+fn main() -> std::io::Result<()> {
+    let Some(path) = std::env::args_os().nth(1) else {
+        eprintln!("usage: order-maintenance-server SOCKET");
+        std::process::exit(2);
+    };
+    order_maintenance::ipc::Server::bind(path)?.serve();
+    Ok(())
+}
+
+#[cfg(not(unix))]
+fn main() {
+    eprintln!("order-maintenance-server only runs on Unix");
+    std::process::exit(1);
+}
@@ -0,0 +1,172 @@
+//! Loading large unordered datasets into a fresh arena.
+//!
+//! Inserting records one by one, in the order of their keys, means inserting each priority at an
+//! arbitrary position, and relabeling whenever that position is crowded. A [`BulkLoader`] instead
+//! buffers records, sorting them a chunk at a time as they arrive, and only allocates priorities
+//! once every record is known, in [`finish()`](BulkLoader::finish): as one evenly spaced
+//! [sequence](crate::MaintainedOrd::new_sequence), without relabeling anything.
+//!
+//! ```rust
+//! # use order_maintenance::{bulk::BulkLoader, tag_range::Priority};
+//! let mut loader = BulkLoader::with_chunk_size(2);
+//! loader.extend([(30, "c"), (10, "a"), (20, "b")]);
+//! assert_eq!(loader.sorted_chunks(), 1);
+//!
+//! let loaded: Vec<(i32, &str, Priority)> = loader.finish();
+//! assert_eq!(loaded[0].1, "a");
+//! assert!(loaded[0].2 < loaded[1].2 && loaded[1].2 < loaded[2].2);
+//! ```
+//!
+//! Producers can apply back-pressure by watching [`sorted_chunks()`](BulkLoader::sorted_chunks)
+//! and [`len()`](BulkLoader::len), since a loader holds every record until it is finished.
+use crate::MaintainedOrd;
+use std::cmp::{Ordering, Reverse};
+use std::collections::BinaryHeap;
+
+/// Records are sorted in chunks of this many by default.
+pub const DEFAULT_CHUNK_SIZE: usize = 1 << 16;
+
+/// Buffers records of keys of type `K` and payloads of type `V`, to allocate priorities for them
+/// in order of their keys.
+#[derive(Debug, Clone)]
+pub struct BulkLoader<K, V> {
+    /// Chunks of records that are sorted by key.
+    sorted: Vec<Vec<(K, V)>>,
+    /// Records that have yet to be sorted, fewer than `chunk_size`.
+    pending: Vec<(K, V)>,
+    chunk_size: usize,
+}
+
+impl<K: Ord, V> BulkLoader<K, V> {
+    /// A loader that sorts records in chunks of [`DEFAULT_CHUNK_SIZE`].
+    pub fn new() -> Self {
+        Self::with_chunk_size(DEFAULT_CHUNK_SIZE)
+    }
+
+    /// A loader that sorts records in chunks of `chunk_size`.
+    ///
+    /// Chunks grow as records are pushed, so a large `chunk_size` does not allocate anything up
+    /// front. Panics if `chunk_size` is 0.
+    pub fn with_chunk_size(chunk_size: usize) -> Self {
+        assert!(chunk_size > 0, "chunks must hold some records");
+        Self {
+            sorted: Vec::new(),
+            pending: Vec::new(),
+            chunk_size,
+        }
+    }
+
+    /// Buffer the record with key `key` and payload `payload`, sorting the current chunk if it is
+    /// full.
+    pub fn push(&mut self, key: K, payload: V) {
+        self.pending.push((key, payload));
+        if self.pending.len() == self.chunk_size {
+            let mut chunk = std::mem::take(&mut self.pending);
+            chunk.sort_by(|a, b| a.0.cmp(&b.0));
+            self.sorted.push(chunk);
+        }
+    }
+
+    /// Number of records buffered so far.
+    pub fn len(&self) -> usize {
+        self.sorted.iter().map(Vec::len).sum::<usize>() + self.pending.len()
+    }
+
+    /// Whether no records were buffered yet.
+    pub fn is_empty(&self) -> bool {
+        self.sorted.is_empty() && self.pending.is_empty()
+    }
+
+    /// Number of full chunks that were sorted so far.
+    pub fn sorted_chunks(&self) -> usize {
+        self.sorted.len()
+    }
+
+    /// Allocate a priority for each record, in a fresh arena of priorities of type `P`.
+    ///
+    /// Returns the records in increasing order of key, each with its priority, which increase
+    /// along with the keys. Records with equal keys get distinct priorities, in the order that
+    /// they were pushed.
+    pub fn finish<P: MaintainedOrd>(mut self) -> Vec<(K, V, P)> {
+        self.pending.sort_by(|a, b| a.0.cmp(&b.0));
+        self.sorted.push(self.pending);
+        let records = merge(self.sorted);
+        let ps = P::new_sequence(records.len());
+        records
+            .into_iter()
+            .zip(ps)
+            .map(|((key, payload), p)| (key, payload, p))
+            .collect()
+    }
+}
+
+impl<K: Ord, V> Default for BulkLoader<K, V> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<K: Ord, V> Extend<(K, V)> for BulkLoader<K, V> {
+    fn extend<I: IntoIterator<Item = (K, V)>>(&mut self, records: I) {
+        for (key, payload) in records {
+            self.push(key, payload);
+        }
+    }
+}
+
+/// The next record of a chunk, ordered by key, then by the index of its chunk.
+struct Head<K, V> {
+    key: K,
+    chunk: usize,
+    payload: V,
+}
+
+impl<K: Ord, V> PartialEq for Head<K, V> {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl<K: Ord, V> Eq for Head<K, V> {}
+
+impl<K: Ord, V> PartialOrd for Head<K, V> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<K: Ord, V> Ord for Head<K, V> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        (&self.key, self.chunk).cmp(&(&other.key, other.chunk))
+    }
+}
+
+/// Merge sorted chunks into one sorted sequence, keeping records with equal keys in the order of
+/// their chunks.
+fn merge<K: Ord, V>(chunks: Vec<Vec<(K, V)>>) -> Vec<(K, V)> {
+    let len = chunks.iter().map(Vec::len).sum();
+    let mut chunks: Vec<_> = chunks.into_iter().map(Vec::into_iter).collect();
+    let mut heads = BinaryHeap::with_capacity(chunks.len());
+    for (chunk, records) in chunks.iter_mut().enumerate() {
+        if let Some((key, payload)) = records.next() {
+            heads.push(Reverse(Head {
+                key,
+                chunk,
+                payload,
+            }));
+        }
+    }
+
+    let mut merged = Vec::with_capacity(len);
+    while let Some(Reverse(head)) = heads.pop() {
+        if let Some((key, payload)) = chunks[head.chunk].next() {
+            heads.push(Reverse(Head {
+                key,
+                chunk: head.chunk,
+                payload,
+            }));
+        }
+        merged.push((head.key, head.payload));
+    }
+    merged
+}
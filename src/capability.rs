@@ -0,0 +1,205 @@
+//! Compile-time capabilities of priority types.
+//!
+//! Algorithms trade capabilities off against each other: tag-range priorities compare in constant
+//! time but can run out of labels, whereas big priorities never run out of labels but compare in
+//! time proportional to the length of their labels. Code that relies on one of these capabilities
+//! can bound its priority type on the marker trait for it, so that choosing an unsuitable
+//! algorithm is a compile error rather than a panic or a slowdown at run time:
+//!
+//! ```rust
+//! # use order_maintenance::{capability::ConstantTimeCompare, tag_range::Priority, MaintainedOrd};
+//! fn hot_loop<P: MaintainedOrd + ConstantTimeCompare>(ps: &[P]) -> usize {
+//!     ps.windows(2).filter(|w| w[0] < w[1]).count()
+//! }
+//!
+//! assert_eq!(hot_loop(&Priority::new_sequence(3)), 2);
+//! ```
+//!
+//! Tag-range priorities have a bounded label space, so they are rejected where unbounded capacity
+//! is required:
+//!
+//! ```rust,compile_fail
+//! # use order_maintenance::{capability::UnboundedCapacity, tag_range::Priority, MaintainedOrd};
+//! fn grow_forever<P: MaintainedOrd + UnboundedCapacity>(p: P) {}
+//!
+//! grow_forever(Priority::new());
+//! ```
+//!
+//! Wrappers such as [`Rc`](std::rc::Rc) and [`Descending`](crate::descending::Descending) have the
+//! capabilities of the priorities they wrap.
+use crate::internal::CapacityContext;
+use std::fmt::Display;
+
+/// Priorities that compare in constant time, regardless of how many priorities their arena
+/// holds.
+pub trait ConstantTimeCompare {}
+
+/// Priorities whose arenas never run out of labels, so inserting them never panics for lack of
+/// capacity.
+pub trait UnboundedCapacity {}
+
+/// Priorities whose arenas report running out of labels as an error, rather than by panicking.
+///
+/// ```rust
+/// # use order_maintenance::{capability::TryInsert, define_priority, MaintainedOrd};
+/// define_priority! {
+///     /// Tag-range priorities with 4-bit labels.
+///     Tiny: tag_range(threshold = 1.3, bits = 4)
+/// }
+///
+/// let first = Tiny::new();
+/// let mut ps = vec![];
+/// let error = loop {
+///     match first.try_insert() {
+///         Ok(p) => ps.push(p),
+///         Err(e) => break e,
+///     }
+/// };
+/// assert_eq!(error.live(), ps.len() + 1);
+/// ```
+pub trait TryInsert: crate::MaintainedOrd + Sized {
+    /// Insert a new priority right after this one, like
+    /// [`MaintainedOrd::insert()`](crate::MaintainedOrd::insert), unless its arena has run out
+    /// of labels for it or would exceed its quota, in which case the arena is left as is.
+    fn try_insert(&self) -> Result<Self, CapacityError>;
+}
+
+/// Error returned when an arena has run out of labels for another priority, or has reached one
+/// of its quotas.
+///
+/// Its message is the one that inserting into the arena would have panicked with.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CapacityError {
+    context: CapacityContext,
+    kind: CapacityErrorKind,
+}
+
+/// What an arena ran out of, refusing to insert another priority.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum CapacityErrorKind {
+    /// Labels for the priority.
+    Labels,
+    /// Its quota of live priorities; see
+    /// [`PriorityConfig::max_live()`](crate::config::PriorityConfig::max_live).
+    LiveQuota {
+        /// Maximum number of priorities that the arena may hold.
+        max: usize,
+    },
+    /// Its quota of relabeling work; see
+    /// [`PriorityConfig::max_relabeled_per_sec()`](crate::config::PriorityConfig::max_relabeled_per_sec).
+    RelabelQuota {
+        /// Maximum number of priorities that may be relabeled per second.
+        max_per_sec: u64,
+    },
+}
+
+impl CapacityError {
+    /// An error for running out of labels, in the arena and circumstances described by `context`.
+    pub(crate) fn new(context: CapacityContext) -> Self {
+        Self::with_kind(context, CapacityErrorKind::Labels)
+    }
+
+    /// An error for running out of `kind`, in the arena described by `context`.
+    pub(crate) fn with_kind(context: CapacityContext, kind: CapacityErrorKind) -> Self {
+        Self { context, kind }
+    }
+
+    /// What the arena ran out of.
+    pub fn kind(&self) -> CapacityErrorKind {
+        self.kind
+    }
+
+    /// Number of priorities in the arena, including any that it hides, e.g., its base priority.
+    pub fn live(&self) -> usize {
+        self.context.live
+    }
+
+    /// Identifier of the arena, as shown by the `Debug` output of its priorities.
+    pub fn arena_id(&self) -> usize {
+        self.context.arena_id
+    }
+}
+
+impl Display for CapacityError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let context = &self.context;
+        match (self.kind, context.window) {
+            (CapacityErrorKind::LiveQuota { max }, _) => {
+                write!(f, "Quota of {max} live priorities was reached ({context})")
+            }
+            (CapacityErrorKind::RelabelQuota { max_per_sec }, _) => write!(
+                f,
+                "Quota of {max_per_sec} relabeled priorities per second was reached ({context})"
+            ),
+            (CapacityErrorKind::Labels, Some(_)) => write!(
+                f,
+                "Too many priorities were inserted, the root is overflowing! ({context})"
+            ),
+            (CapacityErrorKind::Labels, None) => write!(
+                f,
+                "Too many priorities were inserted: {} ({context})",
+                context.live
+            ),
+        }
+    }
+}
+
+impl std::error::Error for CapacityError {}
+
+/// Priorities that can be inserted before every other priority in their arena.
+///
+/// Inserting after a priority can never produce the least priority of an arena, so this is the
+/// only way to extend an order at its front. Implementations take care of whatever the start of
+/// their label space requires, e.g., relabeling the least priority if its label is the least label.
+pub trait InsertFront: crate::MaintainedOrd {
+    /// Insert a new priority before every other priority in this priority's arena.
+    fn insert_front(&self) -> Self;
+}
+
+/// Priorities that can be inserted before every other priority in their arena, reporting running
+/// out of labels as an error, like [`TryInsert`].
+///
+/// ```rust
+/// # use order_maintenance::{capability::TryInsertFront, tag_range::CappedPriority};
+/// let last = CappedPriority::<2>::new();
+/// let first = last.try_insert_front().unwrap();
+/// assert!(first < last);
+/// assert!(last.try_insert_front().is_err());
+/// ```
+pub trait TryInsertFront: InsertFront + TryInsert {
+    /// Insert a new priority before every other priority in this priority's arena, like
+    /// [`InsertFront::insert_front()`], unless its arena has run out of labels for it or would
+    /// exceed its quota, in which case the arena is left as is.
+    fn try_insert_front(&self) -> Result<Self, CapacityError>;
+}
+
+/// Priorities that can be sent to and shared between threads.
+///
+/// This is implemented for every priority type that is [`Send`] and [`Sync`]. None of the
+/// priority types in this crate is, since their handles share their arenas through
+/// [`Rc`](std::rc::Rc); the trait lets generic code spell out that requirement regardless.
+pub trait ThreadSafe: Send + Sync {}
+
+impl<P: crate::MaintainedOrd + Send + Sync> ThreadSafe for P {}
+
+impl<P: ConstantTimeCompare> ConstantTimeCompare for std::rc::Rc<P> {}
+
+impl<P: UnboundedCapacity> UnboundedCapacity for std::rc::Rc<P> {}
+
+impl<P: TryInsert> TryInsert for std::rc::Rc<P> {
+    fn try_insert(&self) -> Result<Self, CapacityError> {
+        (**self).try_insert().map(std::rc::Rc::new)
+    }
+}
+
+impl<P: InsertFront> InsertFront for std::rc::Rc<P> {
+    fn insert_front(&self) -> Self {
+        std::rc::Rc::new((**self).insert_front())
+    }
+}
+
+impl<P: TryInsertFront> TryInsertFront for std::rc::Rc<P> {
+    fn try_insert_front(&self) -> Result<Self, CapacityError> {
+        (**self).try_insert_front().map(std::rc::Rc::new)
+    }
+}
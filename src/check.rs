@@ -0,0 +1,174 @@
+//! Checking the internal invariants of arenas.
+//!
+//! Invariants can be checked on demand, or sampled as an arena is used: with sampling enabled,
+//! every `n`th insertion or removal in an arena also checks its invariants and records the result
+//! in a [`SelfCheckReport`]. Each check takes time linear in the size of the arena, so sampling
+//! adds `O(size / n)` amortized time per operation.
+//!
+//! [`self_test()`] runs a quick battery of checks on a priority type instead, e.g., at startup:
+//!
+//! ```rust
+//! # use order_maintenance::{self_test, tag_range::Priority};
+//! let report = self_test::<Priority>();
+//! assert!(report.is_ok(), "{:?}", report.problems);
+//! ```
+
+use crate::InArena;
+use std::fmt::Display;
+
+/// A violation of the internal invariants of an arena, found by checking them.
+///
+/// Priorities are identified by their key in the arena.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Violation {
+    /// A priority's list pointers refer to a priority that does not exist.
+    DanglingLink {
+        /// The priority whose pointers are dangling.
+        key: usize,
+    },
+    /// A priority's next priority does not point back to it.
+    BrokenLink {
+        /// The priority whose next priority does not point back to it.
+        key: usize,
+    },
+    /// A priority is allocated without any handles to it.
+    Unreferenced {
+        /// The unreferenced priority.
+        key: usize,
+    },
+    /// A priority's label is out of the label space, or not greater than the previous priority's.
+    Unordered {
+        /// The unordered priority.
+        key: usize,
+    },
+    /// The list of priorities does not contain every priority in the arena.
+    Miscounted {
+        /// The number of priorities that the arena is meant to contain.
+        total: usize,
+        /// The number of priorities found in the list.
+        found: usize,
+    },
+}
+
+impl Display for Violation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Violation::DanglingLink { key } => write!(f, "priority {key} has a dangling link"),
+            Violation::BrokenLink { key } => {
+                write!(
+                    f,
+                    "next priority of priority {key} does not link back to it"
+                )
+            }
+            Violation::Unreferenced { key } => write!(f, "priority {key} has no references"),
+            Violation::Unordered { key } => write!(f, "priority {key} has an out-of-order label"),
+            Violation::Miscounted { total, found } => {
+                write!(f, "arena should have {total} priorities, but found {found}")
+            }
+        }
+    }
+}
+
+impl std::error::Error for Violation {}
+
+/// Results of the invariant checks sampled in an arena.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SelfCheckReport {
+    /// Number of checks performed.
+    pub checks: u64,
+    /// Number of checks that found a violation.
+    pub failures: u64,
+    /// The first violation found, if any.
+    pub first_violation: Option<Violation>,
+}
+
+impl SelfCheckReport {
+    /// Record the result of a check.
+    pub(crate) fn record(&mut self, result: Result<(), Violation>) {
+        self.checks += 1;
+        if let Err(violation) = result {
+            self.failures += 1;
+            self.first_violation.get_or_insert(violation);
+        }
+    }
+}
+
+/// Priorities whose arenas' invariants can be checked, e.g., by [`self_test()`].
+pub trait Checked: InArena + Clone {
+    /// Check the internal invariants of the arena that this priority belongs to.
+    fn check_invariants(&self) -> Result<(), Violation>;
+
+    /// The greatest number of priorities that an arena of this type can hold.
+    fn capacity() -> usize;
+
+    /// Problems with the compile-time parameters of this priority type, e.g., its capacities.
+    fn param_problems() -> Vec<String>;
+}
+
+/// Results of a [`self_test()`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SelfTestReport {
+    /// Number of checks performed.
+    pub checks: usize,
+    /// Descriptions of the problems found, if any.
+    pub problems: Vec<String>,
+}
+
+impl SelfTestReport {
+    /// Whether every check passed.
+    pub fn is_ok(&self) -> bool {
+        self.problems.is_empty()
+    }
+}
+
+/// Maximum number of priorities allocated by each workload of a [`self_test()`].
+const SELF_TEST_SIZE: usize = 1000;
+
+/// Run a quick battery of checks on the priority type `P`, on the current target.
+///
+/// This checks `P`'s parameters, e.g., that its capacity tables are consistent with its label
+/// width, then runs a few small workloads of insertions and removals, checking the order of the
+/// priorities and the invariants of their arena along the way. It takes a few milliseconds.
+pub fn self_test<P: Checked>() -> SelfTestReport {
+    let mut report = SelfTestReport {
+        checks: 1,
+        problems: P::param_problems(),
+    };
+
+    // Each workload chooses where to insert the `i`th priority, given the number so far.
+    type Workload = (&'static str, fn(usize, usize) -> usize);
+    let workloads: [Workload; 3] = [
+        ("append", |_, len| len - 1),
+        ("insert-front", |_, _| 0),
+        ("scattered", |i, len| i * 7919 % len),
+    ];
+    // Leave plenty of room in label spaces that are too narrow for the full workload.
+    let size = SELF_TEST_SIZE.min(P::capacity() / 2);
+    for (name, next_index) in workloads {
+        // The priorities of the arena, kept in increasing order.
+        let mut ps = vec![P::new()];
+        for i in 1..size {
+            let j = next_index(i, ps.len());
+            let p = ps[j].insert();
+            ps.insert(j + 1, p);
+            if i % 4 == 0 {
+                ps.remove(i * 31 % ps.len());
+            }
+
+            if i % (size / 8).max(1) == 0 || i + 1 == size {
+                report.checks += 1;
+                if let Err(violation) = ps[0].check_invariants() {
+                    report.problems.push(format!("{name}: {violation}"));
+                    break;
+                }
+                if !ps.windows(2).all(|w| w[0] < w[1]) || ps[0].arena_priorities() != ps {
+                    report
+                        .problems
+                        .push(format!("{name}: priorities out of order"));
+                    break;
+                }
+            }
+        }
+    }
+    report
+}
@@ -0,0 +1,256 @@
+//! Tracking the changes made to an arena since a checkpoint, for incremental persistence.
+//!
+//! Only available with the `checkpoint` feature. Once a [`Checkpoint`] has been taken in an arena,
+//! the arena logs every priority that is inserted, removed or relabeled from then on, so that
+//! [`Checkpointed::changes_since()`] can report just what must be persisted to bring a copy from
+//! that checkpoint up to date, rather than a full snapshot:
+//!
+//! ```rust
+//! # use order_maintenance::{checkpoint::Checkpointed, tag_range::Priority, MaintainedOrd};
+//! let ps = Priority::new_sequence(1000);
+//! let token = ps[0].checkpoint();
+//!
+//! let p = ps[500].insert();
+//! let changes = ps[0].changes_since(&token).unwrap();
+//! assert_eq!(changes.inserted.len(), 1);
+//! assert!(changes.removed.is_empty());
+//! # drop(p);
+//! ```
+//!
+//! Priorities are identified by their keys in the arena, which are reused once priorities are
+//! removed; a key that was removed and then reused is reported as both removed and inserted.
+use std::collections::HashMap;
+
+/// A point in the history of an arena, returned by [`Checkpointed::checkpoint()`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Checkpoint {
+    /// Identifier of the arena that the checkpoint was taken in.
+    arena: usize,
+    /// Number of changes logged in the arena before the checkpoint.
+    position: u64,
+}
+
+/// A priority whose change since a checkpoint is reported by [`Checkpointed::changes_since()`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct NodeChange {
+    /// The key that identifies the priority in its arena.
+    pub key: usize,
+    /// The current raw label of the priority.
+    pub label: usize,
+}
+
+/// The net changes made to an arena since a checkpoint.
+///
+/// Each list is sorted by key. Priorities that were inserted and then removed since the checkpoint
+/// do not appear at all, and inserted priorities are not also reported as relabeled.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Changes {
+    /// Priorities inserted since the checkpoint, with their current labels.
+    pub inserted: Vec<NodeChange>,
+    /// Keys of priorities that were removed since the checkpoint.
+    pub removed: Vec<usize>,
+    /// Priorities that were relabeled since the checkpoint, with their current labels.
+    pub relabeled: Vec<NodeChange>,
+}
+
+impl Changes {
+    /// Whether nothing has changed since the checkpoint.
+    pub fn is_empty(&self) -> bool {
+        self.inserted.is_empty() && self.removed.is_empty() && self.relabeled.is_empty()
+    }
+}
+
+/// Priorities whose arenas can track their changes since a checkpoint.
+pub trait Checkpointed {
+    /// Take a checkpoint in the arena that this priority belongs to.
+    ///
+    /// The arena only starts logging changes at its first checkpoint.
+    fn checkpoint(&self) -> Checkpoint;
+
+    /// The net changes made to this priority's arena since `checkpoint`.
+    ///
+    /// Returns `None` if `checkpoint` was taken in another arena, or its changes were discarded.
+    fn changes_since(&self, checkpoint: &Checkpoint) -> Option<Changes>;
+
+    /// The key that identifies this priority in [`Changes`].
+    fn change_key(&self) -> usize;
+
+    /// Discard the logged changes made before `checkpoint` in this priority's arena, e.g., once
+    /// they have been persisted, so that the log does not grow without bound.
+    ///
+    /// Changes since earlier checkpoints are no longer available afterwards.
+    fn discard_changes_before(&self, checkpoint: &Checkpoint);
+}
+
+/// A change logged in an arena.
+#[derive(Debug, Clone, Copy)]
+enum Change {
+    Inserted(usize),
+    Removed(usize),
+    Relabeled(usize),
+}
+
+/// Net effect of the changes to a single key.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Net {
+    Inserted,
+    Removed,
+    /// Removed, and then reused by an inserted priority.
+    Replaced,
+    Relabeled,
+}
+
+/// Log of the changes made to an arena since its first checkpoint.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct ChangeLog {
+    /// Number of changes discarded from the front of the log.
+    discarded: u64,
+    changes: Vec<Change>,
+}
+
+impl ChangeLog {
+    pub(crate) fn inserted(&mut self, key: usize) {
+        self.changes.push(Change::Inserted(key));
+    }
+
+    pub(crate) fn removed(&mut self, key: usize) {
+        self.changes.push(Change::Removed(key));
+    }
+
+    pub(crate) fn relabeled(&mut self, key: usize) {
+        self.changes.push(Change::Relabeled(key));
+    }
+
+    /// Take a checkpoint at the end of the log, in the arena with the given identifier.
+    pub(crate) fn checkpoint(&self, arena: usize) -> Checkpoint {
+        Checkpoint {
+            arena,
+            position: self.discarded + self.changes.len() as u64,
+        }
+    }
+
+    /// The changes logged since `checkpoint`, which must have been taken in the arena with the
+    /// given identifier, looking up the current label of each key with `label`.
+    pub(crate) fn since(
+        &self,
+        arena: usize,
+        checkpoint: &Checkpoint,
+        label: impl Fn(usize) -> usize,
+    ) -> Option<Changes> {
+        let start = self.offset(arena, checkpoint)?;
+        let mut net: HashMap<usize, Net> = HashMap::new();
+        for change in &self.changes[start..] {
+            match *change {
+                Change::Inserted(key) => {
+                    let was = net.get(&key).copied();
+                    net.insert(
+                        key,
+                        if was == Some(Net::Removed) {
+                            Net::Replaced
+                        } else {
+                            Net::Inserted
+                        },
+                    );
+                }
+                Change::Removed(key) => match net.get(&key) {
+                    Some(Net::Inserted) => {
+                        net.remove(&key);
+                    }
+                    _ => {
+                        net.insert(key, Net::Removed);
+                    }
+                },
+                Change::Relabeled(key) => {
+                    net.entry(key).or_insert(Net::Relabeled);
+                }
+            }
+        }
+
+        let mut keys: Vec<(usize, Net)> = net.into_iter().collect();
+        keys.sort_unstable_by_key(|&(key, _)| key);
+        let mut changes = Changes::default();
+        for (key, net) in keys {
+            let node = || NodeChange {
+                key,
+                label: label(key),
+            };
+            match net {
+                Net::Inserted => changes.inserted.push(node()),
+                Net::Removed => changes.removed.push(key),
+                Net::Replaced => {
+                    changes.removed.push(key);
+                    changes.inserted.push(node());
+                }
+                Net::Relabeled => changes.relabeled.push(node()),
+            }
+        }
+        Some(changes)
+    }
+
+    /// Discard the changes logged before `checkpoint`, which must have been taken in the arena
+    /// with the given identifier.
+    pub(crate) fn discard_before(&mut self, arena: usize, checkpoint: &Checkpoint) {
+        if let Some(start) = self.offset(arena, checkpoint) {
+            self.changes.drain(..start);
+            self.discarded = checkpoint.position;
+        }
+    }
+
+    /// The index in the log at which `checkpoint` was taken, if it is still in the log.
+    fn offset(&self, arena: usize, checkpoint: &Checkpoint) -> Option<usize> {
+        if checkpoint.arena != arena {
+            return None;
+        }
+        let start = checkpoint.position.checked_sub(self.discarded)?;
+        usize::try_from(start)
+            .ok()
+            .filter(|&start| start <= self.changes.len())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn net_changes() {
+        let mut log = ChangeLog::default();
+        log.inserted(1);
+        let token = log.checkpoint(0);
+        log.inserted(2);
+        log.relabeled(2);
+        log.relabeled(1);
+        log.inserted(3);
+        log.removed(3);
+        log.removed(1);
+        log.inserted(1);
+
+        let changes = log.since(0, &token, |key| key * 10).unwrap();
+        let node = |key| NodeChange {
+            key,
+            label: key * 10,
+        };
+        assert_eq!(changes.inserted, vec![node(1), node(2)]);
+        assert_eq!(changes.removed, vec![1]);
+        assert!(changes.relabeled.is_empty());
+        assert!(log.since(1, &token, |key| key).is_none());
+    }
+
+    #[test]
+    fn discard() {
+        let mut log = ChangeLog::default();
+        let old = log.checkpoint(0);
+        log.relabeled(4);
+        let token = log.checkpoint(0);
+        log.relabeled(5);
+
+        log.discard_before(0, &token);
+        assert!(log.since(0, &old, |key| key).is_none());
+        let changes = log.since(0, &token, |key| key).unwrap();
+        assert_eq!(changes.relabeled, vec![NodeChange { key: 5, label: 5 }]);
+
+        // Discarding before an already-discarded checkpoint does nothing.
+        log.discard_before(0, &old);
+        assert!(log.since(0, &token, |key| key).is_some());
+    }
+}
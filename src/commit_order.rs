@@ -0,0 +1,190 @@
+//! Commit timestamps for multi-version concurrency control.
+//!
+//! A [`CommitOrder`] hands out [`Transaction`]s, each of which is assigned a start priority when
+//! it begins and a [`Timestamp`] that is finalized when it commits. A transaction sees exactly
+//! those versions whose timestamps were committed before it began (plus its own writes), which can
+//! be decided with a single priority comparison.
+//!
+//! ## Usage
+//!
+//! ```rust
+//! # use order_maintenance::commit_order::*;
+//! let order: CommitOrder = CommitOrder::new();
+//!
+//! let t1 = order.begin();
+//! let t2 = order.begin();
+//! let w1 = t1.timestamp();
+//! let w2 = t2.timestamp();
+//!
+//! // Writes of a transaction are visible only to itself until it commits.
+//! assert!(t1.sees(&w1));
+//! assert!(!t2.sees(&w1));
+//!
+//! t1.commit();
+//! let t3 = order.begin();
+//! assert!(!t2.sees(&w1)); // t2 began before t1 committed.
+//! assert!(t3.sees(&w1));
+//!
+//! t2.abort();
+//! assert!(!t3.sees(&w2));
+//! ```
+use crate::tag_range;
+pub use crate::MaintainedOrd;
+use std::{cell::RefCell, cmp::Ordering, rc::Rc};
+
+/// Status of a transaction's timestamp.
+#[derive(Debug)]
+enum Status<P> {
+    /// Transaction is still running.
+    Provisional,
+    /// Transaction committed at this priority.
+    Committed(P),
+    /// Transaction was aborted; its writes are never visible to anyone else.
+    Aborted,
+}
+
+/// Source of start and commit priorities for transactions.
+///
+/// Every start and commit event is placed after all previous events, so a version committed at
+/// priority `c` is visible to a transaction started at priority `s` iff `c < s`.
+///
+/// The priority type `P` defaults to [`tag_range::Priority`].
+#[derive(Debug)]
+pub struct CommitOrder<P = tag_range::Priority> {
+    /// The most recently allocated priority.
+    last: RefCell<P>,
+}
+
+impl<P: MaintainedOrd + Clone> CommitOrder<P> {
+    /// Construct a new commit order, with no transactions.
+    pub fn new() -> Self {
+        Self {
+            last: RefCell::new(P::new()),
+        }
+    }
+
+    /// Allocate a priority after all previously allocated priorities.
+    fn next(&self) -> P {
+        let mut last = self.last.borrow_mut();
+        *last = last.insert();
+        last.clone()
+    }
+
+    /// Begin a new transaction.
+    ///
+    /// The transaction's start priority is placed after every commit so far, and before every
+    /// commit from now on.
+    pub fn begin(&self) -> Transaction<'_, P> {
+        Transaction {
+            order: self,
+            start: self.next(),
+            timestamp: Timestamp(Rc::new(RefCell::new(Status::Provisional))),
+        }
+    }
+}
+
+impl<P: MaintainedOrd + Clone> Default for CommitOrder<P> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// A running transaction.
+///
+/// Transactions must either be committed with [`Transaction::commit()`] or aborted with
+/// [`Transaction::abort()`]; a transaction that is simply dropped is treated as aborted.
+#[derive(Debug)]
+pub struct Transaction<'a, P = tag_range::Priority> {
+    order: &'a CommitOrder<P>,
+    start: P,
+    timestamp: Timestamp<P>,
+}
+
+impl<'a, P: MaintainedOrd + Clone> Transaction<'a, P> {
+    /// The priority at which this transaction began.
+    pub fn start(&self) -> &P {
+        &self.start
+    }
+
+    /// The timestamp of this transaction, which should be attached to the versions it writes.
+    ///
+    /// The timestamp is provisional until the transaction commits, at which point all copies of
+    /// it are finalized at once.
+    pub fn timestamp(&self) -> Timestamp<P> {
+        self.timestamp.clone()
+    }
+
+    /// Whether a version with the given timestamp is visible to this transaction.
+    pub fn sees(&self, version: &Timestamp<P>) -> bool {
+        if Rc::ptr_eq(&self.timestamp.0, &version.0) {
+            return true;
+        }
+        match &*version.0.borrow() {
+            Status::Committed(c) => *c < self.start,
+            Status::Provisional | Status::Aborted => false,
+        }
+    }
+
+    /// Commit this transaction, placing its timestamp after all other commits so far.
+    pub fn commit(self) -> Timestamp<P> {
+        let commit = self.order.next();
+        *self.timestamp.0.borrow_mut() = Status::Committed(commit);
+        self.timestamp.clone()
+    }
+
+    /// Abort this transaction; versions written with its timestamp become invisible to all other
+    /// transactions.
+    pub fn abort(self) {
+        // Handled by Drop.
+    }
+}
+
+impl<'a, P> Drop for Transaction<'a, P> {
+    fn drop(&mut self) {
+        let mut status = self.timestamp.0.borrow_mut();
+        if let Status::Provisional = *status {
+            *status = Status::Aborted;
+        }
+    }
+}
+
+/// The commit timestamp of a transaction.
+///
+/// Committed timestamps are ordered by commit order. A provisional timestamp (belonging to a
+/// running transaction) is greater than every committed timestamp, but cannot be compared with
+/// other provisional timestamps. Aborted timestamps cannot be compared at all, except for equality
+/// with themselves.
+#[derive(Debug, Clone)]
+pub struct Timestamp<P = tag_range::Priority>(Rc<RefCell<Status<P>>>);
+
+impl<P> Timestamp<P> {
+    /// Whether the transaction this timestamp belongs to has committed.
+    pub fn is_committed(&self) -> bool {
+        matches!(*self.0.borrow(), Status::Committed(_))
+    }
+
+    /// Whether the transaction this timestamp belongs to was aborted.
+    pub fn is_aborted(&self) -> bool {
+        matches!(*self.0.borrow(), Status::Aborted)
+    }
+}
+
+impl<P: MaintainedOrd> PartialEq for Timestamp<P> {
+    fn eq(&self, other: &Self) -> bool {
+        Rc::ptr_eq(&self.0, &other.0)
+    }
+}
+
+impl<P: MaintainedOrd> PartialOrd for Timestamp<P> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        if self == other {
+            return Some(Ordering::Equal);
+        }
+        match (&*self.0.borrow(), &*other.0.borrow()) {
+            (Status::Committed(a), Status::Committed(b)) => a.partial_cmp(b),
+            (Status::Committed(_), Status::Provisional) => Some(Ordering::Less),
+            (Status::Provisional, Status::Committed(_)) => Some(Ordering::Greater),
+            _ => None,
+        }
+    }
+}
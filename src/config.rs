@@ -0,0 +1,265 @@
+//! Runtime configuration of arenas.
+//!
+//! Compile-time parameters are given by a priority type's [`Params`](crate::params::Params); a
+//! [`PriorityConfig`] tunes an individual arena on top of those, as it is constructed:
+//!
+//! ```rust
+//! # use order_maintenance::{config::PriorityConfig, tag_range::Priority, MaintainedOrd};
+//! let first: Priority = PriorityConfig::new()
+//!     .threshold(1.3)
+//!     .initial_capacity(1024)
+//!     .build_arena();
+//! assert!(first < first.insert());
+//! ```
+use crate::internal::{Arena, Label};
+use crate::params::Capacities;
+use std::num::{NonZeroU64, NonZeroUsize};
+
+/// Builder of arenas with tuned parameters.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct PriorityConfig {
+    threshold: Option<f64>,
+    initial_capacity: usize,
+    self_check: Option<NonZeroU64>,
+    cross_arena: CrossArena,
+    deferred_drop: bool,
+    explain_relabels: bool,
+    soft_cap: Option<(usize, fn(usize))>,
+    relabel_yield: Option<(NonZeroUsize, fn(usize))>,
+    max_live: Option<usize>,
+    max_relabeled_per_sec: Option<u64>,
+}
+
+/// What comparing priorities from different arenas does.
+///
+/// Such comparisons are usually mistakes, but some applications legitimately mix priorities from
+/// several arenas, e.g., in one collection. The behavior is set per arena with
+/// [`PriorityConfig::on_cross_arena()`]; if the two arenas of a comparison differ, panicking
+/// takes precedence, and ordering requires both arenas to be ordered.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum CrossArena {
+    /// Priorities from different arenas are incomparable, i.e., `partial_cmp()` returns `None`.
+    #[default]
+    Incomparable,
+    /// Comparing priorities from different arenas panics, naming both arenas.
+    Panic,
+    /// Priorities are ordered first by their arenas, in the order in which the arenas were
+    /// constructed, then by their order within the arena; see
+    /// [`PriorityConfig::order_across_arenas()`].
+    OrderByArena,
+}
+
+/// Priority types whose arenas can be constructed from a [`PriorityConfig`].
+pub trait Configurable {
+    /// Construct the first priority in a new arena, configured by `config`.
+    fn from_config(config: &PriorityConfig) -> Self;
+}
+
+impl PriorityConfig {
+    /// A configuration that constructs arenas just like [`MaintainedOrd::new()`] does.
+    ///
+    /// [`MaintainedOrd::new()`]: crate::MaintainedOrd::new
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Only relabel using thresholds up to `threshold`, for priority types with several.
+    ///
+    /// Lower thresholds have higher capacities, so they leave room for more priorities, but
+    /// relabel smaller ranges, which fill up again sooner; if `threshold` is lower than all of a
+    /// priority type's thresholds, its lowest threshold is used. Ignored by priority types without
+    /// thresholds.
+    ///
+    /// Panics unless `threshold` is strictly between 1.0 and 2.0.
+    pub fn threshold(mut self, threshold: f64) -> Self {
+        assert!(
+            1.0 < threshold && threshold < 2.0,
+            "threshold must be strictly between 1.0 and 2.0: {threshold}"
+        );
+        self.threshold = Some(threshold);
+        self
+    }
+
+    /// Reserve room for `capacity` priorities, so the arena does not grow until it exceeds them.
+    pub fn initial_capacity(mut self, capacity: usize) -> Self {
+        self.initial_capacity = capacity;
+        self
+    }
+
+    /// Check the arena's invariants on every `one_in`th insertion or removal.
+    ///
+    /// See [`check`](crate::check) for details.
+    pub fn self_check(mut self, one_in: NonZeroU64) -> Self {
+        self.self_check = Some(one_in);
+        self
+    }
+
+    /// Make priorities in the arena comparable with those of other arenas configured likewise.
+    ///
+    /// Such priorities are ordered first by the order in which their arenas were constructed,
+    /// then by their order within the arena. The order across arenas is arbitrary, but consistent,
+    /// so it suffices for, e.g., deduplicating priorities from several arenas in a `BTreeSet`.
+    ///
+    /// This is short for [`on_cross_arena(CrossArena::OrderByArena)`](Self::on_cross_arena).
+    pub fn order_across_arenas(self) -> Self {
+        self.on_cross_arena(CrossArena::OrderByArena)
+    }
+
+    /// Choose what comparing priorities in the arena with those of other arenas does; see
+    /// [`CrossArena`].
+    pub fn on_cross_arena(mut self, behavior: CrossArena) -> Self {
+        self.cross_arena = behavior;
+        self
+    }
+
+    /// Defer releasing priorities whose handles are dropped until the arena is collected.
+    ///
+    /// Dropping a handle to such an arena never borrows it, so handles may be dropped at any time,
+    /// in any order, even while the arena is borrowed, e.g., while a panic unwinds through it.
+    /// Dropped priorities are only freed by the priority type's `gc()` method; until then, they
+    /// keep taking up room in the arena, and are included in, e.g.,
+    /// [`InArena::arena_priorities()`](crate::InArena::arena_priorities).
+    pub fn deferred_drop(mut self) -> Self {
+        self.deferred_drop = true;
+        self
+    }
+
+    /// Call `on_cross` whenever an insertion makes the arena hold more than `cap` priorities.
+    ///
+    /// `on_cross` is given the number of priorities in the arena, and is called once each time the
+    /// arena crosses the cap, i.e., not again until priorities are dropped so that it holds at most
+    /// `cap` of them. It is called once the arena is no longer borrowed, so it may use the arena,
+    /// e.g., to shed load before the arena runs out of labels and panics. Whether the arena is
+    /// currently over the cap can also be checked before inserting, e.g., for admission control,
+    /// with the priority type's `over_soft_cap()` method.
+    pub fn soft_cap(mut self, cap: usize, on_cross: fn(usize)) -> Self {
+        self.soft_cap = Some((cap, on_cross));
+        self
+    }
+
+    /// Call `on_yield` every `every` priorities relabeled by a relabeling pass in the arena.
+    ///
+    /// Large relabeling passes are rare, but may take milliseconds; yielding lets, e.g., a game
+    /// loop or GUI thread interleave other work with them. `on_yield` is given the number of
+    /// priorities relabeled so far by the pass in progress, so only passes that relabel at least
+    /// `every` priorities call it, including those of the priority type's `rebalance_all()`.
+    /// Passes of custom [`RelabelPolicy`](crate::policy::RelabelPolicy)s do not yield.
+    ///
+    /// The arena is borrowed while it relabels, so using it from `on_yield` panics, but unrelated
+    /// work, e.g., polling for input or pumping events, is fine.
+    pub fn yield_during_relabel(mut self, every: NonZeroUsize, on_yield: fn(usize)) -> Self {
+        self.relabel_yield = Some((every, on_yield));
+        self
+    }
+
+    /// Refuse insertions that would make the arena hold more than `max` priorities.
+    ///
+    /// Unlike a [`soft_cap()`](Self::soft_cap), this quota is enforced: inserting past it panics,
+    /// while [`TryInsert::try_insert()`](crate::capability::TryInsert::try_insert) returns a
+    /// [`CapacityError`](crate::capability::CapacityError) of kind
+    /// [`LiveQuota`](crate::capability::CapacityErrorKind::LiveQuota), leaving the arena as is.
+    /// Priorities are counted like [`CapacityError::live()`](crate::capability::CapacityError::live)
+    /// does, i.e., including any that the arena hides. Quotas let a process that hosts arenas for
+    /// several tenants keep each of them in check.
+    ///
+    /// Only insertions relabeled by the crate's own algorithms are checked, not those of custom
+    /// [`RelabelPolicy`](crate::policy::RelabelPolicy)s.
+    pub fn max_live(mut self, max: usize) -> Self {
+        self.max_live = Some(max);
+        self
+    }
+
+    /// Refuse insertions once relabeling passes in the arena have relabeled `max` priorities within
+    /// the current second.
+    ///
+    /// The quota is checked before each insertion, so the pass that reaches it completes, and
+    /// insertions resume in the next second. Passes of the priority type's `rebalance_all()` count
+    /// towards the quota, but are never refused. Violations are reported like those of
+    /// [`max_live()`](Self::max_live), with kind
+    /// [`RelabelQuota`](crate::capability::CapacityErrorKind::RelabelQuota).
+    pub fn max_relabeled_per_sec(mut self, max: u64) -> Self {
+        self.max_relabeled_per_sec = Some(max);
+        self
+    }
+
+    /// Keep a report of the arena's last relabeling pass, explaining how it chose what to relabel.
+    ///
+    /// See [`explain`](crate::explain) for details.
+    pub fn explain_relabels(mut self) -> Self {
+        self.explain_relabels = true;
+        self
+    }
+
+    /// Construct a new arena with this configuration, returning its first priority.
+    pub fn build_arena<P: Configurable>(&self) -> P {
+        P::from_config(self)
+    }
+
+    /// Construct an empty arena with this configuration, for labels within `mask`.
+    pub(crate) fn arena(&self, mask: Label) -> Arena {
+        let mut arena = Arena::with_capacity(self.initial_capacity);
+        arena.set_self_check(self.self_check, mask);
+        match self.cross_arena {
+            CrossArena::Incomparable => (),
+            CrossArena::Panic => arena.panic_across_arenas(),
+            CrossArena::OrderByArena => arena.order_across_arenas(),
+        }
+        if self.deferred_drop {
+            arena.defer_drops();
+        }
+        if self.explain_relabels {
+            arena.explain_relabels();
+        }
+        if let Some((cap, on_cross)) = self.soft_cap {
+            arena.set_soft_cap(cap, on_cross);
+        }
+        if let Some((every, on_yield)) = self.relabel_yield {
+            arena.set_relabel_yield(every, on_yield);
+        }
+        if self.max_live.is_some() || self.max_relabeled_per_sec.is_some() {
+            arena.set_quota(self.max_live, self.max_relabeled_per_sec);
+        }
+        arena
+    }
+
+    /// The index of the highest of `P`'s thresholds that does not exceed the configured threshold.
+    pub(crate) fn max_threshold<P: Capacities>(&self) -> Option<usize> {
+        let threshold = self.threshold?;
+        let below = (0..P::THRESHOLDS).rev().find(|&i| {
+            let (num, den) = P::threshold(i);
+            num as f64 / den as f64 <= threshold
+        });
+        Some(below.unwrap_or(0))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tag_range::DefaultParams;
+
+    #[test]
+    fn max_thresholds() {
+        let config = PriorityConfig::new();
+        assert_eq!(config.max_threshold::<DefaultParams>(), None);
+        // Default thresholds are 1.1 + i * 0.8 / 17.
+        assert_eq!(
+            config
+                .clone()
+                .threshold(1.3)
+                .max_threshold::<DefaultParams>(),
+            Some(4)
+        );
+        assert_eq!(
+            config
+                .clone()
+                .threshold(1.05)
+                .max_threshold::<DefaultParams>(),
+            Some(0)
+        );
+        assert_eq!(
+            config.threshold(1.99).max_threshold::<DefaultParams>(),
+            Some(16)
+        );
+    }
+}
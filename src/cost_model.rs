@@ -0,0 +1,75 @@
+//! Amortized costs of tag-range priorities, for choosing between incremental insertion and bulk
+//! rebuilds.
+//!
+//! Tag-range arenas relabel a window of `2^level` labels once it holds as many priorities as its
+//! capacity at that level. Relabeling a window spreads its priorities evenly, so each of its two
+//! halves must take `capacity(level - 1) - capacity(level) / 2` more insertions before it can be
+//! relabeled again, after which relabeling it costs at most `capacity(level)`. With capacities of
+//! `(2 / t)^level` for a threshold `t`, this charges every insertion
+//! [`relabels_per_level(t)`](relabels_per_level) relabels for each level that it may be relabeled
+//! through, which is bounded by the first level whose capacity holds the whole arena:
+//!
+//! ```rust
+//! # use order_maintenance::{cost_model, tag_range::DefaultParams};
+//! let incremental = cost_model::relabels_per_insert::<DefaultParams>(1_000_000);
+//! assert!(incremental > cost_model::relabels_per_insert::<DefaultParams>(1_000));
+//!
+//! // Inserting a handful of priorities is cheaper than rebuilding a large arena, but inserting
+//! // as many priorities again is not.
+//! assert!(!cost_model::prefer_rebuild::<DefaultParams>(1_000_000, 10));
+//! assert!(cost_model::prefer_rebuild::<DefaultParams>(1_000_000, 1_000_000));
+//! ```
+//!
+//! These are worst-case amortized bounds, which typical workloads stay well below.
+use crate::params::Capacities;
+
+/// Relabels charged to each insertion per level of windows, at the threshold `num / den`.
+///
+/// This is `2 / (t - 1)` for a threshold `t`, so lower thresholds, which leave more room in each
+/// window, cost more per level, but keep more levels sparse enough to insert into.
+///
+/// Panics unless `num > den`, i.e., the threshold is greater than 1: at a threshold of 1 or less,
+/// a relabeled window has no room left for insertions.
+pub fn relabels_per_level(num: u64, den: u64) -> f64 {
+    assert!(num > den, "thresholds must be greater than 1: {num}/{den}");
+    2.0 * den as f64 / (num - den) as f64
+}
+
+/// The number of levels of windows that an insertion into an arena of `n` priorities may be
+/// relabeled through, at the threshold with index `threshold`.
+///
+/// This is the first level whose capacity is at least `n`, or the number of levels if there is
+/// none.
+pub fn levels<P: Capacities>(threshold: usize, n: usize) -> usize {
+    (0..P::BITS)
+        .find(|&level| P::capacity(threshold, level) >= n)
+        .unwrap_or(P::BITS)
+}
+
+/// Worst-case amortized number of priorities relabeled per insertion into an arena of `n`
+/// priorities, at the threshold that the arena uses for that many.
+///
+/// This is infinite once the arena has run out of labels, i.e., inserting would panic.
+pub fn relabels_per_insert<P: Capacities>(n: usize) -> f64 {
+    match P::threshold_for(n) {
+        Some(threshold) => {
+            let (num, den) = P::threshold(threshold);
+            relabels_per_level(num, den) * levels::<P>(threshold, n) as f64
+        }
+        None => f64::INFINITY,
+    }
+}
+
+/// Number of priorities relabeled by rebuilding an arena of `n` priorities in one pass, e.g., with
+/// [`MaintainedOrd::new_sequence()`](crate::MaintainedOrd::new_sequence) or
+/// [`rebalance_all()`](crate::tag_range::GenericPriority::rebalance_all).
+pub fn rebuild_cost(n: usize) -> f64 {
+    n as f64
+}
+
+/// Whether rebuilding an arena of `n` priorities with `k` more priorities is expected to be
+/// cheaper than inserting those `k` priorities one by one.
+pub fn prefer_rebuild<P: Capacities>(n: usize, k: usize) -> bool {
+    let total = n.saturating_add(k);
+    rebuild_cost(total) < k as f64 * relabels_per_insert::<P>(total)
+}
@@ -0,0 +1,77 @@
+//! Priorities in descending order.
+//!
+//! A [`Descending`] priority wraps a priority and reverses its order, so inserting after a
+//! descending priority produces the next _smaller_ one:
+//!
+//! ```rust
+//! # use order_maintenance::{descending::DescendingPriority, MaintainedOrd};
+//! let a = DescendingPriority::new();
+//! let b = a.insert();
+//! let c = b.insert();
+//! assert!(a > b && b > c);
+//! ```
+//!
+//! Descending priorities are priorities in their own right, so code that is generic over its
+//! priority type is reversed consistently by using them, without wrapping every element in
+//! [`std::cmp::Reverse`]. For example, a max-heap of descending priorities pops the least
+//! priority, in the ascending sense, first:
+//!
+//! ```rust
+//! # use order_maintenance::{descending::DescendingPriority, MaintainedOrd, TotalOrder};
+//! # use std::collections::BinaryHeap;
+//! let ps = DescendingPriority::new_sequence(3);
+//! let mut heap: BinaryHeap<_> = ps.iter().cloned().map(TotalOrder).collect();
+//! assert!(heap.pop().unwrap().0 == ps[2]);
+//! ```
+use crate::capability::{ConstantTimeCompare, UnboundedCapacity};
+use crate::{tag_range, InArena, MaintainedOrd};
+use std::cmp::Ordering;
+
+/// A priority of type `P` whose order is reversed.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub struct Descending<P>(pub P);
+
+/// Descending tag-range priorities.
+pub type DescendingPriority = Descending<tag_range::Priority>;
+
+impl<P> Descending<P> {
+    /// The wrapped priority, in ascending order.
+    pub fn into_inner(self) -> P {
+        self.0
+    }
+}
+
+impl<P: PartialOrd> PartialOrd for Descending<P> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        other.0.partial_cmp(&self.0)
+    }
+}
+
+impl<P: MaintainedOrd> MaintainedOrd for Descending<P> {
+    fn new() -> Self {
+        Self(P::new())
+    }
+
+    fn insert(&self) -> Self {
+        Self(self.0.insert())
+    }
+
+    fn new_sequence(n: usize) -> Vec<Self> {
+        P::new_sequence(n).into_iter().rev().map(Self).collect()
+    }
+}
+
+impl<P: ConstantTimeCompare> ConstantTimeCompare for Descending<P> {}
+
+impl<P: UnboundedCapacity> UnboundedCapacity for Descending<P> {}
+
+impl<P: InArena> InArena for Descending<P> {
+    fn arena_priorities(&self) -> Vec<Self> {
+        self.0
+            .arena_priorities()
+            .into_iter()
+            .rev()
+            .map(Self)
+            .collect()
+    }
+}
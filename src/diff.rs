@@ -0,0 +1,237 @@
+//! Comparing the orders that two arenas assign to the same logical elements.
+//!
+//! This is useful for forensics, e.g., when replicas that should agree on an order do not:
+//!
+//! ```rust
+//! # use order_maintenance::{diff::diff, tag_range::Priority, MaintainedOrd};
+//! let a = Priority::new();
+//! let b = a.insert();
+//! let c = b.insert();
+//!
+//! let x = Priority::new();
+//! let z = x.insert();
+//! let y = z.insert();
+//!
+//! let d = diff([("a", a), ("b", b), ("c", c)], [("a", x), ("b", y), ("c", z)]);
+//! assert_eq!(d.inversions, 1);
+//! assert_eq!(d.moved, vec![vec!["b"]]);
+//! assert_eq!(d.first_divergence, Some("b"));
+//! ```
+//!
+//! To merely check whether two arenas agree, use [`order_eq()`].
+use crate::InArena;
+use std::collections::{HashMap, HashSet};
+use std::hash::Hash;
+
+/// Differences between two orders over the same logical elements, as computed by [`diff()`].
+///
+/// Elements are identified by keys; the first order is "ours" and the second is "theirs".
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OrderDiff<K> {
+    /// Keys that only appear in our order, in our order.
+    pub only_ours: Vec<K>,
+    /// Keys that only appear in their order, in their order.
+    pub only_theirs: Vec<K>,
+    /// Number of pairs of common elements that the two orders disagree on.
+    pub inversions: usize,
+    /// The fewest common elements that could be moved in our order to make it agree with theirs,
+    /// grouped into runs that are contiguous among the common elements in our order.
+    pub moved: Vec<Vec<K>>,
+    /// The first common element, in our order, whose position differs from their order.
+    pub first_divergence: Option<K>,
+}
+
+impl<K> OrderDiff<K> {
+    /// Whether the two orders contain the same elements, in the same order.
+    pub fn is_empty(&self) -> bool {
+        self.only_ours.is_empty() && self.only_theirs.is_empty() && self.inversions == 0
+    }
+}
+
+/// Sort `elems` by priority, returning their keys.
+fn sorted_keys<K, P: PartialOrd>(elems: impl IntoIterator<Item = (K, P)>) -> Vec<K> {
+    let mut elems: Vec<(K, P)> = elems.into_iter().collect();
+    elems.sort_by(|(_, a), (_, b)| a.partial_cmp(b).expect("priorities are not comparable"));
+    elems.into_iter().map(|(k, _)| k).collect()
+}
+
+/// Count the pairs `i < j` with `xs[i] > xs[j]`, by merge sort.
+fn count_inversions(xs: &mut [usize]) -> usize {
+    if xs.len() < 2 {
+        return 0;
+    }
+    let mid = xs.len() / 2;
+    let mut count = count_inversions(&mut xs[..mid]) + count_inversions(&mut xs[mid..]);
+
+    let mut merged = Vec::with_capacity(xs.len());
+    let (mut i, mut j) = (0, mid);
+    while i < mid && j < xs.len() {
+        if xs[i] <= xs[j] {
+            merged.push(xs[i]);
+            i += 1;
+        } else {
+            // xs[j] is less than each of the remaining elements of the left half.
+            count += mid - i;
+            merged.push(xs[j]);
+            j += 1;
+        }
+    }
+    merged.extend_from_slice(&xs[i..mid]);
+    merged.extend_from_slice(&xs[j..]);
+    xs.copy_from_slice(&merged);
+    count
+}
+
+/// Indices of a longest strictly increasing subsequence of `xs`.
+fn longest_increasing(xs: &[usize]) -> Vec<usize> {
+    // tails[l] is the index of the least tail of an increasing subsequence of length l + 1.
+    let mut tails: Vec<usize> = Vec::new();
+    let mut prev: Vec<Option<usize>> = vec![None; xs.len()];
+    for (i, &x) in xs.iter().enumerate() {
+        let l = tails.partition_point(|&t| xs[t] < x);
+        prev[i] = l.checked_sub(1).map(|l| tails[l]);
+        if l == tails.len() {
+            tails.push(i);
+        } else {
+            tails[l] = i;
+        }
+    }
+
+    let mut lis = Vec::with_capacity(tails.len());
+    let mut i = tails.last().copied();
+    while let Some(j) = i {
+        lis.push(j);
+        i = prev[j];
+    }
+    lis.reverse();
+    lis
+}
+
+/// Compare the orders given by two sets of keyed priorities.
+///
+/// Priorities on each side must be comparable with each other, e.g., allocated in the same arena,
+/// but need not be comparable with the other side. Keys should be unique on each side.
+pub fn diff<K, P, Q>(
+    ours: impl IntoIterator<Item = (K, P)>,
+    theirs: impl IntoIterator<Item = (K, Q)>,
+) -> OrderDiff<K>
+where
+    K: Eq + Hash + Clone,
+    P: PartialOrd,
+    Q: PartialOrd,
+{
+    let ours = sorted_keys(ours);
+    let theirs = sorted_keys(theirs);
+
+    let their_index: HashMap<&K, usize> = theirs.iter().enumerate().map(|(i, k)| (k, i)).collect();
+    let our_keys: HashSet<&K> = ours.iter().collect();
+
+    // The common elements in our order, and their positions in their order.
+    let (common, mut positions): (Vec<&K>, Vec<usize>) = ours
+        .iter()
+        .filter_map(|k| their_index.get(k).map(|&i| (k, i)))
+        .unzip();
+
+    let first_divergence = {
+        let mut in_theirs = theirs.iter().filter(|k| our_keys.contains(k));
+        common
+            .iter()
+            .zip(&mut in_theirs)
+            .find(|(a, b)| **a != *b)
+            .map(|(a, _)| (*a).clone())
+    };
+
+    // Common elements outside a longest increasing subsequence of positions have moved.
+    let kept = longest_increasing(&positions);
+    let mut moved: Vec<Vec<K>> = Vec::new();
+    let mut kept_iter = kept.iter().peekable();
+    let mut in_run = false;
+    for (i, k) in common.iter().enumerate() {
+        if kept_iter.peek() == Some(&&i) {
+            kept_iter.next();
+            in_run = false;
+        } else {
+            match moved.last_mut() {
+                Some(run) if in_run => run.push((*k).clone()),
+                _ => {
+                    moved.push(vec![(*k).clone()]);
+                    in_run = true;
+                }
+            }
+        }
+    }
+
+    OrderDiff {
+        only_ours: ours
+            .iter()
+            .filter(|k| !their_index.contains_key(k))
+            .cloned()
+            .collect(),
+        only_theirs: theirs
+            .iter()
+            .filter(|k| !our_keys.contains(k))
+            .cloned()
+            .collect(),
+        inversions: count_inversions(&mut positions),
+        moved,
+        first_divergence,
+    }
+}
+
+/// Whether the arenas of `ours` and `theirs` hold the same order, under `mapping`.
+///
+/// `mapping` gives the counterpart of each priority in our arena, if any. The orders agree if
+/// every priority in our arena has a distinct counterpart in their arena, their arena has no other
+/// priorities, and counterparts are in the same order as the priorities they correspond to.
+pub fn order_eq<P, Q>(ours: &P, theirs: &Q, mut mapping: impl FnMut(&P) -> Option<Q>) -> bool
+where
+    P: InArena,
+    Q: InArena,
+{
+    let ours = ours.arena_priorities();
+    let theirs = theirs.arena_priorities();
+    // Both are in increasing order, so the orders agree iff the mapping pairs them up in order.
+    ours.len() == theirs.len()
+        && ours
+            .iter()
+            .zip(&theirs)
+            .all(|(p, q)| mapping(p).as_ref() == Some(q))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn inversions() {
+        assert_eq!(count_inversions(&mut [0, 1, 2, 3]), 0);
+        assert_eq!(count_inversions(&mut [3, 2, 1, 0]), 6);
+        assert_eq!(count_inversions(&mut [1, 0, 3, 2]), 2);
+    }
+
+    #[test]
+    fn increasing_subsequences() {
+        assert_eq!(longest_increasing(&[0, 1, 2]), vec![0, 1, 2]);
+        assert_eq!(longest_increasing(&[2, 0, 1, 3]), vec![1, 2, 3]);
+        assert!(longest_increasing(&[]).is_empty());
+    }
+
+    #[test]
+    fn diff_orders() {
+        // Priorities can be anything ordered; here, the keys' positions in each order.
+        let ours = [("a", 0), ("b", 1), ("c", 2), ("d", 3), ("e", 4), ("x", 5)];
+        let theirs = [("a", 0), ("d", 1), ("e", 2), ("b", 3), ("c", 4), ("y", 5)];
+        let d = diff(ours, theirs);
+        assert_eq!(d.only_ours, vec!["x"]);
+        assert_eq!(d.only_theirs, vec!["y"]);
+        assert_eq!(d.inversions, 4);
+        assert_eq!(d.moved, vec![vec!["b", "c"]]);
+        assert_eq!(d.first_divergence, Some("b"));
+        assert!(!d.is_empty());
+
+        let same = diff(ours, ours);
+        assert!(same.is_empty());
+        assert!(same.moved.is_empty());
+        assert_eq!(same.first_divergence, None);
+    }
+}
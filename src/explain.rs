@@ -0,0 +1,59 @@
+//! Explanations of why relabeling passes were as large as they were.
+//!
+//! Arenas configured with [`explain_relabels()`](crate::config::PriorityConfig::explain_relabels)
+//! keep a [`RelabelReport`] of their last relabeling pass, which traces how the pass searched for
+//! a range of priorities that was sparse enough to relabel:
+//!
+//! ```rust
+//! # use order_maintenance::{config::PriorityConfig, tag_range::Priority, MaintainedOrd};
+//! let first: Priority = PriorityConfig::new().explain_relabels().build_arena();
+//! let ps: Vec<Priority> = (0..100).map(|_| first.insert()).collect();
+//!
+//! let report = first.last_relabel_report().unwrap();
+//! assert!(report.steps.last().unwrap().accepted());
+//! assert_eq!(report.steps.last().unwrap().count, report.relabeled);
+//! # drop(ps);
+//! ```
+
+/// One step of the search for a range of priorities to relabel.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct WindowStep {
+    /// Level of the window in the label space; in tag-range arenas, the window spans `2^level`
+    /// labels.
+    pub level: usize,
+    /// The least and greatest raw labels in the window, inclusive.
+    pub window: (usize, usize),
+    /// Number of priorities found in the window.
+    pub count: usize,
+    /// Number of priorities that the window must hold fewer of to be relabeled, if the algorithm
+    /// uses a fixed capacity per level.
+    pub capacity: Option<usize>,
+}
+
+impl WindowStep {
+    /// Whether the window was sparse enough to be relabeled.
+    pub fn accepted(&self) -> bool {
+        self.capacity.is_none_or(|capacity| self.count < capacity)
+    }
+}
+
+/// Trace of a relabeling pass, as returned by, e.g.,
+/// [`tag_range::GenericPriority::last_relabel_report()`](crate::tag_range::GenericPriority::last_relabel_report).
+#[derive(Debug, Clone, PartialEq)]
+pub struct RelabelReport {
+    /// The label epoch that the pass advanced its arena to.
+    pub epoch: crate::types::Epoch,
+    /// Number of priorities in the arena when the pass ran, including its base priority.
+    pub live: usize,
+    /// Index of the density threshold that the pass used, for algorithms with thresholds.
+    pub threshold: Option<usize>,
+    /// The windows that the pass considered, from narrowest to widest; the last one was relabeled.
+    ///
+    /// List-range arenas grow their range one priority at a time rather than by levels, so their
+    /// reports only have a single step, at level 0, spanning the labels that were redistributed.
+    pub steps: Vec<WindowStep>,
+    /// Number of priorities that the pass relabeled.
+    pub relabeled: usize,
+    /// Fraction of the labels in the relabeled window that are taken by priorities.
+    pub density: f64,
+}
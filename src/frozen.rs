@@ -0,0 +1,177 @@
+//! Immutable snapshots of the order of an arena.
+//!
+//! Once an arena is no longer modified, freezing it trades the ability to insert priorities for
+//! cheaper comparisons: a [`FrozenPriority`] is just an ordinal, so it is `Copy`, `Send` and
+//! `Sync`, and comparing two of them never touches the arena.
+//!
+//! ```rust
+//! # use order_maintenance::{tag_range::Priority, types::Rank, MaintainedOrd};
+//! let a = Priority::new();
+//! let c = a.insert();
+//! let b = a.insert();
+//!
+//! let (order, handles) = a.freeze_arena();
+//! assert_eq!(order.len(), 3);
+//! let frozen: Vec<_> = [&a, &b, &c]
+//!     .into_iter()
+//!     .map(|p| handles.iter().find(|(q, _)| q == p).unwrap().1)
+//!     .collect();
+//! assert!(frozen[0] < frozen[1] && frozen[1] < frozen[2]);
+//! assert_eq!(order.rank(frozen[2]), Some(Rank::new(2)));
+//!
+//! // Frozen priorities can be shared across threads.
+//! std::thread::spawn(move || assert!(frozen[0] < frozen[2]))
+//!     .join()
+//!     .unwrap();
+//! ```
+//!
+//! Freezing only suits arenas that are no longer modified, since it hands out a handle to every
+//! priority. To read the order of an arena that is still being modified, take a [`ReadSnapshot`]
+//! instead, which maps the [identifiers](crate::id) of priorities to their frozen counterparts.
+use crate::id::PriorityId;
+use crate::types::{Epoch, Rank};
+use crate::MaintainedOrd;
+use std::cmp::Ordering;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering as AtomicOrdering};
+
+/// The order of every priority in an arena, at the time it was frozen.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FrozenOrder {
+    /// Identifier of this frozen order, unique within the process.
+    id: u64,
+    /// Labels of the priorities, relative to the arena's base label, in increasing order.
+    labels: Box<[usize]>,
+}
+
+/// A priority in a [`FrozenOrder`].
+///
+/// Frozen priorities are comparable if they belong to the same frozen order.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct FrozenPriority {
+    order: u64,
+    ordinal: usize,
+}
+
+impl FrozenOrder {
+    /// Freeze an order, given the relative labels of its priorities in increasing order.
+    pub(crate) fn new(labels: Vec<usize>) -> Self {
+        static NEXT_ID: AtomicU64 = AtomicU64::new(0);
+        debug_assert!(labels.windows(2).all(|w| w[0] < w[1]));
+        Self {
+            id: NEXT_ID.fetch_add(1, AtomicOrdering::Relaxed),
+            labels: labels.into_boxed_slice(),
+        }
+    }
+
+    /// Number of priorities in this order.
+    pub fn len(&self) -> usize {
+        self.labels.len()
+    }
+
+    /// Whether this order has no priorities.
+    pub fn is_empty(&self) -> bool {
+        self.labels.is_empty()
+    }
+
+    /// The priority of the given rank in this order, if there are enough priorities.
+    pub fn get(&self, rank: usize) -> Option<FrozenPriority> {
+        (rank < self.len()).then_some(FrozenPriority {
+            order: self.id,
+            ordinal: rank,
+        })
+    }
+
+    /// The priorities in this order, in increasing order.
+    pub fn iter(&self) -> impl Iterator<Item = FrozenPriority> + '_ {
+        (0..self.len()).map(|ordinal| FrozenPriority {
+            order: self.id,
+            ordinal,
+        })
+    }
+
+    /// The rank of `p` in this order, i.e., the number of priorities less than it.
+    ///
+    /// Returns `None` if `p` belongs to another frozen order.
+    pub fn rank(&self, p: FrozenPriority) -> Option<Rank> {
+        (p.order == self.id).then_some(Rank::new(p.ordinal))
+    }
+
+    /// Reconstruct a live arena of type `P` with this order, with evenly spaced labels.
+    ///
+    /// Returns handles to the new priorities in increasing order, so that the handle at index
+    /// `i` corresponds to the frozen priority of rank `i`.
+    pub fn thaw<P: MaintainedOrd>(&self) -> Vec<P> {
+        P::new_sequence(self.len())
+    }
+
+    /// The label that `p` had when its arena was frozen, relative to the arena's base label.
+    ///
+    /// Returns `None` if `p` belongs to another frozen order.
+    pub fn label(&self, p: FrozenPriority) -> Option<usize> {
+        self.rank(p).map(|rank| self.labels[rank.get()])
+    }
+}
+
+impl PartialOrd for FrozenPriority {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        (self.order == other.order).then(|| self.ordinal.cmp(&other.ordinal))
+    }
+}
+
+/// A frozen order of every priority in an arena, along with the identifiers of those priorities.
+///
+/// Snapshots are taken by, e.g.,
+/// [`tag_range::GenericPriority::read_snapshot()`](crate::tag_range::GenericPriority::read_snapshot),
+/// which copies the labels of the arena without creating any handles. A snapshot keeps the order
+/// that the arena had when it was taken, however the arena changes afterwards, and can be sent to
+/// other threads, e.g., to render one frame while the arena keeps being modified.
+#[derive(Debug, Clone)]
+pub struct ReadSnapshot {
+    order: FrozenOrder,
+    /// Rank of each priority in `order`, by identifier.
+    ranks: HashMap<PriorityId, usize>,
+    /// Label epoch of the arena when the snapshot was taken.
+    epoch: u64,
+}
+
+impl ReadSnapshot {
+    /// Snapshot the arena with identifier `arena` at label epoch `epoch`, given the key and relative
+    /// label of each of its priorities in increasing order.
+    pub(crate) fn new(arena: usize, entries: Vec<(usize, usize)>, epoch: u64) -> Self {
+        let ranks = entries
+            .iter()
+            .enumerate()
+            .map(|(rank, &(key, _))| (PriorityId::new(arena, key), rank))
+            .collect();
+        let order = FrozenOrder::new(entries.into_iter().map(|(_, label)| label).collect());
+        Self {
+            order,
+            ranks,
+            epoch,
+        }
+    }
+
+    /// The frozen order of the arena's priorities.
+    pub fn order(&self) -> &FrozenOrder {
+        &self.order
+    }
+
+    /// The frozen counterpart of the priority with identifier `id`, if it was in the arena when
+    /// the snapshot was taken.
+    ///
+    /// Identifiers of dropped priorities are reused, so the identifier of a priority that was
+    /// inserted after the snapshot may be that of a priority in the snapshot that was dropped
+    /// since.
+    pub fn get(&self, id: PriorityId) -> Option<FrozenPriority> {
+        self.ranks.get(&id).and_then(|&rank| self.order.get(rank))
+    }
+
+    /// The [label epoch](crate::tag_range::GenericPriority::label_epoch) of the arena when the
+    /// snapshot was taken.
+    ///
+    /// If the arena's label epoch and size are still the same, so is its order.
+    pub fn label_epoch(&self) -> Epoch {
+        Epoch::new(self.epoch)
+    }
+}
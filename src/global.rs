@@ -0,0 +1,101 @@
+//! Arenas that can be declared as statics.
+//!
+//! A [`GlobalArena`] has a `const` constructor and only allocates its arena when it is first used,
+//! so a module can expose a shared order without any lazy initialization of its own:
+//!
+//! ```rust
+//! # use order_maintenance::global::GlobalArena;
+//! static ORDER: GlobalArena = GlobalArena::new();
+//!
+//! let a = ORDER.push();
+//! let b = ORDER.push();
+//! assert!(ORDER.first() < a && a < b);
+//! ```
+//!
+//! Priorities cannot be sent between threads, so neither can arenas: each thread that uses a
+//! global arena gets an arena of its own, which lives until the thread exits. Priorities from the
+//! arenas of different threads never meet, since they stay on the thread that created them.
+use crate::{tag_range, MaintainedOrd};
+use std::any::Any;
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::marker::PhantomData;
+
+thread_local! {
+    /// The state of each global arena that was used on this thread, by address.
+    static ARENAS: RefCell<HashMap<usize, Box<dyn Any>>> = RefCell::new(HashMap::new());
+}
+
+/// An arena of priorities of type `P` that is allocated lazily, once per thread.
+#[derive(Debug)]
+pub struct GlobalArena<P = tag_range::Priority> {
+    /// Makes the arena take up space, so that distinct statics have distinct addresses.
+    _unique: u8,
+    _priority: PhantomData<fn() -> P>,
+}
+
+/// The state of a global arena on a single thread.
+struct State<P> {
+    first: P,
+    last: P,
+}
+
+impl<P> GlobalArena<P> {
+    /// A global arena that has not allocated anything yet.
+    pub const fn new() -> Self {
+        Self {
+            _unique: 0,
+            _priority: PhantomData,
+        }
+    }
+
+    /// The key of this arena in [`ARENAS`].
+    fn key(&'static self) -> usize {
+        self as *const Self as usize
+    }
+}
+
+impl<P: MaintainedOrd + Clone + 'static> GlobalArena<P> {
+    /// Whether this arena has been allocated on the current thread.
+    pub fn is_allocated(&'static self) -> bool {
+        ARENAS.with(|arenas| arenas.borrow().contains_key(&self.key()))
+    }
+
+    /// The first priority in this thread's arena, allocating the arena if necessary.
+    pub fn first(&'static self) -> P {
+        self.with_state(|state| state.first.clone())
+    }
+
+    /// A new priority after every priority returned by [`first()`](Self::first) or
+    /// [`push()`](Self::push) so far, on this thread.
+    pub fn push(&'static self) -> P {
+        self.with_state(|state| {
+            let p = state.last.insert();
+            state.last = p.clone();
+            p
+        })
+    }
+
+    /// Run `f` on this thread's state for this arena, allocating it if necessary.
+    ///
+    /// `f` must not use this thread's global arenas, which are borrowed while it runs.
+    fn with_state<R>(&'static self, f: impl FnOnce(&mut State<P>) -> R) -> R {
+        ARENAS.with(|arenas| {
+            let mut arenas = arenas.borrow_mut();
+            let state = arenas.entry(self.key()).or_insert_with(|| {
+                let first = P::new();
+                let last = first.clone();
+                Box::new(State { first, last })
+            });
+            f(state
+                .downcast_mut()
+                .expect("global arena state has the arena's priority type"))
+        })
+    }
+}
+
+impl<P> Default for GlobalArena<P> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,316 @@
+//! Maintaining orders over the nodes of [`petgraph`] graphs.
+//!
+//! Only available with the `petgraph` feature. A [`NodeOrder`] assigns a priority to each node of
+//! a graph, and is kept up to date as nodes are added and removed. A [`TopoOrder`] is a node order
+//! that is also kept topological as edges are added, so that whether one node may reach another
+//! can be ruled out with a single comparison:
+//!
+//! ```rust
+//! # use order_maintenance::graph::TopoOrder;
+//! # use petgraph::Graph;
+//! let mut graph = Graph::<(), ()>::new();
+//! let mut order: TopoOrder = TopoOrder::new();
+//! let a = graph.add_node(());
+//! let b = graph.add_node(());
+//! order.add_node(a);
+//! order.add_node(b);
+//!
+//! // Adding an edge against the order reorders the nodes it affects.
+//! order.add_edge(&graph, b, a).unwrap();
+//! graph.add_edge(b, a, ());
+//! assert!(order.precedes(b, a));
+//!
+//! // Edges that would close a cycle are rejected.
+//! assert!(order.add_edge(&graph, a, b).is_err());
+//! ```
+use crate::{tag_range, MaintainedOrd};
+use petgraph::graph::{DefaultIx, IndexType, NodeIndex};
+use petgraph::visit::IntoNeighbors;
+use petgraph::{EdgeType, Graph};
+use std::cmp::Ordering;
+use std::collections::HashSet;
+
+/// A total order over the nodes of a graph, with a priority per node.
+///
+/// Node indices are those of a [`Graph`] or a [`petgraph::stable_graph::StableGraph`]; removing a
+/// node must be mirrored with [`remove_node()`](Self::remove_node) or
+/// [`remove_stable_node()`](Self::remove_stable_node), respectively, so that indices keep
+/// referring to the same nodes.
+#[derive(Debug, Clone)]
+pub struct NodeOrder<Ix = DefaultIx, P = tag_range::Priority> {
+    /// Priority of each node, by index.
+    priorities: Vec<Option<P>>,
+    /// The greatest priority assigned so far, which may outlive its node.
+    last: Option<P>,
+    _index: std::marker::PhantomData<Ix>,
+}
+
+impl<Ix: IndexType, P: MaintainedOrd + Clone> NodeOrder<Ix, P> {
+    /// An empty order.
+    pub fn new() -> Self {
+        Self {
+            priorities: Vec::new(),
+            last: None,
+            _index: std::marker::PhantomData,
+        }
+    }
+
+    /// An order over `nodes`, in the order given, e.g., by [`petgraph::algo::toposort()`].
+    pub fn from_nodes(nodes: impl IntoIterator<Item = NodeIndex<Ix>>) -> Self {
+        let mut order = Self::new();
+        for node in nodes {
+            order.push(node);
+        }
+        order
+    }
+
+    /// The priority of `node`, if it is in the order.
+    pub fn priority(&self, node: NodeIndex<Ix>) -> Option<&P> {
+        self.priorities.get(node.index())?.as_ref()
+    }
+
+    /// Whether `node` is in the order.
+    pub fn contains(&self, node: NodeIndex<Ix>) -> bool {
+        self.priority(node).is_some()
+    }
+
+    /// Compare the priorities of `a` and `b`, or `None` if either is not in the order.
+    pub fn compare(&self, a: NodeIndex<Ix>, b: NodeIndex<Ix>) -> Option<Ordering> {
+        self.priority(a)?.partial_cmp(self.priority(b)?)
+    }
+
+    /// Whether `a` comes before `b` in the order; `false` if either is not in it.
+    pub fn precedes(&self, a: NodeIndex<Ix>, b: NodeIndex<Ix>) -> bool {
+        self.compare(a, b) == Some(Ordering::Less)
+    }
+
+    /// Sort `nodes` by their priorities; panics unless they are all in the order.
+    pub fn sort(&self, nodes: &mut [NodeIndex<Ix>]) {
+        nodes.sort_by(|&a, &b| {
+            self.compare(a, b)
+                .expect("sorted nodes must be in the order")
+        });
+    }
+
+    /// Add `node` after every other node.
+    ///
+    /// Panics if `node` is already in the order.
+    pub fn push(&mut self, node: NodeIndex<Ix>) {
+        let p = match &self.last {
+            Some(last) => last.insert(),
+            None => P::new(),
+        };
+        self.last = Some(p.clone());
+        self.set(node, p);
+    }
+
+    /// Add `node` right after `anchor`.
+    ///
+    /// Panics if `node` is already in the order, or `anchor` is not.
+    pub fn insert_after(&mut self, node: NodeIndex<Ix>, anchor: NodeIndex<Ix>) {
+        let p = self.insert_after_priority(
+            self.priority(anchor)
+                .expect("anchor must be in the order")
+                .clone(),
+        );
+        self.set(node, p);
+    }
+
+    /// Remove `node` from the order, after it was removed from `graph`.
+    ///
+    /// Graphs move their last node into the index of the removed one, so the priority of that node,
+    /// if it is in the order, moves along with it.
+    pub fn remove_node<N, E, Ty: EdgeType>(
+        &mut self,
+        graph: &Graph<N, E, Ty, Ix>,
+        node: NodeIndex<Ix>,
+    ) {
+        self.remove_stable_node(node);
+        let moved = graph.node_count();
+        if node.index() < moved && moved < self.priorities.len() {
+            self.priorities.swap(node.index(), moved);
+            self.remove_stable_node(NodeIndex::new(moved));
+        }
+    }
+
+    /// Remove `node` from the order, after it was removed from a
+    /// [`petgraph::stable_graph::StableGraph`], whose other node indices stay the same.
+    pub fn remove_stable_node(&mut self, node: NodeIndex<Ix>) {
+        if let Some(slot) = self.priorities.get_mut(node.index()) {
+            *slot = None;
+        }
+        while let Some(None) = self.priorities.last() {
+            self.priorities.pop();
+        }
+    }
+
+    /// A new priority right after `anchor`.
+    fn insert_after_priority(&mut self, anchor: P) -> P {
+        let p = anchor.insert();
+        if self.last.as_ref() == Some(&anchor) {
+            self.last = Some(p.clone());
+        }
+        p
+    }
+
+    /// Assign the priority `p` to `node`, which must not be in the order yet.
+    fn set(&mut self, node: NodeIndex<Ix>, p: P) {
+        let i = node.index();
+        if self.priorities.len() <= i {
+            self.priorities.resize_with(i + 1, || None);
+        }
+        assert!(
+            self.priorities[i].is_none(),
+            "node {i} is already in the order"
+        );
+        self.priorities[i] = Some(p);
+    }
+}
+
+impl<Ix: IndexType, P: MaintainedOrd + Clone> Default for NodeOrder<Ix, P> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Error returned when adding an edge would close a cycle.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CycleError<N> {
+    /// The target of the rejected edge, which can already reach its source.
+    pub node: N,
+}
+
+impl<N: std::fmt::Debug> std::fmt::Display for CycleError<N> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "adding the edge would close a cycle through {:?}",
+            self.node
+        )
+    }
+}
+
+impl<N: std::fmt::Debug> std::error::Error for CycleError<N> {}
+
+/// A topological order over the nodes of a directed acyclic graph, maintained incrementally.
+///
+/// Before an edge is added to the graph, it must be announced with [`add_edge()`](Self::add_edge),
+/// which reorders just the nodes that the edge would otherwise put out of order, following
+/// Marchetti-Spaccamela et al.'s algorithm; rather than renumbering those nodes, they are given
+/// new priorities right after the source of the edge.
+#[derive(Debug, Clone)]
+pub struct TopoOrder<Ix = DefaultIx, P = tag_range::Priority> {
+    order: NodeOrder<Ix, P>,
+}
+
+impl<Ix: IndexType, P: MaintainedOrd + Clone> TopoOrder<Ix, P> {
+    /// An empty order.
+    pub fn new() -> Self {
+        Self {
+            order: NodeOrder::new(),
+        }
+    }
+
+    /// A topological order over `nodes`, which must already be in topological order, e.g., as
+    /// given by [`petgraph::algo::toposort()`].
+    pub fn from_toposort(nodes: impl IntoIterator<Item = NodeIndex<Ix>>) -> Self {
+        Self {
+            order: NodeOrder::from_nodes(nodes),
+        }
+    }
+
+    /// The underlying order over nodes.
+    pub fn order(&self) -> &NodeOrder<Ix, P> {
+        &self.order
+    }
+
+    /// Whether `a` comes before `b` in the order; if not, `a` cannot reach `b`.
+    pub fn precedes(&self, a: NodeIndex<Ix>, b: NodeIndex<Ix>) -> bool {
+        self.order.precedes(a, b)
+    }
+
+    /// Add `node`, which has no edges yet, after every other node.
+    pub fn add_node(&mut self, node: NodeIndex<Ix>) {
+        self.order.push(node);
+    }
+
+    /// Reorder the nodes of `graph` for an edge from `from` to `to` that is about to be added.
+    ///
+    /// Fails if `to` can already reach `from` in `graph`, in which case the order is unchanged and
+    /// the edge must not be added. Panics unless both nodes are in the order.
+    pub fn add_edge<G>(
+        &mut self,
+        graph: G,
+        from: NodeIndex<Ix>,
+        to: NodeIndex<Ix>,
+    ) -> Result<(), CycleError<NodeIndex<Ix>>>
+    where
+        G: IntoNeighbors<NodeId = NodeIndex<Ix>>,
+    {
+        if from == to {
+            return Err(CycleError { node: to });
+        }
+        let source = self
+            .order
+            .priority(from)
+            .expect("source of the edge must be in the order")
+            .clone();
+        match self.order.compare(from, to) {
+            Some(Ordering::Less) => return Ok(()),
+            Some(_) => (),
+            None => panic!("target of the edge must be in the order"),
+        }
+
+        // Find the nodes reachable from `to` that come before `from`, which must move after it.
+        let mut moved = vec![to];
+        let mut seen = HashSet::from([to]);
+        let mut stack = vec![to];
+        while let Some(node) = stack.pop() {
+            for next in graph.neighbors(node) {
+                if next == from {
+                    return Err(CycleError { node: to });
+                }
+                if !seen.insert(next) {
+                    continue;
+                }
+                let before = self.order.priority(next).is_some_and(|p| *p < source);
+                if before {
+                    moved.push(next);
+                    stack.push(next);
+                }
+            }
+        }
+
+        // Move them right after `from`, keeping their relative order.
+        self.order.sort(&mut moved);
+        let mut anchor = source;
+        for node in moved {
+            self.order.remove_stable_node(node);
+            anchor = self.order.insert_after_priority(anchor);
+            self.order.set(node, anchor.clone());
+        }
+        Ok(())
+    }
+
+    /// Remove `node` from the order, after it was removed from `graph`; see
+    /// [`NodeOrder::remove_node()`].
+    pub fn remove_node<N, E, Ty: EdgeType>(
+        &mut self,
+        graph: &Graph<N, E, Ty, Ix>,
+        node: NodeIndex<Ix>,
+    ) {
+        self.order.remove_node(graph, node);
+    }
+
+    /// Remove `node` from the order, after it was removed from a
+    /// [`petgraph::stable_graph::StableGraph`]; see [`NodeOrder::remove_stable_node()`].
+    pub fn remove_stable_node(&mut self, node: NodeIndex<Ix>) {
+        self.order.remove_stable_node(node);
+    }
+}
+
+impl<Ix: IndexType, P: MaintainedOrd + Clone> Default for TopoOrder<Ix, P> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,106 @@
+//! Plain identifiers of priorities, for comparing them without touching reference counts.
+//!
+//! Cloning a handle to a priority bumps the reference count of its node, and dropping it decrements
+//! that count again, which hot loops that only compare priorities should not pay for. A
+//! [`PriorityId`] is instead a plain `Copy` value, which can be compared with other identifiers
+//! through an [`IdOrder`]: a borrow of their arena that is taken once, for any number of
+//! comparisons.
+//!
+//! ```rust
+//! # use order_maintenance::{id::Identified, tag_range::Priority, MaintainedOrd};
+//! let a = Priority::new();
+//! let c = a.insert();
+//! let b = a.insert();
+//! let mut ids = [c.id(), a.id(), b.id()];
+//!
+//! let order = a.id_order();
+//! ids.sort_by(|x, y| order.compare(*x, *y).unwrap());
+//! assert_eq!(ids, [a.id(), b.id(), c.id()]);
+//! ```
+//!
+//! Identifiers do not keep their priorities alive. Once a priority is dropped, its identifier may
+//! be reused for a new priority in the same arena, so identifiers should only be compared while
+//! some handle keeps their priorities alive.
+//!
+//! The arena stays borrowed for as long as an [`IdOrder`] lives, during which inserting into it,
+//! or dropping handles to it, panics; cloning handles to it is fine.
+use crate::internal::{Arena, Label};
+use std::cell::Ref;
+use std::cmp::Ordering;
+
+/// An identifier of a priority, which is only meaningful in the arena of that priority.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct PriorityId {
+    /// Identifier of the arena that the priority belongs to.
+    arena: usize,
+    /// The key of the priority in its arena.
+    key: usize,
+}
+
+impl PriorityId {
+    /// The identifier of the priority with key `key` in the arena with identifier `arena`.
+    pub(crate) fn new(arena: usize, key: usize) -> Self {
+        Self { arena, key }
+    }
+}
+
+/// A borrow of an arena, for comparing the [`PriorityId`]s of its priorities.
+pub struct IdOrder<'a> {
+    arena: Ref<'a, Arena>,
+    /// Identifier of the borrowed arena.
+    arena_id: usize,
+    /// Mask of the bits used by labels in the arena.
+    mask: Label,
+}
+
+impl<'a> IdOrder<'a> {
+    /// Compare identifiers through `arena`, which has the identifier `arena_id`, and labels
+    /// within `mask`.
+    pub(crate) fn new(arena: Ref<'a, Arena>, arena_id: usize, mask: Label) -> Self {
+        Self {
+            arena,
+            arena_id,
+            mask,
+        }
+    }
+
+    /// The label that the priority with identifier `id` is compared by, if it is in this arena.
+    fn relative(&self, id: PriorityId) -> Option<Label> {
+        if id.arena != self.arena_id {
+            return None;
+        }
+        let base = self.arena.get(self.arena.base()).label();
+        let label = self.arena.try_get(id.key)?.label();
+        Some((label - base) & self.mask)
+    }
+
+    /// Whether `id` identifies a live priority in this arena.
+    pub fn contains(&self, id: PriorityId) -> bool {
+        self.relative(id).is_some()
+    }
+
+    /// Compare the priorities identified by `a` and `b`, like
+    /// [`partial_cmp()`](PartialOrd::partial_cmp) on their handles.
+    ///
+    /// Returns `None` unless both identify live priorities in this arena.
+    pub fn compare(&self, a: PriorityId, b: PriorityId) -> Option<Ordering> {
+        Some(self.relative(a)?.cmp(&self.relative(b)?))
+    }
+}
+
+impl std::fmt::Debug for IdOrder<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("IdOrder")
+            .field("arena", &format_args!("{:#x}", self.arena_id))
+            .finish()
+    }
+}
+
+/// Priorities that have [`PriorityId`]s.
+pub trait Identified {
+    /// The identifier of this priority.
+    fn id(&self) -> PriorityId;
+
+    /// Borrow this priority's arena, for comparing the identifiers of its priorities.
+    fn id_order(&self) -> IdOrder<'_>;
+}
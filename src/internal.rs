@@ -1,21 +1,25 @@
 //! Internal representation and memory management of priorities.
 
+use crate::capability::{CapacityError, CapacityErrorKind};
+use crate::check::{SelfCheckReport, Violation};
 pub(crate) use crate::label::Label;
+use crate::policy::Cursor;
+use crate::track_alloc;
 use slab::Slab;
+use std::any::Any;
 use std::cell::RefCell;
 use std::cmp::Ordering;
+use std::collections::HashMap;
+use std::fmt::Debug;
+use std::num::{NonZeroU64, NonZeroUsize};
 use std::rc::Rc;
+use std::sync::atomic::{self, AtomicU64};
+use std::time::{Duration, Instant};
 
 /// Index to a priority in the priority arena.
 #[derive(Debug, Clone, PartialEq, Eq, Copy)]
 pub(crate) struct PriorityKey(usize);
 
-impl From<usize> for PriorityKey {
-    fn from(value: usize) -> Self {
-        Self(value)
-    }
-}
-
 impl PriorityKey {
     /// "Dereferences" this index in an arena.
     ///
@@ -43,6 +47,82 @@ pub(crate) struct Arena {
 
     /// Key to the base priority, which should never be deleted (unless the arena is dropped).
     base: PriorityKey,
+
+    /// Index of the highest threshold that relabeling may use, if limited.
+    max_threshold: Option<usize>,
+
+    /// Configuration of sampled invariant checks, if enabled.
+    self_check: Option<SelfCheck>,
+
+    /// Results of sampled invariant checks.
+    self_check_report: SelfCheckReport,
+
+    /// Number of relabeling passes performed in this arena.
+    label_epoch: u64,
+
+    /// Total number of priorities relabeled by those passes.
+    relabeled: u64,
+
+    /// Creation ordinal of this arena among those that order priorities across arenas, if it does.
+    ordinal: Option<u64>,
+
+    /// Whether comparing priorities in this arena with those of other arenas panics.
+    panic_across_arenas: bool,
+
+    /// Whether dropping a handle defers releasing it until [`PriorityRef::gc()`].
+    deferred_drop: bool,
+
+    /// Data attached to this arena by its user, if any.
+    data: Option<Rc<dyn Any>>,
+
+    /// Metadata attached to priorities in this arena by their users, by key.
+    meta: HashMap<usize, Rc<dyn Any>>,
+
+    /// Metadata of priorities that were freed, to drop once this arena is no longer borrowed, since
+    /// dropping it may drop handles to this arena.
+    freed_meta: Vec<Rc<dyn Any>>,
+
+    /// Algorithm that switchable priorities in this arena are relabeled with.
+    algorithm: crate::workload::Algorithm,
+
+    /// Whether relabeling passes report how they chose the range to relabel.
+    explain: bool,
+
+    /// Soft limit on the number of priorities in this arena, if any.
+    soft_cap: Option<SoftCap>,
+
+    /// How often relabeling passes yield to their caller, if they do.
+    relabel_yield: Option<RelabelYield>,
+
+    /// Quota on insertions into this arena, if any.
+    quota: Option<Quota>,
+
+    /// Number of priorities that relabeling passes leave room for right after priorities, in the
+    /// regions reserved there, by key.
+    regions: HashMap<usize, usize>,
+
+    /// Report of the last relabeling pass, if relabeling passes are explained.
+    last_relabel: Option<crate::explain::RelabelReport>,
+
+    /// Statistics about relabeling in this arena, since it was constructed or last reset.
+    #[cfg(feature = "stats")]
+    stats: crate::stats::RelabelStats,
+
+    /// Statistics about relabeling in this arena, since they were last polled.
+    #[cfg(feature = "stats")]
+    stats_window: crate::stats::RelabelStats,
+
+    /// Log of the changes made to this arena since its first checkpoint, if any.
+    #[cfg(feature = "checkpoint")]
+    changes: Option<crate::checkpoint::ChangeLog>,
+
+    /// Identifier of this arena that is unique with high probability, even across processes.
+    #[cfg(feature = "persistent-id")]
+    uuid: u64,
+
+    /// Creation index of the most recently allocated priority.
+    #[cfg(any(feature = "introspect", feature = "persistent-id"))]
+    created: usize,
 }
 
 impl Arena {
@@ -53,16 +133,24 @@ impl Arena {
     ///
     /// Comes pre-allocated with a base priority, used by tag-range relabeling.
     pub(crate) fn new() -> Self {
-        let mut priorities = Slab::new();
-        let base_key = priorities.vacant_key().into();
-        let base = priorities
-            .insert(PriorityInner {
+        Self::with_capacity(0)
+    }
+
+    /// Construct a new arena with room for `capacity` priorities before it must grow.
+    pub(crate) fn with_capacity(capacity: usize) -> Self {
+        let mut priorities = Slab::with_capacity(capacity);
+        let base_key = PriorityKey(priorities.vacant_key());
+        let base = PriorityKey(track_alloc::alloc_node(
+            &mut priorities,
+            PriorityInner {
                 next: RefCell::new(base_key),
                 prev: RefCell::new(base_key),
                 label: RefCell::new(Arena::BASE),
                 ref_count: RefCell::new(1),
-            })
-            .into();
+                #[cfg(any(feature = "introspect", feature = "persistent-id"))]
+                creation_index: 0,
+            },
+        ));
 
         debug_assert_eq!(base_key, base);
 
@@ -70,6 +158,34 @@ impl Arena {
             total: 1,
             priorities,
             base,
+            max_threshold: None,
+            self_check: None,
+            self_check_report: Default::default(),
+            label_epoch: 0,
+            relabeled: 0,
+            ordinal: None,
+            panic_across_arenas: false,
+            deferred_drop: false,
+            data: None,
+            meta: HashMap::new(),
+            freed_meta: Vec::new(),
+            algorithm: crate::workload::Algorithm::TagRange,
+            explain: false,
+            last_relabel: None,
+            soft_cap: None,
+            relabel_yield: None,
+            quota: None,
+            regions: HashMap::new(),
+            #[cfg(feature = "stats")]
+            stats: Default::default(),
+            #[cfg(feature = "stats")]
+            stats_window: Default::default(),
+            #[cfg(feature = "checkpoint")]
+            changes: None,
+            #[cfg(any(feature = "introspect", feature = "persistent-id"))]
+            created: 0,
+            #[cfg(feature = "persistent-id")]
+            uuid: crate::persistent_id::fresh_arena_uuid(),
         }
     }
 
@@ -79,8 +195,21 @@ impl Arena {
     }
 
     /// Retrieve a reference to a priority from the priorities store using a key.
+    ///
+    /// This only fails, i.e., panics, if the arena's bookkeeping is broken: keys can only be
+    /// constructed in this module, from the store's own allocations, they are only held by handles
+    /// and by the links between priorities, and a priority is only freed once it has been unlinked
+    /// and no handle refers to it.
     pub(crate) fn get(&self, key: PriorityKey) -> &PriorityInner {
-        self.priorities.get(key.key()).unwrap()
+        &self.priorities[key.key()]
+    }
+
+    /// Retrieve a reference to the priority with key `key`, if it is live.
+    ///
+    /// Unlike [`get()`](Self::get), this is for keys that no handle vouches for, e.g., those in
+    /// [`PriorityId`](crate::id::PriorityId)s.
+    pub(crate) fn try_get(&self, key: usize) -> Option<&PriorityInner> {
+        self.priorities.get(key)
     }
 
     /// Total number of priorities allocated in this arena.
@@ -88,27 +217,383 @@ impl Arena {
         self.total
     }
 
+    /// Number of relabeling passes performed in this arena; see [`record_relabel()`].
+    ///
+    /// [`record_relabel()`]: Self::record_relabel
+    pub(crate) fn label_epoch(&self) -> u64 {
+        self.label_epoch
+    }
+
+    /// Total number of priorities relabeled in this arena; see [`record_relabel()`].
+    ///
+    /// [`record_relabel()`]: Self::record_relabel
+    pub(crate) fn relabeled(&self) -> u64 {
+        self.relabeled
+    }
+
+    /// Number of priorities that this arena has room for before its storage must grow.
+    pub(crate) fn capacity(&self) -> usize {
+        self.priorities.capacity()
+    }
+
+    /// Order priorities in this arena before those of arenas that opt into this later.
+    ///
+    /// Priorities from different arenas are only comparable if both arenas opted into this.
+    pub(crate) fn order_across_arenas(&mut self) {
+        static NEXT_ORDINAL: AtomicU64 = AtomicU64::new(0);
+        if self.ordinal.is_none() {
+            self.ordinal = Some(NEXT_ORDINAL.fetch_add(1, atomic::Ordering::Relaxed));
+        }
+    }
+
+    /// Panic when priorities in this arena are compared with those of other arenas.
+    ///
+    /// This takes precedence over [`order_across_arenas()`](Self::order_across_arenas) of either
+    /// arena.
+    pub(crate) fn panic_across_arenas(&mut self) {
+        self.panic_across_arenas = true;
+    }
+
+    /// Defer releasing handles to priorities in this arena until they are collected.
+    ///
+    /// Must be set before the arena is shared by any handles.
+    pub(crate) fn defer_drops(&mut self) {
+        self.deferred_drop = true;
+    }
+
+    /// Attach `data` to this arena, replacing any data attached before.
+    pub(crate) fn set_data(&mut self, data: Rc<dyn Any>) {
+        self.data = Some(data);
+    }
+
+    /// Attach `meta` to the priority with key `key`, or detach its metadata if `meta` is `None`;
+    /// returns the metadata attached before, if any.
+    pub(crate) fn set_meta(
+        &mut self,
+        key: PriorityKey,
+        meta: Option<Rc<dyn Any>>,
+    ) -> Option<Rc<dyn Any>> {
+        match meta {
+            Some(meta) => self.meta.insert(key.key(), meta),
+            None => self.meta.remove(&key.key()),
+        }
+    }
+
+    /// The metadata attached to the priority with key `key`, if any.
+    pub(crate) fn meta(&self, key: PriorityKey) -> Option<Rc<dyn Any>> {
+        self.meta.get(&key.key()).cloned()
+    }
+
+    /// Take the metadata of the priorities freed since this was last called, to drop it once this
+    /// arena is no longer borrowed.
+    pub(crate) fn take_freed_meta(&mut self) -> Vec<Rc<dyn Any>> {
+        std::mem::take(&mut self.freed_meta)
+    }
+
+    /// Call `on_cross` whenever an insertion makes this arena hold more than `cap` priorities.
+    pub(crate) fn set_soft_cap(&mut self, cap: usize, on_cross: fn(usize)) {
+        self.soft_cap = Some(SoftCap {
+            cap,
+            on_cross,
+            over: self.total > cap,
+        });
+    }
+
+    /// Whether this arena holds more priorities than its soft cap, if it has one.
+    pub(crate) fn over_soft_cap(&self) -> bool {
+        self.soft_cap.is_some_and(|soft_cap| soft_cap.over)
+    }
+
+    /// The callback to call if the last insertion crossed this arena's soft cap, along with the
+    /// number of priorities it holds now.
+    ///
+    /// The callback is returned rather than called, so that it can be called once the arena is no
+    /// longer borrowed.
+    fn crossed_soft_cap(&mut self) -> Option<(fn(usize), usize)> {
+        let soft_cap = self.soft_cap.as_mut()?;
+        if soft_cap.over || self.total <= soft_cap.cap {
+            return None;
+        }
+        soft_cap.over = true;
+        Some((soft_cap.on_cross, self.total))
+    }
+
+    /// Report how relabeling passes chose the range to relabel, from now on.
+    pub(crate) fn explain_relabels(&mut self) {
+        self.explain = true;
+    }
+
+    /// Whether relabeling passes should report how they chose the range to relabel.
+    pub(crate) fn explaining(&self) -> bool {
+        self.explain
+    }
+
+    /// Report of the last relabeling pass, if relabeling passes are explained.
+    pub(crate) fn last_relabel_report(&self) -> Option<&crate::explain::RelabelReport> {
+        self.last_relabel.as_ref()
+    }
+
+    /// Keep `report` of the relabeling pass that was just recorded, replacing any earlier one.
+    pub(crate) fn set_relabel_report(&mut self, report: crate::explain::RelabelReport) {
+        self.last_relabel = Some(report);
+    }
+
+    /// Record a relabeling pass over `size` priorities, advancing the label epoch.
+    ///
+    /// The distribution of sizes is only recorded with the `stats` feature.
+    #[inline(always)]
+    pub(crate) fn record_relabel(&mut self, size: usize) {
+        self.label_epoch += 1;
+        self.relabeled += size as u64;
+        if let Some(quota) = &mut self.quota {
+            quota.roll_window();
+            quota.window_relabeled += size as u64;
+        }
+        #[cfg(feature = "stats")]
+        {
+            self.stats.record(size);
+            self.stats_window.record(size);
+        }
+    }
+
+    /// Call `on_yield` every `every` priorities relabeled by a relabeling pass in this arena.
+    pub(crate) fn set_relabel_yield(&mut self, every: NonZeroUsize, on_yield: fn(usize)) {
+        self.relabel_yield = Some(RelabelYield { every, on_yield });
+    }
+
+    /// Note that the relabeling pass in progress has relabeled `done` priorities so far, yielding
+    /// to the arena's callback if it is due.
+    #[inline(always)]
+    pub(crate) fn relabel_progress(&self, done: usize) {
+        if let Some(y) = self.relabel_yield {
+            if done % y.every == 0 {
+                (y.on_yield)(done);
+            }
+        }
+    }
+
+    /// Refuse insertions that would make this arena hold more than `max_live` priorities, or that
+    /// come after relabeling `max_relabeled_per_sec` priorities within the current second.
+    pub(crate) fn set_quota(
+        &mut self,
+        max_live: Option<usize>,
+        max_relabeled_per_sec: Option<u64>,
+    ) {
+        self.quota = Some(Quota {
+            max_live,
+            max_relabeled_per_sec,
+            window_start: Instant::now(),
+            window_relabeled: 0,
+        });
+    }
+
+    /// Check that inserting `count` more priorities into this arena, identified by `arena_id`, is
+    /// within its quota, if it has one.
+    pub(crate) fn check_quota(
+        &mut self,
+        arena_id: usize,
+        count: usize,
+    ) -> Result<(), CapacityError> {
+        let Some(quota) = &mut self.quota else {
+            return Ok(());
+        };
+        let kind = match (quota.max_live, quota.max_relabeled_per_sec) {
+            (Some(max), _) if self.total + count > max => CapacityErrorKind::LiveQuota { max },
+            (_, Some(max_per_sec)) => {
+                quota.roll_window();
+                if quota.window_relabeled < max_per_sec {
+                    return Ok(());
+                }
+                CapacityErrorKind::RelabelQuota { max_per_sec }
+            }
+            _ => return Ok(()),
+        };
+        let context = CapacityContext {
+            arena_id,
+            live: self.total,
+            threshold: None,
+            window: None,
+        };
+        Err(CapacityError::with_kind(context, kind))
+    }
+
+    /// Reserve a region with room for `room` more priorities right after the priority with key
+    /// `key`.
+    pub(crate) fn reserve_region(&mut self, key: PriorityKey, room: usize) {
+        *self.regions.entry(key.key()).or_default() += room;
+    }
+
+    /// Release a region with room for `room` priorities reserved right after the priority with key
+    /// `key`.
+    pub(crate) fn release_region(&mut self, key: PriorityKey, room: usize) {
+        if let Some(reserved) = self.regions.get_mut(&key.key()) {
+            *reserved -= room;
+            if *reserved == 0 {
+                self.regions.remove(&key.key());
+            }
+        }
+    }
+
+    /// Number of priorities that relabeling passes leave room for right after the priority with key
+    /// `key`, as if they were already there.
+    #[inline(always)]
+    pub(crate) fn region_after(&self, key: PriorityKey) -> usize {
+        if self.regions.is_empty() {
+            return 0;
+        }
+        self.regions.get(&key.key()).copied().unwrap_or(0)
+    }
+
+    /// Number of priorities that relabeling passes leave room for in every region of this arena.
+    pub(crate) fn reserved(&self) -> usize {
+        self.regions.values().sum()
+    }
+
+    /// Spread the labels of every priority in this arena evenly across the labels within `mask`,
+    /// in list order, starting from `first` at the base priority, leaving room for reserved regions.
+    ///
+    /// Records the pass as relabeling every priority, and returns their number.
+    pub(crate) fn rebalance(&mut self, first: Label, mask: Label) -> usize {
+        let count = self.total;
+        let slots = count + self.reserved();
+        let space = u128::from(mask) + 1;
+        let mut key = self.base;
+        let mut reserved = 0;
+        for k in 0..count {
+            let offset = (k + reserved) as u128 * space / slots as u128;
+            let prio = self.get(key);
+            prio.set_label((first + offset as usize) & mask);
+            reserved += self.region_after(key);
+            key = prio.next();
+            self.relabel_progress(k + 1);
+        }
+        self.record_relabel(count);
+        self.note_relabeled(self.base, count);
+        count
+    }
+
+    /// Renumber every priority in this arena with consecutive labels within `mask`, in list order,
+    /// starting from `first` at the base priority, skipping a label per priority that reserved
+    /// regions leave room for.
+    ///
+    /// Records the pass as relabeling every priority, and returns their number.
+    pub(crate) fn compact(&mut self, first: Label, mask: Label) -> usize {
+        let count = self.total;
+        let mut key = self.base;
+        let mut reserved = 0;
+        for k in 0..count {
+            let prio = self.get(key);
+            prio.set_label((first + k + reserved) & mask);
+            reserved += self.region_after(key);
+            key = prio.next();
+            self.relabel_progress(k + 1);
+        }
+        self.record_relabel(count);
+        self.note_relabeled(self.base, count);
+        count
+    }
+
+    /// Note that the `count` priorities starting from `first`, in list order, were relabeled.
+    ///
+    /// Only logged with the `checkpoint` feature, once the arena has taken a checkpoint.
+    #[inline(always)]
+    pub(crate) fn note_relabeled(&mut self, first: PriorityKey, count: usize) {
+        #[cfg(feature = "checkpoint")]
+        if let Some(changes) = &mut self.changes {
+            let mut key = first;
+            for _ in 0..count {
+                changes.relabeled(key.key());
+                key = self.priorities[key.key()].next();
+            }
+        }
+        #[cfg(not(feature = "checkpoint"))]
+        let _ = (first, count);
+    }
+
     /// Insert a new priority into priorities store, constructing that priority using the given
     /// closure that takes the new key as argument.
     pub(crate) fn insert_after(&mut self, label: Label, prev_key: PriorityKey) -> PriorityKey {
         self.total += 1;
+        #[cfg(any(feature = "introspect", feature = "persistent-id"))]
+        {
+            self.created += 1;
+        }
         let next_key = self.get(prev_key).next();
-        let new_key = self
-            .priorities
-            .insert(PriorityInner {
+        let new_key = PriorityKey(track_alloc::alloc_node(
+            &mut self.priorities,
+            PriorityInner {
                 next: RefCell::new(next_key),
                 prev: RefCell::new(prev_key),
                 label: RefCell::new(label),
                 ref_count: RefCell::new(1),
-            })
-            .into();
+                #[cfg(any(feature = "introspect", feature = "persistent-id"))]
+                creation_index: self.created,
+            },
+        ));
         self.get(prev_key).set_next(new_key);
         self.get(next_key).set_prev(new_key);
+        #[cfg(feature = "checkpoint")]
+        if let Some(changes) = &mut self.changes {
+            changes.inserted(new_key.key());
+        }
+        self.tick();
         new_key
     }
 
+    /// Move the priority right after the base priority before it, making it the new base.
+    ///
+    /// The two priorities swap labels, so the labels of the list stay increasing from the base;
+    /// this is recorded as relabeling the old base priority.
+    pub(crate) fn promote_to_base(&mut self, key: PriorityKey) {
+        let old = self.base;
+        debug_assert!(self.get(old).next() == key);
+        let next = self.get(key).next();
+        self.get(old).set_next(next);
+        self.get(next).set_prev(old);
+        // Read the last priority only after unlinking, in case the list only held the two.
+        let last = self.get(old).prev();
+        self.get(last).set_next(key);
+        self.get(key).set_prev(last);
+        self.get(key).set_next(old);
+        self.get(old).set_prev(key);
+
+        let label = self.get(old).label();
+        self.get(old).set_label(self.get(key).label());
+        self.get(key).set_label(label);
+        self.base = key;
+        self.record_relabel(1);
+        self.note_relabeled(old, 1);
+    }
+
+    /// Insert `count` new priorities after `prev_key`, with labels spread evenly across the open
+    /// interval `(lo, hi)`.
+    ///
+    /// Returns the keys of the new priorities, in order.
+    pub(crate) fn insert_spread_after(
+        &mut self,
+        prev_key: PriorityKey,
+        count: usize,
+        lo: Label,
+        hi: Label,
+    ) -> Vec<PriorityKey> {
+        let width = u128::from(hi - lo);
+        let mut keys = Vec::with_capacity(count);
+        let mut prev_key = prev_key;
+        for k in 1..=count {
+            let offset = width * k as u128 / (count as u128 + 1);
+            prev_key = self.insert_after(lo + offset as usize, prev_key);
+            keys.push(prev_key);
+        }
+        keys
+    }
+
     /// Remove a priority from the priorities store.
+    ///
+    /// If the base priority is removed, the next priority becomes the base.
     pub(crate) fn remove(&mut self, key: PriorityKey) {
+        if key == self.base {
+            self.base = self.get(key).next();
+        }
         match self.total.cmp(&2) {
             Ordering::Greater => {
                 let prio = self.get(key);
@@ -126,8 +611,246 @@ impl Arena {
             Ordering::Less => (),
         }
 
-        self.priorities.remove(key.key());
+        track_alloc::free_node(&mut self.priorities, key.key());
+        if let Some(meta) = self.meta.remove(&key.key()) {
+            self.freed_meta.push(meta);
+        }
+        #[cfg(feature = "checkpoint")]
+        if let Some(changes) = &mut self.changes {
+            changes.removed(key.key());
+        }
         self.total -= 1;
+        if let Some(soft_cap) = &mut self.soft_cap {
+            soft_cap.over &= self.total > soft_cap.cap;
+        }
+        if self.total > 0 {
+            self.tick();
+        }
+    }
+
+    /// Check the invariants of this arena, for labels within `mask`.
+    ///
+    /// Starting from the base priority, the list of priorities must contain every priority in the
+    /// arena, each with consistent links and a positive reference count, and with labels that
+    /// strictly increase relative to the base label.
+    pub(crate) fn check(&self, mask: Label) -> Result<(), Violation> {
+        let base_label = self.get(self.base).label();
+        let mut key = self.base;
+        let mut prev_relative = None;
+        let mut found = 0;
+        loop {
+            let prio = self.get(key);
+            let next_key = prio.next();
+            let dangling = Violation::DanglingLink { key: key.key() };
+            let next = self
+                .priorities
+                .get(next_key.key())
+                .ok_or(dangling.clone())?;
+            if !self.priorities.contains(prio.prev().key()) {
+                return Err(dangling);
+            }
+            if next.prev() != key {
+                return Err(Violation::BrokenLink { key: key.key() });
+            }
+            if *prio.ref_count.borrow() == 0 {
+                return Err(Violation::Unreferenced { key: key.key() });
+            }
+
+            let relative = (prio.label() - base_label) & mask;
+            if prio.label() & mask != prio.label() || prev_relative >= Some(relative) {
+                return Err(Violation::Unordered { key: key.key() });
+            }
+            prev_relative = Some(relative);
+
+            found += 1;
+            key = next_key;
+            if key == self.base || found > self.priorities.len() {
+                break;
+            }
+        }
+
+        if found != self.total || self.total != self.priorities.len() {
+            return Err(Violation::Miscounted {
+                total: self.total,
+                found,
+            });
+        }
+        Ok(())
+    }
+
+    /// Index of the highest threshold that relabeling may use, if limited.
+    pub(crate) fn max_threshold(&self) -> Option<usize> {
+        self.max_threshold
+    }
+
+    /// Limit relabeling to thresholds with indices up to `max_threshold`, if any.
+    pub(crate) fn set_max_threshold(&mut self, max_threshold: Option<usize>) {
+        self.max_threshold = max_threshold;
+    }
+
+    /// Check the invariants of this arena on every `every`th insertion or removal, for labels
+    /// within `mask`; or never, if `every` is `None`.
+    pub(crate) fn set_self_check(&mut self, every: Option<NonZeroU64>, mask: Label) {
+        self.self_check = every.map(|every| SelfCheck {
+            every,
+            countdown: every.get(),
+            mask,
+        });
+    }
+
+    /// Results of the sampled invariant checks in this arena.
+    pub(crate) fn self_check_report(&self) -> &SelfCheckReport {
+        &self.self_check_report
+    }
+
+    /// Take a checkpoint in this arena, which has the given identifier, starting to log changes.
+    #[cfg(feature = "checkpoint")]
+    pub(crate) fn checkpoint(&mut self, arena_id: usize) -> crate::checkpoint::Checkpoint {
+        self.changes
+            .get_or_insert_with(Default::default)
+            .checkpoint(arena_id)
+    }
+
+    /// The net changes made to this arena, which has the given identifier, since `checkpoint`.
+    #[cfg(feature = "checkpoint")]
+    pub(crate) fn changes_since(
+        &self,
+        arena_id: usize,
+        checkpoint: &crate::checkpoint::Checkpoint,
+    ) -> Option<crate::checkpoint::Changes> {
+        self.changes.as_ref()?.since(arena_id, checkpoint, |key| {
+            self.priorities[key].label().into()
+        })
+    }
+
+    /// Discard the changes logged before `checkpoint` in this arena, which has the given
+    /// identifier.
+    #[cfg(feature = "checkpoint")]
+    pub(crate) fn discard_changes_before(
+        &mut self,
+        arena_id: usize,
+        checkpoint: &crate::checkpoint::Checkpoint,
+    ) {
+        if let Some(changes) = &mut self.changes {
+            changes.discard_before(arena_id, checkpoint);
+        }
+    }
+
+    /// Count an insertion or removal, checking invariants if it is time to.
+    #[inline]
+    fn tick(&mut self) {
+        let Some(self_check) = &mut self.self_check else {
+            return;
+        };
+        self_check.countdown -= 1;
+        if self_check.countdown == 0 {
+            self_check.countdown = self_check.every.get();
+            let mask = self_check.mask;
+            let result = self.check(mask);
+            self.self_check_report.record(result);
+        }
+    }
+}
+
+/// Soft limit on the number of priorities in an arena, and whether it is exceeded.
+#[derive(Debug, Clone, Copy)]
+struct SoftCap {
+    /// Number of priorities that the arena may hold without exceeding the limit.
+    cap: usize,
+
+    /// Called with the number of priorities in the arena when an insertion exceeds the limit.
+    on_cross: fn(usize),
+
+    /// Whether the arena currently exceeds the limit.
+    over: bool,
+}
+
+/// How often relabeling passes in an arena yield to their caller.
+#[derive(Debug, Clone, Copy)]
+struct RelabelYield {
+    /// Number of priorities to relabel between calls to `on_yield`.
+    every: NonZeroUsize,
+
+    /// Called with the number of priorities relabeled so far by the pass in progress.
+    on_yield: fn(usize),
+}
+
+/// Quota on insertions into an arena, and the relabeling work counted against it.
+#[derive(Debug, Clone, Copy)]
+struct Quota {
+    /// Maximum number of priorities that the arena may hold, if limited.
+    max_live: Option<usize>,
+
+    /// Maximum number of priorities that may be relabeled per second, if limited.
+    max_relabeled_per_sec: Option<u64>,
+
+    /// Start of the current one-second window.
+    window_start: Instant,
+
+    /// Number of priorities relabeled within the current window.
+    window_relabeled: u64,
+}
+
+impl Quota {
+    /// Start a new window if the current one is over.
+    fn roll_window(&mut self) {
+        if self.window_start.elapsed() >= Duration::from_secs(1) {
+            self.window_start = Instant::now();
+            self.window_relabeled = 0;
+        }
+    }
+}
+
+/// Configuration and state of sampled invariant checks in an arena.
+#[derive(Debug)]
+struct SelfCheck {
+    /// Invariants are checked on every `every`th operation.
+    every: NonZeroU64,
+
+    /// Number of operations until the next check.
+    countdown: u64,
+
+    /// Mask of the bits used by labels in the arena.
+    mask: Label,
+}
+
+/// Context of a panic caused by running out of labels, formatted into its message.
+///
+/// Included so that crash reports can be acted on without reproducing them.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct CapacityContext {
+    /// Identifier of the arena that ran out of labels; see [`PriorityRef::arena_id()`].
+    pub(crate) arena_id: usize,
+
+    /// Number of live priorities in the arena.
+    pub(crate) live: usize,
+
+    /// Index of the threshold in use, if any applies.
+    pub(crate) threshold: Option<usize>,
+
+    /// Window of labels being relabeled (inclusive), and its level, if relabeling had started.
+    pub(crate) window: Option<(Label, Label, usize)>,
+}
+
+impl std::fmt::Display for CapacityContext {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "arena: {:#x}, live priorities: {}",
+            self.arena_id, self.live
+        )?;
+        if let Some(threshold) = self.threshold {
+            write!(f, ", threshold index: {threshold}")?;
+        }
+        if let Some((lo, hi, level)) = self.window {
+            write!(
+                f,
+                ", label window: [{}, {}] at level {level}",
+                usize::from(lo),
+                usize::from(hi)
+            )?;
+        }
+        Ok(())
     }
 }
 
@@ -149,6 +872,10 @@ pub(crate) struct PriorityInner {
 
     /// Reference count; when this reaches zero, it will be deallocated from the [`Arena`].
     ref_count: RefCell<usize>,
+
+    /// Number of priorities allocated in the arena before this one.
+    #[cfg(any(feature = "introspect", feature = "persistent-id"))]
+    creation_index: usize,
 }
 
 impl PriorityInner {
@@ -186,24 +913,108 @@ impl PriorityInner {
         *self.ref_count.borrow_mut() -= 1;
         *self.ref_count.borrow() == 0
     }
+
+    /// Number of handles to this priority.
+    #[cfg(feature = "introspect")]
+    pub(crate) fn ref_count(&self) -> usize {
+        *self.ref_count.borrow()
+    }
+
+    /// Number of priorities allocated in the arena before this one.
+    #[cfg(any(feature = "introspect", feature = "persistent-id"))]
+    pub(crate) fn creation_index(&self) -> usize {
+        self.creation_index
+    }
+}
+
+/// An arena, shared by the handles to its priorities.
+#[derive(Debug)]
+struct SharedArena {
+    arena: RefCell<Arena>,
+
+    /// Keys of priorities whose handles were dropped but not yet released, if drops are deferred.
+    ///
+    /// Kept outside of the arena so that dropping a handle never borrows the arena.
+    dropped: Option<RefCell<Vec<PriorityKey>>>,
+
+    /// Log of the operations performed on the arena.
+    ///
+    /// Kept outside of the arena so that cloning a handle never borrows the arena mutably.
+    #[cfg(feature = "oplog")]
+    log: RefCell<crate::oplog::OpLog>,
+}
+
+impl SharedArena {
+    fn new(arena: Arena) -> Rc<Self> {
+        let dropped = arena.deferred_drop.then(Default::default);
+        Rc::new(Self {
+            arena: RefCell::new(arena),
+            dropped,
+            #[cfg(feature = "oplog")]
+            log: Default::default(),
+        })
+    }
+
+    /// Append `op` to the log of the arena.
+    #[cfg(feature = "oplog")]
+    fn record(&self, op: crate::oplog::Op) {
+        self.log.borrow_mut().push(op);
+    }
+
+    /// Release a handle to the priority with key `this` in `a`, which is this arena, borrowed;
+    /// returns whether the priority was freed.
+    fn release(&self, a: &mut Arena, this: PriorityKey) -> bool {
+        #[cfg(feature = "oplog")]
+        self.record(crate::oplog::Op::Drop(this.key()));
+        if a.get(this).ref_dec() {
+            // Ref count reached zero; remove this node from the linked list, then deallocate
+            // it from the arena.
+            a.remove(this);
+            true
+        } else {
+            false
+        }
+    }
+}
+
+impl std::ops::Deref for SharedArena {
+    type Target = RefCell<Arena>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.arena
+    }
 }
 
 /// Smart pointer to an arena and a key to a priority in that arena.
 ///
 /// Reference-counted; `Clone` and `Drop` are implemented so that it acts like a smart pointer.
-#[derive(Debug)]
 pub struct PriorityRef {
-    arena: Rc<RefCell<Arena>>,
+    arena: Rc<SharedArena>,
     this: PriorityKey,
 }
 
 impl PriorityRef {
     /// Allocate a new priority handle.
     pub(crate) fn new(arena: Arena, this: PriorityKey) -> Self {
-        Self {
-            arena: Rc::new(RefCell::new(arena)),
-            this,
-        }
+        let arena = SharedArena::new(arena);
+        #[cfg(feature = "oplog")]
+        arena.record(crate::oplog::Op::New);
+        Self { arena, this }
+    }
+
+    /// Allocate handles to several priorities in the same arena.
+    ///
+    /// Each key must have a reference count of 1 that is not yet owned by any other handle.
+    pub(crate) fn new_many(arena: Arena, keys: Vec<PriorityKey>) -> Vec<Self> {
+        let arena = SharedArena::new(arena);
+        #[cfg(feature = "oplog")]
+        arena.record(crate::oplog::Op::NewSequence(keys.len()));
+        keys.into_iter()
+            .map(|this| Self {
+                arena: arena.clone(),
+                this,
+            })
+            .collect()
     }
 
     /// Get the key
@@ -211,19 +1022,343 @@ impl PriorityRef {
         self.this
     }
 
+    /// Get a new handle to the priority that `find` locates in this priority's arena.
+    ///
+    /// `find` is given the arena and the key of this priority, and must return the key of a live
+    /// priority that is referenced by some handle, i.e., not list-range's base priority.
+    pub(crate) fn find(&self, find: impl FnOnce(&Arena, PriorityKey) -> PriorityKey) -> Self {
+        let this = {
+            let a = self.arena.borrow();
+            let this = find(&a, self.this);
+            a.get(this).ref_inc();
+            this
+        };
+        #[cfg(feature = "oplog")]
+        self.arena.record(crate::oplog::Op::Clone(this.key()));
+        Self {
+            arena: self.arena.clone(),
+            this,
+        }
+    }
+
+    /// The persistent identifier of this priority; see [`crate::persistent_id`].
+    #[cfg(feature = "persistent-id")]
+    pub(crate) fn persistent_id(&self) -> crate::persistent_id::PersistentId {
+        let a = self.arena.borrow();
+        crate::persistent_id::PersistentId::new(a.uuid, a.get(self.this).creation_index() as u64)
+    }
+
+    /// Get a new handle to the live priority of this priority's arena whose persistent identifier
+    /// is `id`, if any.
+    ///
+    /// The base priority is never found if `skip_base`, e.g., since it is not handed out. This
+    /// walks the list from the base, so it takes time linear in the size of the arena.
+    #[cfg(feature = "persistent-id")]
+    pub(crate) fn find_persistent(
+        &self,
+        id: crate::persistent_id::PersistentId,
+        skip_base: bool,
+    ) -> Option<Self> {
+        let key = {
+            let a = self.arena.borrow();
+            if id.arena() != a.uuid {
+                return None;
+            }
+            let mut key = a.base();
+            if skip_base {
+                key = a.get(key).next();
+            }
+            loop {
+                let prio = a.get(key);
+                if prio.creation_index() as u64 == id.index() {
+                    break key;
+                }
+                key = prio.next();
+                if key == a.base() {
+                    return None;
+                }
+            }
+        };
+        Some(self.find(|_, _| key))
+    }
+
+    /// Get new handles to every priority in this priority's arena, in list order.
+    ///
+    /// Starts from the base priority, unless `skip_base`, e.g., since it is not handed out.
+    pub(crate) fn arena_handles(&self, skip_base: bool) -> Vec<Self> {
+        let keys = {
+            let a = self.arena.borrow();
+            let mut keys = Vec::with_capacity(a.total());
+            let mut key = a.base();
+            loop {
+                keys.push(key);
+                key = key.as_ref(&a).next();
+                if key == a.base() {
+                    break;
+                }
+            }
+            if skip_base {
+                keys.remove(0);
+            }
+            keys
+        };
+        keys.into_iter().map(|key| self.find(|_, _| key)).collect()
+    }
+
+    /// Snapshot the identifiers and labels of every priority in this priority's arena, relative to
+    /// the base label and within `mask`, without creating any handles.
+    ///
+    /// Starts from the base priority, unless `skip_base`, e.g., since it is not handed out.
+    pub(crate) fn read_snapshot(
+        &self,
+        skip_base: bool,
+        mask: Label,
+    ) -> crate::frozen::ReadSnapshot {
+        let a = self.arena.borrow();
+        let base_label = a.get(a.base()).label();
+        let mut entries = Vec::with_capacity(a.total());
+        let mut key = a.base();
+        loop {
+            let relative = (key.as_ref(&a).label() - base_label) & mask;
+            entries.push((key.key(), usize::from(relative)));
+            key = key.as_ref(&a).next();
+            if key == a.base() {
+                break;
+            }
+        }
+        if skip_base {
+            entries.remove(0);
+        }
+        crate::frozen::ReadSnapshot::new(self.arena_id(), entries, a.label_epoch())
+    }
+
     /// Insert a new priority after this one in the arena.
     ///
-    /// The callback `f` is used to:
+    /// The callback `f` is given a cursor at this priority, and is used to:
     /// (1) perform any necessary relabeling, and
     /// (2) compute the new label.
-    pub(crate) fn insert(&self, f: impl FnOnce(&mut Arena) -> Label) -> Self {
+    pub(crate) fn insert(&self, f: impl FnOnce(&mut Cursor<'_>) -> Label) -> Self {
+        match self.try_insert(|at| Ok::<_, std::convert::Infallible>(f(at))) {
+            Ok(p) => p,
+            Err(e) => match e {},
+        }
+    }
+
+    /// Insert a new priority after this one, like [`insert()`](Self::insert), unless `f` fails to
+    /// compute its label, in which case the error is returned and nothing is inserted.
+    pub(crate) fn try_insert<E>(
+        &self,
+        f: impl FnOnce(&mut Cursor<'_>) -> Result<Label, E>,
+    ) -> Result<Self, E> {
+        let arena_id = self.arena_id();
         let mut arena = self.arena.borrow_mut();
-        let new_label = f(&mut arena);
+        let new_label = f(&mut Cursor::new(&mut arena, self.this, arena_id))?;
         let this = arena.insert_after(new_label, self.this());
-        Self {
+        #[cfg(feature = "oplog")]
+        self.arena.record(crate::oplog::Op::Insert(self.this.key()));
+        let crossed = arena.crossed_soft_cap();
+        drop(arena);
+        if let Some((on_cross, live)) = crossed {
+            on_cross(live);
+        }
+        Ok(Self {
             arena: self.arena.clone(),
             this,
+        })
+    }
+
+    /// Insert new priorities after this one in the arena, in order.
+    ///
+    /// The callback `f` is given a cursor at this priority, like that of [`insert()`](Self::insert),
+    /// and computes the labels of all of the new priorities, which must increase from this
+    /// priority's label, and stay below that of the next priority.
+    pub(crate) fn insert_n(&self, f: impl FnOnce(&mut Cursor<'_>) -> Vec<Label>) -> Vec<Self> {
+        let arena_id = self.arena_id();
+        let mut arena = self.arena.borrow_mut();
+        let labels = f(&mut Cursor::new(&mut arena, self.this, arena_id));
+        #[cfg(feature = "oplog")]
+        self.arena
+            .record(crate::oplog::Op::InsertN(self.this.key(), labels.len()));
+        let mut prev = self.this;
+        let mut inserted = Vec::with_capacity(labels.len());
+        for label in labels {
+            let this = arena.insert_after(label, prev);
+            inserted.push(Self {
+                arena: self.arena.clone(),
+                this,
+            });
+            prev = this;
+        }
+        let crossed = arena.crossed_soft_cap();
+        drop(arena);
+        if let Some((on_cross, live)) = crossed {
+            on_cross(live);
+        }
+        inserted
+    }
+
+    /// Reserve a region with room for `room` priorities right after this priority, kept free by
+    /// relabeling passes until it is released.
+    ///
+    /// The callback `f` is given a cursor at this priority once the region is recorded, and is
+    /// used to relabel if the labels right after this priority are not free yet. If it fails, the
+    /// region is released again.
+    pub(crate) fn reserve_region(
+        &self,
+        room: usize,
+        f: impl FnOnce(&mut Cursor<'_>) -> Result<(), CapacityError>,
+    ) -> Result<(), CapacityError> {
+        let arena_id = self.arena_id();
+        let mut arena = self.arena.borrow_mut();
+        arena.reserve_region(self.this, room);
+        let relabeled = f(&mut Cursor::new(&mut arena, self.this, arena_id));
+        if relabeled.is_err() {
+            arena.release_region(self.this, room);
+            return relabeled;
         }
+        #[cfg(feature = "oplog")]
+        self.arena
+            .record(crate::oplog::Op::ReserveRegion(self.this.key(), room));
+        Ok(())
+    }
+
+    /// Release a region with room for `room` priorities reserved right after this priority.
+    pub(crate) fn release_region(&self, room: usize) {
+        let mut arena = self.arena.borrow_mut();
+        arena.release_region(self.this, room);
+        #[cfg(feature = "oplog")]
+        self.arena
+            .record(crate::oplog::Op::ReleaseRegion(self.this.key(), room));
+    }
+
+    /// Prepare to insert a new priority after this one, without inserting it yet.
+    ///
+    /// The callback `f` is like that of [`insert()`](Self::insert). Returns the label that it
+    /// computed, along with the label epoch of the arena after any relabeling that it performed.
+    pub(crate) fn prepare_insert(&self, f: impl FnOnce(&mut Cursor<'_>) -> Label) -> (Label, u64) {
+        let arena_id = self.arena_id();
+        let mut arena = self.arena.borrow_mut();
+        let label = f(&mut Cursor::new(&mut arena, self.this, arena_id));
+        (label, arena.label_epoch())
+    }
+
+    /// Insert a new priority before every other priority in this priority's arena.
+    ///
+    /// The callback `f` is given a cursor at the base priority, like [`insert()`](Self::insert).
+    /// If `promote`, i.e., the base priority is itself the least priority, the new priority is
+    /// then moved before it; see [`Arena::promote_to_base()`]. Otherwise, the base priority must be
+    /// a sentinel that precedes every priority, so inserting right after it suffices.
+    pub(crate) fn insert_front(
+        &self,
+        promote: bool,
+        f: impl FnOnce(&mut Cursor<'_>) -> Label,
+    ) -> Self {
+        match self.try_insert_front(promote, |at| Ok::<_, std::convert::Infallible>(f(at))) {
+            Ok(p) => p,
+            Err(e) => match e {},
+        }
+    }
+
+    /// Insert a new priority before every other priority in this priority's arena, like
+    /// [`insert_front()`](Self::insert_front), unless `f` fails to compute its label, in which case
+    /// the error is returned and nothing is inserted.
+    pub(crate) fn try_insert_front<E>(
+        &self,
+        promote: bool,
+        f: impl FnOnce(&mut Cursor<'_>) -> Result<Label, E>,
+    ) -> Result<Self, E> {
+        let arena_id = self.arena_id();
+        let mut arena = self.arena.borrow_mut();
+        let base = arena.base();
+        let new_label = f(&mut Cursor::new(&mut arena, base, arena_id))?;
+        let this = arena.insert_after(new_label, base);
+        if promote {
+            arena.promote_to_base(this);
+        }
+        #[cfg(feature = "oplog")]
+        self.arena
+            .record(crate::oplog::Op::InsertFront(self.this.key()));
+        let crossed = arena.crossed_soft_cap();
+        drop(arena);
+        if let Some((on_cross, live)) = crossed {
+            on_cross(live);
+        }
+        Ok(Self {
+            arena: self.arena.clone(),
+            this,
+        })
+    }
+
+    /// Spread the labels of every priority in this priority's arena evenly across the labels
+    /// within `mask`; see [`Arena::rebalance()`].
+    ///
+    /// The base priority keeps its label if `keep_base`, or else starts from [`Arena::BASE`].
+    pub(crate) fn rebalance(&self, keep_base: bool, mask: Label) -> usize {
+        let mut arena = self.arena.borrow_mut();
+        let first = if keep_base {
+            arena.get(arena.base()).label()
+        } else {
+            Arena::BASE
+        };
+        #[cfg(feature = "oplog")]
+        self.arena
+            .record(crate::oplog::Op::Rebalance(self.this.key()));
+        arena.rebalance(first, mask)
+    }
+
+    /// The algorithm that switchable priorities in this priority's arena are relabeled with.
+    pub(crate) fn algorithm(&self) -> crate::workload::Algorithm {
+        self.arena.borrow().algorithm
+    }
+
+    /// Relabel switchable priorities in this priority's arena with `algorithm` from now on.
+    pub(crate) fn set_algorithm(&self, algorithm: crate::workload::Algorithm) {
+        self.arena.borrow_mut().algorithm = algorithm;
+    }
+
+    /// Renumber every priority in this priority's arena with consecutive labels within `mask`; see
+    /// [`Arena::compact()`].
+    ///
+    /// The base priority keeps its label if `keep_base`, or else starts from [`Arena::BASE`].
+    pub(crate) fn compact(&self, keep_base: bool, mask: Label) -> usize {
+        let mut arena = self.arena.borrow_mut();
+        let first = if keep_base {
+            arena.get(arena.base()).label()
+        } else {
+            Arena::BASE
+        };
+        #[cfg(feature = "oplog")]
+        self.arena
+            .record(crate::oplog::Op::Compact(self.this.key()));
+        arena.compact(first, mask)
+    }
+
+    /// Whether this priority's arena holds more priorities than its soft cap, if it has one.
+    pub(crate) fn over_soft_cap(&self) -> bool {
+        self.arena.borrow().over_soft_cap()
+    }
+
+    /// Call `f` with this priority's arena, borrowing it just once.
+    pub(crate) fn with_arena<R>(&self, f: impl FnOnce(&Arena) -> R) -> R {
+        f(&self.arena.borrow())
+    }
+
+    /// Attach `meta` to this priority, or detach its metadata if `meta` is `None`.
+    pub(crate) fn set_meta(&self, meta: Option<Rc<dyn Any>>) {
+        // The old metadata is dropped once the arena is no longer borrowed.
+        let _old = self.arena.borrow_mut().set_meta(self.this, meta);
+    }
+
+    /// Get the metadata attached to this priority, if it is of type `T`.
+    pub(crate) fn meta<T: Any>(&self) -> Option<Rc<T>> {
+        let meta = self.arena.borrow().meta(self.this)?;
+        meta.downcast().ok()
+    }
+
+    /// Get the data attached to this priority's arena, if it is of type `T`.
+    pub(crate) fn arena_data<T: Any>(&self) -> Option<Rc<T>> {
+        let data = self.arena.borrow().data.clone()?;
+        data.downcast().ok()
     }
 
     /// Get the label of this priority.
@@ -231,6 +1366,12 @@ impl PriorityRef {
         self.arena.borrow().get(self.this).label()
     }
 
+    /// Get the label of this priority, along with the label epoch of its arena.
+    pub(crate) fn label_with_epoch(&self) -> (Label, u64) {
+        let a = self.arena.borrow();
+        (a.get(self.this).label(), a.label_epoch())
+    }
+
     /// Get the label of the base priority.
     pub(crate) fn base_label(&self) -> Label {
         let a = self.arena.borrow();
@@ -241,12 +1382,203 @@ impl PriorityRef {
     pub(crate) fn same_arena(&self, other: &Self) -> bool {
         Rc::ptr_eq(&self.arena, &other.arena)
     }
+
+    /// Compare this priority with one from another arena, by the creation order of their arenas.
+    ///
+    /// Returns `None` unless both arenas order priorities across arenas; see
+    /// [`Arena::order_across_arenas()`]. Panics if either arena was configured to; see
+    /// [`Arena::panic_across_arenas()`].
+    pub(crate) fn cross_arena_cmp(&self, other: &Self) -> Option<Ordering> {
+        let (ours, theirs) = (self.arena.borrow(), other.arena.borrow());
+        if ours.panic_across_arenas || theirs.panic_across_arenas {
+            panic!(
+                "compared priorities from different arenas: label {} in arena {:#x} with label {} \
+                 in arena {:#x}",
+                usize::from(ours.get(self.this).label()),
+                self.arena_id(),
+                usize::from(theirs.get(other.this).label()),
+                other.arena_id(),
+            );
+        }
+        Some(ours.ordinal?.cmp(&theirs.ordinal?))
+    }
+
+    /// Identifier of the arena this priority belongs to, unique among arenas that are alive.
+    ///
+    /// This does not borrow the arena, so it can be used while the arena is being modified.
+    pub(crate) fn arena_id(&self) -> usize {
+        Rc::as_ptr(&self.arena) as usize
+    }
+
+    /// The identifier of this priority, which does not keep it alive.
+    pub(crate) fn id(&self) -> crate::id::PriorityId {
+        crate::id::PriorityId::new(self.arena_id(), self.this.key())
+    }
+
+    /// Borrow this priority's arena, for comparing identifiers of priorities with labels within
+    /// `mask`.
+    pub(crate) fn id_order(&self, mask: Label) -> crate::id::IdOrder<'_> {
+        crate::id::IdOrder::new(self.arena.borrow(), self.arena_id(), mask)
+    }
+
+    /// Position of this priority in the arena's list, where the base priority is at position 0.
+    ///
+    /// This walks the list from the base, so it takes time linear in the position.
+    pub(crate) fn position(&self) -> usize {
+        let a = self.arena.borrow();
+        let mut key = a.base();
+        let mut position = 0;
+        while key != self.this {
+            key = key.as_ref(&a).next();
+            position += 1;
+        }
+        position
+    }
+
+    /// Check the invariants of this priority's arena; see [`Arena::check()`].
+    pub(crate) fn check(&self, mask: Label) -> Result<(), Violation> {
+        self.arena.borrow().check(mask)
+    }
+
+    /// Configure sampled invariant checks in this priority's arena; see
+    /// [`Arena::set_self_check()`].
+    pub(crate) fn set_self_check(&self, every: Option<NonZeroU64>, mask: Label) {
+        self.arena.borrow_mut().set_self_check(every, mask)
+    }
+
+    /// Get the results of sampled invariant checks in this priority's arena.
+    pub(crate) fn self_check_report(&self) -> SelfCheckReport {
+        self.arena.borrow().self_check_report().clone()
+    }
+
+    /// Get the statistics about relabeling in this priority's arena.
+    #[cfg(feature = "stats")]
+    pub(crate) fn stats(&self) -> crate::stats::RelabelStats {
+        self.arena.borrow().stats.clone()
+    }
+
+    /// Take the statistics about relabeling in this priority's arena since they were last polled,
+    /// starting a new window.
+    #[cfg(feature = "stats")]
+    pub(crate) fn poll_stats(&self) -> crate::stats::RelabelStats {
+        std::mem::take(&mut self.arena.borrow_mut().stats_window)
+    }
+
+    /// Reset the statistics about relabeling in this priority's arena, including the current
+    /// window.
+    #[cfg(feature = "stats")]
+    pub(crate) fn reset_stats(&self) {
+        let mut a = self.arena.borrow_mut();
+        a.stats.reset();
+        a.stats_window.reset();
+    }
+
+    /// Get the log of operations performed on this priority's arena.
+    #[cfg(feature = "oplog")]
+    pub(crate) fn op_log(&self) -> crate::oplog::OpLog {
+        self.arena.log.borrow().clone()
+    }
+
+    /// Get the key of this priority, as recorded in operation logs.
+    #[cfg(feature = "oplog")]
+    pub(crate) fn op_key(&self) -> usize {
+        self.this.key()
+    }
+
+    /// Get the key of this priority, as reported in changes since checkpoints.
+    #[cfg(feature = "checkpoint")]
+    pub(crate) fn change_key(&self) -> usize {
+        self.this.key()
+    }
+
+    /// Take a checkpoint in this priority's arena.
+    #[cfg(feature = "checkpoint")]
+    pub(crate) fn checkpoint(&self) -> crate::checkpoint::Checkpoint {
+        let arena_id = self.arena_id();
+        self.arena.borrow_mut().checkpoint(arena_id)
+    }
+
+    /// The net changes made to this priority's arena since `checkpoint`, if it is still logged.
+    #[cfg(feature = "checkpoint")]
+    pub(crate) fn changes_since(
+        &self,
+        checkpoint: &crate::checkpoint::Checkpoint,
+    ) -> Option<crate::checkpoint::Changes> {
+        self.arena
+            .borrow()
+            .changes_since(self.arena_id(), checkpoint)
+    }
+
+    /// Discard the changes logged before `checkpoint` in this priority's arena.
+    #[cfg(feature = "checkpoint")]
+    pub(crate) fn discard_changes_before(&self, checkpoint: &crate::checkpoint::Checkpoint) {
+        let arena_id = self.arena_id();
+        self.arena
+            .borrow_mut()
+            .discard_changes_before(arena_id, checkpoint);
+    }
+
+    /// Report of the last relabeling pass in this priority's arena, if relabeling passes are
+    /// explained.
+    pub(crate) fn last_relabel_report(&self) -> Option<crate::explain::RelabelReport> {
+        self.arena.borrow().last_relabel_report().cloned()
+    }
+
+    /// Get the creation index of this priority; see [`PriorityInner::creation_index()`].
+    #[cfg(feature = "introspect")]
+    pub(crate) fn creation_index(&self) -> usize {
+        self.arena.borrow().get(self.this).creation_index()
+    }
+
+    /// Get the labels of the previous and next priorities.
+    #[cfg(feature = "introspect")]
+    pub(crate) fn neighbor_labels(&self) -> (Label, Label) {
+        let a = self.arena.borrow();
+        let this = self.this.as_ref(&a);
+        (
+            this.prev().as_ref(&a).label(),
+            this.next().as_ref(&a).label(),
+        )
+    }
+
+    /// Dump every priority in the arena, in list order starting from the base priority.
+    #[cfg(feature = "introspect")]
+    pub(crate) fn dump(&self) -> crate::introspect::ArenaDump {
+        let a = self.arena.borrow();
+        let mut nodes = Vec::with_capacity(a.total());
+        let mut key = a.base();
+        loop {
+            let prio = key.as_ref(&a);
+            nodes.push(crate::introspect::NodeDump {
+                label: prio.label().into(),
+                ref_count: prio.ref_count(),
+                creation_index: prio.creation_index(),
+                is_self: key == self.this,
+            });
+            key = prio.next();
+            if key == a.base() {
+                break;
+            }
+        }
+        crate::introspect::ArenaDump { nodes }
+    }
+}
+
+impl Debug for PriorityRef {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("PriorityRef")
+            .field("arena", &format_args!("{:#x}", self.arena_id()))
+            .field("key", &self.this.key())
+            .finish()
+    }
 }
 
 impl Clone for PriorityRef {
     fn clone(&self) -> Self {
         // Increment ref count of the `PriorityInner`.
         self.arena.borrow().get(self.this).ref_inc();
+        #[cfg(feature = "oplog")]
+        self.arena.record(crate::oplog::Op::Clone(self.this.key()));
 
         Self {
             arena: self.arena.clone(),
@@ -255,13 +1587,36 @@ impl Clone for PriorityRef {
     }
 }
 
+impl PriorityRef {
+    /// Release the handles whose drops were deferred, returning the number of priorities freed.
+    pub(crate) fn gc(&self) -> usize {
+        let Some(dropped) = &self.arena.dropped else {
+            return 0;
+        };
+        let dropped = dropped.take();
+        let mut a = self.arena.borrow_mut();
+        let freed = dropped
+            .into_iter()
+            .filter(|&key| self.arena.release(&mut a, key))
+            .count();
+        let freed_meta = a.take_freed_meta();
+        drop(a);
+        drop(freed_meta);
+        freed
+    }
+}
+
 impl Drop for PriorityRef {
     fn drop(&mut self) {
-        let mut a = self.arena.borrow_mut();
-        if a.get(self.this).ref_dec() {
-            // Ref count reached zero; remove this node from the linked list, then deallocate
-            // it from the arena.
-            a.remove(self.this);
+        match &self.arena.dropped {
+            Some(dropped) => dropped.borrow_mut().push(self.this),
+            None => {
+                let mut a = self.arena.borrow_mut();
+                self.arena.release(&mut a, self.this);
+                let freed_meta = a.take_freed_meta();
+                drop(a);
+                drop(freed_meta);
+            }
         }
     }
 }
@@ -364,4 +1719,67 @@ mod tests {
         assert_priority_count(&p1.arena.borrow(), 2);
         assert_ref_count(&p1, 1);
     }
+
+    #[test]
+    fn insert_spread() {
+        let mut a = Arena::new();
+        let keys = a.insert_spread_after(a.base(), 3, Label::new(0), Label::new(100));
+        assert_priority_count(&a, 4);
+
+        let labels: Vec<Label> = keys.iter().map(|k| k.as_ref(&a).label()).collect();
+        assert_eq!(labels, vec![Label::new(25), Label::new(50), Label::new(75)]);
+        assert_eq!(a.get(a.base()).next(), keys[0]);
+        assert_eq!(a.get(keys[2]).next(), a.base());
+    }
+
+    #[test]
+    fn check_invariants() {
+        let p1 = new_priority_after_base(Label::new(1));
+        let p3 = p1.insert(|_| Label::new(3));
+        let p2 = p1.insert(|_| Label::new(2));
+        assert_eq!(p1.check(Label::MAX), Ok(()));
+
+        // Labels must fit in the mask.
+        assert_eq!(
+            p1.check(Label::new(1)),
+            Err(Violation::Unordered { key: p2.this.key() })
+        );
+
+        p3.arena.borrow().get(p2.this).set_label(Label::new(4));
+        assert_eq!(
+            p1.check(Label::MAX),
+            Err(Violation::Unordered { key: p3.this.key() })
+        );
+        p3.arena.borrow().get(p2.this).set_label(Label::new(2));
+
+        p3.arena.borrow().get(p3.this).set_prev(p1.this);
+        assert_eq!(
+            p1.check(Label::MAX),
+            Err(Violation::BrokenLink { key: p2.this.key() })
+        );
+        p3.arena.borrow().get(p3.this).set_prev(p2.this);
+        assert_eq!(p1.check(Label::MAX), Ok(()));
+    }
+
+    #[test]
+    fn remove_base() {
+        let p1 = new_priority_from_base();
+        let p2 = p1.insert(|_| Label::new(2));
+        drop(p1);
+        assert_eq!(p2.arena.borrow().base(), p2.this);
+        assert_eq!(p2.position(), 0);
+        assert_eq!(p2.check(Label::MAX), Ok(()));
+    }
+
+    #[test]
+    fn priority_position() {
+        let p1 = new_priority_after_base(Label::new(1));
+        let p3 = p1.insert(|_| Label::new(3));
+        let p2 = p1.insert(|_| Label::new(2));
+        assert_eq!(p1.position(), 1);
+        assert_eq!(p2.position(), 2);
+        assert_eq!(p3.position(), 3);
+        assert_eq!(p1.arena_id(), p3.arena_id());
+        assert_ne!(p1.arena_id(), new_priority_from_base().arena_id());
+    }
 }
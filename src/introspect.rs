@@ -0,0 +1,60 @@
+//! Read-only access to the internal state of priorities, for debugging and testing.
+//!
+//! Only available with the `introspect` feature. Labels exposed here are implementation details:
+//! they change whenever an arena is relabeled, and are not meaningful across priority types.
+
+/// A single priority in an [`ArenaDump`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NodeDump {
+    /// The raw label of the priority.
+    pub label: usize,
+    /// Number of handles to the priority.
+    pub ref_count: usize,
+    /// The creation index of the priority; see [`Introspect::creation_index()`].
+    pub creation_index: usize,
+    /// Whether this is the priority that the arena was dumped from.
+    pub is_self: bool,
+}
+
+/// A snapshot of every priority in an arena.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ArenaDump {
+    /// The priorities of the arena in list order, starting from its base priority.
+    pub nodes: Vec<NodeDump>,
+}
+
+impl ArenaDump {
+    /// The raw labels of the priorities in the arena, in list order.
+    pub fn labels(&self) -> Vec<usize> {
+        self.nodes.iter().map(|n| n.label).collect()
+    }
+}
+
+/// Read-only access to the labels of priorities that are maintained in an arena.
+pub trait Introspect {
+    /// The raw label of this priority.
+    fn raw_label(&self) -> usize;
+
+    /// The raw labels of the priorities immediately before and after this one, in list order.
+    ///
+    /// The list is circular, so the first and last priorities are each other's neighbors.
+    fn neighbor_labels(&self) -> (usize, usize);
+
+    /// The label distance from this priority to the next one, i.e., one more than the number of
+    /// free labels between them.
+    ///
+    /// Labels are compared as the priorities are, e.g., relative to the base priority in
+    /// list-range arenas. For the greatest priority, this is the distance to the end of the label
+    /// space instead, saturating at [`u64::MAX`]. Inserting after a priority whose gap is greater
+    /// than 1 never relabels.
+    fn gap_to_next(&self) -> u64;
+
+    /// The number of priorities that were allocated in this priority's arena before it.
+    ///
+    /// Creation indices are unique within an arena, and do not change when priorities are
+    /// relabeled, so they can be used to refer to priorities unambiguously, e.g., in logs.
+    fn creation_index(&self) -> usize;
+
+    /// Dump the state of the arena that this priority belongs to.
+    fn dump_arena(&self) -> ArenaDump;
+}
@@ -0,0 +1,635 @@
+//! Sharing one maintained order between processes, over a Unix socket.
+//!
+//! Only available with the `ipc` feature, on Unix. A [`Server`] owns a single tag-range arena, and
+//! serves requests to insert into, compare and sort its priorities, which [`Client`]s in other
+//! processes refer to by [`Handle`]s. Handles are shared by every client, so one process can insert
+//! a priority and hand its handle to another process, which compares it against its own.
+//!
+//! ```rust
+//! # use order_maintenance::ipc::{Client, Server};
+//! # use std::cmp::Ordering;
+//! let path = std::env::temp_dir().join(format!("om-ipc-doc-{}.sock", std::process::id()));
+//! let server = Server::bind(&path).unwrap();
+//! let stop = server.shutdown_handle();
+//! let serving = std::thread::spawn(move || server.serve());
+//!
+//! let mut client = Client::connect(&path).unwrap();
+//! let a = client.front().unwrap();
+//! let c = client.insert_after(a).unwrap();
+//! let b = client.insert_after(a).unwrap();
+//! assert_eq!(client.compare(b, c).unwrap(), Ordering::Less);
+//! assert_eq!(client.sort(&[c, a, b]).unwrap(), [a, b, c]);
+//!
+//! stop.shutdown();
+//! serving.join().unwrap();
+//! # std::fs::remove_file(&path).unwrap();
+//! ```
+//!
+//! The server serves each connection on its own thread, up to [`MAX_CONNECTIONS`] at a time, but
+//! applies requests one at a time, in the order they arrive, so every client sees the same order.
+//! Priorities live until some client [releases](Client::release) their handles, or until the
+//! client that inserted them disconnects, so a client that goes away without cleaning up does not
+//! leak them. Clients cannot stop the server; only the process that runs it can, through a
+//! [`ShutdownHandle`].
+//!
+//! # Protocol
+//!
+//! Clients in other languages can speak the protocol directly. Each message, in either direction,
+//! is a little-endian `u32` holding the length of its payload, followed by that payload, which is
+//! at most [`MAX_MESSAGE_LEN`] bytes. A payload starts with a tag byte, followed by operands, where
+//! handles are little-endian `u64`s and lists of handles are prefixed by their length as a
+//! little-endian `u32`.
+//!
+//! | Tag | [`Request`]               | Operands       | Response                 |
+//! |-----|---------------------------|----------------|--------------------------|
+//! | `0` | [`Request::Front`]        | none           | [`Response::Handle`]     |
+//! | `1` | [`Request::InsertAfter`]  | a handle       | [`Response::Handle`]     |
+//! | `2` | [`Request::Release`]      | a handle       | [`Response::Done`]       |
+//! | `3` | [`Request::Compare`]      | two handles    | [`Response::Ordering`]   |
+//! | `4` | [`Request::Sort`]         | a list         | [`Response::Sorted`]     |
+//!
+//! | Tag | [`Response`]               | Operands                                     |
+//! |-----|----------------------------|----------------------------------------------|
+//! | `0` | [`Response::Handle`]       | a handle                                     |
+//! | `1` | [`Response::Done`]         | none                                         |
+//! | `2` | [`Response::Ordering`]     | an `i8`: `-1` for less, `0`, or `1` for more |
+//! | `3` | [`Response::Sorted`]       | a list                                       |
+//! | `4` | [`Response::UnknownHandle`]| the handle                                   |
+//! | `5` | [`Response::Malformed`]    | none                                         |
+//! | `6` | [`Response::Full`]         | none                                         |
+//! | `7` | [`Response::Incomparable`] | none                                         |
+//!
+//! A request that refers to a handle that is not live gets [`Response::UnknownHandle`] instead,
+//! and one that cannot be decoded gets [`Response::Malformed`]. An insertion gets
+//! [`Response::Full`] if the order has run out of labels for another priority. A comparison or
+//! sort gets [`Response::Incomparable`] if the server finds that the priorities do not share its
+//! order, which it never should.
+use crate::capability::{TryInsert, TryInsertFront};
+use crate::tag_range::Priority;
+use crate::MaintainedOrd;
+use std::cmp::Ordering;
+use std::collections::HashMap;
+use std::fmt::Display;
+use std::io::{self, Read, Write};
+use std::net::Shutdown;
+use std::os::unix::net::{UnixListener, UnixStream};
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, Ordering as AtomicOrdering};
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::sync::Arc;
+use std::thread::JoinHandle;
+
+/// Payloads longer than this many bytes are rejected, in either direction.
+pub const MAX_MESSAGE_LEN: u32 = 1 << 20;
+
+/// A server serves at most this many connections at a time, each on a thread of its own; it
+/// closes further connections as soon as it accepts them.
+pub const MAX_CONNECTIONS: usize = 64;
+
+/// A handle to a priority held by a [`Server`].
+///
+/// Handles are never reused by the same server, even once they are released.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Handle(pub u64);
+
+/// A request from a [`Client`] to a [`Server`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Request {
+    /// Insert a priority before every other priority, or the first priority if there are none.
+    Front,
+    /// Insert a priority right after the priority with the given handle.
+    InsertAfter(Handle),
+    /// Drop the priority with the given handle.
+    Release(Handle),
+    /// Compare the priorities with the given handles.
+    Compare(Handle, Handle),
+    /// Sort the given handles by their priorities.
+    Sort(Vec<Handle>),
+}
+
+/// A response from a [`Server`] to a [`Client`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Response {
+    /// The handle of the inserted priority.
+    Handle(Handle),
+    /// The request was performed.
+    Done,
+    /// How the first priority compared to the second.
+    Ordering(Ordering),
+    /// The handles, in increasing order of their priorities.
+    Sorted(Vec<Handle>),
+    /// The request referred to a handle that is not live.
+    UnknownHandle(Handle),
+    /// The request could not be decoded.
+    Malformed,
+    /// The order has run out of labels for another priority, so none was inserted.
+    Full,
+    /// The priorities do not share one order, so they cannot be compared.
+    Incomparable,
+}
+
+/// Errors from requests made by a [`Client`].
+#[derive(Debug)]
+pub enum IpcError {
+    /// Talking to the server failed.
+    Io(io::Error),
+    /// The request referred to a handle that is not live.
+    UnknownHandle(Handle),
+    /// The server could not decode the request.
+    Rejected,
+    /// The order has run out of labels for another priority.
+    Full,
+    /// The server could not compare the priorities.
+    Incomparable,
+    /// The server's response could not be decoded, or did not answer the request.
+    Malformed,
+}
+
+impl Display for IpcError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Io(e) => write!(f, "failed to talk to the order server: {e}"),
+            Self::UnknownHandle(h) => write!(f, "no priority has handle {}", h.0),
+            Self::Rejected => write!(f, "the order server could not decode the request"),
+            Self::Full => write!(f, "the order server has run out of labels"),
+            Self::Incomparable => write!(f, "the order server could not compare the priorities"),
+            Self::Malformed => write!(f, "malformed response from the order server"),
+        }
+    }
+}
+
+impl std::error::Error for IpcError {}
+
+impl From<io::Error> for IpcError {
+    fn from(e: io::Error) -> Self {
+        Self::Io(e)
+    }
+}
+
+/// Reads operands from a payload.
+struct Operands<'a>(&'a [u8]);
+
+impl Operands<'_> {
+    fn take<const N: usize>(&mut self) -> Option<[u8; N]> {
+        let (bytes, rest) = self.0.split_first_chunk()?;
+        self.0 = rest;
+        Some(*bytes)
+    }
+
+    fn handle(&mut self) -> Option<Handle> {
+        self.take().map(|b| Handle(u64::from_le_bytes(b)))
+    }
+
+    fn handles(&mut self) -> Option<Vec<Handle>> {
+        let len = u32::from_le_bytes(self.take()?) as usize;
+        // Every handle takes 8 bytes, so a longer list cannot fit in the payload.
+        if len > self.0.len() / 8 {
+            return None;
+        }
+        (0..len).map(|_| self.handle()).collect()
+    }
+
+    /// `Some` if every operand was read.
+    fn finished(&self) -> Option<()> {
+        self.0.is_empty().then_some(())
+    }
+}
+
+fn put_handles(payload: &mut Vec<u8>, handles: &[Handle]) {
+    payload.extend((handles.len() as u32).to_le_bytes());
+    for h in handles {
+        payload.extend(h.0.to_le_bytes());
+    }
+}
+
+impl Request {
+    /// Encode this request as a payload.
+    pub fn encode(&self) -> Vec<u8> {
+        let mut payload = Vec::new();
+        match self {
+            Self::Front => payload.push(0),
+            Self::InsertAfter(h) => {
+                payload.push(1);
+                payload.extend(h.0.to_le_bytes());
+            }
+            Self::Release(h) => {
+                payload.push(2);
+                payload.extend(h.0.to_le_bytes());
+            }
+            Self::Compare(a, b) => {
+                payload.push(3);
+                payload.extend(a.0.to_le_bytes());
+                payload.extend(b.0.to_le_bytes());
+            }
+            Self::Sort(hs) => {
+                payload.push(4);
+                put_handles(&mut payload, hs);
+            }
+        }
+        payload
+    }
+
+    /// Decode a request from a payload, if it is well-formed.
+    pub fn decode(payload: &[u8]) -> Option<Self> {
+        let (&tag, rest) = payload.split_first()?;
+        let mut ops = Operands(rest);
+        let request = match tag {
+            0 => Self::Front,
+            1 => Self::InsertAfter(ops.handle()?),
+            2 => Self::Release(ops.handle()?),
+            3 => Self::Compare(ops.handle()?, ops.handle()?),
+            4 => Self::Sort(ops.handles()?),
+            _ => return None,
+        };
+        ops.finished()?;
+        Some(request)
+    }
+}
+
+impl Response {
+    /// Encode this response as a payload.
+    pub fn encode(&self) -> Vec<u8> {
+        let mut payload = Vec::new();
+        match self {
+            Self::Handle(h) => {
+                payload.push(0);
+                payload.extend(h.0.to_le_bytes());
+            }
+            Self::Done => payload.push(1),
+            Self::Ordering(o) => payload.extend([2, *o as i8 as u8]),
+            Self::Sorted(hs) => {
+                payload.push(3);
+                put_handles(&mut payload, hs);
+            }
+            Self::UnknownHandle(h) => {
+                payload.push(4);
+                payload.extend(h.0.to_le_bytes());
+            }
+            Self::Malformed => payload.push(5),
+            Self::Full => payload.push(6),
+            Self::Incomparable => payload.push(7),
+        }
+        payload
+    }
+
+    /// Decode a response from a payload, if it is well-formed.
+    pub fn decode(payload: &[u8]) -> Option<Self> {
+        let (&tag, rest) = payload.split_first()?;
+        let mut ops = Operands(rest);
+        let response = match tag {
+            0 => Self::Handle(ops.handle()?),
+            1 => Self::Done,
+            2 => Self::Ordering(match ops.take::<1>()?[0] as i8 {
+                -1 => Ordering::Less,
+                0 => Ordering::Equal,
+                1 => Ordering::Greater,
+                _ => return None,
+            }),
+            3 => Self::Sorted(ops.handles()?),
+            4 => Self::UnknownHandle(ops.handle()?),
+            5 => Self::Malformed,
+            6 => Self::Full,
+            7 => Self::Incomparable,
+            _ => return None,
+        };
+        ops.finished()?;
+        Some(response)
+    }
+}
+
+/// Write `payload` to `w` as one message.
+fn write_message(w: &mut impl Write, payload: &[u8]) -> io::Result<()> {
+    let len = u32::try_from(payload.len())
+        .ok()
+        .filter(|&len| len <= MAX_MESSAGE_LEN)
+        .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "message too long"))?;
+    w.write_all(&len.to_le_bytes())?;
+    w.write_all(payload)?;
+    w.flush()
+}
+
+/// Read the payload of one message from `r`, or `None` if `r` ends before the message starts.
+fn read_message(r: &mut impl Read) -> io::Result<Option<Vec<u8>>> {
+    let mut len = [0; 4];
+    match r.read_exact(&mut len) {
+        Ok(()) => (),
+        Err(e) if e.kind() == io::ErrorKind::UnexpectedEof => return Ok(None),
+        Err(e) => return Err(e),
+    }
+    let len = u32::from_le_bytes(len);
+    if len > MAX_MESSAGE_LEN {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            "message too long",
+        ));
+    }
+    let mut payload = vec![0; len as usize];
+    r.read_exact(&mut payload)?;
+    Ok(Some(payload))
+}
+
+/// Identifies a connection to a server, for as long as the server runs.
+type ConnectionId = u64;
+
+/// What connections tell the thread that owns the order.
+enum Message {
+    /// Apply a request from a connection, and send back the response.
+    Request(ConnectionId, Request, Sender<Response>),
+    /// A connection was closed.
+    Disconnected(ConnectionId),
+    /// The process that runs the server asked it to stop.
+    Shutdown,
+}
+
+/// The order that a server maintains, and the handles to its priorities, along with the
+/// connections that inserted them.
+#[derive(Default)]
+struct SharedOrder {
+    priorities: HashMap<Handle, (Priority, ConnectionId)>,
+    next: u64,
+}
+
+impl SharedOrder {
+    fn get(&self, h: Handle) -> Result<&Priority, Response> {
+        self.priorities
+            .get(&h)
+            .map(|(p, _)| p)
+            .ok_or(Response::UnknownHandle(h))
+    }
+
+    fn add(&mut self, p: Priority, owner: ConnectionId) -> Response {
+        let h = Handle(self.next);
+        self.next += 1;
+        self.priorities.insert(h, (p, owner));
+        Response::Handle(h)
+    }
+
+    fn try_apply(&mut self, from: ConnectionId, request: Request) -> Result<Response, Response> {
+        Ok(match request {
+            // Every priority is inserted relative to another, so they all share one arena.
+            Request::Front => match self.priorities.values().next() {
+                Some((p, _)) => {
+                    let p = p.try_insert_front().map_err(|_| Response::Full)?;
+                    self.add(p, from)
+                }
+                None => self.add(Priority::new(), from),
+            },
+            Request::InsertAfter(h) => {
+                let p = self.get(h)?.try_insert().map_err(|_| Response::Full)?;
+                self.add(p, from)
+            }
+            Request::Release(h) => {
+                self.priorities
+                    .remove(&h)
+                    .ok_or(Response::UnknownHandle(h))?;
+                Response::Done
+            }
+            Request::Compare(a, b) => {
+                let (a, b) = (self.get(a)?, self.get(b)?);
+                Response::Ordering(a.partial_cmp(b).ok_or(Response::Incomparable)?)
+            }
+            Request::Sort(hs) => {
+                let ps = hs
+                    .iter()
+                    .map(|&h| self.get(h).cloned())
+                    .collect::<Result<Vec<_>, _>>()?;
+                // Sorting panics unless every priority shares the arena of the first.
+                if let Some(first) = ps.first() {
+                    if ps
+                        .iter()
+                        .any(|p| Priority::witness_same_arena(first, p).is_none())
+                    {
+                        return Err(Response::Incomparable);
+                    }
+                }
+                let order = Priority::order_indices(&ps);
+                Response::Sorted(order.into_iter().map(|i| hs[i]).collect())
+            }
+        })
+    }
+
+    fn apply(&mut self, from: ConnectionId, request: Request) -> Response {
+        self.try_apply(from, request).unwrap_or_else(|error| error)
+    }
+
+    /// Drop the priorities that the connection `id` inserted and that are still live.
+    fn disconnect(&mut self, id: ConnectionId) {
+        self.priorities.retain(|_, (_, owner)| *owner != id);
+    }
+}
+
+/// Serves one maintained order over a Unix socket.
+#[derive(Debug)]
+pub struct Server {
+    listener: UnixListener,
+    messages: Sender<Message>,
+    incoming: Receiver<Message>,
+}
+
+impl Server {
+    /// Listen for clients on a new socket at `path`.
+    ///
+    /// Fails if something already exists at `path`, e.g., the socket of a server that did not
+    /// clean up after itself.
+    pub fn bind(path: impl AsRef<Path>) -> io::Result<Self> {
+        let (messages, incoming) = mpsc::channel();
+        Ok(Self {
+            listener: UnixListener::bind(path)?,
+            messages,
+            incoming,
+        })
+    }
+
+    /// A handle through which the process that runs this server can stop it, e.g., from another
+    /// thread while it [serves](Self::serve).
+    pub fn shutdown_handle(&self) -> ShutdownHandle {
+        ShutdownHandle(self.messages.clone())
+    }
+
+    /// Serve requests from clients until [`ShutdownHandle::shutdown()`] is called.
+    ///
+    /// The order is owned by the calling thread, while connections are accepted and read on
+    /// threads of their own. Once the shutdown arrives, requests that have not been served yet
+    /// are dropped, every connection is closed for reading, and the threads are joined before this
+    /// returns, unless the socket file was removed meanwhile, which leaves the thread that accepts
+    /// connections waiting for one. The socket file is left in place.
+    pub fn serve(self) {
+        let stopping = Arc::new(AtomicBool::new(false));
+        let path = self
+            .listener
+            .local_addr()
+            .ok()
+            .and_then(|addr| addr.as_pathname().map(Path::to_path_buf));
+        let accepting = {
+            let (listener, messages, stopping) = (self.listener, self.messages, stopping.clone());
+            std::thread::spawn(move || accept_connections(listener, messages, &stopping))
+        };
+
+        let mut order = SharedOrder::default();
+        while let Ok(message) = self.incoming.recv() {
+            match message {
+                Message::Request(from, request, reply) => {
+                    let _ = reply.send(order.apply(from, request));
+                }
+                Message::Disconnected(id) => order.disconnect(id),
+                Message::Shutdown => break,
+            }
+        }
+
+        // Connections waiting for responses give up once the requests they sent are dropped.
+        drop(self.incoming);
+        stopping.store(true, AtomicOrdering::SeqCst);
+        // Accepting blocks until a client connects, so connect to wake it up.
+        if path.is_some_and(|path| UnixStream::connect(path).is_ok()) {
+            let _ = accepting.join();
+        }
+    }
+}
+
+/// Stops a [`Server`], from the process that runs it.
+#[derive(Debug, Clone)]
+pub struct ShutdownHandle(Sender<Message>);
+
+impl ShutdownHandle {
+    /// Stop the server, once it has served the requests that arrived before this call; does
+    /// nothing if it has already stopped.
+    ///
+    /// This does not wait for the server to stop: [`Server::serve()`] returns once it has.
+    pub fn shutdown(&self) {
+        let _ = self.0.send(Message::Shutdown);
+    }
+}
+
+/// Accept connections on `listener`, serving each on a thread of its own, up to
+/// [`MAX_CONNECTIONS`] at a time, until `stopping` is set; then close every connection for
+/// reading, and join the threads serving them.
+fn accept_connections(listener: UnixListener, messages: Sender<Message>, stopping: &AtomicBool) {
+    let mut connections: Vec<(JoinHandle<()>, UnixStream)> = Vec::new();
+    for (id, stream) in (0..).zip(listener.incoming()) {
+        if stopping.load(AtomicOrdering::SeqCst) {
+            break;
+        }
+        let Ok((stream, closer)) = stream.and_then(|s| s.try_clone().map(|c| (s, c))) else {
+            continue;
+        };
+        connections.retain(|(serving, _)| !serving.is_finished());
+        if connections.len() >= MAX_CONNECTIONS {
+            // Dropping both ends of the connection closes it.
+            continue;
+        }
+        let messages = messages.clone();
+        let serving = std::thread::spawn(move || serve_connection(id, stream, messages));
+        connections.push((serving, closer));
+    }
+    for (serving, closer) in connections {
+        // Reading then ends as if the client had disconnected, while writing a response that is
+        // under way still succeeds.
+        let _ = closer.shutdown(Shutdown::Read);
+        let _ = serving.join();
+    }
+}
+
+/// Forward the requests read from `stream` to the thread that owns the order, and write back its
+/// responses, until either the client or the server goes away.
+fn serve_connection(id: ConnectionId, mut stream: UnixStream, messages: Sender<Message>) {
+    while let Ok(Some(payload)) = read_message(&mut stream) {
+        let response = match Request::decode(&payload) {
+            Some(request) => {
+                // A channel per request, so that waiting on it ends once the server drops the
+                // request.
+                let (reply, replies) = mpsc::channel();
+                if messages.send(Message::Request(id, request, reply)).is_err() {
+                    return;
+                }
+                match replies.recv() {
+                    Ok(response) => response,
+                    Err(_) => return,
+                }
+            }
+            None => Response::Malformed,
+        };
+        if write_message(&mut stream, &response.encode()).is_err() {
+            break;
+        }
+    }
+    let _ = messages.send(Message::Disconnected(id));
+}
+
+/// A connection to a [`Server`].
+#[derive(Debug)]
+pub struct Client {
+    stream: UnixStream,
+}
+
+impl Client {
+    /// Connect to the server listening at `path`.
+    pub fn connect(path: impl AsRef<Path>) -> io::Result<Self> {
+        Ok(Self {
+            stream: UnixStream::connect(path)?,
+        })
+    }
+
+    /// Send `request` to the server, and wait for its response.
+    pub fn request(&mut self, request: &Request) -> Result<Response, IpcError> {
+        write_message(&mut self.stream, &request.encode())?;
+        let payload = read_message(&mut self.stream)?
+            .ok_or_else(|| io::Error::from(io::ErrorKind::UnexpectedEof))?;
+        match Response::decode(&payload).ok_or(IpcError::Malformed)? {
+            Response::UnknownHandle(h) => Err(IpcError::UnknownHandle(h)),
+            Response::Malformed => Err(IpcError::Rejected),
+            Response::Full => Err(IpcError::Full),
+            Response::Incomparable => Err(IpcError::Incomparable),
+            response => Ok(response),
+        }
+    }
+
+    /// Request a new handle, with the given request.
+    fn request_handle(&mut self, request: &Request) -> Result<Handle, IpcError> {
+        match self.request(request)? {
+            Response::Handle(h) => Ok(h),
+            _ => Err(IpcError::Malformed),
+        }
+    }
+
+    /// Request something to be done, with the given request.
+    fn request_done(&mut self, request: &Request) -> Result<(), IpcError> {
+        match self.request(request)? {
+            Response::Done => Ok(()),
+            _ => Err(IpcError::Malformed),
+        }
+    }
+
+    /// Insert a priority before every other priority in the order, or the first priority if there
+    /// are none; see [`InsertFront`](crate::capability::InsertFront).
+    pub fn front(&mut self) -> Result<Handle, IpcError> {
+        self.request_handle(&Request::Front)
+    }
+
+    /// Insert a priority right after the priority with handle `h`.
+    pub fn insert_after(&mut self, h: Handle) -> Result<Handle, IpcError> {
+        self.request_handle(&Request::InsertAfter(h))
+    }
+
+    /// Drop the priority with handle `h`, after which `h` is no longer live for any client.
+    ///
+    /// Priorities that this client inserted are also dropped once it disconnects.
+    pub fn release(&mut self, h: Handle) -> Result<(), IpcError> {
+        self.request_done(&Request::Release(h))
+    }
+
+    /// Compare the priorities with handles `a` and `b`.
+    pub fn compare(&mut self, a: Handle, b: Handle) -> Result<Ordering, IpcError> {
+        match self.request(&Request::Compare(a, b))? {
+            Response::Ordering(o) => Ok(o),
+            _ => Err(IpcError::Malformed),
+        }
+    }
+
+    /// Sort `handles` by their priorities.
+    pub fn sort(&mut self, handles: &[Handle]) -> Result<Vec<Handle>, IpcError> {
+        match self.request(&Request::Sort(handles.to_vec()))? {
+            Response::Sorted(hs) if hs.len() == handles.len() => Ok(hs),
+            _ => Err(IpcError::Malformed),
+        }
+    }
+}
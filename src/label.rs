@@ -12,6 +12,15 @@ impl Label {
     }
     pub(crate) const MAX: Self = Label(usize::MAX);
     pub(crate) const BITS: usize = usize::BITS as usize;
+
+    /// The greatest label representable with the given number of bits.
+    pub(crate) const fn max_for_bits(bits: usize) -> Self {
+        if bits >= Self::BITS {
+            Self::MAX
+        } else {
+            Label((1 << bits) - 1)
+        }
+    }
 }
 
 impl From<Label> for u128 {
@@ -20,6 +29,12 @@ impl From<Label> for u128 {
     }
 }
 
+impl From<Label> for usize {
+    fn from(l: Label) -> Self {
+        l.0
+    }
+}
+
 impl PartialEq<usize> for Label {
     fn eq(&self, other: &usize) -> bool {
         self.0.eq(other)
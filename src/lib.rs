@@ -1,15 +1,334 @@
 //! Totally-ordered priorities.
+//!
+//! ## Panics
+//!
+//! Inserting, cloning, dropping, comparing and inspecting priorities never panics, except:
+//!
+//! -   Inserting a priority panics if its arena has run out of labels, e.g., once a tag-range arena
+//!     holds as many priorities as the capacity of its label space, or has reached its
+//!     [quota](config::PriorityConfig::max_live).
+//!     [`TryInsert::try_insert()`](capability::TryInsert::try_insert) returns an error instead.
+//! -   Using a handle to an arena within a callback that the arena calls while it is borrowed,
+//!     e.g., one given to [`RawPriority::insert_with()`](raw::RawPriority::insert_with), a
+//!     [`RelabelPolicy`](policy::RelabelPolicy), or a
+//!     [`yield_during_relabel()`](config::PriorityConfig::yield_during_relabel) callback, panics.
+//!     Dropping handles to [arenas that defer drops](config::PriorityConfig::deferred_drop) is
+//!     fine, though.
+//! -   Inserting into an arena, or dropping handles to it, panics while an
+//!     [`IdOrder`](id::IdOrder) borrows it. Cloning handles to it does not, whether or not the
+//!     `oplog` feature records the clones.
+//! -   Comparing priorities from different arenas panics if either arena was configured to, with
+//!     [`CrossArena::Panic`](config::CrossArena::Panic).
+//! -   Functions that document invalid arguments panic on them, e.g., a threshold outside of the
+//!     range `(1, 2)`, or comparing incomparable priorities through [`TotalOrder`].
+mod arith;
+#[cfg(feature = "big")]
 pub mod big;
+pub mod bulk;
+pub mod capability;
+pub mod check;
+#[cfg(feature = "checkpoint")]
+pub mod checkpoint;
+pub mod commit_order;
+pub mod config;
+pub mod cost_model;
+pub mod descending;
+pub mod diff;
+pub mod explain;
+pub mod frozen;
+pub mod global;
+#[cfg(feature = "petgraph")]
+pub mod graph;
+pub mod id;
 mod internal;
+#[cfg(feature = "introspect")]
+pub mod introspect;
+#[cfg(all(feature = "ipc", unix))]
+pub mod ipc;
 mod label;
 pub mod list_range;
+pub mod lru;
+#[cfg(feature = "naive")]
 pub mod naive;
+#[cfg(feature = "oplog")]
+pub mod oplog;
+pub mod params;
+#[cfg(feature = "persistent-id")]
+pub mod persistent_id;
+pub mod policy;
+pub mod raw;
+pub mod reservation;
+pub mod sentinel;
+#[cfg(feature = "stats")]
+pub mod stats;
+pub mod switchable;
 pub mod tag_range;
+pub mod timeline;
+#[cfg(feature = "track-alloc")]
+pub mod track_alloc;
+#[cfg(not(feature = "track-alloc"))]
+mod track_alloc;
+pub mod types;
+pub mod view;
+pub mod workload;
 
-/// TODO: doc
+pub use check::self_test;
+pub use order_maintenance_macros::{define_priority, om_delegate, MaintainedOrder};
+use std::cmp::Ordering;
+use std::rc::Rc;
+
+/// Priorities that stay totally ordered as new priorities are inserted between them.
+///
+/// An order starts with a single priority from [`new()`](Self::new), and grows by
+/// [`insert()`](Self::insert)ing priorities right after existing ones. Only priorities of the same
+/// order are meant to be compared; how priorities of different orders compare depends on the
+/// implementation.
 pub trait MaintainedOrd: PartialEq + PartialOrd {
-    /// TODO: doc
+    /// Construct the first priority of a new order.
     fn new() -> Self;
-    /// TODO: doc
+    /// Insert a new priority right after this one, i.e., greater than this priority but less than
+    /// every other priority of its order that is greater than it.
     fn insert(&self) -> Self;
+
+    /// Construct `n` priorities in increasing order.
+    ///
+    /// By default, this repeatedly inserts after the last priority, but implementations may
+    /// override it to space out the priorities evenly from the start.
+    fn new_sequence(n: usize) -> Vec<Self>
+    where
+        Self: Sized,
+    {
+        let mut ps: Vec<Self> = Vec::with_capacity(n);
+        if n > 0 {
+            ps.push(Self::new());
+        }
+        for i in 1..n {
+            let p = ps[i - 1].insert();
+            ps.push(p);
+        }
+        ps
+    }
+}
+
+/// Shared priorities, which insert through the shared pointer.
+///
+/// Useful for priorities that cannot be cloned, such as naive `UniquePriority`s, or to share a
+/// priority without cloning the handle inside it.
+impl<P: MaintainedOrd> MaintainedOrd for Rc<P> {
+    fn new() -> Self {
+        Rc::new(P::new())
+    }
+
+    fn insert(&self) -> Self {
+        Rc::new((**self).insert())
+    }
+
+    fn new_sequence(n: usize) -> Vec<Self> {
+        P::new_sequence(n).into_iter().map(Rc::new).collect()
+    }
+}
+
+/// Priorities that are allocated in arenas, which can be enumerated.
+pub trait InArena: MaintainedOrd + Sized {
+    /// Handles to every priority in this priority's arena, in increasing order.
+    fn arena_priorities(&self) -> Vec<Self>;
+
+    /// A handle to the least priority greater than this one in its arena, or `None` if this is
+    /// the greatest.
+    ///
+    /// By default, this enumerates every priority in the arena.
+    fn successor(&self) -> Option<Self> {
+        let ps = self.arena_priorities();
+        let i = ps.partition_point(|p| p <= self);
+        ps.into_iter().nth(i)
+    }
+
+    /// Insert `k` priorities spread evenly across the order of this priority's arena.
+    ///
+    /// Splitting the `n` existing priorities into `k` runs of nearly equal length, the `i`th new
+    /// priority is inserted right after the last priority of the `i`th run, so it is greater than
+    /// about `(i + 1) * n / k` existing priorities. Returns the new priorities in increasing order.
+    fn pivots(&self, k: usize) -> Vec<Self> {
+        let ps = self.arena_priorities();
+        let n = ps.len();
+        let mut pivots: Vec<Self> = Vec::with_capacity(k);
+        let mut prev_end = None;
+        for i in 0..k {
+            // Index of the last priority of the `i`th run, rounding up so that it is at least 0.
+            let end = ((i + 1) * n).div_ceil(k) - 1;
+            let pivot = match pivots.last() {
+                // Runs may be empty if k > n; keep pivots after the same priority in order.
+                Some(last) if prev_end == Some(end) => last.insert(),
+                _ => ps[end].insert(),
+            };
+            pivots.push(pivot);
+            prev_end = Some(end);
+        }
+        pivots
+    }
 }
+
+impl<P: InArena> InArena for Rc<P> {
+    fn arena_priorities(&self) -> Vec<Self> {
+        (**self)
+            .arena_priorities()
+            .into_iter()
+            .map(Rc::new)
+            .collect()
+    }
+
+    fn successor(&self) -> Option<Self> {
+        (**self).successor().map(Rc::new)
+    }
+}
+
+/// Sort `items` in increasing order of priority.
+///
+/// Works for anything that compares like a priority, e.g., priorities themselves, or references
+/// and shared pointers to them, so vectors of `&P` or `Rc<P>` sort without wrapping each element
+/// in a [`TotalOrder`]. The sort is stable.
+///
+/// ```rust
+/// # use order_maintenance::{sort_priorities, tag_range::Priority, MaintainedOrd};
+/// # use std::rc::Rc;
+/// let a = Priority::new();
+/// let b = a.insert();
+///
+/// let mut refs = vec![&b, &a];
+/// sort_priorities(&mut refs);
+/// assert!(refs[0] == &a);
+///
+/// let mut shared = vec![Rc::new(b.clone()), Rc::new(a.clone())];
+/// sort_priorities(&mut shared);
+/// assert!(*shared[0] == a);
+/// ```
+///
+/// Panics if some items are not comparable, like [`TotalOrder`].
+pub fn sort_priorities<T: PartialOrd>(items: &mut [T]) {
+    items.sort_by(|a, b| a.partial_cmp(b).expect("priorities are not comparable"));
+}
+
+/// Assign priorities to `items` that reproduce the order given by `cmp`.
+///
+/// Sorts `items` once, then allocates a sequence of priorities in a fresh arena, such that the
+/// `i`th priority returned belongs to the `i`th item. Items that compare equal under `cmp` are
+/// assigned clones of the same priority. Subsequent comparisons between the returned priorities
+/// agree with `cmp`, but without calling it again.
+///
+/// ```rust
+/// # use order_maintenance::{tag_in_order, tag_range::Priority};
+/// let words = ["pear", "fig", "apple", "kiwi"];
+/// let tags: Vec<Priority> = tag_in_order(words, |a, b| a.len().cmp(&b.len()));
+///
+/// assert!(tags[1] < tags[0]); // fig < pear
+/// assert!(tags[0] < tags[2]); // pear < apple
+/// assert!(tags[0] == tags[3]); // pear == kiwi
+/// ```
+pub fn tag_in_order<P, I, F>(items: I, mut cmp: F) -> Vec<P>
+where
+    P: MaintainedOrd + Clone,
+    I: IntoIterator,
+    F: FnMut(&I::Item, &I::Item) -> Ordering,
+{
+    let mut items: Vec<(usize, I::Item)> = items.into_iter().enumerate().collect();
+    items.sort_by(|(_, a), (_, b)| cmp(a, b));
+
+    // Indices into `items` where a new group of equal items begins.
+    let mut groups = Vec::new();
+    for i in 0..items.len() {
+        if i == 0 || cmp(&items[i - 1].1, &items[i].1) != Ordering::Equal {
+            groups.push(i);
+        }
+    }
+
+    let mut tags: Vec<Option<P>> = (0..items.len()).map(|_| None).collect();
+    for (g, p) in P::new_sequence(groups.len()).into_iter().enumerate() {
+        let end = groups.get(g + 1).copied().unwrap_or(items.len());
+        for (i, _) in &items[groups[g]..end] {
+            tags[*i] = Some(p.clone());
+        }
+    }
+    tags.into_iter().map(Option::unwrap).collect()
+}
+
+/// Assign increasing priorities to `items`, which are already in order, in one pass.
+///
+/// Unlike [`tag_in_order()`], this neither sorts nor compares items: the `i`th item is paired with
+/// the `i`th of a [sequence](MaintainedOrd::new_sequence) of priorities in a fresh arena, which
+/// arenas with evenly spread labels construct in linear time, without relabeling. Building an
+/// order by inserting after the last priority over and over instead crowds labels at its end.
+///
+/// ```rust
+/// # use order_maintenance::{tag_sorted, tag_range::Priority};
+/// let tagged: Vec<(&str, Priority)> = tag_sorted(["apple", "fig", "pear"]);
+///
+/// assert_eq!(tagged[1].0, "fig");
+/// assert!(tagged[0].1 < tagged[1].1 && tagged[1].1 < tagged[2].1);
+/// ```
+pub fn tag_sorted<P, I>(items: I) -> Vec<(I::Item, P)>
+where
+    P: MaintainedOrd,
+    I: IntoIterator,
+{
+    let items: Vec<I::Item> = items.into_iter().collect();
+    let ps = P::new_sequence(items.len());
+    items.into_iter().zip(ps).collect()
+}
+
+/// Totally-ordered wrapper around priorities, e.g., for use as keys of a `BTreeSet`.
+///
+/// Comparisons panic if the wrapped priorities are not comparable; priorities from different
+/// arenas are only comparable if both arenas were configured with
+/// [`order_across_arenas()`](config::PriorityConfig::order_across_arenas).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TotalOrder<P>(pub P);
+
+impl<P: Eq + PartialOrd> PartialOrd for TotalOrder<P> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<P: Eq + PartialOrd> Ord for TotalOrder<P> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.0
+            .partial_cmp(&other.0)
+            .expect("priorities are not comparable")
+    }
+}
+
+/// Proof that two priorities belong to the same arena, so comparing them never fails.
+///
+/// Witnesses are obtained from, e.g., [`tag_range::GenericPriority::witness_same_arena()`], and
+/// borrow both priorities, which therefore remain in the same arena for as long as the witness
+/// lives. Comparing through the witness skips checking that their arenas match.
+#[derive(Debug)]
+pub struct SameArena<'a, P> {
+    lhs: &'a P,
+    rhs: &'a P,
+}
+
+impl<'a, P> SameArena<'a, P> {
+    /// Witness that `lhs` and `rhs` belong to the same arena, which the caller has checked.
+    pub(crate) fn new(lhs: &'a P, rhs: &'a P) -> Self {
+        Self { lhs, rhs }
+    }
+
+    /// The left-hand priority.
+    pub fn lhs(&self) -> &'a P {
+        self.lhs
+    }
+
+    /// The right-hand priority.
+    pub fn rhs(&self) -> &'a P {
+        self.rhs
+    }
+}
+
+impl<P> Clone for SameArena<'_, P> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<P> Copy for SameArena<'_, P> {}
@@ -1,8 +1,38 @@
-use crate::internal::{Arena, Label, PriorityRef};
+use crate::arith;
+use crate::capability::CapacityError;
+use crate::check::{Checked, SelfCheckReport, Violation};
+use crate::config::{Configurable, PriorityConfig};
+use crate::explain::{RelabelReport, WindowStep};
+use crate::frozen::{FrozenOrder, FrozenPriority, ReadSnapshot};
+use crate::internal::{Arena, CapacityContext, Label, PriorityKey, PriorityRef};
+use crate::params::Params;
+use crate::policy::Cursor;
+use crate::reservation::{Region, Reservation};
+use crate::sentinel::Bounded;
+use crate::types::{Epoch, LabelSnapshot};
 pub use crate::MaintainedOrd;
-use std::cmp::Ordering;
+use crate::{InArena, SameArena};
+use std::{
+    any::Any, cmp::Ordering, fmt::Debug, marker::PhantomData, num::NonZeroU64, ops::Range, rc::Rc,
+};
 
-/// A totally-ordered priority.
+/// Default parameters: `usize` labels.
+#[derive(Debug)]
+pub struct DefaultParams;
+
+impl Params for DefaultParams {
+    const BITS: usize = usize::BITS as usize;
+}
+
+/// A totally-ordered priority, using the [`DefaultParams`].
+///
+/// See [`GenericPriority`] for details.
+pub type Priority = GenericPriority<DefaultParams>;
+
+/// A totally-ordered priority, parameterized by its label width.
+///
+/// Use [`Priority`] for the default parameters, or [`define_priority!`](crate::define_priority)
+/// to define a priority type with custom parameters.
 ///
 /// These priorities implement Dietz & Sleator (1987)'s solution to the order maintenance problem,
 /// which require a data structure `T` that supports insertion and comparison operations such that
@@ -69,70 +99,830 @@ use std::cmp::Ordering;
 ///
 /// -   Michael A. Bender, Richard Cole, Erik D. Demaine, Martin Farach-Colton, and Jack Zito.
 ///     _Two simplified algorithms for maintaining order in a list._ 2002.
-#[derive(Debug, PartialEq, Eq, Clone)]
-pub struct Priority(PriorityRef);
+pub struct GenericPriority<P>(PriorityRef, PhantomData<P>);
+
+impl<P: Params> GenericPriority<P> {
+    /// Mask of the bits used by labels of this priority type; label arithmetic wraps around it.
+    const MASK: Label = Label::max_for_bits(P::BITS);
 
-impl Priority {
     fn relative(&self) -> Label {
-        self.0.label() - self.0.base_label()
+        (self.0.label() - self.0.base_label()) & Self::MASK
     }
 
-    /// Search for how many nodes we need to relabel, and its weight
-    fn check_label_range(&self, arena: &mut Arena) -> (usize, Label) {
-        let this = self.0.this().as_ref(arena);
+    /// The label that the priority with key `this` is compared by.
+    fn relative_in(arena: &Arena, this: PriorityKey) -> Label {
+        (this.as_ref(arena).label() - arena.base().as_ref(arena).label()) & Self::MASK
+    }
+
+    /// Number of labels spanned by `weight`; a weight of 0 spans the entire label space, 2^BITS,
+    /// which can't be represented as a label but can be represented as a u128.
+    fn span(weight: Label) -> u128 {
+        if weight == 0 {
+            1u128 << P::BITS
+        } else {
+            u128::from(weight)
+        }
+    }
+
+    /// Search for how many nodes we need to relabel, and its weight, to make room for `extra` more
+    /// nodes after `this`, along with the room left for regions reserved right after those nodes
+    fn check_label_range(
+        arena: &Arena,
+        this_key: PriorityKey,
+        extra: usize,
+    ) -> (usize, Label, usize) {
+        let this = this_key.as_ref(arena);
         let mut count = 1;
-        let mut prio = this.next().as_ref(arena);
+        let mut reserved = arena.region_after(this_key);
+        let mut key = this.next();
+        let mut prio = key.as_ref(arena);
 
-        let mut weight = prio.label() - this.label();
-        while weight != 0 && weight <= count * count {
-            prio = prio.next().as_ref(arena);
+        let mut weight = (prio.label() - this.label()) & Self::MASK;
+        let slots = |count: usize, reserved: usize| (count + extra + reserved) as u128;
+        while weight != 0 && u128::from(weight) <= slots(count, reserved).pow(2) {
+            reserved += arena.region_after(key);
+            key = prio.next();
+            prio = key.as_ref(arena);
             count += 1;
-            weight = prio.label() - this.label();
+            weight = (prio.label() - this.label()) & Self::MASK;
         }
-        (count, weight)
+        (count, weight, reserved)
     }
 
-    fn redistribute_labels(&self, arena: &mut Arena, count: usize, weight: Label) {
-        let this = self.0.this().as_ref(arena);
+    /// Spread the labels of the `count` nodes from `this` across `weight`, as if there were `extra`
+    /// more right after `this`, and `reserved` more in the regions reserved right after them.
+    fn redistribute_labels(
+        arena: &Arena,
+        this_key: PriorityKey,
+        count: usize,
+        weight: Label,
+        extra: usize,
+        reserved: usize,
+    ) {
+        let this = this_key.as_ref(arena);
+        let slots = count + extra + reserved;
+        let mut skipped = arena.region_after(this_key);
 
         // Now, adjust labels of those nodes
-        let mut prio = this.next().as_ref(arena);
+        let mut key = this.next();
         for k in 1..count {
-            // if weight == 0, then it should actually encode usize::MAX + 1.
-            let weight_k: u128 = if weight == 0 {
-                // Since we can't actually represent usize::MAX + 1, we just multiply it by
-                // ((usize::MAX + 1) / 2) AKA (1 << (usize::BITS / 2)), and then multiply by 2.
-                (k as u128 * (1 << (Label::BITS / 2))) * 2
-            } else {
-                (k as u128) * u128::from(weight)
-            };
-            prio.set_label(this.label() + (weight_k / count as u128) as usize);
+            let prio = key.as_ref(arena);
+            let slot = (k + extra + skipped) as u128;
+            let weight_k = slot * Self::span(weight);
+            prio.set_label((this.label() + (weight_k / slots as u128) as usize) & Self::MASK);
+
+            skipped += arena.region_after(key);
+            key = prio.next();
+            arena.relabel_progress(k);
+        }
+    }
 
-            prio = prio.next().as_ref(arena);
+    /// Perform relabeling in the arena if necessary to insert after the priority at `at`.
+    ///
+    /// Panics if the arena has run out of labels or reached its quota.
+    pub(crate) fn relabel(at: &mut Cursor<'_>) {
+        if let Err(e) = Self::try_relabel(at) {
+            panic!("{e}")
         }
     }
 
-    /// Perform relabeling in the arena if necessary.
-    fn relabel(&self, arena: &mut Arena) {
+    /// Perform relabeling in the arena if necessary to insert after the priority at `at`, unless
+    /// the arena has run out of labels or reached its quota, in which case it is left as is.
+    pub(crate) fn try_relabel(at: &mut Cursor<'_>) -> Result<(), CapacityError> {
+        at.arena.check_quota(at.arena_id, 1)?;
+        Self::make_room(at, 0)
+    }
+
+    /// Perform relabeling in the arena if necessary to insert `k` priorities after the priority at
+    /// `at`, then compute their labels, evenly spaced up to the next priority.
+    ///
+    /// Panics if the arena has run out of labels or reached its quota.
+    fn relabel_for(at: &mut Cursor<'_>, k: usize) -> Vec<Label> {
+        if let Err(e) = at
+            .arena
+            .check_quota(at.arena_id, k)
+            .and_then(|()| Self::make_room(at, k))
+        {
+            panic!("{e}")
+        }
+        let this = at.at.as_ref(at.arena).label();
+        let next = at.at.as_ref(at.arena).next().as_ref(at.arena).label();
+        let gap = Self::span((next - this) & Self::MASK);
+        (1..=k)
+            .map(|j| (this + (gap * j as u128 / (k as u128 + 1)) as usize) & Self::MASK)
+            .collect()
+    }
+
+    /// Redistribute labels after the priority at `at` to make room for `extra` more priorities
+    /// right after it, or for one if `extra` is 0, unless the arena has run out of labels.
+    fn make_room(at: &mut Cursor<'_>, extra: usize) -> Result<(), CapacityError> {
         // Search for how many nodes we need to relabel, and its weight
-        let (count, weight) = self.check_label_range(arena);
+        let (count, weight, reserved) = Self::check_label_range(at.arena, at.at, extra);
+        // Redistributing leaves this many labels per slot, which must leave room for the new labels
+        // right after this priority: a slot each, or two slots for a single label in between.
+        let span = Self::span(weight);
+        let slots = (count + extra + reserved) as u128;
+        if span / slots < if extra == 0 { 2 } else { 1 } {
+            return Err(CapacityError::new(CapacityContext {
+                arena_id: at.arena_id,
+                live: at.arena.total(),
+                threshold: None,
+                window: None,
+            }));
+        }
         if count > 1 {
-            self.redistribute_labels(arena, count, weight);
+            Self::redistribute_labels(at.arena, at.at, count, weight, extra, reserved);
+            at.arena.record_relabel(count - 1);
+            let first = at.at.as_ref(at.arena).next();
+            at.arena.note_relabeled(first, count - 1);
+            if at.arena.explaining() {
+                let lo = at.at.as_ref(at.arena).label();
+                let report = RelabelReport {
+                    epoch: Epoch::new(at.arena.label_epoch()),
+                    live: at.arena.total(),
+                    threshold: None,
+                    steps: vec![WindowStep {
+                        level: 0,
+                        window: (lo.into(), ((lo + weight - 1) & Self::MASK).into()),
+                        count,
+                        capacity: None,
+                    }],
+                    relabeled: count - 1,
+                    density: count as f64 / span as f64,
+                };
+                at.arena.set_relabel_report(report);
+            }
         }
+        Ok(())
     }
 
-    /// Compute the next label for inserting after `self`.
-    fn next_label(&self, arena: &Arena) -> Label {
-        let this = self.0.this().as_ref(arena);
+    /// Whether `label` is still free right after the priority at `at`, which must not have been
+    /// relabeled since `label` was computed.
+    fn is_free_after(at: &Cursor<'_>, label: Label) -> bool {
+        let this = Self::relative_in(at.arena, at.at);
+        let next = Self::relative_in(at.arena, at.at.as_ref(at.arena).next());
+        let label = (label - at.arena.base().as_ref(at.arena).label()) & Self::MASK;
+        this < label && (next <= this || label < next)
+    }
+
+    /// Compute the next label for inserting after the priority at `at`.
+    pub(crate) fn next_label(at: &Cursor<'_>) -> Label {
+        let arena = &*at.arena;
+        let this = at.at.as_ref(arena);
+        let next = this.next().as_ref(arena);
         // Compute new priority, which is half-way between this priority and the next
-        this.label() + (this.next().as_ref(arena).label() - this.label()) / 2
+        Label::new(arith::masked_midpoint(
+            this.label().into(),
+            next.label().into(),
+            Self::MASK.into(),
+        ))
+    }
+}
+
+impl<P: Params> GenericPriority<P> {
+    /// A sentinel less than every priority of this type, in any arena.
+    pub const MIN_SENTINEL: Bounded<Self> = Bounded::Min;
+
+    /// A sentinel greater than every priority of this type, in any arena.
+    pub const MAX_SENTINEL: Bounded<Self> = Bounded::Max;
+
+    /// Construct the first priority in a new arena, with `data` attached to the arena.
+    ///
+    /// The arena holds one reference to the data, which it drops along with itself, i.e., once
+    /// every priority in it is dropped. The data can be retrieved through any of them with
+    /// [`arena_data()`](Self::arena_data), whose references keep it alive for as long as they
+    /// live.
+    pub fn with_arena_data<T: Any>(data: T) -> Self {
+        let mut arena = Arena::new();
+        arena.set_data(Rc::new(data));
+        Self::first_in(arena)
+    }
+
+    /// The data attached to this priority's arena by [`with_arena_data()`], if it is of type `T`.
+    ///
+    /// [`with_arena_data()`]: Self::with_arena_data
+    pub fn arena_data<T: Any>(&self) -> Option<Rc<T>> {
+        self.0.arena_data()
+    }
+
+    /// Attach `meta` to this priority, replacing any metadata attached to it before.
+    ///
+    /// The metadata is stored in the arena, shared by every handle to this priority, and dropped
+    /// as soon as the priority is, i.e., once every handle to it is dropped.
+    pub fn set_meta<T: Any>(&self, meta: T) {
+        self.0.set_meta(Some(Rc::new(meta)))
+    }
+
+    /// The metadata attached to this priority by [`set_meta()`], if it is of type `T`.
+    ///
+    /// [`set_meta()`]: Self::set_meta
+    pub fn meta<T: Any>(&self) -> Option<Rc<T>> {
+        self.0.meta()
+    }
+
+    /// Detach any metadata from this priority, dropping it unless it is still in use.
+    pub fn clear_meta(&self) {
+        self.0.set_meta(None)
+    }
+
+    /// Redistribute the labels of every priority in this priority's arena evenly across the label
+    /// space, in one linear pass; returns the number of priorities relabeled.
+    ///
+    /// Bursts of insertions in one place leave labels crowded there, which later insertions pay
+    /// for with larger relabeling passes. Rebalancing resets that skew up front, e.g., during a
+    /// maintenance window rather than during latency-sensitive insertions.
+    pub fn rebalance_all(&self) -> usize {
+        self.0.rebalance(true, Self::MASK)
+    }
+
+    /// Renumber every priority in this priority's arena with consecutive labels from the start of
+    /// the label space, in one linear pass; returns the number of priorities relabeled.
+    ///
+    /// After many priorities are dropped, the labels of the rest are spread thin; compacting them
+    /// makes raw labels small, e.g., to shrink snapshots of them, and keeps neighbors close. Like
+    /// any relabeling, it advances the [label epoch](Self::label_epoch), so caches keyed on labels
+    /// know to refresh. Inserting between compacted priorities must relabel them, so compacting
+    /// suits arenas that are mostly read from now on; see [`rebalance_all()`](Self::rebalance_all)
+    /// otherwise.
+    pub fn compact_labels(&self) -> usize {
+        self.0.compact(true, Self::MASK)
+    }
+
+    /// Insert `k` new priorities right after this one, in increasing order, with evenly spaced
+    /// labels.
+    ///
+    /// This relabels at most once, making room for all `k` priorities at the same time, whereas
+    /// inserting them one at a time may relabel again and again. Like
+    /// [`MaintainedOrd::insert()`], panics if the arena runs out of labels or reaches its quota.
+    ///
+    /// ```rust
+    /// # use order_maintenance::{list_range::Priority, MaintainedOrd};
+    /// let first = Priority::new();
+    /// let last = first.insert();
+    /// let between = first.insert_n(3);
+    /// assert!(first < between[0] && between[0] < between[1]);
+    /// assert!(between[1] < between[2] && between[2] < last);
+    /// ```
+    pub fn insert_n(&self, k: usize) -> Vec<Self> {
+        if k == 0 {
+            return Vec::new();
+        }
+        self.0
+            .insert_n(|at| Self::relabel_for(at, k))
+            .into_iter()
+            .map(|p| Self(p, PhantomData))
+            .collect()
+    }
+
+    /// Reserve room for `room` priorities right after this priority, for insertions after it,
+    /// until the returned [`Region`] is released.
+    ///
+    /// Relabeling passes, including those of [`rebalance_all()`](Self::rebalance_all), treat the
+    /// region as if it already held `room` priorities: they leave labels for those, rather than
+    /// spreading other priorities into it. The region thus takes up as large a share of the label
+    /// space as `room` priorities would, which shrinks as the arena grows. If the labels right
+    /// after this priority are not free yet, they are freed now, relabeling once, so that a burst
+    /// of insertions known to land here finds room waiting for it. Like the priorities it stands
+    /// for, a region counts towards running out of labels.
+    ///
+    /// Fails if the arena cannot make room for the region, leaving it as is.
+    ///
+    /// ```rust
+    /// # use order_maintenance::{list_range::Priority, MaintainedOrd};
+    /// let first = Priority::new();
+    /// let last = first.insert();
+    /// let region = first.reserve_region_after(2).unwrap();
+    ///
+    /// // Rebalancing leaves room for two priorities between `first` and `last`.
+    /// first.rebalance_all();
+    /// let gap = last.label_with_epoch().label() - first.label_with_epoch().label();
+    /// assert!(gap > usize::MAX / 2);
+    /// region.release();
+    /// ```
+    pub fn reserve_region_after(&self, room: usize) -> Result<Region<Self>, CapacityError> {
+        self.reserve_room(room)?;
+        Ok(Region {
+            anchor: self.clone(),
+            handle: self.0.clone(),
+            room,
+        })
+    }
+
+    /// Reserve room for `room` priorities right after this priority, for a [`Region`], freeing
+    /// the labels right after it first if they are not free yet.
+    fn reserve_room(&self, room: usize) -> Result<(), CapacityError> {
+        self.0.reserve_region(room, |at| {
+            let this = at.at.as_ref(at.arena).label();
+            let next = at.at.as_ref(at.arena).next().as_ref(at.arena).label();
+            if Self::span((next - this) & Self::MASK) <= room as u128 {
+                Self::make_room(at, 0)?;
+            }
+            Ok(())
+        })
+    }
+
+    /// Prepare to insert a priority after this one, performing any relabeling that it requires
+    /// now; see [`Reservation`].
+    pub fn reserve_after(&self) -> Reservation<Self> {
+        let (label, epoch) = self.0.prepare_insert(|at| {
+            Self::relabel(at);
+            Self::next_label(at)
+        });
+        Reservation {
+            anchor: self.clone(),
+            label,
+            epoch,
+        }
+    }
+
+    /// Free the priorities of this arena whose handles were all dropped, if it was configured
+    /// with [`PriorityConfig::deferred_drop()`]; returns the number of priorities freed.
+    ///
+    /// Otherwise, priorities are freed as soon as their last handle is dropped, and this is a
+    /// no-op.
+    pub fn gc(&self) -> usize {
+        self.0.gc()
+    }
+
+    /// Check the internal invariants of the arena that this priority belongs to.
+    ///
+    /// This takes time linear in the number of priorities in the arena.
+    pub fn check_invariants(&self) -> Result<(), Violation> {
+        self.0.check(Self::MASK)
+    }
+
+    /// Check the invariants of this priority's arena on every `one_in`th insertion or removal,
+    /// recording the results in its [`self_check_report()`](Self::self_check_report); or stop
+    /// checking them, if `one_in` is `None`.
+    pub fn set_self_check(&self, one_in: Option<NonZeroU64>) {
+        self.0.set_self_check(one_in, Self::MASK)
+    }
+
+    /// The results of the sampled invariant checks in this priority's arena.
+    pub fn self_check_report(&self) -> SelfCheckReport {
+        self.0.self_check_report()
+    }
+
+    /// The number of relabeling passes performed in this priority's arena so far.
+    ///
+    /// Labels of priorities in the arena can only change when this does, so caches keyed on
+    /// labels remain valid for as long as it stays the same.
+    pub fn label_epoch(&self) -> Epoch {
+        Epoch::new(self.0.label_with_epoch().1)
+    }
+
+    /// The raw label of this priority, along with the current [label epoch](Self::label_epoch).
+    ///
+    /// The label is an implementation detail, only meaningful in comparison with other labels
+    /// from the same arena and epoch.
+    pub fn label_with_epoch(&self) -> LabelSnapshot {
+        let (label, epoch) = self.0.label_with_epoch();
+        LabelSnapshot::new(label.into(), Epoch::new(epoch))
+    }
+
+    /// Approximate position of this priority in its arena, between 0.0 and 1.0.
+    ///
+    /// This is the position of its label in the label space, which takes constant time to
+    /// compute, unlike its rank; but labels are not evenly spread, so it is only a rough hint.
+    pub fn position_hint(&self) -> f64 {
+        u128::from(self.relative()) as f64 / (u128::from(Self::MASK) + 1) as f64
+    }
+
+    /// A handle to the least priority in this priority's arena.
+    pub fn min_in_arena(&self) -> Self {
+        // The base priority is not handed out, so the least priority is the one after it.
+        Self(
+            self.0.find(|arena, _| arena.base().as_ref(arena).next()),
+            PhantomData,
+        )
+    }
+
+    /// A handle to the greatest priority in this priority's arena.
+    pub fn max_in_arena(&self) -> Self {
+        Self(
+            self.0.find(|arena, _| arena.base().as_ref(arena).prev()),
+            PhantomData,
+        )
+    }
+
+    /// A handle to the least priority greater than this one in its arena, or `None` if this is
+    /// the greatest.
+    pub fn successor(&self) -> Option<Self> {
+        let this = self.0.this();
+        let is_max = self
+            .0
+            .with_arena(|arena| this.as_ref(arena).next() == arena.base());
+        (!is_max).then(|| {
+            Self(
+                self.0.find(|arena, this| this.as_ref(arena).next()),
+                PhantomData,
+            )
+        })
+    }
+
+    /// A handle to the greatest priority less than this one in its arena, or `None` if this is
+    /// the least.
+    pub fn predecessor(&self) -> Option<Self> {
+        // The base priority is not handed out, so the least priority is the one after it.
+        let this = self.0.this();
+        let is_min = self
+            .0
+            .with_arena(|arena| this.as_ref(arena).prev() == arena.base());
+        (!is_min).then(|| {
+            Self(
+                self.0.find(|arena, this| this.as_ref(arena).prev()),
+                PhantomData,
+            )
+        })
+    }
+
+    /// Rebuild the order of every priority in this priority's arena in a new arena of type `T`.
+    ///
+    /// Returns handles to the old priorities in increasing order, each paired with its
+    /// counterpart in the new arena. To convert priorities without an arena, e.g., naive ones,
+    /// use [`tag_in_order()`](crate::tag_in_order) instead.
+    pub fn convert_arena<T: MaintainedOrd>(&self) -> Vec<(Self, T)> {
+        let old = self.arena_priorities();
+        let new = T::new_sequence(old.len());
+        old.into_iter().zip(new).collect()
+    }
+
+    /// Transplant this priority into another arena, given a point `from` in this priority's arena
+    /// that corresponds to `to` in the other.
+    ///
+    /// `from` must be at or before this priority. If this priority is `k` places after `from`,
+    /// the copy is inserted `k` places after `to`, i.e., right after the `k - 1`th priority that
+    /// follows `to`, or after the greatest priority of the other arena if fewer follow `to`.
+    /// Returns `to` itself if `from` is this priority, or `None` if `from` is in another arena or
+    /// comes after this priority.
+    ///
+    /// Transplanting priorities in increasing order, each with the previous one as the
+    /// correspondence point, migrates them incrementally while preserving their order. This takes
+    /// time linear in `k` in this arena, and `k` calls to [`InArena::successor()`] in the other.
+    ///
+    /// ```rust
+    /// # use order_maintenance::{list_range::Priority, MaintainedOrd};
+    /// let ps = Priority::new_sequence(4);
+    /// let qs = Priority::new_sequence(4);
+    /// let q = ps[2].try_clone_into(&ps[0], &qs[0]).unwrap();
+    /// assert!(qs[1] < q && q < qs[2]);
+    /// ```
+    pub fn try_clone_into<T: InArena + Clone>(&self, from: &Self, to: &T) -> Option<T> {
+        // Priorities of arenas that are ordered across arenas are comparable, but not related.
+        if !self.0.same_arena(&from.0) {
+            return None;
+        }
+        let (from, this) = (from.0.this(), self.0.this());
+        let offset = self.0.with_arena(|arena| {
+            let mut key = from;
+            let mut offset = 0;
+            while key != this {
+                key = key.as_ref(arena).next();
+                // Every priority after `from` was passed without finding this one.
+                if key == arena.base() {
+                    return None;
+                }
+                offset += 1;
+            }
+            Some(offset)
+        })?;
+        if offset == 0 {
+            return Some(to.clone());
+        }
+        let mut before = to.clone();
+        for _ in 1..offset {
+            match before.successor() {
+                Some(next) => before = next,
+                None => break,
+            }
+        }
+        Some(before.insert())
+    }
+
+    /// Freeze the order of every priority in this priority's arena.
+    ///
+    /// Returns handles to the priorities in increasing order, each paired with its counterpart
+    /// in the frozen order. See [`frozen`](crate::frozen) for details.
+    pub fn freeze_arena(&self) -> (FrozenOrder, Vec<(Self, FrozenPriority)>) {
+        let handles = self.arena_priorities();
+        let order = FrozenOrder::new(handles.iter().map(|p| p.relative().into()).collect());
+        let frozen = handles.into_iter().zip(order.iter()).collect();
+        (order, frozen)
+    }
+
+    /// Snapshot the order of every priority in this priority's arena, without creating handles to
+    /// them; see [`ReadSnapshot`].
+    ///
+    /// This takes time linear in the number of priorities in the arena, but unlike
+    /// [`freeze_arena()`](Self::freeze_arena), touches no reference counts.
+    pub fn read_snapshot(&self) -> ReadSnapshot {
+        self.0.read_snapshot(true, Self::MASK)
+    }
+
+    /// Witness that `a` and `b` belong to the same arena, if they do.
+    pub fn witness_same_arena<'a>(a: &'a Self, b: &'a Self) -> Option<SameArena<'a, Self>> {
+        a.0.same_arena(&b.0).then(|| SameArena::new(a, b))
+    }
+
+    /// Compare each pair of priorities, like [`partial_cmp()`](PartialOrd::partial_cmp).
+    ///
+    /// This borrows the arena of the first pair only once for all pairs in it, which makes it
+    /// cheaper than comparing each pair separately when most pairs share an arena.
+    pub fn compare_many(pairs: &[(Self, Self)]) -> Vec<Option<Ordering>> {
+        let Some((first, _)) = pairs.first() else {
+            return Vec::new();
+        };
+        first.0.with_arena(|arena| {
+            pairs
+                .iter()
+                .map(|(a, b)| {
+                    if a.0.same_arena(&first.0) && b.0.same_arena(&first.0) {
+                        let (a, b) = (a.0.this(), b.0.this());
+                        Some(Self::relative_in(arena, a).cmp(&Self::relative_in(arena, b)))
+                    } else {
+                        a.partial_cmp(b)
+                    }
+                })
+                .collect()
+        })
+    }
+
+    /// The permutation that sorts `handles`, i.e., their indices in increasing order of priority,
+    /// with equal priorities in their original order.
+    ///
+    /// This snapshots the labels of all priorities under a single borrow of their arena, then
+    /// radix sorts them, rather than comparing them pairwise. Panics unless all priorities belong
+    /// to the same arena.
+    pub fn order_indices(handles: &[Self]) -> Vec<usize> {
+        let Some(first) = handles.first() else {
+            return Vec::new();
+        };
+        assert!(
+            handles.iter().all(|p| p.0.same_arena(&first.0)),
+            "ordered priorities must belong to the same arena"
+        );
+        let labels: Vec<usize> = first.0.with_arena(|arena| {
+            handles
+                .iter()
+                .map(|p| Self::relative_in(arena, p.0.this()).into())
+                .collect()
+        });
+        arith::sort_indices(&labels)
+    }
+
+    /// Sort `handles` in increasing order of priority, then group adjacent handles by `key`;
+    /// returns the ranges of `handles` that hold each group, in order.
+    ///
+    /// Sorting snapshots the labels of all priorities once, like
+    /// [`order_indices()`](Self::order_indices), and `key` is called once per handle. Panics
+    /// unless all priorities belong to the same arena.
+    pub fn group_adjacent_by<K: PartialEq>(
+        handles: &mut [Self],
+        key: impl FnMut(&Self) -> K,
+    ) -> Vec<Range<usize>> {
+        arith::permute(handles, Self::order_indices(handles));
+        arith::adjacent_runs(handles, key)
+    }
+
+    /// Whether this priority's arena holds more priorities than its
+    /// [soft cap](PriorityConfig::soft_cap), if it has one.
+    pub fn over_soft_cap(&self) -> bool {
+        self.0.over_soft_cap()
+    }
+
+    /// Report of the last relabeling pass in this priority's arena, if its arena was configured
+    /// to [explain relabels](PriorityConfig::explain_relabels) and has relabeled since.
+    pub fn last_relabel_report(&self) -> Option<RelabelReport> {
+        self.0.last_relabel_report()
+    }
+
+    /// Merge two sequences of priorities in increasing order into one, in linear time.
+    ///
+    /// This snapshots the labels of all priorities under a single borrow of their arena, rather
+    /// than comparing them pairwise. Panics unless all priorities belong to the same arena.
+    pub fn merge(a: &[Self], b: &[Self]) -> Vec<Self> {
+        let Some(first) = a.first().or(b.first()) else {
+            return Vec::new();
+        };
+        assert!(
+            a.iter().chain(b).all(|p| p.0.same_arena(&first.0)),
+            "merged priorities must belong to the same arena"
+        );
+        let (la, lb): (Vec<Label>, Vec<Label>) = first.0.with_arena(|arena| {
+            let labels = |ps: &[Self]| {
+                ps.iter()
+                    .map(|p| Self::relative_in(arena, p.0.this()))
+                    .collect()
+            };
+            (labels(a), labels(b))
+        });
+
+        let mut merged = Vec::with_capacity(a.len() + b.len());
+        let (mut i, mut j) = (0, 0);
+        while i < a.len() && j < b.len() {
+            if la[i] <= lb[j] {
+                merged.push(a[i].clone());
+                i += 1;
+            } else {
+                merged.push(b[j].clone());
+                j += 1;
+            }
+        }
+        merged.extend_from_slice(&a[i..]);
+        merged.extend_from_slice(&b[j..]);
+        merged
+    }
+}
+
+/// Shows the arena that the priority belongs to, its current label, and its rank in the arena.
+///
+/// Computing the rank takes time linear in the number of priorities in the arena.
+impl<P> Debug for GenericPriority<P> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Priority")
+            .field("arena", &format_args!("{:#x}", self.0.arena_id()))
+            .field("label", &usize::from(self.0.label()))
+            // The base priority is not handed out, so it does not count towards the rank.
+            .field("rank", &(self.0.position() - 1))
+            .finish()
+    }
+}
+
+#[cfg(feature = "stats")]
+impl<P> GenericPriority<P> {
+    /// Statistics about relabeling in the arena that this priority belongs to, since it was
+    /// constructed or its statistics were last [reset](Self::reset_relabel_stats).
+    pub fn relabel_stats(&self) -> crate::stats::RelabelStats {
+        self.0.stats()
+    }
+
+    /// Statistics about relabeling in the arena that this priority belongs to, since they were last
+    /// polled, or since the arena was constructed or its statistics were last reset.
+    ///
+    /// Polling starts a new window of statistics, but leaves [`relabel_stats()`](Self::relabel_stats)
+    /// as is.
+    pub fn poll_relabel_stats(&self) -> crate::stats::RelabelStats {
+        self.0.poll_stats()
+    }
+
+    /// Reset the statistics about relabeling in the arena that this priority belongs to, both
+    /// overall and since they were last polled.
+    pub fn reset_relabel_stats(&self) {
+        self.0.reset_stats()
+    }
+}
+
+#[cfg(feature = "persistent-id")]
+impl<P: Params> GenericPriority<P> {
+    /// An identifier of this priority that never changes, even across processes; see
+    /// [`persistent_id`](crate::persistent_id).
+    pub fn persistent_id(&self) -> crate::persistent_id::PersistentId {
+        self.0.persistent_id()
+    }
+
+    /// A handle to the priority with the identifier `id`, if it is in this priority's arena.
+    ///
+    /// Priorities whose handles were all dropped are not found, unless their arena
+    /// [defers drops](PriorityConfig::deferred_drop) and has not collected them yet. This takes
+    /// time linear in the number of priorities in the arena.
+    pub fn find(&self, id: crate::persistent_id::PersistentId) -> Option<Self> {
+        self.0
+            .find_persistent(id, true)
+            .map(|p| Self(p, PhantomData))
+    }
+
+    /// Every priority in this priority's arena that `map` has data about, in increasing order,
+    /// along with that data.
+    ///
+    /// This takes time linear in the number of priorities in the arena, plus a lookup in `map`
+    /// for each of them.
+    pub fn iter_with<'a, T>(
+        &self,
+        map: &'a impl crate::persistent_id::SecondaryMap<T>,
+    ) -> impl Iterator<Item = (Self, &'a T)> + 'a
+    where
+        P: 'a,
+        T: 'a,
+    {
+        self.arena_priorities().into_iter().filter_map(|p| {
+            let data = map.get_by_id(&p.persistent_id())?;
+            Some((p, data))
+        })
+    }
+}
+
+#[cfg(feature = "oplog")]
+impl<P: Params> crate::oplog::Recorded for GenericPriority<P> {
+    fn op_log(&self) -> crate::oplog::OpLog {
+        self.0.op_log()
+    }
+
+    fn op_key(&self) -> usize {
+        self.0.op_key()
+    }
+
+    fn rebalance_all(&self) -> usize {
+        GenericPriority::rebalance_all(self)
+    }
+
+    fn compact_labels(&self) -> usize {
+        GenericPriority::compact_labels(self)
+    }
+
+    fn insert_n(&self, k: usize) -> Vec<Self> {
+        GenericPriority::insert_n(self, k)
+    }
+
+    fn reserve_region_after(&self, room: usize) -> Result<Region<Self>, CapacityError> {
+        GenericPriority::reserve_region_after(self, room)
+    }
+
+    fn reserve_room_after(&self, room: usize) -> Result<(), CapacityError> {
+        self.reserve_room(room)
+    }
+
+    fn release_room_after(&self, room: usize) {
+        self.0.release_region(room)
+    }
+}
+
+impl<P: Params> crate::id::Identified for GenericPriority<P> {
+    fn id(&self) -> crate::id::PriorityId {
+        self.0.id()
+    }
+
+    fn id_order(&self) -> crate::id::IdOrder<'_> {
+        self.0.id_order(Self::MASK)
     }
 }
 
-impl PartialOrd for Priority {
+#[cfg(feature = "checkpoint")]
+impl<P> crate::checkpoint::Checkpointed for GenericPriority<P> {
+    fn checkpoint(&self) -> crate::checkpoint::Checkpoint {
+        self.0.checkpoint()
+    }
+
+    fn changes_since(
+        &self,
+        checkpoint: &crate::checkpoint::Checkpoint,
+    ) -> Option<crate::checkpoint::Changes> {
+        self.0.changes_since(checkpoint)
+    }
+
+    fn change_key(&self) -> usize {
+        self.0.change_key()
+    }
+
+    fn discard_changes_before(&self, checkpoint: &crate::checkpoint::Checkpoint) {
+        self.0.discard_changes_before(checkpoint)
+    }
+}
+
+#[cfg(feature = "introspect")]
+impl<P: Params> crate::introspect::Introspect for GenericPriority<P> {
+    fn raw_label(&self) -> usize {
+        self.0.label().into()
+    }
+
+    fn neighbor_labels(&self) -> (usize, usize) {
+        let (prev, next) = self.0.neighbor_labels();
+        (prev.into(), next.into())
+    }
+
+    fn gap_to_next(&self) -> u64 {
+        let (_, next) = self.0.neighbor_labels();
+        let next = (next - self.0.base_label()) & Self::MASK;
+        let gap = arith::gap_to_next(self.relative().into(), next.into(), Self::MASK.into());
+        gap.try_into().unwrap_or(u64::MAX)
+    }
+
+    fn creation_index(&self) -> usize {
+        self.0.creation_index()
+    }
+
+    fn dump_arena(&self) -> crate::introspect::ArenaDump {
+        self.0.dump()
+    }
+}
+
+impl<P> Clone for GenericPriority<P> {
+    fn clone(&self) -> Self {
+        Self(self.0.clone(), PhantomData)
+    }
+}
+
+impl<P> PartialEq for GenericPriority<P> {
+    fn eq(&self, other: &Self) -> bool {
+        self.0 == other.0
+    }
+}
+
+impl<P> Eq for GenericPriority<P> {}
+
+impl<P: Params> PartialOrd for GenericPriority<P> {
     fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
         if !self.0.same_arena(&other.0) {
-            None
+            self.0.cross_arena_cmp(&other.0)
         } else if self.0 == other.0 {
             Some(Ordering::Equal)
         } else {
@@ -141,19 +931,146 @@ impl PartialOrd for Priority {
     }
 }
 
-impl MaintainedOrd for Priority {
-    fn new() -> Self {
-        let mut arena = Arena::new();
+impl<P: Params> SameArena<'_, GenericPriority<P>> {
+    /// Compare the witnessed priorities, without checking that they belong to the same arena.
+    pub fn compare(&self) -> Ordering {
+        self.lhs().relative().cmp(&self.rhs().relative())
+    }
+}
 
+impl<P: Params> GenericPriority<P> {
+    /// Construct the first priority in `arena`.
+    fn first_in(mut arena: Arena) -> Self {
         // For list-range, the base is a special priority, so we need to use another one.
-        let this = arena.insert_after(Label::MAX / 2, arena.base());
-        Self(PriorityRef::new(arena, this))
+        let this = arena.insert_after(Self::MASK / 2, arena.base());
+        Self(PriorityRef::new(arena, this), PhantomData)
+    }
+}
+
+/// List-range priorities do not use thresholds, so [`PriorityConfig::threshold()`] is ignored.
+impl<P: Params> Configurable for GenericPriority<P> {
+    fn from_config(config: &PriorityConfig) -> Self {
+        Self::first_in(config.arena(Self::MASK))
+    }
+}
+
+impl<P: Params> MaintainedOrd for GenericPriority<P> {
+    fn new() -> Self {
+        Self::first_in(Arena::new())
+    }
+
+    fn new_sequence(n: usize) -> Vec<Self> {
+        let mut arena = Arena::new();
+        let keys = arena.insert_spread_after(arena.base(), n, Arena::BASE, Self::MASK);
+        PriorityRef::new_many(arena, keys)
+            .into_iter()
+            .map(|p| Self(p, PhantomData))
+            .collect()
     }
 
     fn insert(&self) -> Self {
-        Self(self.0.insert(|arena| {
-            self.relabel(arena);
-            self.next_label(arena)
-        }))
+        Self(
+            self.0.insert(|at| {
+                Self::relabel(at);
+                Self::next_label(at)
+            }),
+            PhantomData,
+        )
+    }
+}
+
+impl<P: Params> Reservation<GenericPriority<P>> {
+    /// Insert a priority after the anchor, at the reserved label if it is still free, or else
+    /// like [`MaintainedOrd::insert()`].
+    ///
+    /// Either way, panics if the arena has reached its quota.
+    pub fn commit(self) -> GenericPriority<P> {
+        let (label, epoch) = (self.label, self.epoch);
+        GenericPriority(
+            self.anchor.0.insert(|at| {
+                if at.arena.label_epoch() == epoch && GenericPriority::<P>::is_free_after(at, label)
+                {
+                    if let Err(e) = at.arena.check_quota(at.arena_id, 1) {
+                        panic!("{e}")
+                    }
+                    return label;
+                }
+                GenericPriority::<P>::relabel(at);
+                GenericPriority::<P>::next_label(at)
+            }),
+            PhantomData,
+        )
+    }
+}
+
+impl<P> crate::capability::ConstantTimeCompare for GenericPriority<P> {}
+
+impl<P: Params> crate::capability::TryInsert for GenericPriority<P> {
+    fn try_insert(&self) -> Result<Self, CapacityError> {
+        let p = self.0.try_insert(|at| {
+            Self::try_relabel(at)?;
+            Ok(Self::next_label(at))
+        })?;
+        Ok(Self(p, PhantomData))
+    }
+}
+
+/// The base priority of a list-range arena is a sentinel that precedes every priority, so the new
+/// priority is inserted right after it.
+impl<P: Params> crate::capability::InsertFront for GenericPriority<P> {
+    fn insert_front(&self) -> Self {
+        Self(
+            self.0.insert_front(false, |at| {
+                Self::relabel(at);
+                Self::next_label(at)
+            }),
+            PhantomData,
+        )
+    }
+}
+
+impl<P: Params> crate::capability::TryInsertFront for GenericPriority<P> {
+    fn try_insert_front(&self) -> Result<Self, CapacityError> {
+        let p = self.0.try_insert_front(false, |at| {
+            Self::try_relabel(at)?;
+            Ok(Self::next_label(at))
+        })?;
+        Ok(Self(p, PhantomData))
+    }
+}
+
+impl<P: Params> Default for GenericPriority<P> {
+    /// Construct the first priority in a new arena, like [`MaintainedOrd::new()`].
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<P: Params> InArena for GenericPriority<P> {
+    fn arena_priorities(&self) -> Vec<Self> {
+        self.0
+            .arena_handles(true)
+            .into_iter()
+            .map(|p| Self(p, PhantomData))
+            .collect()
+    }
+
+    fn successor(&self) -> Option<Self> {
+        GenericPriority::successor(self)
+    }
+}
+
+impl<P: Params> Checked for GenericPriority<P> {
+    fn check_invariants(&self) -> Result<(), Violation> {
+        self.check_invariants()
+    }
+
+    fn capacity() -> usize {
+        // Excluding the base priority.
+        usize::from(Self::MASK)
+    }
+
+    fn param_problems() -> Vec<String> {
+        crate::params::bits_problems::<P>()
     }
 }
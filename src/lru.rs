@@ -0,0 +1,158 @@
+//! Least-recently-used caches that can compare the recency of any two entries.
+//!
+//! An [`OmLru`] assigns each entry a priority, which it replaces with a new priority at the
+//! most-recently-used end of the order whenever the entry is used. Besides evicting the least
+//! recently used entry like any other LRU cache, it can tell which of two entries was used more
+//! recently in constant time:
+//!
+//! ```rust
+//! # use order_maintenance::lru::OmLru;
+//! let mut cache: OmLru<&str, u32> = OmLru::new(2);
+//! cache.insert("a", 1);
+//! cache.insert("b", 2);
+//! assert_eq!(cache.used_after(&"b", &"a"), Some(true));
+//!
+//! assert_eq!(cache.get(&"a"), Some(&1));
+//! assert_eq!(cache.used_after(&"b", &"a"), Some(false));
+//!
+//! // "b" is now the least recently used entry, so it is evicted first.
+//! assert_eq!(cache.insert("c", 3), Some(("b", 2)));
+//! assert_eq!(cache.iter().map(|(k, _)| *k).collect::<Vec<_>>(), ["a", "c"]);
+//! ```
+//!
+//! Priorities cannot be moved within their arena, so using an entry inserts a priority after the
+//! most recent one and drops the entry's old priority. Finding the least recently used entry then
+//! takes more than the arena: the cache also keeps its keys in a [`BTreeMap`] ordered by their
+//! priorities, so using, inserting, removing and evicting entries each take time logarithmic in
+//! the number of entries, on top of inserting a priority. Only comparing the recency of two
+//! entries takes constant time.
+use crate::{tag_range, MaintainedOrd, TotalOrder};
+use std::collections::{BTreeMap, HashMap};
+use std::hash::Hash;
+
+/// A least-recently-used cache, ordered by priorities of type `P`.
+///
+/// Using an entry costs the same as inserting a priority, plus a logarithmic update of the order
+/// of entries. The priority type `P` defaults to [`tag_range::Priority`].
+#[derive(Debug)]
+pub struct OmLru<K, V, P = tag_range::Priority> {
+    capacity: usize,
+    /// The priority of the most recently used entry, or the first priority of the arena.
+    mru: P,
+    /// Each entry's value, and the priority of its last use.
+    entries: HashMap<K, (V, P)>,
+    /// Keys of the entries, from least to most recently used.
+    order: BTreeMap<TotalOrder<P>, K>,
+}
+
+impl<K, V, P> OmLru<K, V, P>
+where
+    K: Eq + Hash + Clone,
+    P: MaintainedOrd + Eq + Clone,
+{
+    /// Construct a cache that holds up to `capacity` entries.
+    ///
+    /// Panics if `capacity` is 0.
+    pub fn new(capacity: usize) -> Self {
+        assert!(capacity > 0, "capacity must be positive");
+        Self {
+            capacity,
+            mru: P::new(),
+            entries: HashMap::with_capacity(capacity),
+            order: BTreeMap::new(),
+        }
+    }
+
+    /// Maximum number of entries in the cache.
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// Number of entries in the cache.
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Whether the cache has no entries.
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Allocate the priority of a new use, after that of every other use.
+    fn next_use(&mut self) -> P {
+        self.mru = self.mru.insert();
+        self.mru.clone()
+    }
+
+    /// Insert an entry, or update it if it exists, as the most recently used entry.
+    ///
+    /// Returns the least recently used entry, if it was evicted to make room.
+    pub fn insert(&mut self, key: K, value: V) -> Option<(K, V)> {
+        if self.touch(&key) {
+            if let Some((v, _)) = self.entries.get_mut(&key) {
+                *v = value;
+            }
+            return None;
+        }
+        let evicted = if self.len() == self.capacity {
+            self.pop_lru()
+        } else {
+            None
+        };
+        let p = self.next_use();
+        self.order.insert(TotalOrder(p.clone()), key.clone());
+        self.entries.insert(key, (value, p));
+        evicted
+    }
+
+    /// Mark an entry as the most recently used one, returning whether it exists.
+    pub fn touch(&mut self, key: &K) -> bool {
+        let Some((_, last_use)) = self.entries.get_mut(key) else {
+            return false;
+        };
+        self.mru = self.mru.insert();
+        let old = std::mem::replace(last_use, self.mru.clone());
+        self.order.remove(&TotalOrder(old));
+        self.order.insert(TotalOrder(self.mru.clone()), key.clone());
+        true
+    }
+
+    /// Get the value of an entry, marking it as the most recently used one.
+    pub fn get(&mut self, key: &K) -> Option<&V> {
+        self.touch(key);
+        self.peek(key)
+    }
+
+    /// Get the value of an entry, without marking it as used.
+    pub fn peek(&self, key: &K) -> Option<&V> {
+        self.entries.get(key).map(|(v, _)| v)
+    }
+
+    /// Remove an entry, returning its value.
+    pub fn remove(&mut self, key: &K) -> Option<V> {
+        let (value, p) = self.entries.remove(key)?;
+        self.order.remove(&TotalOrder(p));
+        Some(value)
+    }
+
+    /// Remove the least recently used entry.
+    pub fn pop_lru(&mut self) -> Option<(K, V)> {
+        let (_, key) = self.order.pop_first()?;
+        let (value, _) = self.entries.remove(&key)?;
+        Some((key, value))
+    }
+
+    /// Whether `x` was used more recently than `y`, if both are in the cache.
+    ///
+    /// This takes constant time, besides looking up the entries.
+    pub fn used_after(&self, x: &K, y: &K) -> Option<bool> {
+        let (_, px) = self.entries.get(x)?;
+        let (_, py) = self.entries.get(y)?;
+        Some(px > py)
+    }
+
+    /// The entries of the cache, from least to most recently used.
+    pub fn iter(&self) -> impl Iterator<Item = (&K, &V)> + '_ {
+        self.order.values().map(|k| (k, &self.entries[k].0))
+    }
+}
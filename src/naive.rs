@@ -11,7 +11,16 @@ impl MaintainedOrd for Priority {
     }
 
     fn insert(&self) -> Self {
-        Self(Rc::new(self.0.insert()))
+        Self(self.0.insert())
+    }
+}
+
+impl crate::capability::ConstantTimeCompare for Priority {}
+
+impl Default for Priority {
+    /// Construct a new priority, like [`MaintainedOrd::new()`].
+    fn default() -> Self {
+        Self::new()
     }
 }
 
@@ -54,6 +63,15 @@ impl MaintainedOrd for UniquePriority {
     }
 }
 
+impl crate::capability::ConstantTimeCompare for UniquePriority {}
+
+impl Default for UniquePriority {
+    /// Construct a new priority, like [`MaintainedOrd::new()`].
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl PartialOrd for UniquePriority {
     fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
         match self.depth.get().cmp(&other.depth.get()) {
@@ -0,0 +1,682 @@
+//! Recording and replaying the operations performed on an arena.
+//!
+//! Only available with the `oplog` feature. Every arena keeps an [`OpLog`] of the operations that
+//! were performed on it, from its creation onwards. Replaying that log with the same priority type
+//! reconstructs the exact same internal state, so a log is enough to reproduce a bug:
+//!
+//! ```rust
+//! # use order_maintenance::{oplog::{OpLog, Recorded}, tag_range::Priority, MaintainedOrd};
+//! let a = Priority::new();
+//! let b = a.insert();
+//! let c = a.insert();
+//! drop(b);
+//!
+//! let bytes = c.op_log().to_bytes();
+//! let replayed: Vec<Priority> = OpLog::from_bytes(&bytes).unwrap().replay();
+//! assert_eq!(replayed.len(), 2);
+//! assert!(replayed[0] < replayed[1]);
+//! ```
+//!
+//! A [`HistoricalOrder`] answers questions about the past of an arena, by reconstructing its order
+//! as of any operation in its log:
+//!
+//! ```rust
+//! # use order_maintenance::{oplog::{HistoricalOrder, Recorded}, tag_range::Priority, MaintainedOrd};
+//! # use std::cmp::Ordering;
+//! let a = Priority::new();
+//! let c = a.insert();
+//! let b = c.insert();
+//! let (b_key, c_key) = (b.op_key(), c.op_key());
+//! let d = b.insert();
+//! drop(c);
+//!
+//! let history = HistoricalOrder::<Priority>::new(a.op_log());
+//! assert_eq!(history.at(2).compare(c_key, b_key), Some(Ordering::Less));
+//! // When was c no longer ahead of b?
+//! let dropped = history.bisect(|order| order.compare(c_key, b_key) != Some(Ordering::Less));
+//! assert_eq!(dropped, Some(history.len() - 1));
+//! # drop(d);
+//! ```
+//!
+//! # Format
+//!
+//! Encoded logs are meant to be read and written outside of this crate too, so their format is
+//! pinned down here, and by the golden files in `tests/golden/`. Version 2 of the format, which
+//! [`OpLog::to_bytes()`] produces, consists of:
+//!
+//! 1. The four bytes of [`MAGIC`], i.e., `OMLG` in ASCII.
+//! 2. One byte holding the format version, i.e., `0x02`.
+//! 3. Each operation in order, as a tag byte, followed by its operands:
+//!
+//!    | Tag  | Operation             | Operands                                             |
+//!    |------|-----------------------|------------------------------------------------------|
+//!    | `0`  | [`Op::New`]           | none                                                 |
+//!    | `1`  | [`Op::NewSequence`]   | the number of priorities                             |
+//!    | `2`  | [`Op::Insert`]        | the key of the anchor                                |
+//!    | `3`  | [`Op::Clone`]         | the key of the priority                              |
+//!    | `4`  | [`Op::Drop`]          | the key of the priority                              |
+//!    | `5`  | [`Op::InsertFront`]   | the key of the anchor                                |
+//!    | `6`  | [`Op::Rebalance`]     | the key of the priority                              |
+//!    | `7`  | [`Op::Compact`]       | the key of the priority                              |
+//!    | `8`  | [`Op::InsertN`]       | the key of the anchor, then the number of priorities |
+//!    | `9`  | [`Op::ReserveRegion`] | the key of the anchor, then the room in the region   |
+//!    | `10` | [`Op::ReleaseRegion`] | the key of the anchor, then the room in the region   |
+//!
+//! Operands are unsigned LEB128 varints: seven bits at a time, least significant first, with the
+//! high bit of every byte but the last set. Nothing follows the last operation. A log that can be
+//! replayed also creates its arena with its first operation, and only then; producers outside of
+//! this crate can check that their logs are well-formed with [`validate_snapshot()`]:
+//!
+//! ```rust
+//! # use order_maintenance::oplog::validate_snapshot;
+//! assert!(validate_snapshot(b"OMLG\x02\x00\x02\x00").is_ok());
+//! assert_eq!(validate_snapshot(b"OMLG\x02\x02\x00").unwrap_err().offset, 5);
+//! ```
+//!
+//! Version 1 only lacked tags `6` to `10`, so [`OpLog::migrate()`] reads logs in it as they are.
+use crate::capability::{CapacityError, InsertFront};
+use crate::reservation::Region;
+use crate::types::Rank;
+use std::cmp::Ordering;
+use std::collections::HashMap;
+use std::fmt::Display;
+use std::marker::PhantomData;
+
+/// An operation on an arena.
+///
+/// Priorities are identified by their key in the arena, as given by [`Recorded::op_key()`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Op {
+    /// The arena was created by [`MaintainedOrd::new()`](crate::MaintainedOrd::new).
+    New,
+    /// The arena was created by
+    /// [`MaintainedOrd::new_sequence()`](crate::MaintainedOrd::new_sequence) with this many
+    /// priorities.
+    NewSequence(usize),
+    /// A priority was inserted after the priority with this key.
+    Insert(usize),
+    /// A priority was inserted at the front of the arena, through the priority with this key.
+    InsertFront(usize),
+    /// A handle to the priority with this key was cloned.
+    Clone(usize),
+    /// A handle to the priority with this key was dropped.
+    Drop(usize),
+    /// The arena was rebalanced by [`Recorded::rebalance_all()`] through the priority with
+    /// this key.
+    Rebalance(usize),
+    /// The arena was compacted by [`Recorded::compact_labels()`] through the priority with
+    /// this key.
+    Compact(usize),
+    /// This many priorities were inserted after the priority with this key, in one go, by
+    /// [`Recorded::insert_n()`].
+    InsertN(usize, usize),
+    /// A [`Region`] with room for this many priorities was reserved
+    /// after the priority with this key, by [`Recorded::reserve_region_after()`].
+    ReserveRegion(usize, usize),
+    /// A [`Region`] with room for this many priorities was released
+    /// after the priority with this key.
+    ReleaseRegion(usize, usize),
+}
+
+impl Op {
+    fn tag(&self) -> u8 {
+        match self {
+            Op::New => 0,
+            Op::NewSequence(_) => 1,
+            Op::Insert(_) => 2,
+            Op::Clone(_) => 3,
+            Op::Drop(_) => 4,
+            Op::InsertFront(_) => 5,
+            Op::Rebalance(_) => 6,
+            Op::Compact(_) => 7,
+            Op::InsertN(_, _) => 8,
+            Op::ReserveRegion(_, _) => 9,
+            Op::ReleaseRegion(_, _) => 10,
+        }
+    }
+}
+
+/// Bytes that encoded logs start with, followed by their format version, since version 1.
+pub const MAGIC: &[u8; 4] = b"OMLG";
+
+/// Version of the format that logs are encoded in by [`OpLog::to_bytes()`].
+pub const FORMAT_VERSION: u8 = 2;
+
+/// Log of the operations performed on an arena, in order.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct OpLog {
+    ops: Vec<Op>,
+}
+
+/// Error returned when decoding a malformed [`OpLog`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DecodeError {
+    /// Offset of the byte at which decoding failed.
+    pub offset: usize,
+}
+
+impl Display for DecodeError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "malformed operation log at byte {}", self.offset)
+    }
+}
+
+impl std::error::Error for DecodeError {}
+
+/// Priorities whose arenas record an [`OpLog`].
+pub trait Recorded: InsertFront + Clone {
+    /// The log of operations performed on the arena that this priority belongs to.
+    fn op_log(&self) -> OpLog;
+
+    /// The key that identifies this priority in [`Op`]s.
+    fn op_key(&self) -> usize;
+
+    /// Redistribute the labels of every priority in this priority's arena evenly across the label
+    /// space; returns the number of priorities relabeled.
+    fn rebalance_all(&self) -> usize;
+
+    /// Renumber every priority in this priority's arena with consecutive labels from the start of
+    /// the label space; returns the number of priorities relabeled.
+    fn compact_labels(&self) -> usize;
+
+    /// Insert `k` new priorities right after this one, in increasing order, with evenly spaced
+    /// labels.
+    fn insert_n(&self, k: usize) -> Vec<Self>;
+
+    /// Reserve a [`Region`] with room for `room` priorities right after this priority.
+    fn reserve_region_after(&self, room: usize) -> Result<Region<Self>, CapacityError>;
+
+    /// Reserve room for `room` priorities right after this priority, like a [`Region`] does, but
+    /// without a region to release it again.
+    ///
+    /// This is how [`OpLog::replay()`] replays [`Op::ReserveRegion`], since the handles that the
+    /// region held on to are logged, and replayed, on their own. The room stays reserved until it
+    /// is released by [`release_room_after()`](Self::release_room_after).
+    fn reserve_room_after(&self, room: usize) -> Result<(), CapacityError>;
+
+    /// Release room for `room` priorities reserved right after this priority by
+    /// [`reserve_room_after()`](Self::reserve_room_after).
+    fn release_room_after(&self, room: usize);
+}
+
+impl OpLog {
+    /// Append an operation to the log.
+    pub(crate) fn push(&mut self, op: Op) {
+        self.ops.push(op);
+    }
+
+    /// The operations in the log, in order.
+    pub fn ops(&self) -> &[Op] {
+        &self.ops
+    }
+
+    /// Encode the log compactly, in version [`FORMAT_VERSION`] of the format.
+    ///
+    /// The encoding starts with [`MAGIC`] and the version byte, followed by a tag byte and LEB128
+    /// varint operands per operation.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(MAGIC.len() + 1 + self.ops.len() * 2);
+        bytes.extend_from_slice(MAGIC);
+        bytes.push(FORMAT_VERSION);
+        for op in &self.ops {
+            bytes.push(op.tag());
+            let n = match *op {
+                Op::New => continue,
+                Op::InsertN(key, n) | Op::ReserveRegion(key, n) | Op::ReleaseRegion(key, n) => {
+                    push_varint(&mut bytes, key);
+                    n
+                }
+                Op::NewSequence(n)
+                | Op::Insert(n)
+                | Op::InsertFront(n)
+                | Op::Clone(n)
+                | Op::Drop(n)
+                | Op::Rebalance(n)
+                | Op::Compact(n) => n,
+            };
+            push_varint(&mut bytes, n);
+        }
+        bytes
+    }
+
+    /// The format version of an encoded log, or `None` if it predates format versions.
+    ///
+    /// Logs without a version, i.e., version 0, consist of just the encoded operations.
+    pub fn format_version(bytes: &[u8]) -> Option<u8> {
+        bytes.strip_prefix(MAGIC)?.first().copied()
+    }
+
+    /// Decode a log encoded by [`to_bytes()`](Self::to_bytes), in version [`FORMAT_VERSION`].
+    ///
+    /// Logs in other versions are rejected; use [`migrate()`](Self::migrate) for older ones.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, DecodeError> {
+        Self::check_header(bytes)?;
+        Self::decode_ops(bytes, MAGIC.len() + 1)
+    }
+
+    /// Check that `bytes` start with a header for version [`FORMAT_VERSION`].
+    fn check_header(bytes: &[u8]) -> Result<(), DecodeError> {
+        if Self::format_version(bytes) != Some(FORMAT_VERSION) {
+            let offset = if bytes.starts_with(MAGIC) {
+                MAGIC.len()
+            } else {
+                0
+            };
+            return Err(DecodeError { offset });
+        }
+        Ok(())
+    }
+
+    /// Decode the operations encoded in `bytes` from offset `start` onwards.
+    fn decode_ops(bytes: &[u8], start: usize) -> Result<Self, DecodeError> {
+        let ops = decode_ops_at(bytes, start)?
+            .into_iter()
+            .map(|(_, op)| op)
+            .collect();
+        Ok(Self { ops })
+    }
+
+    /// Decode a log encoded in the current or any prior version of the format.
+    pub fn migrate(old_bytes: &[u8]) -> Result<Self, DecodeError> {
+        match Self::format_version(old_bytes) {
+            // Version 0 did not have a header; its operations are encoded like in version 1.
+            None => Self::decode_ops(old_bytes, 0),
+            // Version 1 encodes a subset of the operations of version 2, in the same way.
+            Some(1) => Self::decode_ops(old_bytes, MAGIC.len() + 1),
+            Some(_) => Self::from_bytes(old_bytes),
+        }
+    }
+
+    /// Replay the log in a fresh arena, returning the handles that are still alive at its end.
+    ///
+    /// Handles are returned in the order of the priorities they refer to. The internal state of the
+    /// new arena is the same as that of the arena that the log was recorded from.
+    ///
+    /// Panics if the log does not start by creating an arena, or refers to a priority that is not
+    /// alive at that point.
+    pub fn replay<P: Recorded>(&self) -> Vec<P> {
+        // Live handles for each key; a key can have several handles after `Op::Clone`.
+        let mut live: HashMap<usize, Vec<P>> = HashMap::new();
+        let add = |live: &mut HashMap<usize, Vec<P>>, p: P| {
+            live.entry(p.op_key()).or_default().push(p);
+        };
+        let handle = |live: &mut HashMap<usize, Vec<P>>, key: usize| -> P {
+            match live.get_mut(&key).and_then(Vec::pop) {
+                Some(p) => p,
+                None => panic!("operation log refers to a dead priority: {key}"),
+            }
+        };
+
+        let mut ops = self.ops.iter();
+        match ops.next() {
+            Some(Op::New) => add(&mut live, P::new()),
+            Some(Op::NewSequence(n)) => {
+                for p in P::new_sequence(*n) {
+                    add(&mut live, p);
+                }
+            }
+            _ => panic!("operation log does not start by creating an arena"),
+        }
+
+        for op in ops {
+            match *op {
+                Op::New | Op::NewSequence(_) => panic!("operation log creates an arena twice"),
+                Op::Insert(key) => {
+                    let p = handle(&mut live, key);
+                    let q = p.insert();
+                    add(&mut live, p);
+                    add(&mut live, q);
+                }
+                Op::InsertFront(key) => {
+                    let p = handle(&mut live, key);
+                    let q = p.insert_front();
+                    add(&mut live, p);
+                    add(&mut live, q);
+                }
+                Op::Clone(key) => {
+                    let p = handle(&mut live, key);
+                    let q = p.clone();
+                    add(&mut live, p);
+                    add(&mut live, q);
+                }
+                Op::Drop(key) => drop(handle(&mut live, key)),
+                Op::Rebalance(key) => {
+                    let p = handle(&mut live, key);
+                    p.rebalance_all();
+                    add(&mut live, p);
+                }
+                Op::Compact(key) => {
+                    let p = handle(&mut live, key);
+                    p.compact_labels();
+                    add(&mut live, p);
+                }
+                Op::InsertN(key, k) => {
+                    let p = handle(&mut live, key);
+                    for q in p.insert_n(k) {
+                        add(&mut live, q);
+                    }
+                    add(&mut live, p);
+                }
+                // The handles that regions hold on to are logged as clones, and dropped as such.
+                Op::ReserveRegion(key, room) => {
+                    let p = handle(&mut live, key);
+                    if let Err(e) = p.reserve_room_after(room) {
+                        panic!("{e}")
+                    }
+                    add(&mut live, p);
+                }
+                Op::ReleaseRegion(key, room) => {
+                    let p = handle(&mut live, key);
+                    p.release_room_after(room);
+                    add(&mut live, p);
+                }
+            }
+        }
+
+        let mut handles: Vec<P> = live.into_values().flatten().collect();
+        handles.sort_by(|a, b| a.partial_cmp(b).unwrap());
+        handles
+    }
+}
+
+/// The recorded history of an arena, for reconstructing its order as of any past operation.
+///
+/// Reconstructing the order after an operation replays the log up to it with priorities of type
+/// `P`, which must be the type that the log was recorded with, since keys depend on it.
+/// Priorities are identified by their [keys](Recorded::op_key), which are reused once
+/// priorities are freed, so a key only identifies the same priority while it is live.
+#[derive(Debug, Clone)]
+pub struct HistoricalOrder<P> {
+    log: OpLog,
+    _priority: PhantomData<fn() -> P>,
+}
+
+/// The order of an arena as of some operation in its history, as reconstructed by
+/// [`HistoricalOrder::at()`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OrderAt {
+    /// Keys of the live priorities, in increasing order.
+    keys: Vec<usize>,
+    /// Rank of each live priority, by key.
+    ranks: HashMap<usize, usize>,
+}
+
+impl<P: Recorded> HistoricalOrder<P> {
+    /// The history recorded in `log`.
+    pub fn new(log: OpLog) -> Self {
+        Self {
+            log,
+            _priority: PhantomData,
+        }
+    }
+
+    /// Number of operations in the history.
+    pub fn len(&self) -> usize {
+        self.log.ops.len()
+    }
+
+    /// Whether the history has no operations.
+    pub fn is_empty(&self) -> bool {
+        self.log.ops.is_empty()
+    }
+
+    /// The order of the arena right after the operation with index `op_index`.
+    ///
+    /// This replays the history up to that operation, so it takes time linear in `op_index`.
+    /// Panics if there is no such operation.
+    pub fn at(&self, op_index: usize) -> OrderAt {
+        let prefix = OpLog {
+            ops: self.log.ops[..=op_index].to_vec(),
+        };
+        let mut keys: Vec<usize> = prefix.replay::<P>().iter().map(P::op_key).collect();
+        // Clones of a handle share its key.
+        keys.dedup();
+        let ranks = keys
+            .iter()
+            .enumerate()
+            .map(|(rank, &key)| (key, rank))
+            .collect();
+        OrderAt { keys, ranks }
+    }
+
+    /// The index of the first operation after which `pred` holds of the order, assuming that it
+    /// holds after every operation from then on, e.g., "A is ahead of B".
+    ///
+    /// Bisects the history, so it reconstructs the order only logarithmically many times.
+    pub fn bisect(&self, mut pred: impl FnMut(&OrderAt) -> bool) -> Option<usize> {
+        let (mut lo, mut hi) = (0, self.len());
+        while lo < hi {
+            let mid = lo + (hi - lo) / 2;
+            if pred(&self.at(mid)) {
+                hi = mid;
+            } else {
+                lo = mid + 1;
+            }
+        }
+        (lo < self.len()).then_some(lo)
+    }
+}
+
+impl OrderAt {
+    /// Keys of the live priorities, in increasing order.
+    pub fn keys(&self) -> &[usize] {
+        &self.keys
+    }
+
+    /// The rank of the priority with key `key`, if it is live.
+    pub fn rank(&self, key: usize) -> Option<Rank> {
+        self.ranks.get(&key).copied().map(Rank::new)
+    }
+
+    /// Compare the priorities with keys `a` and `b`, if both are live.
+    pub fn compare(&self, a: usize, b: usize) -> Option<Ordering> {
+        Some(self.rank(a)?.cmp(&self.rank(b)?))
+    }
+}
+
+/// Decode the operations encoded in `bytes` from offset `start` onwards, with the offsets of their
+/// tags.
+fn decode_ops_at(bytes: &[u8], start: usize) -> Result<Vec<(usize, Op)>, DecodeError> {
+    let mut ops = Vec::new();
+    let mut i = start;
+    while i < bytes.len() {
+        let tag_offset = i;
+        let tag = bytes[i];
+        i += 1;
+        if tag == 0 {
+            ops.push((tag_offset, Op::New));
+            continue;
+        }
+
+        let n = read_varint(bytes, &mut i)?;
+        ops.push((
+            tag_offset,
+            match tag {
+                1 => Op::NewSequence(n),
+                2 => Op::Insert(n),
+                3 => Op::Clone(n),
+                4 => Op::Drop(n),
+                5 => Op::InsertFront(n),
+                6 => Op::Rebalance(n),
+                7 => Op::Compact(n),
+                8 => Op::InsertN(n, read_varint(bytes, &mut i)?),
+                9 => Op::ReserveRegion(n, read_varint(bytes, &mut i)?),
+                10 => Op::ReleaseRegion(n, read_varint(bytes, &mut i)?),
+                _ => return Err(DecodeError { offset: tag_offset }),
+            },
+        ));
+    }
+    Ok(ops)
+}
+
+/// Append `n` to `bytes` as an unsigned LEB128 varint.
+fn push_varint(bytes: &mut Vec<u8>, mut n: usize) {
+    loop {
+        let byte = (n & 0x7f) as u8;
+        n >>= 7;
+        if n == 0 {
+            bytes.push(byte);
+            break;
+        }
+        bytes.push(byte | 0x80);
+    }
+}
+
+/// Decode an unsigned LEB128 varint from `bytes` at offset `*i`, advancing `*i` past it.
+///
+/// Fails at the first byte whose payload does not fit in a `usize`, rather than dropping its high
+/// bits.
+fn read_varint(bytes: &[u8], i: &mut usize) -> Result<usize, DecodeError> {
+    let mut n: usize = 0;
+    let mut shift = 0;
+    loop {
+        let byte = *bytes.get(*i).ok_or(DecodeError { offset: *i })?;
+        let payload = (byte & 0x7f) as usize;
+        if shift >= usize::BITS || (payload << shift) >> shift != payload {
+            return Err(DecodeError { offset: *i });
+        }
+        n |= payload << shift;
+        shift += 7;
+        *i += 1;
+        if byte & 0x80 == 0 {
+            return Ok(n);
+        }
+    }
+}
+
+/// Check that `bytes` encode a log, in version [`FORMAT_VERSION`] of the [format](self#format),
+/// that creates its arena with its first operation and never again.
+///
+/// This is meant for logs produced outside of this crate; the error points at the first byte that
+/// is malformed, or at the tag of the first operation that cannot be replayed. It does not check
+/// that the keys in the log refer to live priorities, since those depend on the priority type that
+/// the log is replayed with.
+pub fn validate_snapshot(bytes: &[u8]) -> Result<(), DecodeError> {
+    OpLog::check_header(bytes)?;
+    let ops = decode_ops_at(bytes, MAGIC.len() + 1)?;
+    let creates = |op: &Op| matches!(op, Op::New | Op::NewSequence(_));
+    match ops.split_first() {
+        Some((first, _)) if !creates(&first.1) => Err(DecodeError { offset: first.0 }),
+        Some((_, rest)) => match rest.iter().find(|(_, op)| creates(op)) {
+            Some(&(offset, _)) => Err(DecodeError { offset }),
+            None => Ok(()),
+        },
+        None => Err(DecodeError {
+            offset: bytes.len(),
+        }),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn encoding_round_trip() {
+        let mut log = OpLog::default();
+        for op in [Op::New, Op::Insert(0), Op::Clone(300), Op::Drop(1)] {
+            log.push(op);
+        }
+        let bytes = log.to_bytes();
+        assert_eq!(bytes[..5], *b"OMLG\x02");
+        assert_eq!(bytes[5..], [0, 2, 0, 3, 0xac, 0x02, 4, 1]);
+        assert_eq!(OpLog::format_version(&bytes), Some(2));
+        assert_eq!(OpLog::from_bytes(&bytes).unwrap(), log);
+    }
+
+    #[test]
+    fn encoding_two_operands() {
+        let mut log = OpLog::default();
+        for op in [Op::New, Op::InsertN(0, 300)] {
+            log.push(op);
+        }
+        let bytes = log.to_bytes();
+        assert_eq!(bytes[5..], [0, 8, 0, 0xac, 0x02]);
+        assert_eq!(OpLog::from_bytes(&bytes).unwrap(), log);
+        assert_eq!(
+            OpLog::from_bytes(&bytes[..8]),
+            Err(DecodeError { offset: 8 })
+        );
+    }
+
+    #[test]
+    fn migrate_versions() {
+        let mut log = OpLog::default();
+        for op in [Op::New, Op::Insert(0), Op::Clone(300), Op::Drop(1)] {
+            log.push(op);
+        }
+        let v0 = [0, 2, 0, 3, 0xac, 0x02, 4, 1];
+        assert_eq!(OpLog::format_version(&v0), None);
+        assert_eq!(OpLog::from_bytes(&v0), Err(DecodeError { offset: 0 }));
+        assert_eq!(OpLog::migrate(&v0).unwrap(), log);
+        assert_eq!(OpLog::migrate(&log.to_bytes()).unwrap(), log);
+
+        let v1 = [b"OMLG\x01".as_slice(), &v0].concat();
+        assert_eq!(OpLog::format_version(&v1), Some(1));
+        assert_eq!(OpLog::from_bytes(&v1), Err(DecodeError { offset: 4 }));
+        assert_eq!(OpLog::migrate(&v1).unwrap(), log);
+
+        let v3 = b"OMLG\x03";
+        assert_eq!(OpLog::format_version(v3), Some(3));
+        assert_eq!(OpLog::migrate(v3), Err(DecodeError { offset: 4 }));
+    }
+
+    #[test]
+    fn malformed_logs() {
+        assert_eq!(
+            OpLog::from_bytes(b"OMLG\x02\x02"),
+            Err(DecodeError { offset: 6 })
+        );
+        assert_eq!(
+            OpLog::from_bytes(b"OMLG\x02\x00\x0b\x01"),
+            Err(DecodeError { offset: 6 })
+        );
+    }
+
+    #[test]
+    fn overlong_varints() {
+        let mut bytes = b"OMLG\x02\x00\x02".to_vec();
+        push_varint(&mut bytes, usize::MAX);
+        let last = bytes.len() - 1;
+        let log = OpLog::from_bytes(&bytes).unwrap();
+        assert_eq!(log.ops(), [Op::New, Op::Insert(usize::MAX)]);
+
+        // Setting a bit beyond the width of `usize` in the last byte must not wrap around.
+        bytes[last] |= 0x02;
+        assert_eq!(OpLog::from_bytes(&bytes), Err(DecodeError { offset: last }));
+        assert_eq!(validate_snapshot(&bytes), Err(DecodeError { offset: last }));
+
+        // Neither may continuing past the width of `usize`, even with zero bits.
+        bytes[last] = 0x80 | 0x01;
+        bytes.push(0x00);
+        assert_eq!(
+            OpLog::from_bytes(&bytes),
+            Err(DecodeError { offset: last + 1 })
+        );
+    }
+
+    #[test]
+    fn validate_snapshots() {
+        assert_eq!(validate_snapshot(b"OMLG\x02\x01\x03\x02\x00"), Ok(()));
+        assert_eq!(
+            validate_snapshot(b"OMLG\x00"),
+            Err(DecodeError { offset: 4 })
+        );
+        assert_eq!(
+            validate_snapshot(b"OMLG\x02"),
+            Err(DecodeError { offset: 5 })
+        );
+        assert_eq!(
+            validate_snapshot(b"OMLG\x02\x04\x00"),
+            Err(DecodeError { offset: 5 })
+        );
+        assert_eq!(
+            validate_snapshot(b"OMLG\x02\x00\x02\x00\x00"),
+            Err(DecodeError { offset: 8 })
+        );
+        assert_eq!(
+            validate_snapshot(b"OMLG\x02\x00\x02"),
+            Err(DecodeError { offset: 7 })
+        );
+    }
+}
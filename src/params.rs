@@ -0,0 +1,271 @@
+//! Compile-time parameters for priority implementations.
+//!
+//! These traits are usually implemented by [`define_priority!`](crate::define_priority), rather
+//! than by hand.
+
+/// Parameters shared by all labeling algorithms.
+pub trait Params {
+    /// Number of bits used for labels, between 1 and `usize::BITS`.
+    ///
+    /// Labels range over `0..2^BITS`.
+    const BITS: usize;
+}
+
+/// Parameters for labeling algorithms that relabel according to a density threshold.
+pub trait Capacities: Params {
+    /// Number of thresholds that capacities are available for.
+    const THRESHOLDS: usize;
+
+    /// The number of priorities that fit in a range of `2^level` labels, at the threshold with
+    /// index `threshold`.
+    ///
+    /// Thresholds are ordered from lowest to highest; `level` ranges over `0..BITS`.
+    fn capacity(threshold: usize, level: usize) -> usize;
+
+    /// The threshold with index `threshold`, as a `(numerator, denominator)` pair.
+    fn threshold(threshold: usize) -> (u64, u64);
+
+    /// The greatest number of priorities that fit in the label space, at the threshold with index
+    /// `threshold`.
+    fn max_capacity(threshold: usize) -> usize {
+        Self::capacity(threshold, Self::BITS - 1)
+    }
+
+    /// The threshold with index `threshold` can be used while fewer than this many priorities
+    /// have been inserted.
+    ///
+    /// Breakpoints are non-increasing, since higher thresholds have lower capacities.
+    fn breakpoint(threshold: usize) -> usize {
+        Self::max_capacity(threshold).saturating_sub(1)
+    }
+
+    /// The index of the highest threshold that can be used once `total` priorities have been
+    /// inserted, or `None` if there are too many priorities for any threshold.
+    fn threshold_for(total: usize) -> Option<usize> {
+        // Thresholds below lo can be used, while those at or above hi cannot.
+        let (mut lo, mut hi) = (0, Self::THRESHOLDS);
+        while lo < hi {
+            let mid = lo + (hi - lo) / 2;
+            if total < Self::breakpoint(mid) {
+                lo = mid + 1;
+            } else {
+                hi = mid;
+            }
+        }
+        lo.checked_sub(1)
+    }
+}
+
+/// Compute the capacities for the threshold `t_num / t_den`, for labels with `BITS` bits.
+///
+/// The `level`th capacity is `floor((2 / t)^level)`, the number of priorities that fit in a range
+/// of `2^level` labels when the density threshold is `1 / t^level`. This only uses integer
+/// arithmetic, so it can be used to construct capacity tables in const contexts, e.g.:
+///
+/// ```rust
+/// # use order_maintenance::params::capacities_for_threshold;
+/// const CAPACITIES: [usize; 8] = capacities_for_threshold(5, 4); // t = 1.25
+/// assert_eq!(CAPACITIES, [1, 1, 2, 4, 6, 10, 16, 26]);
+/// ```
+///
+/// Capacities that do not fit in a `usize` saturate at `usize::MAX`.
+///
+/// Panics if `t_num / t_den` is not strictly between 1 and 2.
+pub const fn capacities_for_threshold<const BITS: usize>(t_num: u32, t_den: u32) -> [usize; BITS] {
+    assert!(
+        t_den < t_num && t_num < 2 * t_den,
+        "threshold must be strictly between 1 and 2"
+    );
+
+    // (2 / t)^level is tracked as a fixed-point number with this many fractional bits.
+    const FRAC: u32 = 64;
+    let num = t_num as u128;
+    let den = 2 * t_den as u128;
+
+    let mut capas = [0; BITS];
+    let mut x: u128 = 1 << FRAC;
+    let mut level = 0;
+    while level < BITS {
+        let c = x >> FRAC;
+        capas[level] = if c > usize::MAX as u128 {
+            usize::MAX
+        } else {
+            c as usize
+        };
+
+        // x * (2 / t), split to avoid overflowing the intermediate product.
+        x = match (x / num).checked_mul(den) {
+            Some(hi) => hi.saturating_add(x % num * den / num),
+            None => u128::MAX,
+        };
+        level += 1;
+    }
+    capas
+}
+
+/// Problems with the label width of `P`.
+pub(crate) fn bits_problems<P: Params>() -> Vec<String> {
+    let mut problems = Vec::new();
+    if !(1..=usize::BITS as usize).contains(&P::BITS) {
+        problems.push(format!(
+            "label width of {} bits is not between 1 and {}",
+            P::BITS,
+            usize::BITS
+        ));
+    }
+    problems
+}
+
+/// Problems with the thresholds and capacities of `P`, e.g., tables generated for another target.
+pub(crate) fn capacity_problems<P: Capacities>() -> Vec<String> {
+    let mut problems = bits_problems::<P>();
+    if !problems.is_empty() {
+        return problems;
+    }
+    if P::THRESHOLDS == 0 {
+        problems.push("no thresholds".to_string());
+    }
+    for t in 0..P::THRESHOLDS {
+        let (num, den) = P::threshold(t);
+        if !(den < num && num < 2 * den) {
+            problems.push(format!(
+                "threshold {t} is {num}/{den}, not strictly between 1 and 2"
+            ));
+        }
+        if t > 0 {
+            let (prev_num, prev_den) = P::threshold(t - 1);
+            if u128::from(num) * u128::from(prev_den) <= u128::from(prev_num) * u128::from(den) {
+                problems.push(format!(
+                    "threshold {t} is not greater than threshold {}",
+                    t - 1
+                ));
+            }
+        }
+        if P::capacity(t, 0) != 1 {
+            problems.push(format!("capacity of 1 label at threshold {t} is not 1"));
+        }
+        for level in 1..P::BITS {
+            let capacity = P::capacity(t, level);
+            if capacity < P::capacity(t, level - 1) {
+                problems.push(format!(
+                    "capacities at threshold {t} decrease at level {level}"
+                ));
+            }
+            if level < usize::BITS as usize && capacity > 1 << level {
+                problems.push(format!(
+                    "capacity at threshold {t} and level {level} exceeds the number of labels"
+                ));
+            }
+            if t > 0 && capacity > P::capacity(t - 1, level) {
+                problems.push(format!(
+                    "capacity at threshold {t} and level {level} exceeds that of threshold {}",
+                    t - 1
+                ));
+            }
+        }
+        if P::max_capacity(t) != P::capacity(t, P::BITS - 1) {
+            problems.push(format!("maximum capacity at threshold {t} is inconsistent"));
+        }
+        if P::breakpoint(t) != P::max_capacity(t).saturating_sub(1) {
+            problems.push(format!("breakpoint of threshold {t} is inconsistent"));
+        }
+    }
+    problems
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn capacities_t1_4() {
+        let t1_4: [usize; 64] = [
+            1, 1, 2, 2, 4, 5, 8, 12, 17, 24, 35, 50, 72, 103, 147, 210, 300, 429, 614, 877, 1253,
+            1790, 2557, 3653, 5219, 7456, 10652, 15217, 21739, 31056, 44366, 63381, 90544, 129349,
+            184784, 263978, 377112, 538731, 769616, 1099452, 1570646, 2243780, 3205400, 4579143,
+            6541633, 9345191, 13350273, 19071819, 27245455, 38922079, 55602971, 79432816,
+            113475451, 162107787, 231582554, 330832220, 472617457, 675167795, 964525422,
+            1377893461, 1968419230, 2812027471, 4017182102, 5738831574,
+        ];
+        assert_eq!(t1_4, capacities_for_threshold::<64>(7, 5));
+    }
+
+    #[test]
+    fn capacities_t1_8() {
+        let t1_8: [usize; 64] = [
+            1, 1, 1, 1, 1, 1, 1, 2, 2, 2, 2, 3, 3, 3, 4, 4, 5, 5, 6, 7, 8, 9, 10, 11, 12, 13, 15,
+            17, 19, 21, 23, 26, 29, 32, 35, 39, 44, 49, 54, 60, 67, 75, 83, 92, 103, 114, 127, 141,
+            157, 174, 194, 215, 239, 266, 295, 328, 365, 405, 450, 500, 556, 618, 687, 763,
+        ];
+        assert_eq!(t1_8, capacities_for_threshold::<64>(9, 5));
+    }
+
+    struct Fake;
+
+    impl Params for Fake {
+        const BITS: usize = 8;
+    }
+
+    impl Capacities for Fake {
+        const THRESHOLDS: usize = 3;
+
+        fn capacity(threshold: usize, level: usize) -> usize {
+            const CAPAS: [[usize; 8]; 3] = [
+                capacities_for_threshold(11, 10),
+                capacities_for_threshold(13, 10),
+                capacities_for_threshold(3, 2),
+            ];
+            CAPAS[threshold][level]
+        }
+
+        fn threshold(threshold: usize) -> (u64, u64) {
+            [(11, 10), (13, 10), (3, 2)][threshold]
+        }
+    }
+
+    /// A capacity table whose entries were truncated, as if generated for a wider target.
+    struct Truncated;
+
+    impl Params for Truncated {
+        const BITS: usize = 16;
+    }
+
+    impl Capacities for Truncated {
+        const THRESHOLDS: usize = 1;
+
+        fn capacity(_: usize, level: usize) -> usize {
+            const CAPAS: [usize; 16] = capacities_for_threshold(11, 10);
+            CAPAS[level] as u8 as usize
+        }
+
+        fn threshold(_: usize) -> (u64, u64) {
+            (11, 10)
+        }
+    }
+
+    #[test]
+    fn capacity_problems_found() {
+        assert_eq!(capacity_problems::<Fake>(), Vec::<String>::new());
+        assert!(!capacity_problems::<Truncated>().is_empty());
+    }
+
+    #[test]
+    fn threshold_for_total() {
+        // The highest threshold for a given total, found by scanning.
+        let scan = |total: usize| {
+            (0..Fake::THRESHOLDS)
+                .rev()
+                .find(|&i| total + 1 < Fake::max_capacity(i))
+        };
+        for total in 0..Fake::max_capacity(0) + 2 {
+            assert_eq!(Fake::threshold_for(total), scan(total), "total = {total}");
+        }
+    }
+
+    #[test]
+    fn capacities_saturate() {
+        let capas = capacities_for_threshold::<128>(1001, 1000);
+        assert_eq!(capas[127], usize::MAX);
+        assert!(capas.windows(2).all(|w| w[0] <= w[1]));
+    }
+}
@@ -0,0 +1,126 @@
+//! Identifiers of priorities that survive relabeling and serialization.
+//!
+//! Only available with the `persistent-id` feature. Labels change whenever an arena is relabeled,
+//! so they cannot identify positions outside of the process, e.g., in messages or on disk. A
+//! [`PersistentId`] instead combines an identifier of the arena, which is unique with high
+//! probability even across processes, with the creation index of the priority within its arena.
+//! It never changes, and can be resolved back to a handle by any other handle to the same arena:
+//!
+//! ```rust
+//! # use order_maintenance::{persistent_id::PersistentId, tag_range::Priority, MaintainedOrd};
+//! let a = Priority::new();
+//! let b = a.insert();
+//! let id = b.persistent_id();
+//!
+//! // Relabel everything, then send the identifier somewhere and back.
+//! a.rebalance_all();
+//! let id = PersistentId::from_be_bytes(id.to_be_bytes());
+//! assert!(a.find(id) == Some(b.clone()));
+//!
+//! // Identifiers are not reused once their priorities are gone.
+//! drop(b);
+//! assert!(a.find(id).is_none());
+//! assert!(a.insert().persistent_id() != id);
+//! ```
+//!
+//! Identifiers also key data about priorities that is stored outside of them, in any
+//! [`SecondaryMap`]. Iterating over an arena with such a map zips each priority with its data in
+//! order, in a single pass over the arena rather than by sorting:
+//!
+//! ```rust
+//! # use order_maintenance::{tag_range::Priority, MaintainedOrd};
+//! # use std::collections::HashMap;
+//! let a = Priority::new();
+//! let c = a.insert();
+//! let b = a.insert();
+//! let names = HashMap::from([(c.persistent_id(), "c"), (a.persistent_id(), "a")]);
+//!
+//! let ordered: Vec<_> = b.iter_with(&names).map(|(_, name)| *name).collect();
+//! assert_eq!(ordered, ["a", "c"]);
+//! ```
+use std::collections::{BTreeMap, HashMap};
+use std::fmt::Display;
+use std::hash::BuildHasher;
+
+/// A 128-bit identifier of a priority that is stable for as long as the priority is alive.
+///
+/// The upper 64 bits identify the arena, and the lower 64 bits the priority within it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct PersistentId(u128);
+
+impl PersistentId {
+    /// The identifier of the priority with creation index `index` in the arena `arena`.
+    pub(crate) fn new(arena: u64, index: u64) -> Self {
+        Self(u128::from(arena) << 64 | u128::from(index))
+    }
+
+    /// The identifier of the arena that the priority belongs to.
+    pub fn arena(&self) -> u64 {
+        (self.0 >> 64) as u64
+    }
+
+    /// The creation index of the priority within its arena.
+    pub fn index(&self) -> u64 {
+        self.0 as u64
+    }
+
+    /// The identifier as a single integer.
+    pub fn as_u128(&self) -> u128 {
+        self.0
+    }
+
+    /// The identifier encoded as an integer by [`as_u128()`](Self::as_u128).
+    pub fn from_u128(id: u128) -> Self {
+        Self(id)
+    }
+
+    /// Encode the identifier in big-endian byte order.
+    pub fn to_be_bytes(&self) -> [u8; 16] {
+        self.0.to_be_bytes()
+    }
+
+    /// Decode an identifier encoded by [`to_be_bytes()`](Self::to_be_bytes).
+    pub fn from_be_bytes(bytes: [u8; 16]) -> Self {
+        Self(u128::from_be_bytes(bytes))
+    }
+}
+
+impl Display for PersistentId {
+    /// Formats the identifier as 32 hexadecimal digits.
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{:032x}", self.0)
+    }
+}
+
+/// Storage of data of type `T` about priorities, keyed by their [`PersistentId`]s.
+pub trait SecondaryMap<T> {
+    /// The data about the priority with the identifier `id`, if any.
+    fn get_by_id(&self, id: &PersistentId) -> Option<&T>;
+}
+
+impl<T, S: BuildHasher> SecondaryMap<T> for HashMap<PersistentId, T, S> {
+    fn get_by_id(&self, id: &PersistentId) -> Option<&T> {
+        self.get(id)
+    }
+}
+
+impl<T> SecondaryMap<T> for BTreeMap<PersistentId, T> {
+    fn get_by_id(&self, id: &PersistentId) -> Option<&T> {
+        self.get(id)
+    }
+}
+
+/// A new identifier for an arena, which is unique with high probability.
+///
+/// Mixes a per-process counter into a hash that is keyed randomly for each process, so
+/// identifiers differ both within a process and across processes.
+pub(crate) fn fresh_arena_uuid() -> u64 {
+    use std::collections::hash_map::RandomState;
+    use std::hash::Hasher;
+    use std::sync::atomic::{AtomicU64, Ordering};
+
+    static NEXT: AtomicU64 = AtomicU64::new(0);
+    let mut hasher = RandomState::new().build_hasher();
+    hasher.write_u64(NEXT.fetch_add(1, Ordering::Relaxed));
+    hasher.finish()
+}
@@ -0,0 +1,285 @@
+//! Pluggable relabeling policies.
+//!
+//! A [`RelabelPolicy`] decides which priorities to relabel to make room for an insertion, and how
+//! to distribute labels among them; [`CustomPriority`] takes care of everything else, i.e.,
+//! arenas, reference counting and comparisons. The algorithms of [tag-range](crate::tag_range)
+//! and [list-range](crate::list_range) priorities are available as [`TagRangePolicy`] and
+//! [`ListRangePolicy`], but any other policy can be plugged in:
+//!
+//! ```rust
+//! # use order_maintenance::{params::Params, policy::{Cursor, CustomPriority, RelabelPolicy}};
+//! # use order_maintenance::MaintainedOrd;
+//! /// Relabels every priority evenly whenever there is no room left.
+//! struct Naive;
+//!
+//! impl Params for Naive {
+//!     const BITS: usize = 16;
+//! }
+//!
+//! impl RelabelPolicy for Naive {
+//!     fn relabel(at: &mut Cursor<'_>) {
+//!         let gap = at.next_label().wrapping_sub(at.label()) & 0xffff;
+//!         if gap == 1 {
+//!             let step = 0x10000 / (at.total() + 1);
+//!             while !at.is_base() {
+//!                 at.move_next();
+//!             }
+//!             for i in 1..at.total() {
+//!                 at.move_next();
+//!                 at.set_label(i * step);
+//!             }
+//!             at.record_relabel(at.total() - 1);
+//!         }
+//!     }
+//!
+//!     fn next_label(at: &Cursor<'_>) -> usize {
+//!         let gap = at.next_label().wrapping_sub(at.label()) & 0xffff;
+//!         at.label() + (gap + 1) / 2
+//!     }
+//! }
+//!
+//! let a = CustomPriority::<Naive>::new();
+//! let mut ps = vec![a.insert()];
+//! for _ in 0..100 {
+//!     let p = ps[0].insert();
+//!     ps.push(p);
+//! }
+//! assert!(a < ps[0]);
+//! assert!(ps[1..].windows(2).all(|w| w[0] > w[1]));
+//! ```
+use crate::internal::{Arena, Label, PriorityKey, PriorityRef};
+use crate::params::{Capacities, Params};
+use crate::{InArena, MaintainedOrd};
+use std::{cmp::Ordering, fmt::Debug, marker::PhantomData};
+
+/// A cursor into the circular list of priorities in an arena, given to a [`RelabelPolicy`].
+///
+/// The list starts at the arena's base priority, which precedes every other priority, and wraps
+/// around from the last priority back to the base.
+pub struct Cursor<'a> {
+    pub(crate) arena: &'a mut Arena,
+    pub(crate) at: PriorityKey,
+    pub(crate) arena_id: usize,
+}
+
+/// A position in the list of priorities, saved by [`Cursor::mark()`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Mark(PriorityKey);
+
+impl<'a> Cursor<'a> {
+    /// Construct a cursor at the priority with key `at`, in the arena with the given identifier.
+    pub(crate) fn new(arena: &'a mut Arena, at: PriorityKey, arena_id: usize) -> Self {
+        Self {
+            arena,
+            at,
+            arena_id,
+        }
+    }
+
+    /// The label of the priority at the cursor.
+    pub fn label(&self) -> usize {
+        self.at.as_ref(self.arena).label().into()
+    }
+
+    /// Set the label of the priority at the cursor.
+    pub fn set_label(&mut self, label: usize) {
+        self.at.as_ref(self.arena).set_label(Label::new(label));
+        self.arena.note_relabeled(self.at, 1);
+    }
+
+    /// The label of the priority after the cursor.
+    pub fn next_label(&self) -> usize {
+        let next = self.at.as_ref(self.arena).next();
+        next.as_ref(self.arena).label().into()
+    }
+
+    /// The label of the priority before the cursor.
+    pub fn prev_label(&self) -> usize {
+        let prev = self.at.as_ref(self.arena).prev();
+        prev.as_ref(self.arena).label().into()
+    }
+
+    /// Move the cursor to the next priority.
+    pub fn move_next(&mut self) {
+        self.at = self.at.as_ref(self.arena).next();
+    }
+
+    /// Move the cursor to the previous priority.
+    pub fn move_prev(&mut self) {
+        self.at = self.at.as_ref(self.arena).prev();
+    }
+
+    /// Whether the cursor is at the base priority of the arena.
+    pub fn is_base(&self) -> bool {
+        self.at == self.arena.base()
+    }
+
+    /// Number of priorities in the arena, including its base priority.
+    pub fn total(&self) -> usize {
+        self.arena.total()
+    }
+
+    /// Save the position of the cursor, to return to it with [`seek()`](Self::seek).
+    pub fn mark(&self) -> Mark {
+        Mark(self.at)
+    }
+
+    /// Move the cursor to a position saved by [`mark()`](Self::mark).
+    pub fn seek(&mut self, mark: Mark) {
+        self.at = mark.0;
+    }
+
+    /// Record a relabeling pass over `size` priorities, advancing the arena's label epoch.
+    pub fn record_relabel(&mut self, size: usize) {
+        self.arena.record_relabel(size);
+    }
+}
+
+/// A policy for relabeling priorities to make room for insertions.
+pub trait RelabelPolicy: Params {
+    /// Label of the first priority in a new arena.
+    ///
+    /// The arena's base priority is labeled 0, and precedes every other priority.
+    fn first_label() -> usize {
+        usize::from(Label::max_for_bits(Self::BITS)) / 2
+    }
+
+    /// Relabel priorities as necessary to make room for a new priority after the one at `at`.
+    ///
+    /// Priorities are ordered by their labels relative to the base label, modulo `2^BITS`; that
+    /// order must be preserved. The cursor may be left anywhere.
+    fn relabel(at: &mut Cursor<'_>);
+
+    /// The label of a new priority inserted after the one at `at`, once [`relabel()`] made room.
+    ///
+    /// [`relabel()`]: Self::relabel
+    fn next_label(at: &Cursor<'_>) -> usize;
+}
+
+/// The relabeling policy of [tag-range priorities](crate::tag_range::GenericPriority).
+#[derive(Debug)]
+pub struct TagRangePolicy<P>(PhantomData<P>);
+
+impl<P: Capacities> Params for TagRangePolicy<P> {
+    const BITS: usize = P::BITS;
+}
+
+impl<P: Capacities> RelabelPolicy for TagRangePolicy<P> {
+    fn relabel(at: &mut Cursor<'_>) {
+        crate::tag_range::GenericPriority::<P>::relabel(at)
+    }
+
+    fn next_label(at: &Cursor<'_>) -> usize {
+        crate::tag_range::GenericPriority::<P>::next_label(at).into()
+    }
+}
+
+/// The relabeling policy of [list-range priorities](crate::list_range::GenericPriority).
+#[derive(Debug)]
+pub struct ListRangePolicy<P>(PhantomData<P>);
+
+impl<P: Params> Params for ListRangePolicy<P> {
+    const BITS: usize = P::BITS;
+}
+
+impl<P: Params> RelabelPolicy for ListRangePolicy<P> {
+    fn relabel(at: &mut Cursor<'_>) {
+        crate::list_range::GenericPriority::<P>::relabel(at)
+    }
+
+    fn next_label(at: &Cursor<'_>) -> usize {
+        crate::list_range::GenericPriority::<P>::next_label(at).into()
+    }
+}
+
+/// Priorities that are relabeled according to the policy `R`.
+pub struct CustomPriority<R>(PriorityRef, PhantomData<R>);
+
+impl<R: RelabelPolicy> CustomPriority<R> {
+    /// Mask of the bits used by labels of this priority type; label arithmetic wraps around it.
+    const MASK: Label = Label::max_for_bits(R::BITS);
+
+    fn relative(&self) -> Label {
+        (self.0.label() - self.0.base_label()) & Self::MASK
+    }
+}
+
+/// Shows the arena that the priority belongs to, its current label, and its rank in the arena.
+///
+/// Computing the rank takes time linear in the number of priorities in the arena.
+impl<R> Debug for CustomPriority<R> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Priority")
+            .field("arena", &format_args!("{:#x}", self.0.arena_id()))
+            .field("label", &usize::from(self.0.label()))
+            // The base priority is not handed out, so it does not count towards the rank.
+            .field("rank", &(self.0.position() - 1))
+            .finish()
+    }
+}
+
+impl<R> Clone for CustomPriority<R> {
+    fn clone(&self) -> Self {
+        Self(self.0.clone(), PhantomData)
+    }
+}
+
+impl<R> PartialEq for CustomPriority<R> {
+    fn eq(&self, other: &Self) -> bool {
+        self.0 == other.0
+    }
+}
+
+impl<R> Eq for CustomPriority<R> {}
+
+impl<R: RelabelPolicy> PartialOrd for CustomPriority<R> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        if !self.0.same_arena(&other.0) {
+            self.0.cross_arena_cmp(&other.0)
+        } else if self.0 == other.0 {
+            Some(Ordering::Equal)
+        } else {
+            self.relative().partial_cmp(&other.relative())
+        }
+    }
+}
+
+impl<R: RelabelPolicy> MaintainedOrd for CustomPriority<R> {
+    fn new() -> Self {
+        let mut arena = Arena::new();
+        let first = Label::new(R::first_label()) & Self::MASK;
+        let this = arena.insert_after(first, arena.base());
+        Self(PriorityRef::new(arena, this), PhantomData)
+    }
+
+    fn insert(&self) -> Self {
+        Self(
+            self.0.insert(|at| {
+                let this = at.mark();
+                R::relabel(at);
+                at.seek(this);
+                Label::new(R::next_label(at)) & Self::MASK
+            }),
+            PhantomData,
+        )
+    }
+}
+
+impl<R> crate::capability::ConstantTimeCompare for CustomPriority<R> {}
+
+impl<R: RelabelPolicy> Default for CustomPriority<R> {
+    /// Construct the first priority in a new arena, like [`MaintainedOrd::new()`].
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<R: RelabelPolicy> InArena for CustomPriority<R> {
+    fn arena_priorities(&self) -> Vec<Self> {
+        self.0
+            .arena_handles(true)
+            .into_iter()
+            .map(|p| Self(p, PhantomData))
+            .collect()
+    }
+}
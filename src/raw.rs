@@ -0,0 +1,111 @@
+//! Low-level access to the arenas that priorities are allocated in.
+//!
+//! An arena is a reference-counted, circular doubly-linked list of labeled nodes, starting from a
+//! base node that lives as long as the arena. A [`RawPriority`] is a handle to one node; nodes are
+//! freed once their last handle is dropped. This module takes care of that memory management, but
+//! leaves labeling entirely to its users: nothing here checks that labels respect the list order,
+//! so comparing raw labels is only meaningful if the labeling algorithm built on top maintains it.
+//!
+//! Mislabeling cannot cause undefined behavior, only wrong comparisons, so this API is safe, but
+//! it is unchecked; [`RawPriority::check_invariants()`] can be used to validate a labeling
+//! algorithm. For a higher-level extension point, see [`policy`](crate::policy).
+//!
+//! ```rust
+//! # use order_maintenance::raw::RawPriority;
+//! let first = RawPriority::new(8);
+//! // Label each new node halfway between its predecessor and successor.
+//! let second = first.insert_with(|at| {
+//!     let next = if at.next_label() == 0 { 16 } else { at.next_label() };
+//!     (at.label() + next) / 2
+//! });
+//! assert_eq!((first.base_label(), first.label(), second.label()), (0, 8, 12));
+//! assert_eq!(second.position(), 2);
+//! assert!(second.check_invariants(4).is_ok());
+//! ```
+use crate::check::Violation;
+use crate::internal::{Arena, Label, PriorityRef};
+use std::fmt::Debug;
+
+pub use crate::policy::{Cursor, Mark};
+
+/// Handle to a node in an arena, whose label is managed by the user.
+///
+/// Handles are compared by identity: two handles are equal if they refer to the same node.
+#[derive(Clone, PartialEq, Eq)]
+pub struct RawPriority(PriorityRef);
+
+impl RawPriority {
+    /// Construct a new arena, with a base node labeled 0, and a node labeled `label` after it.
+    ///
+    /// Returns a handle to the latter.
+    pub fn new(label: usize) -> Self {
+        Self::with_capacity(label, 0)
+    }
+
+    /// Like [`new()`](Self::new), but reserves room for `capacity` nodes in the arena.
+    pub fn with_capacity(label: usize, capacity: usize) -> Self {
+        let mut arena = Arena::with_capacity(capacity);
+        let this = arena.insert_after(Label::new(label), arena.base());
+        Self(PriorityRef::new(arena, this))
+    }
+
+    /// Insert a new node after this one, returning a handle to it.
+    ///
+    /// The callback `f` is given a cursor at this node, and may relabel any nodes through it
+    /// before returning the label of the new node. The arena is borrowed throughout, so `f` must
+    /// not use any other handles to it.
+    pub fn insert_with(&self, f: impl FnOnce(&mut Cursor<'_>) -> usize) -> Self {
+        Self(self.0.insert(|at| Label::new(f(at))))
+    }
+
+    /// The current label of this node.
+    pub fn label(&self) -> usize {
+        self.0.label().into()
+    }
+
+    /// The number of relabeling passes recorded in this node's arena.
+    ///
+    /// Passes are recorded through [`Cursor::record_relabel()`].
+    pub fn label_epoch(&self) -> crate::types::Epoch {
+        crate::types::Epoch::new(self.0.label_with_epoch().1)
+    }
+
+    /// The current label of the base node of this node's arena.
+    pub fn base_label(&self) -> usize {
+        self.0.base_label().into()
+    }
+
+    /// Whether this node is in the same arena as another.
+    pub fn same_arena(&self, other: &Self) -> bool {
+        self.0.same_arena(&other.0)
+    }
+
+    /// Identifier of this node's arena, unique among arenas that are alive.
+    pub fn arena_id(&self) -> usize {
+        self.0.arena_id()
+    }
+
+    /// Position of this node in the arena's list, where the base node is at position 0.
+    ///
+    /// This walks the list from the base, so it takes time linear in the position.
+    pub fn position(&self) -> usize {
+        self.0.position()
+    }
+
+    /// Check the invariants of this node's arena, for labels of `bits` bits.
+    ///
+    /// See [`check`](crate::check) for details.
+    pub fn check_invariants(&self, bits: usize) -> Result<(), Violation> {
+        self.0.check(Label::max_for_bits(bits))
+    }
+}
+
+/// Shows the arena that the node belongs to, and its current label.
+impl Debug for RawPriority {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("RawPriority")
+            .field("arena", &format_args!("{:#x}", self.arena_id()))
+            .field("label", &self.label())
+            .finish()
+    }
+}
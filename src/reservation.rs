@@ -0,0 +1,91 @@
+//! Two-phase insertion: reserving a position now, and inserting into it later.
+//!
+//! Inserting a priority may have to relabel others first, which takes time. A [`Reservation`]
+//! does that work up front, e.g., while a transaction is being validated, and only remembers a
+//! free label right after its anchor. Committing the reservation then inserts a priority with
+//! that label, without relabeling anything, as long as the label is still free:
+//!
+//! ```rust
+//! # use order_maintenance::{tag_range::Priority, MaintainedOrd};
+//! let a = Priority::new();
+//! let b = a.insert();
+//!
+//! let reservation = a.reserve_after();
+//! let epoch = a.label_epoch();
+//! let p = reservation.commit();
+//! assert!(a < p && p < b);
+//! assert_eq!(a.label_epoch(), epoch);
+//! ```
+//!
+//! Reservations do not hold on to their labels: inserting into the same gap, or relabeling the
+//! arena, before a reservation is committed may take its label, in which case committing falls
+//! back to an ordinary insertion after the anchor. Aborting a reservation frees nothing, since
+//! nothing was allocated for it.
+//!
+//! A [`Region`] does hold on to labels, for a burst of insertions that is known to be coming: it
+//! keeps labels for a given number of priorities right after its anchor free, until it is
+//! released.
+use crate::internal::{Label, PriorityRef};
+
+/// A position right after a priority of type `P`, prepared for inserting a priority into.
+///
+/// Reservations are made by, e.g.,
+/// [`tag_range::GenericPriority::reserve_after()`](crate::tag_range::GenericPriority::reserve_after).
+#[derive(Debug, Clone)]
+pub struct Reservation<P> {
+    /// The priority that the reserved position follows.
+    pub(crate) anchor: P,
+    /// The free label that was found right after the anchor.
+    pub(crate) label: Label,
+    /// The label epoch of the anchor's arena when the label was found.
+    pub(crate) epoch: u64,
+}
+
+impl<P> Reservation<P> {
+    /// The priority that the reserved position follows.
+    pub fn anchor(&self) -> &P {
+        &self.anchor
+    }
+
+    /// Give up on the reservation, without inserting anything.
+    pub fn abort(self) {}
+}
+
+/// A region of labels reserved right after a priority of type `P`, kept free for insertions
+/// after it.
+///
+/// Regions are reserved by, e.g.,
+/// [`tag_range::GenericPriority::reserve_region_after()`](crate::tag_range::GenericPriority::reserve_region_after).
+/// Until a region is released, relabeling passes in its arena lay out the other priorities around
+/// it, rather than spreading them into it; insertions right after the anchor take labels from it
+/// as usual. Dropping a region releases it.
+#[derive(Debug)]
+pub struct Region<P> {
+    /// The priority that the region follows.
+    pub(crate) anchor: P,
+    /// Handle to the anchor's priority in its arena, through which the region is released.
+    pub(crate) handle: PriorityRef,
+    /// Number of priorities that relabeling passes leave room for in the region.
+    pub(crate) room: usize,
+}
+
+impl<P> Region<P> {
+    /// The priority that the region follows.
+    pub fn anchor(&self) -> &P {
+        &self.anchor
+    }
+
+    /// Number of priorities that relabeling passes leave room for in the region.
+    pub fn room(&self) -> usize {
+        self.room
+    }
+
+    /// Release the region, so that relabeling passes may spread priorities into it again.
+    pub fn release(self) {}
+}
+
+impl<P> Drop for Region<P> {
+    fn drop(&mut self) {
+        self.handle.release_region(self.room);
+    }
+}
@@ -0,0 +1,73 @@
+//! Sentinel values that bound every priority.
+//!
+//! Algorithms often need guard values that compare below or above every priority, e.g., the ends
+//! of a sweep line. Since priorities live in arenas, they cannot be created in const contexts, and
+//! extreme priorities fabricated by hand must be carefully kept at the ends of their arena.
+//! A [`Bounded`] priority is either a priority, or one of two sentinels that need no arena:
+//!
+//! ```rust
+//! # use order_maintenance::{sentinel::Bounded, tag_range::Priority, MaintainedOrd};
+//! const LO: Bounded<Priority> = Priority::MIN_SENTINEL;
+//! const HI: Bounded<Priority> = Priority::MAX_SENTINEL;
+//!
+//! let a = Bounded::from(Priority::new());
+//! let b = Bounded::from(Priority::new());
+//! assert!(LO < a && a < HI);
+//! // Sentinels are comparable with priorities of every arena.
+//! assert!(LO < b && b < HI);
+//! assert_eq!(a.partial_cmp(&b), None);
+//! ```
+use std::cmp::Ordering;
+
+/// A priority of type `P`, or a sentinel that is less or greater than every priority.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum Bounded<P> {
+    /// Less than every priority.
+    Min,
+    /// A priority.
+    Priority(P),
+    /// Greater than every priority.
+    Max,
+}
+
+impl<P> Bounded<P> {
+    /// The priority, if this is not a sentinel.
+    pub fn priority(&self) -> Option<&P> {
+        match self {
+            Bounded::Priority(p) => Some(p),
+            _ => None,
+        }
+    }
+
+    /// The priority, if this is not a sentinel.
+    pub fn into_priority(self) -> Option<P> {
+        match self {
+            Bounded::Priority(p) => Some(p),
+            _ => None,
+        }
+    }
+
+    /// Whether this is one of the sentinels.
+    pub fn is_sentinel(&self) -> bool {
+        !matches!(self, Bounded::Priority(_))
+    }
+}
+
+impl<P> From<P> for Bounded<P> {
+    fn from(p: P) -> Self {
+        Bounded::Priority(p)
+    }
+}
+
+/// Sentinels are equal to themselves, and comparable with every priority.
+impl<P: PartialOrd> PartialOrd for Bounded<P> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        use Bounded::*;
+        match (self, other) {
+            (Priority(p), Priority(q)) => p.partial_cmp(q),
+            (Min, Min) | (Max, Max) => Some(Ordering::Equal),
+            (Min, _) | (_, Max) => Some(Ordering::Less),
+            (Max, _) | (_, Min) => Some(Ordering::Greater),
+        }
+    }
+}
@@ -0,0 +1,164 @@
+//! Statistics about relabeling, collected per arena.
+//!
+//! Only available with the `stats` feature. Each arena keeps its statistics since it was
+//! constructed, or since they were last reset, as well as a window of statistics since they were
+//! last polled. Polling suits metrics exporters, which can compute rates from each window without
+//! keeping deltas of their own:
+//!
+//! ```rust
+//! # use order_maintenance::{tag_range::Priority, MaintainedOrd};
+//! let a = Priority::new();
+//! let ps: Vec<_> = (0..100).map(|_| a.insert()).collect();
+//! let first = a.poll_relabel_stats();
+//! assert_eq!(first, a.relabel_stats());
+//!
+//! let more: Vec<_> = (0..100).map(|_| a.insert()).collect();
+//! let second = a.poll_relabel_stats();
+//! assert_eq!(first.passes() + second.passes(), a.relabel_stats().passes());
+//! # drop((ps, more));
+//! ```
+
+/// Number of buckets in a [`RelabelStats`] histogram, one for each power of two.
+const BUCKETS: usize = usize::BITS as usize;
+
+/// Distribution of the sizes of relabeling passes in an arena.
+///
+/// The size of a pass is the number of priorities whose labels it changed. Sizes are kept in a
+/// histogram of power-of-two buckets: bucket `i` counts passes of sizes in `2^i..2^(i+1)`, except
+/// that bucket 0 also counts passes of size 0. Percentiles are thus approximate, but never more
+/// than twice the true value; the maximum is exact.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RelabelStats {
+    buckets: [u64; BUCKETS],
+    passes: u64,
+    relabeled: u64,
+    max: usize,
+}
+
+impl Default for RelabelStats {
+    fn default() -> Self {
+        Self {
+            buckets: [0; BUCKETS],
+            passes: 0,
+            relabeled: 0,
+            max: 0,
+        }
+    }
+}
+
+impl RelabelStats {
+    /// Record a relabeling pass over `size` priorities.
+    pub(crate) fn record(&mut self, size: usize) {
+        self.buckets[Self::bucket(size)] += 1;
+        self.passes += 1;
+        self.relabeled += size as u64;
+        self.max = self.max.max(size);
+    }
+
+    /// Forget every relabeling pass recorded so far.
+    pub fn reset(&mut self) {
+        *self = Self::default();
+    }
+
+    /// The bucket that passes of `size` are counted in.
+    fn bucket(size: usize) -> usize {
+        (usize::BITS - size.max(1).leading_zeros() - 1) as usize
+    }
+
+    /// Number of relabeling passes.
+    pub fn passes(&self) -> u64 {
+        self.passes
+    }
+
+    /// Total number of priorities relabeled, over all passes.
+    pub fn relabeled(&self) -> u64 {
+        self.relabeled
+    }
+
+    /// Size of the largest relabeling pass, or 0 if there were none.
+    pub fn max(&self) -> usize {
+        self.max
+    }
+
+    /// The histogram of pass sizes, where the `i`th count is of passes of sizes in `2^i..2^(i+1)`.
+    pub fn buckets(&self) -> &[u64] {
+        &self.buckets
+    }
+
+    /// An upper bound on the `p`th percentile of pass sizes, for `p` between 0 and 100.
+    ///
+    /// This is the greatest size in the bucket containing the percentile, capped at [`max()`],
+    /// or 0 if there were no passes.
+    ///
+    /// [`max()`]: Self::max
+    pub fn percentile(&self, p: f64) -> usize {
+        assert!((0.0..=100.0).contains(&p), "percentile out of range: {p}");
+        if self.passes == 0 {
+            return 0;
+        }
+        let rank = ((p / 100.0 * self.passes as f64).ceil() as u64).max(1);
+        let mut seen = 0;
+        for (i, &count) in self.buckets.iter().enumerate() {
+            seen += count;
+            if seen >= rank {
+                let upper = if i + 1 >= BUCKETS {
+                    usize::MAX
+                } else {
+                    (1 << (i + 1)) - 1
+                };
+                return upper.min(self.max);
+            }
+        }
+        self.max
+    }
+
+    /// An upper bound on the median pass size; see [`percentile()`](Self::percentile).
+    pub fn p50(&self) -> usize {
+        self.percentile(50.0)
+    }
+
+    /// An upper bound on the 95th percentile of pass sizes; see [`percentile()`](Self::percentile).
+    pub fn p95(&self) -> usize {
+        self.percentile(95.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn empty_stats() {
+        let stats = RelabelStats::default();
+        assert_eq!(stats.passes(), 0);
+        assert_eq!(stats.p50(), 0);
+        assert_eq!(stats.max(), 0);
+    }
+
+    #[test]
+    fn percentiles() {
+        let mut stats = RelabelStats::default();
+        for _ in 0..90 {
+            stats.record(3);
+        }
+        for _ in 0..10 {
+            stats.record(100);
+        }
+        assert_eq!(stats.passes(), 100);
+        assert_eq!(stats.relabeled(), 90 * 3 + 10 * 100);
+        assert_eq!(stats.buckets()[1], 90);
+        assert_eq!(stats.buckets()[6], 10);
+        assert_eq!(stats.p50(), 3);
+        assert_eq!(stats.percentile(90.0), 3);
+        assert_eq!(stats.p95(), 100);
+        assert_eq!(stats.max(), 100);
+    }
+
+    #[test]
+    fn reset() {
+        let mut stats = RelabelStats::default();
+        stats.record(100);
+        stats.reset();
+        assert_eq!(stats, RelabelStats::default());
+    }
+}
@@ -0,0 +1,150 @@
+//! Priorities whose relabeling algorithm can be switched at runtime.
+//!
+//! Every arena of [`SwitchablePriority`]s is relabeled with one [`Algorithm`] at a time, using the
+//! default parameters of that algorithm. [`switch_algorithm()`](SwitchablePriority::switch_algorithm)
+//! changes it in place: it spreads the labels of the arena evenly, which suits either algorithm,
+//! and relabels with the new algorithm from then on. The arena keeps its priorities, so every
+//! handle to them stays valid, and none of them changes order:
+//!
+//! ```rust
+//! # use order_maintenance::{switchable::SwitchablePriority, workload::Algorithm, MaintainedOrd};
+//! let a = SwitchablePriority::with_algorithm(Algorithm::ListRange);
+//! let c = a.insert();
+//! let b = a.insert();
+//!
+//! a.switch_algorithm(Algorithm::TagRange);
+//! assert_eq!(b.algorithm(), Algorithm::TagRange);
+//! assert!(a < b && b < c);
+//! let d = c.insert();
+//! assert!(c < d);
+//! ```
+//!
+//! Switching takes time linear in the number of priorities in the arena, like
+//! [`tag_range::GenericPriority::rebalance_all()`](crate::tag_range::GenericPriority::rebalance_all).
+use crate::internal::{Arena, Label, PriorityRef};
+use crate::workload::Algorithm;
+use crate::{list_range, tag_range, InArena, MaintainedOrd};
+use std::{cmp::Ordering, fmt::Debug};
+
+/// A priority that is relabeled with the [`Algorithm`] of its arena.
+///
+/// Its arena has a base priority that is not handed out, like that of
+/// [list-range priorities](list_range::GenericPriority), which both algorithms maintain labels
+/// relative to.
+pub struct SwitchablePriority(PriorityRef);
+
+impl SwitchablePriority {
+    /// Mask of the bits used by labels of this priority type; label arithmetic wraps around it.
+    const MASK: Label = Label::max_for_bits(usize::BITS as usize);
+
+    fn relative(&self) -> Label {
+        (self.0.label() - self.0.base_label()) & Self::MASK
+    }
+
+    /// Construct the first priority in a new arena that is relabeled with `algorithm`.
+    pub fn with_algorithm(algorithm: Algorithm) -> Self {
+        let mut arena = Arena::new();
+        let this = arena.insert_after(Self::MASK / 2, arena.base());
+        let p = Self(PriorityRef::new(arena, this));
+        p.0.set_algorithm(algorithm);
+        p
+    }
+
+    /// The algorithm that this priority's arena is currently relabeled with.
+    pub fn algorithm(&self) -> Algorithm {
+        self.0.algorithm()
+    }
+
+    /// Relabel this priority's arena with `algorithm` from now on; returns the number of
+    /// priorities relabeled to prepare for it.
+    ///
+    /// This is a no-op if the arena is already relabeled with `algorithm`. Otherwise, labels are
+    /// spread evenly across the label space, keeping the order of every priority.
+    pub fn switch_algorithm(&self, algorithm: Algorithm) -> usize {
+        if self.algorithm() == algorithm {
+            return 0;
+        }
+        let relabeled = self.0.rebalance(true, Self::MASK);
+        self.0.set_algorithm(algorithm);
+        relabeled
+    }
+}
+
+/// Shows the arena that the priority belongs to, its algorithm, its current label, and its rank
+/// in the arena.
+///
+/// Computing the rank takes time linear in the number of priorities in the arena.
+impl Debug for SwitchablePriority {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Priority")
+            .field("arena", &format_args!("{:#x}", self.0.arena_id()))
+            .field("algorithm", &self.algorithm())
+            .field("label", &usize::from(self.0.label()))
+            // The base priority is not handed out, so it does not count towards the rank.
+            .field("rank", &(self.0.position() - 1))
+            .finish()
+    }
+}
+
+impl Clone for SwitchablePriority {
+    fn clone(&self) -> Self {
+        Self(self.0.clone())
+    }
+}
+
+impl PartialEq for SwitchablePriority {
+    fn eq(&self, other: &Self) -> bool {
+        self.0 == other.0
+    }
+}
+
+impl Eq for SwitchablePriority {}
+
+impl PartialOrd for SwitchablePriority {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        if !self.0.same_arena(&other.0) {
+            self.0.cross_arena_cmp(&other.0)
+        } else if self.0 == other.0 {
+            Some(Ordering::Equal)
+        } else {
+            self.relative().partial_cmp(&other.relative())
+        }
+    }
+}
+
+impl MaintainedOrd for SwitchablePriority {
+    /// Construct the first priority in a new arena that is relabeled with
+    /// [`Algorithm::TagRange`].
+    fn new() -> Self {
+        Self::with_algorithm(Algorithm::TagRange)
+    }
+
+    fn insert(&self) -> Self {
+        let algorithm = self.algorithm();
+        Self(self.0.insert(|at| match algorithm {
+            Algorithm::TagRange => {
+                tag_range::Priority::relabel(at);
+                tag_range::Priority::next_label(at)
+            }
+            Algorithm::ListRange => {
+                list_range::Priority::relabel(at);
+                list_range::Priority::next_label(at)
+            }
+        }))
+    }
+}
+
+impl crate::capability::ConstantTimeCompare for SwitchablePriority {}
+
+impl Default for SwitchablePriority {
+    /// Construct the first priority in a new arena, like [`MaintainedOrd::new()`].
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl InArena for SwitchablePriority {
+    fn arena_priorities(&self) -> Vec<Self> {
+        self.0.arena_handles(true).into_iter().map(Self).collect()
+    }
+}
@@ -1,14 +1,257 @@
-use crate::internal::{Arena, Label, PriorityRef};
+use crate::arith;
+use crate::capability::{CapacityError, CapacityErrorKind, InsertFront, TryInsert, TryInsertFront};
+use crate::check::{Checked, SelfCheckReport, Violation};
+use crate::config::{Configurable, PriorityConfig};
+use crate::explain::{RelabelReport, WindowStep};
+use crate::frozen::{FrozenOrder, FrozenPriority, ReadSnapshot};
+use crate::internal::{Arena, CapacityContext, Label, PriorityKey, PriorityRef};
+use crate::params::{capacities_for_threshold, Capacities, Params};
+use crate::policy::Cursor;
+use crate::reservation::{Region, Reservation};
+use crate::sentinel::Bounded;
+use crate::types::{Epoch, LabelSnapshot};
 pub use crate::MaintainedOrd;
+use crate::{InArena, SameArena};
 use order_maintenance_macros::generate_capacities;
-use std::cmp::Ordering;
+use std::{
+    any::Any, cmp::Ordering, fmt::Debug, marker::PhantomData, num::NonZeroU64, ops::Range, rc::Rc,
+};
 
 generate_capacities! {
-    /// Capacities for 17 thresholds in the range `(1.1..=1.9)` (inclusive) with 64-bit tags.
-    const CAPACITIES: [[1.1..=1.9; 64]; 17];
+    /// Capacities for 17 thresholds in the range `(1.1..1.9)` (exclusive) with `usize` tags.
+    const CAPACITIES: [[1.1..1.9; usize]; 17];
 }
 
-/// A totally-ordered priority.
+/// Default parameters: `usize` labels, with 17 thresholds in the range `(1.1..1.9)`.
+#[derive(Debug)]
+pub struct DefaultParams;
+
+impl Params for DefaultParams {
+    const BITS: usize = usize::BITS as usize;
+}
+
+impl Capacities for DefaultParams {
+    const THRESHOLDS: usize = CAPACITIES.len();
+
+    fn capacity(threshold: usize, level: usize) -> usize {
+        CAPACITIES[threshold][level]
+    }
+
+    fn threshold(threshold: usize) -> (u64, u64) {
+        CAPACITIES_RATIOS[threshold]
+    }
+
+    fn max_capacity(threshold: usize) -> usize {
+        CAPACITIES_MAX[threshold]
+    }
+
+    fn breakpoint(threshold: usize) -> usize {
+        CAPACITIES_BREAKPOINTS[threshold]
+    }
+}
+
+/// A totally-ordered priority, using the [`DefaultParams`].
+///
+/// See [`GenericPriority`] for details.
+pub type Priority = GenericPriority<DefaultParams>;
+
+/// Parameters with `usize` labels and a single threshold of `T_TENTHS / 10`, e.g., `13` for 1.3.
+///
+/// The capacities for that threshold are computed at compile time, and relabeling never needs to
+/// choose among several thresholds at runtime, which suits workloads whose density is known in
+/// advance. `T_TENTHS` must be between 11 and 19; other values fail to compile once used.
+#[derive(Debug)]
+pub struct FixedThreshold<const T_TENTHS: usize>;
+
+impl<const T_TENTHS: usize> FixedThreshold<T_TENTHS> {
+    const CAPACITIES: [usize; usize::BITS as usize] = capacities_for_threshold(T_TENTHS as u32, 10);
+}
+
+impl<const T_TENTHS: usize> Params for FixedThreshold<T_TENTHS> {
+    const BITS: usize = usize::BITS as usize;
+}
+
+impl<const T_TENTHS: usize> Capacities for FixedThreshold<T_TENTHS> {
+    const THRESHOLDS: usize = 1;
+
+    fn capacity(_: usize, level: usize) -> usize {
+        Self::CAPACITIES[level]
+    }
+
+    fn threshold(_: usize) -> (u64, u64) {
+        (T_TENTHS as u64, 10)
+    }
+
+    fn threshold_for(total: usize) -> Option<usize> {
+        (total < Self::breakpoint(0)).then_some(0)
+    }
+}
+
+/// A totally-ordered priority, with a single threshold of `T_TENTHS / 10` chosen at compile time.
+///
+/// For example, `FixedPriority<13>` relabels with a threshold of 1.3; see [`FixedThreshold`].
+pub type FixedPriority<const T_TENTHS: usize> = GenericPriority<FixedThreshold<T_TENTHS>>;
+
+/// A [`FixedPriority<T_TENTHS>`] whose arena holds at most `N` priorities at a time, which is
+/// checked against the capacity of the label space at compile time.
+///
+/// Insertion only ever runs out of labels once an arena holds as many priorities as its label
+/// space has capacity for. Since `N` must be less than that capacity, insertion never runs out of
+/// labels: once the arena is full, [`try_insert()`](TryInsert::try_insert) fails with a
+/// [`LiveQuota`](CapacityErrorKind::LiveQuota) error of `N` instead, like an arena that has reached
+/// its [quota](crate::config::PriorityConfig::max_live), and succeeds again as soon as some priority is dropped.
+///
+/// ```rust
+/// # use order_maintenance::capability::{CapacityErrorKind, TryInsert};
+/// # use order_maintenance::tag_range::CappedPriority;
+/// let p0 = CappedPriority::<2>::new();
+/// let p1 = p0.try_insert().unwrap();
+/// assert!(p0 < p1);
+/// let e = p1.try_insert().unwrap_err();
+/// assert_eq!(e.kind(), CapacityErrorKind::LiveQuota { max: 2 });
+///
+/// drop(p1);
+/// assert!(p0.try_insert().is_ok());
+/// ```
+///
+/// Values of `N` that the label space cannot hold fail to compile once used:
+///
+/// ```rust,compile_fail
+/// # use order_maintenance::tag_range::CappedPriority;
+/// let p = CappedPriority::<{ usize::MAX }>::new();
+/// ```
+pub struct CappedPriority<const N: usize, const T_TENTHS: usize = 13>(FixedPriority<T_TENTHS>);
+
+impl<const N: usize, const T_TENTHS: usize> CappedPriority<N, T_TENTHS> {
+    /// The greatest number of priorities that an arena can hold at a time.
+    pub const CAPACITY: usize = N;
+
+    /// Relabeling within the top level of the label space succeeds while fewer than
+    /// `CAPACITIES[BITS - 1] - 1` priorities are live, which holds whenever there is room for
+    /// another of at most `N` priorities.
+    const FITS: () = assert!(
+        0 < N && N < FixedThreshold::<T_TENTHS>::CAPACITIES[usize::BITS as usize - 1],
+        "arenas of this capacity do not fit in the label space"
+    );
+
+    /// Construct the first priority in a new arena.
+    pub fn new() -> Self {
+        let () = Self::FITS;
+        Self(FixedPriority::new())
+    }
+
+    /// Number of priorities in this priority's arena.
+    pub fn arena_len(&self) -> usize {
+        self.0 .0.with_arena(Arena::total)
+    }
+
+    /// Whether this priority's arena is full, so that no more priorities can be inserted into it
+    /// until some are dropped.
+    pub fn is_full(&self) -> bool {
+        self.arena_len() >= N
+    }
+
+    /// Fail with a [`LiveQuota`](CapacityErrorKind::LiveQuota) error if this priority's arena is
+    /// full.
+    fn check_room(&self) -> Result<(), CapacityError> {
+        if !self.is_full() {
+            return Ok(());
+        }
+        let context = CapacityContext {
+            arena_id: self.0 .0.arena_id(),
+            live: self.arena_len(),
+            threshold: None,
+            window: None,
+        };
+        Err(CapacityError::with_kind(
+            context,
+            CapacityErrorKind::LiveQuota { max: N },
+        ))
+    }
+}
+
+impl<const N: usize, const T_TENTHS: usize> MaintainedOrd for CappedPriority<N, T_TENTHS> {
+    fn new() -> Self {
+        Self::new()
+    }
+
+    /// Insert a new priority right after this one.
+    ///
+    /// Panics if the arena is full; see [`try_insert()`](TryInsert::try_insert).
+    fn insert(&self) -> Self {
+        match self.try_insert() {
+            Ok(p) => p,
+            Err(e) => panic!("{e}"),
+        }
+    }
+}
+
+impl<const N: usize, const T_TENTHS: usize> TryInsert for CappedPriority<N, T_TENTHS> {
+    fn try_insert(&self) -> Result<Self, CapacityError> {
+        self.check_room()?;
+        Ok(Self(self.0.insert()))
+    }
+}
+
+impl<const N: usize, const T_TENTHS: usize> InsertFront for CappedPriority<N, T_TENTHS> {
+    /// Panics if the arena is full; see [`try_insert_front()`](TryInsertFront::try_insert_front).
+    fn insert_front(&self) -> Self {
+        match self.try_insert_front() {
+            Ok(p) => p,
+            Err(e) => panic!("{e}"),
+        }
+    }
+}
+
+impl<const N: usize, const T_TENTHS: usize> TryInsertFront for CappedPriority<N, T_TENTHS> {
+    fn try_insert_front(&self) -> Result<Self, CapacityError> {
+        self.check_room()?;
+        Ok(Self(self.0.insert_front()))
+    }
+}
+
+impl<const N: usize, const T_TENTHS: usize> Debug for CappedPriority<N, T_TENTHS> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+impl<const N: usize, const T_TENTHS: usize> Clone for CappedPriority<N, T_TENTHS> {
+    fn clone(&self) -> Self {
+        Self(self.0.clone())
+    }
+}
+
+impl<const N: usize, const T_TENTHS: usize> PartialEq for CappedPriority<N, T_TENTHS> {
+    fn eq(&self, other: &Self) -> bool {
+        self.0 == other.0
+    }
+}
+
+impl<const N: usize, const T_TENTHS: usize> Eq for CappedPriority<N, T_TENTHS> {}
+
+impl<const N: usize, const T_TENTHS: usize> Default for CappedPriority<N, T_TENTHS> {
+    /// Construct the first priority in a new arena, like [`new()`](Self::new).
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<const N: usize, const T_TENTHS: usize> PartialOrd for CappedPriority<N, T_TENTHS> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        self.0.partial_cmp(&other.0)
+    }
+}
+
+impl<const N: usize, const T_TENTHS: usize> crate::capability::ConstantTimeCompare
+    for CappedPriority<N, T_TENTHS>
+{
+}
+
+/// A totally-ordered priority, parameterized by its label width and capacities.
+///
+/// Use [`Priority`] for the default parameters, or [`define_priority!`](crate::define_priority)
+/// to define a priority type with custom parameters.
 ///
 /// These priorities implement Bender et al. (2002)'s solution to the order maintenance problem,
 /// which require a data structure `T` that supports insertion and comparison operations such that
@@ -43,131 +286,867 @@ generate_capacities! {
 /// assert!(p1 < p3);
 /// assert!(p2 < p3);
 /// ```
-#[derive(Debug, PartialEq, Eq, Clone)]
-pub struct Priority(PriorityRef);
+pub struct GenericPriority<P>(PriorityRef, PhantomData<P>);
+
+impl<P: Capacities> GenericPriority<P> {
+    /// The greatest label used by this priority type.
+    const MAX_LABEL: Label = Label::max_for_bits(P::BITS);
 
-impl Priority {
     fn relative(&self) -> Label {
         self.0.label()
     }
 
-    /// Find the correct list of capacities depending onnumber of priorities already inserted.
-    fn threshold_index(&self, total: usize) -> usize {
-        for (i, _) in CAPACITIES.iter().enumerate().rev() {
-            let last = *unsafe { CAPACITIES[i].last().unwrap_unchecked() };
-            if total + 1 < last {
-                return i;
-            }
-        }
+    /// The label that the priority with key `this` is compared by.
+    fn relative_in(arena: &Arena, this: PriorityKey) -> Label {
+        this.as_ref(arena).label()
+    }
 
-        panic!("Too many priorities were inserted: {total}");
+    /// Find the correct list of capacities depending on the number of priorities already inserted.
+    ///
+    /// This is the highest threshold whose capacity still leaves room for another priority, on
+    /// top of `extra` more and those that reserved regions leave room for, up to the arena's
+    /// maximum threshold, if any.
+    fn threshold_index(
+        arena: &Arena,
+        arena_id: usize,
+        extra: usize,
+    ) -> Result<usize, CapacityError> {
+        let total = arena.total();
+        match P::threshold_for(total + extra + arena.reserved()) {
+            Some(i) => Ok(arena.max_threshold().map_or(i, |max| i.min(max))),
+            None => Err(CapacityError::new(CapacityContext {
+                arena_id,
+                live: total,
+                threshold: None,
+                window: None,
+            })),
+        }
     }
 
-    /// Perform relabeling in the arena.
-    fn do_relabel(&self, arena: &mut Arena) {
-        let this = self.0.this().as_ref(arena);
+    /// Perform relabeling in the arena, around the priority at `at`, leaving room for `extra` more
+    /// labels right after it.
+    ///
+    /// Fails before relabeling anything if there is no window sparse enough to relabel.
+    fn do_relabel(at: &mut Cursor<'_>, extra: usize) -> Result<(), CapacityError> {
+        let arena_id = at.arena_id;
+        let arena = &mut *at.arena;
+        let this = at.at.as_ref(arena);
 
-        let t_index = self.threshold_index(arena.total());
+        let t_index = Self::threshold_index(arena, arena_id, extra)?;
 
         let mut i = 0;
         let mut range_size = 1;
         let mut range_count = 1;
-        let mut internal_node_tag = this.label();
-
+        // number of priorities in the range before this one
+        let mut before = 0;
         // the subrange is [min_lab, max_lab] (inclusive)
-        let mut min_lab = internal_node_tag;
-        let mut max_lab = internal_node_tag;
+        let mut min_lab = this.label();
+        let mut max_lab = this.label();
 
         let mut begin = this;
+        let mut begin_key = at.at;
         let mut end = this;
+        // room left for regions reserved right after priorities in the range
+        let mut reserved = arena.region_after(at.at);
+
+        let mut steps = arena.explaining().then(Vec::new);
 
         // The density threshold is 1/T^i
         // So we want to find the smallest subrange so that count/2^i <= 1/T^i
         // or count <= (2/T)^i = CAPA[t_index][i]
 
-        while range_size < usize::MAX {
+        loop {
             loop {
-                let new_begin = begin.prev().as_ref(arena);
+                let new_begin_key = begin.prev();
+                let new_begin = new_begin_key.as_ref(arena);
                 if new_begin.label() < min_lab || new_begin.label() >= begin.label() {
                     break;
                 }
                 range_count += 1;
+                before += 1;
                 begin = new_begin;
+                begin_key = new_begin_key;
+                reserved += arena.region_after(begin_key);
             }
             loop {
-                let new_end = end.next().as_ref(arena);
+                let new_end_key = end.next();
+                let new_end = new_end_key.as_ref(arena);
                 if new_end.label() > max_lab || new_end.label() <= end.label() {
                     break;
                 }
                 range_count += 1;
                 end = new_end;
+                reserved += arena.region_after(new_end_key);
             }
 
-            if range_count < CAPACITIES[t_index][i] {
-                // Range found, relabel
-                let gap = range_size / range_count;
-                let mut rem = range_size % range_count; // note: the reminder is spread out
-                let mut new_label = min_lab;
-
-                while begin.label() != end.label() {
-                    begin.set_label(new_label);
-                    begin = begin.next().as_ref(arena);
-                    new_label += gap;
-                    if rem > 0 {
-                        new_label += 1;
-                        rem -= 1;
-                    }
-                }
-                end.set_label(new_label); // the end is part of the range
+            if let Some(steps) = &mut steps {
+                steps.push(WindowStep {
+                    level: i,
+                    window: (min_lab.into(), max_lab.into()),
+                    count: range_count,
+                    capacity: Some(P::capacity(t_index, i)),
+                });
+            }
 
+            if range_count + extra + reserved < P::capacity(t_index, i) {
+                // Range found, relabel, skipping the extra labels right after this priority, and
+                // those left for the regions reserved right after any priority
+                let min = usize::from(min_lab);
+                let slots = range_count + extra + reserved;
+                let mut key = begin_key;
+                let mut skipped = 0;
+                for k in 0..range_count {
+                    let prio = key.as_ref(arena);
+                    let slot = if k > before { k + extra } else { k };
+                    prio.set_label(Label::new(arith::spread_label(
+                        min,
+                        range_size,
+                        slots,
+                        slot + skipped,
+                    )));
+                    skipped += arena.region_after(key);
+                    key = prio.next();
+                    arena.relabel_progress(k + 1);
+                }
                 break;
             } else {
-                if range_size == usize::MAX {
-                    panic!("Too many priorities were inserted, the root is overflowing!");
+                if i + 1 >= P::BITS {
+                    return Err(CapacityError::new(CapacityContext {
+                        arena_id,
+                        live: arena.total(),
+                        threshold: Some(t_index),
+                        window: Some((min_lab, max_lab, i)),
+                    }));
                 }
                 i += 1;
                 range_size *= 2;
-                internal_node_tag >>= 1;
-                min_lab = internal_node_tag << i; // add i zeros
-                max_lab = !(!internal_node_tag << i) // add i ones
+                let (min, max) = arith::window(usize::from(this.label()), i);
+                (min_lab, max_lab) = (Label::new(min), Label::new(max));
             }
         }
+        arena.record_relabel(range_count);
+        arena.note_relabeled(begin_key, range_count);
+        if let Some(steps) = steps {
+            arena.set_relabel_report(RelabelReport {
+                epoch: Epoch::new(arena.label_epoch()),
+                live: arena.total(),
+                threshold: Some(t_index),
+                steps,
+                relabeled: range_count,
+                density: range_count as f64 / range_size as f64,
+            });
+        }
+        Ok(())
     }
 
-    /// Perform relabeling in the arena if necessary.
-    fn relabel(&self, arena: &mut Arena) {
-        let this = self.0.this().as_ref(arena);
+    /// Number of labels between the priority with key `this` and the next priority.
+    ///
+    /// If the next priority wraps around to the beginning of the label space, it is treated as if
+    /// it were at `2^BITS`, just past the greatest label. That may not fit in a label, so the gap
+    /// is computed as a `u128`.
+    fn gap_to_next(arena: &Arena, this: PriorityKey) -> u128 {
+        let this = this.as_ref(arena);
         let next = this.next().as_ref(arena);
-        let next_lab = if next.label() <= this.label() {
-            Label::MAX
-        } else {
-            next.label()
-        };
+        arith::gap_to_next(
+            this.label().into(),
+            next.label().into(),
+            Self::MAX_LABEL.into(),
+        )
+    }
 
-        if this.label() + 1 == next_lab {
-            self.do_relabel(arena)
+    /// Perform relabeling in the arena if necessary to insert after the priority at `at`.
+    ///
+    /// Panics if the arena has run out of labels or reached its quota.
+    pub(crate) fn relabel(at: &mut Cursor<'_>) {
+        if let Err(e) = Self::try_relabel(at) {
+            panic!("{e}")
         }
     }
 
-    /// Compute the next label for inserting after `self`.
-    fn next_label(&self, arena: &Arena) -> Label {
-        let this = self.0.this().as_ref(arena);
-        let this_lab = this.label();
-        let mut next_lab = this.next().as_ref(arena).label();
-        next_lab = if next_lab <= this_lab {
-            Label::MAX
-        } else {
-            next_lab
+    /// Perform relabeling in the arena if necessary to insert after the priority at `at`, unless
+    /// the arena has run out of labels or reached its quota, in which case it is left as is.
+    pub(crate) fn try_relabel(at: &mut Cursor<'_>) -> Result<(), CapacityError> {
+        at.arena.check_quota(at.arena_id, 1)?;
+        if Self::gap_to_next(at.arena, at.at) <= 1 {
+            Self::do_relabel(at, 0)?;
+        }
+        Ok(())
+    }
+
+    /// Perform relabeling in the arena if necessary to insert `k` priorities after the priority at
+    /// `at`, then compute their labels, evenly spaced up to the next priority.
+    ///
+    /// Panics if the arena has run out of labels or reached its quota.
+    fn relabel_for(at: &mut Cursor<'_>, k: usize) -> Vec<Label> {
+        if let Err(e) = at.arena.check_quota(at.arena_id, k) {
+            panic!("{e}")
+        }
+        if Self::gap_to_next(at.arena, at.at) <= k as u128 {
+            if let Err(e) = Self::do_relabel(at, k) {
+                panic!("{e}")
+            }
+        }
+        let this = usize::from(at.at.as_ref(at.arena).label());
+        let gap = Self::gap_to_next(at.arena, at.at);
+        (1..=k)
+            .map(|j| Label::new(this + (gap * j as u128 / (k as u128 + 1)) as usize))
+            .collect()
+    }
+
+    /// Whether `label` is still free right after the priority at `at`, which must not have been
+    /// relabeled since `label` was computed.
+    fn is_free_after(at: &Cursor<'_>, label: Label) -> bool {
+        let this = at.at.as_ref(at.arena).label();
+        let next = at.at.as_ref(at.arena).next().as_ref(at.arena).label();
+        this < label && (next <= this || label < next)
+    }
+
+    /// Compute the next label for inserting after the priority at `at`.
+    pub(crate) fn next_label(at: &Cursor<'_>) -> Label {
+        let this = at.at.as_ref(at.arena);
+        let next = this.next().as_ref(at.arena);
+        Label::new(arith::midpoint(
+            this.label().into(),
+            next.label().into(),
+            Self::MAX_LABEL.into(),
+        ))
+    }
+}
+
+impl<P: Capacities> GenericPriority<P> {
+    /// A sentinel less than every priority of this type, in any arena.
+    pub const MIN_SENTINEL: Bounded<Self> = Bounded::Min;
+
+    /// A sentinel greater than every priority of this type, in any arena.
+    pub const MAX_SENTINEL: Bounded<Self> = Bounded::Max;
+
+    /// Construct the first priority in a new arena, with `data` attached to the arena.
+    ///
+    /// The arena holds one reference to the data, which it drops along with itself, i.e., once
+    /// every priority in it is dropped. The data can be retrieved through any of them with
+    /// [`arena_data()`](Self::arena_data), whose references keep it alive for as long as they
+    /// live.
+    pub fn with_arena_data<T: Any>(data: T) -> Self {
+        let mut arena = Arena::new();
+        arena.set_data(Rc::new(data));
+        Self::first_in(arena)
+    }
+
+    /// The data attached to this priority's arena by [`with_arena_data()`], if it is of type `T`.
+    ///
+    /// [`with_arena_data()`]: Self::with_arena_data
+    pub fn arena_data<T: Any>(&self) -> Option<Rc<T>> {
+        self.0.arena_data()
+    }
+
+    /// Attach `meta` to this priority, replacing any metadata attached to it before.
+    ///
+    /// The metadata is stored in the arena, shared by every handle to this priority, and dropped
+    /// as soon as the priority is, i.e., once every handle to it is dropped.
+    pub fn set_meta<T: Any>(&self, meta: T) {
+        self.0.set_meta(Some(Rc::new(meta)))
+    }
+
+    /// The metadata attached to this priority by [`set_meta()`], if it is of type `T`.
+    ///
+    /// [`set_meta()`]: Self::set_meta
+    pub fn meta<T: Any>(&self) -> Option<Rc<T>> {
+        self.0.meta()
+    }
+
+    /// Detach any metadata from this priority, dropping it unless it is still in use.
+    pub fn clear_meta(&self) {
+        self.0.set_meta(None)
+    }
+
+    /// Redistribute the labels of every priority in this priority's arena evenly across the label
+    /// space, in one linear pass; returns the number of priorities relabeled.
+    ///
+    /// Bursts of insertions in one place leave labels crowded there, which later insertions pay
+    /// for with larger relabeling passes. Rebalancing resets that skew up front, e.g., during a
+    /// maintenance window rather than during latency-sensitive insertions.
+    pub fn rebalance_all(&self) -> usize {
+        self.0.rebalance(false, Self::MAX_LABEL)
+    }
+
+    /// Renumber every priority in this priority's arena with consecutive labels from the start of
+    /// the label space, in one linear pass; returns the number of priorities relabeled.
+    ///
+    /// After many priorities are dropped, the labels of the rest are spread thin; compacting them
+    /// makes raw labels small, e.g., to shrink snapshots of them, and keeps neighbors close. Like
+    /// any relabeling, it advances the [label epoch](Self::label_epoch), so caches keyed on labels
+    /// know to refresh. Inserting between compacted priorities must relabel them, so compacting
+    /// suits arenas that are mostly read from now on; see [`rebalance_all()`](Self::rebalance_all)
+    /// otherwise.
+    pub fn compact_labels(&self) -> usize {
+        self.0.compact(false, Self::MAX_LABEL)
+    }
+
+    /// Insert `k` new priorities right after this one, in increasing order, with evenly spaced
+    /// labels.
+    ///
+    /// This relabels at most once, making room for all `k` priorities at the same time, whereas
+    /// inserting them one at a time may relabel again and again. Like
+    /// [`MaintainedOrd::insert()`], panics if the arena runs out of labels or reaches its quota.
+    ///
+    /// ```rust
+    /// # use order_maintenance::{tag_range::Priority, MaintainedOrd};
+    /// let first = Priority::new();
+    /// let last = first.insert();
+    /// let between = first.insert_n(3);
+    /// assert!(first < between[0] && between[0] < between[1]);
+    /// assert!(between[1] < between[2] && between[2] < last);
+    /// ```
+    pub fn insert_n(&self, k: usize) -> Vec<Self> {
+        if k == 0 {
+            return Vec::new();
+        }
+        self.0
+            .insert_n(|at| Self::relabel_for(at, k))
+            .into_iter()
+            .map(|p| Self(p, PhantomData))
+            .collect()
+    }
+
+    /// Reserve room for `room` priorities right after this priority, for insertions after it,
+    /// until the returned [`Region`] is released.
+    ///
+    /// Relabeling passes, including those of [`rebalance_all()`](Self::rebalance_all), treat the
+    /// region as if it already held `room` priorities: they leave labels for those, rather than
+    /// spreading other priorities into it. The region thus takes up as large a share of the label
+    /// space as `room` priorities would, which shrinks as the arena grows. If the labels right
+    /// after this priority are not free yet, they are freed now, relabeling once, so that a burst
+    /// of insertions known to land here finds room waiting for it. Like the priorities it stands
+    /// for, a region counts towards running out of labels.
+    ///
+    /// Fails if the arena cannot make room for the region, leaving it as is.
+    ///
+    /// ```rust
+    /// # use order_maintenance::{tag_range::Priority, MaintainedOrd};
+    /// let first = Priority::new();
+    /// let last = first.insert();
+    /// let region = first.reserve_region_after(2).unwrap();
+    ///
+    /// // Rebalancing leaves room for two priorities between `first` and `last`.
+    /// first.rebalance_all();
+    /// let gap = last.label_with_epoch().label() - first.label_with_epoch().label();
+    /// assert!(gap > usize::MAX / 2);
+    /// region.release();
+    /// ```
+    pub fn reserve_region_after(&self, room: usize) -> Result<Region<Self>, CapacityError> {
+        self.reserve_room(room)?;
+        Ok(Region {
+            anchor: self.clone(),
+            handle: self.0.clone(),
+            room,
+        })
+    }
+
+    /// Reserve room for `room` priorities right after this priority, for a [`Region`], freeing
+    /// the labels right after it first if they are not free yet.
+    fn reserve_room(&self, room: usize) -> Result<(), CapacityError> {
+        self.0.reserve_region(room, |at| {
+            Self::threshold_index(at.arena, at.arena_id, 0)?;
+            if Self::gap_to_next(at.arena, at.at) <= room as u128 {
+                Self::do_relabel(at, 0)?;
+            }
+            Ok(())
+        })
+    }
+
+    /// Prepare to insert a priority after this one, performing any relabeling that it requires
+    /// now; see [`Reservation`].
+    pub fn reserve_after(&self) -> Reservation<Self> {
+        let (label, epoch) = self.0.prepare_insert(|at| {
+            Self::relabel(at);
+            Self::next_label(at)
+        });
+        Reservation {
+            anchor: self.clone(),
+            label,
+            epoch,
+        }
+    }
+
+    /// Free the priorities of this arena whose handles were all dropped, if it was configured
+    /// with [`PriorityConfig::deferred_drop()`]; returns the number of priorities freed.
+    ///
+    /// Otherwise, priorities are freed as soon as their last handle is dropped, and this is a
+    /// no-op.
+    pub fn gc(&self) -> usize {
+        self.0.gc()
+    }
+
+    /// Check the internal invariants of the arena that this priority belongs to.
+    ///
+    /// This takes time linear in the number of priorities in the arena.
+    pub fn check_invariants(&self) -> Result<(), Violation> {
+        self.0.check(Self::MAX_LABEL)
+    }
+
+    /// Check the invariants of this priority's arena on every `one_in`th insertion or removal,
+    /// recording the results in its [`self_check_report()`](Self::self_check_report); or stop
+    /// checking them, if `one_in` is `None`.
+    pub fn set_self_check(&self, one_in: Option<NonZeroU64>) {
+        self.0.set_self_check(one_in, Self::MAX_LABEL)
+    }
+
+    /// The results of the sampled invariant checks in this priority's arena.
+    pub fn self_check_report(&self) -> SelfCheckReport {
+        self.0.self_check_report()
+    }
+
+    /// The number of relabeling passes performed in this priority's arena so far.
+    ///
+    /// Labels of priorities in the arena can only change when this does, so caches keyed on
+    /// labels remain valid for as long as it stays the same.
+    pub fn label_epoch(&self) -> Epoch {
+        Epoch::new(self.0.label_with_epoch().1)
+    }
+
+    /// The raw label of this priority, along with the current [label epoch](Self::label_epoch).
+    ///
+    /// The label is an implementation detail, only meaningful in comparison with other labels
+    /// from the same arena and epoch.
+    pub fn label_with_epoch(&self) -> LabelSnapshot {
+        let (label, epoch) = self.0.label_with_epoch();
+        LabelSnapshot::new(label.into(), Epoch::new(epoch))
+    }
+
+    /// Approximate position of this priority in its arena, between 0.0 and 1.0.
+    ///
+    /// This is the position of its label in the label space, which takes constant time to
+    /// compute, unlike its rank; but labels are not evenly spread, so it is only a rough hint.
+    pub fn position_hint(&self) -> f64 {
+        u128::from(self.relative()) as f64 / (u128::from(Self::MAX_LABEL) + 1) as f64
+    }
+
+    /// A handle to the least priority in this priority's arena.
+    pub fn min_in_arena(&self) -> Self {
+        Self(self.0.find(|arena, _| arena.base()), PhantomData)
+    }
+
+    /// A handle to the greatest priority in this priority's arena.
+    pub fn max_in_arena(&self) -> Self {
+        Self(
+            self.0.find(|arena, _| arena.base().as_ref(arena).prev()),
+            PhantomData,
+        )
+    }
+
+    /// A handle to the least priority greater than this one in its arena, or `None` if this is
+    /// the greatest.
+    pub fn successor(&self) -> Option<Self> {
+        let this = self.0.this();
+        let is_max = self
+            .0
+            .with_arena(|arena| this.as_ref(arena).next() == arena.base());
+        (!is_max).then(|| {
+            Self(
+                self.0.find(|arena, this| this.as_ref(arena).next()),
+                PhantomData,
+            )
+        })
+    }
+
+    /// A handle to the greatest priority less than this one in its arena, or `None` if this is
+    /// the least.
+    pub fn predecessor(&self) -> Option<Self> {
+        let this = self.0.this();
+        let is_min = self.0.with_arena(|arena| this == arena.base());
+        (!is_min).then(|| {
+            Self(
+                self.0.find(|arena, this| this.as_ref(arena).prev()),
+                PhantomData,
+            )
+        })
+    }
+
+    /// Rebuild the order of every priority in this priority's arena in a new arena of type `T`.
+    ///
+    /// Returns handles to the old priorities in increasing order, each paired with its
+    /// counterpart in the new arena. To convert priorities without an arena, e.g., naive ones,
+    /// use [`tag_in_order()`](crate::tag_in_order) instead.
+    pub fn convert_arena<T: MaintainedOrd>(&self) -> Vec<(Self, T)> {
+        let old = self.arena_priorities();
+        let new = T::new_sequence(old.len());
+        old.into_iter().zip(new).collect()
+    }
+
+    /// Transplant this priority into another arena, given a point `from` in this priority's arena
+    /// that corresponds to `to` in the other.
+    ///
+    /// `from` must be at or before this priority. If this priority is `k` places after `from`,
+    /// the copy is inserted `k` places after `to`, i.e., right after the `k - 1`th priority that
+    /// follows `to`, or after the greatest priority of the other arena if fewer follow `to`.
+    /// Returns `to` itself if `from` is this priority, or `None` if `from` is in another arena or
+    /// comes after this priority.
+    ///
+    /// Transplanting priorities in increasing order, each with the previous one as the
+    /// correspondence point, migrates them incrementally while preserving their order. This takes
+    /// time linear in `k` in this arena, and `k` calls to [`InArena::successor()`] in the other.
+    ///
+    /// ```rust
+    /// # use order_maintenance::{tag_range::Priority, MaintainedOrd};
+    /// let ps = Priority::new_sequence(4);
+    /// let qs = Priority::new_sequence(4);
+    /// let q = ps[2].try_clone_into(&ps[0], &qs[0]).unwrap();
+    /// assert!(qs[1] < q && q < qs[2]);
+    /// ```
+    pub fn try_clone_into<T: InArena + Clone>(&self, from: &Self, to: &T) -> Option<T> {
+        // Priorities of arenas that are ordered across arenas are comparable, but not related.
+        if !self.0.same_arena(&from.0) {
+            return None;
+        }
+        let (from, this) = (from.0.this(), self.0.this());
+        let offset = self.0.with_arena(|arena| {
+            let mut key = from;
+            let mut offset = 0;
+            while key != this {
+                key = key.as_ref(arena).next();
+                // Every priority after `from` was passed without finding this one.
+                if key == arena.base() {
+                    return None;
+                }
+                offset += 1;
+            }
+            Some(offset)
+        })?;
+        if offset == 0 {
+            return Some(to.clone());
+        }
+        let mut before = to.clone();
+        for _ in 1..offset {
+            match before.successor() {
+                Some(next) => before = next,
+                None => break,
+            }
+        }
+        Some(before.insert())
+    }
+
+    /// Freeze the order of every priority in this priority's arena.
+    ///
+    /// Returns handles to the priorities in increasing order, each paired with its counterpart
+    /// in the frozen order. See [`frozen`](crate::frozen) for details.
+    pub fn freeze_arena(&self) -> (FrozenOrder, Vec<(Self, FrozenPriority)>) {
+        let handles = self.arena_priorities();
+        let order = FrozenOrder::new(handles.iter().map(|p| p.relative().into()).collect());
+        let frozen = handles.into_iter().zip(order.iter()).collect();
+        (order, frozen)
+    }
+
+    /// Snapshot the order of every priority in this priority's arena, without creating handles to
+    /// them; see [`ReadSnapshot`].
+    ///
+    /// This takes time linear in the number of priorities in the arena, but unlike
+    /// [`freeze_arena()`](Self::freeze_arena), touches no reference counts.
+    pub fn read_snapshot(&self) -> ReadSnapshot {
+        self.0.read_snapshot(false, Self::MAX_LABEL)
+    }
+
+    /// Witness that `a` and `b` belong to the same arena, if they do.
+    pub fn witness_same_arena<'a>(a: &'a Self, b: &'a Self) -> Option<SameArena<'a, Self>> {
+        a.0.same_arena(&b.0).then(|| SameArena::new(a, b))
+    }
+
+    /// Compare each pair of priorities, like [`partial_cmp()`](PartialOrd::partial_cmp).
+    ///
+    /// This borrows the arena of the first pair only once for all pairs in it, which makes it
+    /// cheaper than comparing each pair separately when most pairs share an arena.
+    pub fn compare_many(pairs: &[(Self, Self)]) -> Vec<Option<Ordering>> {
+        let Some((first, _)) = pairs.first() else {
+            return Vec::new();
+        };
+        first.0.with_arena(|arena| {
+            pairs
+                .iter()
+                .map(|(a, b)| {
+                    if a.0.same_arena(&first.0) && b.0.same_arena(&first.0) {
+                        let (a, b) = (a.0.this(), b.0.this());
+                        Some(Self::relative_in(arena, a).cmp(&Self::relative_in(arena, b)))
+                    } else {
+                        a.partial_cmp(b)
+                    }
+                })
+                .collect()
+        })
+    }
+
+    /// The permutation that sorts `handles`, i.e., their indices in increasing order of priority,
+    /// with equal priorities in their original order.
+    ///
+    /// This snapshots the labels of all priorities under a single borrow of their arena, then
+    /// radix sorts them, rather than comparing them pairwise. Panics unless all priorities belong
+    /// to the same arena.
+    pub fn order_indices(handles: &[Self]) -> Vec<usize> {
+        let Some(first) = handles.first() else {
+            return Vec::new();
+        };
+        assert!(
+            handles.iter().all(|p| p.0.same_arena(&first.0)),
+            "ordered priorities must belong to the same arena"
+        );
+        let labels: Vec<usize> = first.0.with_arena(|arena| {
+            handles
+                .iter()
+                .map(|p| Self::relative_in(arena, p.0.this()).into())
+                .collect()
+        });
+        arith::sort_indices(&labels)
+    }
+
+    /// Sort `handles` in increasing order of priority, then group adjacent handles by `key`;
+    /// returns the ranges of `handles` that hold each group, in order.
+    ///
+    /// Sorting snapshots the labels of all priorities once, like
+    /// [`order_indices()`](Self::order_indices), and `key` is called once per handle. Panics
+    /// unless all priorities belong to the same arena.
+    pub fn group_adjacent_by<K: PartialEq>(
+        handles: &mut [Self],
+        key: impl FnMut(&Self) -> K,
+    ) -> Vec<Range<usize>> {
+        arith::permute(handles, Self::order_indices(handles));
+        arith::adjacent_runs(handles, key)
+    }
+
+    /// Whether this priority's arena holds more priorities than its
+    /// [soft cap](PriorityConfig::soft_cap), if it has one.
+    pub fn over_soft_cap(&self) -> bool {
+        self.0.over_soft_cap()
+    }
+
+    /// Report of the last relabeling pass in this priority's arena, if its arena was configured
+    /// to [explain relabels](PriorityConfig::explain_relabels) and has relabeled since.
+    pub fn last_relabel_report(&self) -> Option<RelabelReport> {
+        self.0.last_relabel_report()
+    }
+
+    /// Merge two sequences of priorities in increasing order into one, in linear time.
+    ///
+    /// This snapshots the labels of all priorities under a single borrow of their arena, rather
+    /// than comparing them pairwise. Panics unless all priorities belong to the same arena.
+    pub fn merge(a: &[Self], b: &[Self]) -> Vec<Self> {
+        let Some(first) = a.first().or(b.first()) else {
+            return Vec::new();
         };
+        assert!(
+            a.iter().chain(b).all(|p| p.0.same_arena(&first.0)),
+            "merged priorities must belong to the same arena"
+        );
+        let (la, lb): (Vec<Label>, Vec<Label>) = first.0.with_arena(|arena| {
+            let labels = |ps: &[Self]| {
+                ps.iter()
+                    .map(|p| Self::relative_in(arena, p.0.this()))
+                    .collect()
+            };
+            (labels(a), labels(b))
+        });
+
+        let mut merged = Vec::with_capacity(a.len() + b.len());
+        let (mut i, mut j) = (0, 0);
+        while i < a.len() && j < b.len() {
+            if la[i] <= lb[j] {
+                merged.push(a[i].clone());
+                i += 1;
+            } else {
+                merged.push(b[j].clone());
+                j += 1;
+            }
+        }
+        merged.extend_from_slice(&a[i..]);
+        merged.extend_from_slice(&b[j..]);
+        merged
+    }
+}
+
+/// Shows the arena that the priority belongs to, its current label, and its rank in the arena.
+///
+/// Computing the rank takes time linear in the number of priorities in the arena.
+impl<P> Debug for GenericPriority<P> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Priority")
+            .field("arena", &format_args!("{:#x}", self.0.arena_id()))
+            .field("label", &usize::from(self.0.label()))
+            .field("rank", &self.0.position())
+            .finish()
+    }
+}
+
+#[cfg(feature = "stats")]
+impl<P> GenericPriority<P> {
+    /// Statistics about relabeling in the arena that this priority belongs to, since it was
+    /// constructed or its statistics were last [reset](Self::reset_relabel_stats).
+    pub fn relabel_stats(&self) -> crate::stats::RelabelStats {
+        self.0.stats()
+    }
+
+    /// Statistics about relabeling in the arena that this priority belongs to, since they were last
+    /// polled, or since the arena was constructed or its statistics were last reset.
+    ///
+    /// Polling starts a new window of statistics, but leaves [`relabel_stats()`](Self::relabel_stats)
+    /// as is.
+    pub fn poll_relabel_stats(&self) -> crate::stats::RelabelStats {
+        self.0.poll_stats()
+    }
+
+    /// Reset the statistics about relabeling in the arena that this priority belongs to, both
+    /// overall and since they were last polled.
+    pub fn reset_relabel_stats(&self) {
+        self.0.reset_stats()
+    }
+}
+
+#[cfg(feature = "persistent-id")]
+impl<P: Capacities> GenericPriority<P> {
+    /// An identifier of this priority that never changes, even across processes; see
+    /// [`persistent_id`](crate::persistent_id).
+    pub fn persistent_id(&self) -> crate::persistent_id::PersistentId {
+        self.0.persistent_id()
+    }
+
+    /// A handle to the priority with the identifier `id`, if it is in this priority's arena.
+    ///
+    /// Priorities whose handles were all dropped are not found, unless their arena
+    /// [defers drops](PriorityConfig::deferred_drop) and has not collected them yet. This takes
+    /// time linear in the number of priorities in the arena.
+    pub fn find(&self, id: crate::persistent_id::PersistentId) -> Option<Self> {
+        self.0
+            .find_persistent(id, false)
+            .map(|p| Self(p, PhantomData))
+    }
+
+    /// Every priority in this priority's arena that `map` has data about, in increasing order,
+    /// along with that data.
+    ///
+    /// This takes time linear in the number of priorities in the arena, plus a lookup in `map`
+    /// for each of them.
+    pub fn iter_with<'a, T>(
+        &self,
+        map: &'a impl crate::persistent_id::SecondaryMap<T>,
+    ) -> impl Iterator<Item = (Self, &'a T)> + 'a
+    where
+        P: 'a,
+        T: 'a,
+    {
+        self.arena_priorities().into_iter().filter_map(|p| {
+            let data = map.get_by_id(&p.persistent_id())?;
+            Some((p, data))
+        })
+    }
+}
+
+#[cfg(feature = "oplog")]
+impl<P: Capacities> crate::oplog::Recorded for GenericPriority<P> {
+    fn op_log(&self) -> crate::oplog::OpLog {
+        self.0.op_log()
+    }
+
+    fn op_key(&self) -> usize {
+        self.0.op_key()
+    }
+
+    fn rebalance_all(&self) -> usize {
+        GenericPriority::rebalance_all(self)
+    }
+
+    fn compact_labels(&self) -> usize {
+        GenericPriority::compact_labels(self)
+    }
+
+    fn insert_n(&self, k: usize) -> Vec<Self> {
+        GenericPriority::insert_n(self, k)
+    }
+
+    fn reserve_region_after(&self, room: usize) -> Result<Region<Self>, CapacityError> {
+        GenericPriority::reserve_region_after(self, room)
+    }
 
-        this_lab + (next_lab - this_lab) / 2
+    fn reserve_room_after(&self, room: usize) -> Result<(), CapacityError> {
+        self.reserve_room(room)
+    }
+
+    fn release_room_after(&self, room: usize) {
+        self.0.release_region(room)
+    }
+}
+
+impl<P: Capacities> crate::id::Identified for GenericPriority<P> {
+    fn id(&self) -> crate::id::PriorityId {
+        self.0.id()
+    }
+
+    fn id_order(&self) -> crate::id::IdOrder<'_> {
+        self.0.id_order(Self::MAX_LABEL)
+    }
+}
+
+#[cfg(feature = "checkpoint")]
+impl<P> crate::checkpoint::Checkpointed for GenericPriority<P> {
+    fn checkpoint(&self) -> crate::checkpoint::Checkpoint {
+        self.0.checkpoint()
+    }
+
+    fn changes_since(
+        &self,
+        checkpoint: &crate::checkpoint::Checkpoint,
+    ) -> Option<crate::checkpoint::Changes> {
+        self.0.changes_since(checkpoint)
+    }
+
+    fn change_key(&self) -> usize {
+        self.0.change_key()
+    }
+
+    fn discard_changes_before(&self, checkpoint: &crate::checkpoint::Checkpoint) {
+        self.0.discard_changes_before(checkpoint)
     }
 }
 
-impl PartialOrd for Priority {
+#[cfg(feature = "introspect")]
+impl<P: Capacities> crate::introspect::Introspect for GenericPriority<P> {
+    fn raw_label(&self) -> usize {
+        self.0.label().into()
+    }
+
+    fn neighbor_labels(&self) -> (usize, usize) {
+        let (prev, next) = self.0.neighbor_labels();
+        (prev.into(), next.into())
+    }
+
+    fn gap_to_next(&self) -> u64 {
+        let (_, next) = self.0.neighbor_labels();
+        let gap = arith::gap_to_next(self.relative().into(), next.into(), Self::MAX_LABEL.into());
+        gap.try_into().unwrap_or(u64::MAX)
+    }
+
+    fn creation_index(&self) -> usize {
+        self.0.creation_index()
+    }
+
+    fn dump_arena(&self) -> crate::introspect::ArenaDump {
+        self.0.dump()
+    }
+}
+
+impl<P> Clone for GenericPriority<P> {
+    fn clone(&self) -> Self {
+        Self(self.0.clone(), PhantomData)
+    }
+}
+
+impl<P> PartialEq for GenericPriority<P> {
+    fn eq(&self, other: &Self) -> bool {
+        self.0 == other.0
+    }
+}
+
+impl<P> Eq for GenericPriority<P> {}
+
+impl<P: Capacities> PartialOrd for GenericPriority<P> {
     fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
         if !self.0.same_arena(&other.0) {
-            None
+            self.0.cross_arena_cmp(&other.0)
         } else if self.0 == other.0 {
             Some(Ordering::Equal)
         } else {
@@ -176,19 +1155,152 @@ impl PartialOrd for Priority {
     }
 }
 
-impl MaintainedOrd for Priority {
-    fn new() -> Self {
-        let arena = Arena::new();
+impl<P: Capacities> SameArena<'_, GenericPriority<P>> {
+    /// Compare the witnessed priorities, without checking that they belong to the same arena.
+    pub fn compare(&self) -> Ordering {
+        self.lhs().relative().cmp(&self.rhs().relative())
+    }
+}
+
+impl<P: Capacities> GenericPriority<P> {
+    /// Construct the first priority in `arena`.
+    fn first_in(arena: Arena) -> Self {
         // Base is not a specially designated priority in this implementation, so we
         // can use it as the first priority.
         let this = arena.base();
-        Priority(PriorityRef::new(arena, this))
+        Self(PriorityRef::new(arena, this), PhantomData)
+    }
+}
+
+impl<P: Capacities> Configurable for GenericPriority<P> {
+    fn from_config(config: &PriorityConfig) -> Self {
+        let mut arena = config.arena(Self::MAX_LABEL);
+        arena.set_max_threshold(config.max_threshold::<P>());
+        Self::first_in(arena)
+    }
+}
+
+impl<P: Capacities> MaintainedOrd for GenericPriority<P> {
+    fn new() -> Self {
+        Self::first_in(Arena::new())
+    }
+
+    fn new_sequence(n: usize) -> Vec<Self> {
+        if n == 0 {
+            return Vec::new();
+        }
+        let mut arena = Arena::new();
+        let base = arena.base();
+        let mut keys = vec![base];
+        keys.extend(arena.insert_spread_after(base, n - 1, Arena::BASE, Self::MAX_LABEL));
+        PriorityRef::new_many(arena, keys)
+            .into_iter()
+            .map(|p| Self(p, PhantomData))
+            .collect()
     }
 
     fn insert(&self) -> Self {
-        Self(self.0.insert(|arena| {
-            self.relabel(arena);
-            self.next_label(arena)
-        }))
+        Self(
+            self.0.insert(|at| {
+                Self::relabel(at);
+                Self::next_label(at)
+            }),
+            PhantomData,
+        )
+    }
+}
+
+impl<P: Capacities> Reservation<GenericPriority<P>> {
+    /// Insert a priority after the anchor, at the reserved label if it is still free, or else
+    /// like [`MaintainedOrd::insert()`].
+    ///
+    /// Either way, panics if the arena has reached its quota.
+    pub fn commit(self) -> GenericPriority<P> {
+        let (label, epoch) = (self.label, self.epoch);
+        GenericPriority(
+            self.anchor.0.insert(|at| {
+                if at.arena.label_epoch() == epoch && GenericPriority::<P>::is_free_after(at, label)
+                {
+                    if let Err(e) = at.arena.check_quota(at.arena_id, 1) {
+                        panic!("{e}")
+                    }
+                    return label;
+                }
+                GenericPriority::<P>::relabel(at);
+                GenericPriority::<P>::next_label(at)
+            }),
+            PhantomData,
+        )
+    }
+}
+
+impl<P> crate::capability::ConstantTimeCompare for GenericPriority<P> {}
+
+impl<P: Capacities> TryInsert for GenericPriority<P> {
+    fn try_insert(&self) -> Result<Self, CapacityError> {
+        let p = self.0.try_insert(|at| {
+            Self::try_relabel(at)?;
+            Ok(Self::next_label(at))
+        })?;
+        Ok(Self(p, PhantomData))
+    }
+}
+
+/// The base priority of a tag-range arena is its least priority, so the new priority is inserted
+/// right after it, then swaps places and labels with it.
+impl<P: Capacities> InsertFront for GenericPriority<P> {
+    fn insert_front(&self) -> Self {
+        Self(
+            self.0.insert_front(true, |at| {
+                Self::relabel(at);
+                Self::next_label(at)
+            }),
+            PhantomData,
+        )
+    }
+}
+
+impl<P: Capacities> TryInsertFront for GenericPriority<P> {
+    fn try_insert_front(&self) -> Result<Self, CapacityError> {
+        let p = self.0.try_insert_front(true, |at| {
+            Self::try_relabel(at)?;
+            Ok(Self::next_label(at))
+        })?;
+        Ok(Self(p, PhantomData))
+    }
+}
+
+impl<P: Capacities> Default for GenericPriority<P> {
+    /// Construct the first priority in a new arena, like [`MaintainedOrd::new()`].
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<P: Capacities> InArena for GenericPriority<P> {
+    fn arena_priorities(&self) -> Vec<Self> {
+        self.0
+            .arena_handles(false)
+            .into_iter()
+            .map(|p| Self(p, PhantomData))
+            .collect()
+    }
+
+    fn successor(&self) -> Option<Self> {
+        GenericPriority::successor(self)
+    }
+}
+
+impl<P: Capacities> Checked for GenericPriority<P> {
+    fn check_invariants(&self) -> Result<(), Violation> {
+        self.check_invariants()
+    }
+
+    fn capacity() -> usize {
+        P::breakpoint(0)
+    }
+
+    fn param_problems() -> Vec<String> {
+        crate::params::capacity_problems::<P>()
     }
 }
@@ -0,0 +1,215 @@
+//! Append-optimized timelines.
+//!
+//! A [`Timeline`] is an ordered log of [`Entry`]s that is expected to be appended to most of the
+//! time, with occasional out-of-order insertions into the past.
+//!
+//! Appended entries are labeled lazily: instead of allocating a priority for each appended entry,
+//! the timeline records them as a _run_ of pending entries following the last labeled entry (the
+//! _tail_). Pending entries are compared with each other by their offset within the run, and are
+//! always greater than every labeled entry. The run is only materialized into actual priorities
+//! when an out-of-order insertion needs to be placed inside of it.
+//!
+//! ## Usage
+//!
+//! ```rust
+//! # use order_maintenance::timeline::*;
+//! let log: Timeline = Timeline::new();
+//! let e0 = log.append();
+//! let e2 = log.append();
+//! let e1 = log.insert_after(&e0);
+//! let e3 = log.append();
+//!
+//! assert!(e0 < e1);
+//! assert!(e1 < e2);
+//! assert!(e2 < e3);
+//! ```
+use crate::tag_range;
+pub use crate::MaintainedOrd;
+use std::{
+    cell::RefCell,
+    cmp::Ordering,
+    fmt::Debug,
+    rc::{Rc, Weak},
+};
+
+/// Position of an entry in the timeline.
+#[derive(Debug)]
+enum Slot<P> {
+    /// Lazily labeled entry, at some offset in the pending run after the tail.
+    Pending(usize),
+    /// Entry that has been assigned an actual priority.
+    Labeled(P),
+}
+
+/// Shared state of a timeline.
+#[derive(Debug)]
+struct Inner<P> {
+    /// The greatest labeled priority; all pending entries come after it.
+    tail: P,
+    /// Entries appended after the tail that have not yet been assigned a priority.
+    run: Vec<Weak<RefCell<Slot<P>>>>,
+}
+
+impl<P: MaintainedOrd + Clone> Inner<P> {
+    /// Assign priorities to all pending entries in the run, in order.
+    fn materialize(&mut self) {
+        for slot in self.run.drain(..) {
+            // Entries that were dropped while pending never need a priority.
+            if let Some(slot) = slot.upgrade() {
+                let p = self.tail.insert();
+                *slot.borrow_mut() = Slot::Labeled(p.clone());
+                self.tail = p;
+            }
+        }
+    }
+
+    /// Append a new pending entry to the end of the run.
+    fn push(&mut self) -> Rc<RefCell<Slot<P>>> {
+        let slot = Rc::new(RefCell::new(Slot::Pending(self.run.len())));
+        self.run.push(Rc::downgrade(&slot));
+        slot
+    }
+
+    /// Insert a labeled entry immediately after the labeled priority `p`.
+    fn insert_labeled(&mut self, p: &P) -> Rc<RefCell<Slot<P>>> {
+        let new = p.insert();
+        // An entry right after the tail still comes before the pending run, which is labeled after
+        // whatever the tail is by the time it is materialized.
+        if *p == self.tail {
+            self.tail = new.clone();
+        }
+        Rc::new(RefCell::new(Slot::Labeled(new)))
+    }
+}
+
+/// An append-optimized ordered log.
+///
+/// Appending with [`Timeline::append()`] takes constant time and does not touch the underlying
+/// priorities at all. Inserting anywhere else with [`Timeline::insert_after()`] costs the same as
+/// inserting a priority of type `P`, plus a one-time cost to label the run of entries appended
+/// since the last out-of-order insertion (if the insertion lands inside of that run).
+///
+/// The priority type `P` defaults to [`tag_range::Priority`].
+#[derive(Debug)]
+pub struct Timeline<P = tag_range::Priority> {
+    /// The first priority of the timeline, which precedes all entries.
+    head: P,
+    inner: Rc<RefCell<Inner<P>>>,
+}
+
+impl<P: MaintainedOrd + Clone> Timeline<P> {
+    /// Construct a new, empty timeline.
+    pub fn new() -> Self {
+        let head = P::new();
+        Self {
+            inner: Rc::new(RefCell::new(Inner {
+                tail: head.clone(),
+                run: Vec::new(),
+            })),
+            head,
+        }
+    }
+
+    /// Append an entry after all other entries in the timeline.
+    pub fn append(&self) -> Entry<P> {
+        Entry {
+            slot: self.inner.borrow_mut().push(),
+            timeline: self.inner.clone(),
+        }
+    }
+
+    /// Insert an entry before all other entries in the timeline.
+    pub fn insert_front(&self) -> Entry<P> {
+        Entry {
+            slot: self.inner.borrow_mut().insert_labeled(&self.head),
+            timeline: self.inner.clone(),
+        }
+    }
+
+    /// Insert an entry immediately after `entry`.
+    ///
+    /// Panics if `entry` belongs to a different timeline.
+    pub fn insert_after(&self, entry: &Entry<P>) -> Entry<P> {
+        assert!(
+            Rc::ptr_eq(&self.inner, &entry.timeline),
+            "entry belongs to a different timeline"
+        );
+        let mut inner = self.inner.borrow_mut();
+
+        let offset = match &*entry.slot.borrow() {
+            Slot::Pending(offset) => Some(*offset),
+            Slot::Labeled(_) => None,
+        };
+
+        let slot = match offset {
+            // Inserting after the last pending entry is just an append.
+            Some(offset) if offset + 1 == inner.run.len() => inner.push(),
+            Some(_) => {
+                inner.materialize();
+                let p = entry.labeled().unwrap();
+                inner.insert_labeled(&p)
+            }
+            None => {
+                let p = entry.labeled().unwrap();
+                inner.insert_labeled(&p)
+            }
+        };
+
+        Entry {
+            slot,
+            timeline: self.inner.clone(),
+        }
+    }
+
+    /// Number of entries appended since the timeline was last labeled.
+    pub fn pending(&self) -> usize {
+        self.inner.borrow().run.len()
+    }
+}
+
+impl<P: MaintainedOrd + Clone> Default for Timeline<P> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// An entry in a [`Timeline`].
+///
+/// Entries can be cloned; clones compare equal to one another. Entries from different timelines
+/// cannot be compared with one another.
+#[derive(Debug, Clone)]
+pub struct Entry<P = tag_range::Priority> {
+    timeline: Rc<RefCell<Inner<P>>>,
+    slot: Rc<RefCell<Slot<P>>>,
+}
+
+impl<P: Clone> Entry<P> {
+    /// The priority of this entry, if it has been labeled.
+    fn labeled(&self) -> Option<P> {
+        match &*self.slot.borrow() {
+            Slot::Labeled(p) => Some(p.clone()),
+            Slot::Pending(_) => None,
+        }
+    }
+}
+
+impl<P: MaintainedOrd> PartialEq for Entry<P> {
+    fn eq(&self, other: &Self) -> bool {
+        self.partial_cmp(other) == Some(Ordering::Equal)
+    }
+}
+
+impl<P: MaintainedOrd> PartialOrd for Entry<P> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        if !Rc::ptr_eq(&self.timeline, &other.timeline) {
+            return None;
+        }
+        match (&*self.slot.borrow(), &*other.slot.borrow()) {
+            (Slot::Pending(a), Slot::Pending(b)) => a.partial_cmp(b),
+            // Labeled entries always precede the pending run.
+            (Slot::Labeled(_), Slot::Pending(_)) => Some(Ordering::Less),
+            (Slot::Pending(_), Slot::Labeled(_)) => Some(Ordering::Greater),
+            (Slot::Labeled(a), Slot::Labeled(b)) => a.partial_cmp(b),
+        }
+    }
+}
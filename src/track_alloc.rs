@@ -0,0 +1,74 @@
+//! Allocation tracking for arenas.
+//!
+//! All allocations of priorities in arenas are routed through this module. With the `track-alloc`
+//! feature, its functions are never inlined, so heap profilers like `dhat` or `heaptrack` attribute
+//! allocations to them in backtraces; each allocation is also reported to the hook installed by
+//! [`set_alloc_hook()`].
+use crate::internal::PriorityInner;
+use slab::Slab;
+
+/// An allocation event in an arena.
+#[cfg(feature = "track-alloc")]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AllocEvent {
+    /// The store of priorities in an arena grew to make room for more.
+    SlabGrow {
+        /// Capacity of the store before it grew.
+        old_capacity: usize,
+        /// Capacity of the store after it grew.
+        new_capacity: usize,
+    },
+    /// A priority was allocated in an arena.
+    NodeAlloc,
+    /// A priority was deallocated from an arena.
+    NodeFree,
+}
+
+#[cfg(feature = "track-alloc")]
+std::thread_local! {
+    static HOOK: std::cell::Cell<Option<fn(AllocEvent)>> = const { std::cell::Cell::new(None) };
+}
+
+/// Install a hook that is called on every allocation event in arenas on this thread, replacing any
+/// previous hook; or remove the hook, if `hook` is `None`.
+#[cfg(feature = "track-alloc")]
+pub fn set_alloc_hook(hook: Option<fn(AllocEvent)>) {
+    HOOK.with(|h| h.set(hook));
+}
+
+#[cfg(feature = "track-alloc")]
+fn report(event: AllocEvent) {
+    if let Some(hook) = HOOK.with(|h| h.get()) {
+        hook(event);
+    }
+}
+
+/// Allocate a priority in `priorities`, returning its key.
+#[cfg_attr(feature = "track-alloc", inline(never))]
+pub(crate) fn alloc_node(priorities: &mut Slab<PriorityInner>, node: PriorityInner) -> usize {
+    #[cfg(feature = "track-alloc")]
+    let old_capacity = priorities.capacity();
+
+    let key = priorities.insert(node);
+
+    #[cfg(feature = "track-alloc")]
+    {
+        if priorities.capacity() != old_capacity {
+            report(AllocEvent::SlabGrow {
+                old_capacity,
+                new_capacity: priorities.capacity(),
+            });
+        }
+        report(AllocEvent::NodeAlloc);
+    }
+    key
+}
+
+/// Deallocate the priority with `key` from `priorities`.
+#[cfg_attr(feature = "track-alloc", inline(never))]
+pub(crate) fn free_node(priorities: &mut Slab<PriorityInner>, key: usize) {
+    priorities.remove(key);
+
+    #[cfg(feature = "track-alloc")]
+    report(AllocEvent::NodeFree);
+}
@@ -0,0 +1,135 @@
+//! Small newtypes for the numbers that describe priorities and their arenas.
+//!
+//! Ranks, label epochs and raw labels are all plain integers underneath, but mixing them up is
+//! always a bug: a rank is not a label, and an epoch of one arena says nothing about another. These
+//! types keep them apart, while converting to and from their integers wherever those are needed:
+//!
+//! ```rust
+//! # use order_maintenance::{tag_range::Priority, types::Epoch, MaintainedOrd};
+//! let a = Priority::new();
+//! let snapshot = a.label_with_epoch();
+//! assert_eq!(snapshot.epoch(), a.label_epoch());
+//! assert_eq!(u64::from(a.label_epoch()), 0);
+//! assert!(snapshot.is_current(Epoch::new(0)));
+//! ```
+use std::fmt::Display;
+
+/// The number of relabeling passes performed in an arena, as of some point in time.
+///
+/// Labels of priorities in an arena can only change when its epoch does. Epochs are only
+/// meaningful for the arena they were read from.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Epoch(u64);
+
+impl Epoch {
+    /// The epoch after `passes` relabeling passes.
+    pub const fn new(passes: u64) -> Self {
+        Self(passes)
+    }
+
+    /// The number of relabeling passes that this epoch is after.
+    pub const fn get(self) -> u64 {
+        self.0
+    }
+
+    /// The epoch after one more relabeling pass.
+    pub const fn next(self) -> Self {
+        Self(self.0 + 1)
+    }
+}
+
+impl From<u64> for Epoch {
+    fn from(passes: u64) -> Self {
+        Self(passes)
+    }
+}
+
+impl From<Epoch> for u64 {
+    fn from(epoch: Epoch) -> Self {
+        epoch.0
+    }
+}
+
+impl Display for Epoch {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "epoch {}", self.0)
+    }
+}
+
+/// The rank of a priority in some order, i.e., the number of priorities less than it.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Rank(usize);
+
+impl Rank {
+    /// The rank of a priority with `less` priorities less than it.
+    pub const fn new(less: usize) -> Self {
+        Self(less)
+    }
+
+    /// The number of priorities less than a priority of this rank, e.g., for indexing a sorted
+    /// slice.
+    pub const fn get(self) -> usize {
+        self.0
+    }
+}
+
+impl From<usize> for Rank {
+    fn from(less: usize) -> Self {
+        Self(less)
+    }
+}
+
+impl From<Rank> for usize {
+    fn from(rank: Rank) -> Self {
+        rank.0
+    }
+}
+
+impl Display for Rank {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "rank {}", self.0)
+    }
+}
+
+/// The raw label of a priority, along with the epoch of its arena when the label was read.
+///
+/// The label is an implementation detail, only meaningful in comparison with other labels from the
+/// same arena and epoch; once the arena moves on to another epoch, it may be stale.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct LabelSnapshot {
+    label: usize,
+    epoch: Epoch,
+}
+
+impl LabelSnapshot {
+    /// A snapshot of the label `label`, read at epoch `epoch`.
+    pub const fn new(label: usize, epoch: Epoch) -> Self {
+        Self { label, epoch }
+    }
+
+    /// The raw label.
+    pub const fn label(self) -> usize {
+        self.label
+    }
+
+    /// The epoch of the arena when the label was read.
+    pub const fn epoch(self) -> Epoch {
+        self.epoch
+    }
+
+    /// Whether the label is still current in an arena at epoch `epoch`.
+    pub fn is_current(self, epoch: Epoch) -> bool {
+        self.epoch == epoch
+    }
+
+    /// The raw label and the epoch it was read at.
+    pub const fn into_parts(self) -> (usize, Epoch) {
+        (self.label, self.epoch)
+    }
+}
+
+impl From<LabelSnapshot> for (usize, Epoch) {
+    fn from(snapshot: LabelSnapshot) -> Self {
+        snapshot.into_parts()
+    }
+}
@@ -0,0 +1,81 @@
+//! Read-only views of priorities.
+//!
+//! An [`OrderView`] refers to a priority, but only supports comparing it and enumerating its
+//! arena; it offers no way to insert new priorities. Views can thus be handed to code that should
+//! be able to query an order, but not change it:
+//!
+//! ```rust
+//! # use order_maintenance::{tag_range::Priority, view::OrderView, MaintainedOrd};
+//! let a = Priority::new();
+//! let b = a.insert();
+//!
+//! let (va, vb) = (OrderView::of(&a), OrderView::of(&b));
+//! assert!(va < vb);
+//! assert!(vb.is(&b));
+//! assert_eq!(va.arena_views(), [va.clone(), vb.clone()]);
+//! ```
+//!
+//! Views do not implement [`MaintainedOrd`](crate::MaintainedOrd), so inserting through them does
+//! not compile:
+//!
+//! ```rust,compile_fail
+//! # use order_maintenance::{tag_range::Priority, view::OrderView, MaintainedOrd};
+//! let view = OrderView::of(&Priority::new());
+//! let _ = view.insert();
+//! ```
+//!
+//! A view keeps the priority it refers to alive, like any other handle, but never keeps any other
+//! priority alive.
+use crate::id::{IdOrder, Identified, PriorityId};
+use crate::InArena;
+use std::cmp::Ordering;
+
+/// A read-only view of a priority of type `P`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct OrderView<P>(P);
+
+impl<P: Clone> OrderView<P> {
+    /// A view of the priority `p`.
+    pub fn of(p: &P) -> Self {
+        Self(p.clone())
+    }
+}
+
+impl<P: PartialOrd> OrderView<P> {
+    /// Whether this view refers to the priority `p`, i.e., one that compares equal to it.
+    pub fn is(&self, p: &P) -> bool {
+        self.0 == *p
+    }
+
+    /// Compare the priority that this view refers to with the priority `p`.
+    pub fn partial_cmp_priority(&self, p: &P) -> Option<Ordering> {
+        self.0.partial_cmp(p)
+    }
+}
+
+impl<P: InArena> OrderView<P> {
+    /// Views of every priority in the arena of the priority that this view refers to, in
+    /// increasing order.
+    pub fn arena_views(&self) -> Vec<Self> {
+        self.0.arena_priorities().into_iter().map(Self).collect()
+    }
+}
+
+impl<P: Identified> OrderView<P> {
+    /// The identifier of the priority that this view refers to.
+    pub fn id(&self) -> PriorityId {
+        self.0.id()
+    }
+
+    /// Borrow the arena of the priority that this view refers to, for comparing the identifiers
+    /// of its priorities; see [`id`](crate::id).
+    pub fn id_order(&self) -> IdOrder<'_> {
+        self.0.id_order()
+    }
+}
+
+impl<P: PartialOrd> PartialOrd for OrderView<P> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        self.0.partial_cmp(&other.0)
+    }
+}
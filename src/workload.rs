@@ -0,0 +1,291 @@
+//! Scripted workloads, for reproducing and triaging bugs.
+//!
+//! A workload is a script of [`Step`]s that refer to priorities by their position among the live
+//! priorities, so any subsequence of a script is also a valid script. This lets [`minimize()`]
+//! shrink a failing script by deleting steps, until no single step can be deleted:
+//!
+//! ```rust
+//! # use order_maintenance::workload::{minimize, run, Step};
+//! # use order_maintenance::{tag_range::Priority, MaintainedOrd};
+//! let script: Vec<Step> = (0..100).map(|i| Step::Insert(i / 2)).collect();
+//! // Pretend that there is a bug whenever more than 10 priorities are alive.
+//! let fails = |script: &[Step]| run::<Priority>(script).len() > 10;
+//! assert_eq!(minimize(&script, fails).len(), 10);
+//! ```
+//!
+//! A script can also be [`simulate`]d, to estimate its cost without allocating any priorities:
+//!
+//! ```rust
+//! # use order_maintenance::workload::{simulate, Algorithm, Step};
+//! let script: Vec<Step> = (0..10_000).map(|_| Step::Insert(0)).collect();
+//! let report = simulate(&script, Algorithm::ListRange);
+//! assert_eq!(report.peak_live, 10_001);
+//! assert!(report.relabels > 0);
+//! ```
+//!
+//! Scripts can be [`save`]d to and [`load`]ed from files, so that a failing script, e.g., one
+//! shrunk by quickcheck, can be checked in and replayed as a regression test. The format is plain
+//! text, with one step per line, written `insert <position>` or `drop <position>`; blank lines and
+//! lines starting with `#` are ignored:
+//!
+//! ```rust
+//! # use order_maintenance::workload::{parse, to_text, Step};
+//! let text = "# two inserts and a drop\ninsert 0\ninsert 1\n\ndrop 0\n";
+//! let script = parse(text).unwrap();
+//! assert_eq!(script, [Step::Insert(0), Step::Insert(1), Step::Drop(0)]);
+//! assert_eq!(parse(&to_text(&script)).unwrap(), script);
+//! ```
+use crate::internal::{Arena, Label, PriorityInner, PriorityKey};
+use crate::policy::Cursor;
+use crate::{list_range, params::Params, tag_range, MaintainedOrd};
+use std::fmt::Display;
+use std::path::Path;
+use std::str::FromStr;
+
+/// A step of a workload.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Step {
+    /// Insert a priority after the live priority at this position.
+    Insert(usize),
+    /// Drop the live priority at this position.
+    Drop(usize),
+}
+
+impl Display for Step {
+    /// Formats the step as a line of a workload file, without the line break.
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Step::Insert(i) => write!(f, "insert {i}"),
+            Step::Drop(i) => write!(f, "drop {i}"),
+        }
+    }
+}
+
+/// Error returned when parsing a malformed workload.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseError {
+    /// Number of the line at which parsing failed, starting from 1.
+    pub line: usize,
+}
+
+impl Display for ParseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "malformed workload step at line {}", self.line)
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+impl FromStr for Step {
+    type Err = ParseError;
+
+    /// Parses a single step, as formatted by [`Display`]; the error is always at line 1.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let err = ParseError { line: 1 };
+        let (op, position) = s.trim().split_once(' ').ok_or(err.clone())?;
+        let position = position.trim().parse().map_err(|_| err.clone())?;
+        match op {
+            "insert" => Ok(Step::Insert(position)),
+            "drop" => Ok(Step::Drop(position)),
+            _ => Err(err),
+        }
+    }
+}
+
+/// Format a script as the contents of a workload file.
+pub fn to_text(script: &[Step]) -> String {
+    script.iter().map(|step| format!("{step}\n")).collect()
+}
+
+/// Parse the contents of a workload file.
+pub fn parse(text: &str) -> Result<Vec<Step>, ParseError> {
+    text.lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty() && !line.trim_start().starts_with('#'))
+        .map(|(i, line)| line.parse().map_err(|_| ParseError { line: i + 1 }))
+        .collect()
+}
+
+/// Write a script to a workload file at `path`, replacing it if it exists.
+pub fn save(path: impl AsRef<Path>, script: &[Step]) -> std::io::Result<()> {
+    std::fs::write(path, to_text(script))
+}
+
+/// Read a script from a workload file at `path`, e.g., to replay it with [`run()`].
+///
+/// Malformed files are reported as errors of kind [`std::io::ErrorKind::InvalidData`], wrapping a
+/// [`ParseError`].
+pub fn load(path: impl AsRef<Path>) -> std::io::Result<Vec<Step>> {
+    let text = std::fs::read_to_string(path)?;
+    parse(&text).map_err(|err| std::io::Error::new(std::io::ErrorKind::InvalidData, err))
+}
+
+/// Run a workload in a fresh arena, returning the live priorities at its end, in order.
+///
+/// Steps that refer to positions past the last live priority are skipped, as is dropping the last
+/// live priority, so that every script is valid.
+pub fn run<P: MaintainedOrd>(script: &[Step]) -> Vec<P> {
+    let mut ps = vec![P::new()];
+    for &step in script {
+        match step {
+            Step::Insert(i) if i < ps.len() => {
+                let p = ps[i].insert();
+                ps.insert(i + 1, p);
+            }
+            Step::Drop(i) if i < ps.len() && ps.len() > 1 => {
+                ps.remove(i);
+            }
+            _ => (),
+        }
+    }
+    ps
+}
+
+/// Shrink a script while preserving a failure, by delta debugging.
+///
+/// `fails` must return whether a script fails; if `script` does not fail, it is returned as is.
+/// Otherwise, the result is a failing subsequence of `script` from which no single step can be
+/// deleted while preserving the failure. This calls `fails` `O(n^2)` times in the worst case,
+/// but typically only `O(log n)` times for each step of the result.
+pub fn minimize<T: Clone>(script: &[T], mut fails: impl FnMut(&[T]) -> bool) -> Vec<T> {
+    let mut script = script.to_vec();
+    if !fails(&script) {
+        return script;
+    }
+    // Number of chunks that the script is split into.
+    let mut n = 2;
+    while script.len() >= 2 {
+        let chunk = script.len().div_ceil(n);
+        let chunks = (0..script.len())
+            .step_by(chunk)
+            .map(|lo| lo..script.len().min(lo + chunk));
+
+        let mut reduced = None;
+        for range in chunks.clone() {
+            if fails(&script[range.clone()]) {
+                reduced = Some((script[range].to_vec(), 2));
+                break;
+            }
+        }
+        if reduced.is_none() && n > 2 {
+            for range in chunks {
+                let complement = [&script[..range.start], &script[range.end..]].concat();
+                if fails(&complement) {
+                    reduced = Some((complement, (n - 1).max(2)));
+                    break;
+                }
+            }
+        }
+
+        match reduced {
+            Some((smaller, chunks)) => {
+                script = smaller;
+                n = chunks;
+            }
+            None if n >= script.len() => break,
+            None => n = (2 * n).min(script.len()),
+        }
+    }
+    // The loop above never tries to delete the last step.
+    if script.len() == 1 && fails(&[]) {
+        script.clear();
+    }
+    script
+}
+
+/// An algorithm whose cost can be [`simulate`]d, with its default parameters.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Algorithm {
+    /// [Tag-range](crate::tag_range) relabeling.
+    TagRange,
+    /// [List-range](crate::list_range) relabeling.
+    ListRange,
+}
+
+/// The estimated cost of a workload, as [`simulate`]d.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CostReport {
+    /// Number of insertions performed.
+    pub inserts: usize,
+    /// Number of relabeling passes.
+    pub relabels: u64,
+    /// Total number of priorities relabeled by those passes.
+    pub relabeled: u64,
+    /// Greatest number of priorities alive at once.
+    pub peak_live: usize,
+    /// Greatest size of the arena's storage for priorities, in bytes.
+    ///
+    /// This excludes handles to priorities, and anything that they are stored in.
+    pub peak_bytes: usize,
+}
+
+/// Estimate the cost of running a workload with an algorithm.
+///
+/// This runs the algorithm on a bare arena, without any handles to its priorities, so it is
+/// cheaper than running the workload with actual priorities, but relabels exactly the same way.
+/// Steps are interpreted like by [`run()`].
+///
+/// Panics if the algorithm runs out of labels, like actual priorities would.
+pub fn simulate(script: &[Step], algo: Algorithm) -> CostReport {
+    type Tag = tag_range::Priority;
+    type List = list_range::Priority;
+    match algo {
+        // The base priority is the first tag-range priority.
+        Algorithm::TagRange => simulate_with(
+            script,
+            |arena| arena.base(),
+            |at| {
+                Tag::relabel(at);
+                Tag::next_label(at)
+            },
+        ),
+        // The base priority is a sentinel that precedes every list-range priority.
+        Algorithm::ListRange => simulate_with(
+            script,
+            |arena| {
+                let first = Label::max_for_bits(list_range::DefaultParams::BITS) / 2;
+                arena.insert_after(first, arena.base())
+            },
+            |at| {
+                List::relabel(at);
+                List::next_label(at)
+            },
+        ),
+    }
+}
+
+/// Run a workload on a bare arena, starting from the priority allocated by `first`, and inserting
+/// each priority with the label computed by `insert`, which may relabel others.
+fn simulate_with(
+    script: &[Step],
+    first: impl FnOnce(&mut Arena) -> PriorityKey,
+    mut insert: impl FnMut(&mut Cursor<'_>) -> Label,
+) -> CostReport {
+    let mut arena = Arena::new();
+    let mut keys = vec![first(&mut arena)];
+    let mut report = CostReport::default();
+    let peak = |report: &mut CostReport, arena: &Arena, live| {
+        report.peak_live = report.peak_live.max(live);
+        let bytes = arena.capacity() * std::mem::size_of::<PriorityInner>();
+        report.peak_bytes = report.peak_bytes.max(bytes);
+    };
+    peak(&mut report, &arena, keys.len());
+    for &step in script {
+        match step {
+            Step::Insert(i) if i < keys.len() => {
+                let label = insert(&mut Cursor::new(&mut arena, keys[i], 0));
+                let key = arena.insert_after(label, keys[i]);
+                keys.insert(i + 1, key);
+                report.inserts += 1;
+            }
+            Step::Drop(i) if i < keys.len() && keys.len() > 1 => {
+                arena.remove(keys.remove(i));
+            }
+            _ => (),
+        }
+        peak(&mut report, &arena, keys.len());
+    }
+    report.relabels = arena.label_epoch();
+    report.relabeled = arena.relabeled();
+    report
+}
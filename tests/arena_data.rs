@@ -0,0 +1,88 @@
+use order_maintenance::{list_range, tag_range, InArena, MaintainedOrd};
+use std::cell::Cell;
+use std::rc::Rc;
+
+/// Bookkeeping that counts how many times it was dropped.
+struct Bookkeeping(Rc<Cell<usize>>);
+
+impl Drop for Bookkeeping {
+    fn drop(&mut self) {
+        self.0.set(self.0.get() + 1);
+    }
+}
+
+macro_rules! arena_data_tests {
+    ($name:ident, $P:ty) => {
+        #[test]
+        fn $name() {
+            let drops = Rc::new(Cell::new(0));
+            let first = <$P>::with_arena_data(Bookkeeping(drops.clone()));
+            let second = first.insert();
+            drop(first);
+
+            let data = second.arena_data::<Bookkeeping>().unwrap();
+            assert!(Rc::ptr_eq(&data.0, &drops));
+            assert!(second.arena_data::<String>().is_none());
+            assert!(<$P>::new().arena_data::<Bookkeeping>().is_none());
+
+            // The data lives exactly as long as the arena.
+            drop(data);
+            assert_eq!(drops.get(), 0);
+            drop(second);
+            assert_eq!(drops.get(), 1);
+        }
+    };
+}
+
+arena_data_tests!(tag_range_arena_data, tag_range::Priority);
+arena_data_tests!(list_range_arena_data, list_range::Priority);
+
+macro_rules! meta_tests {
+    ($name:ident, $P:ty) => {
+        #[test]
+        fn $name() {
+            let drops = Rc::new(Cell::new(0));
+            let first = <$P>::new();
+            let second = first.insert();
+            let also_second = second.clone();
+            second.set_meta(Bookkeeping(drops.clone()));
+            first.set_meta("first");
+
+            let meta = also_second.meta::<Bookkeeping>().unwrap();
+            assert!(Rc::ptr_eq(&meta.0, &drops));
+            assert!(second.meta::<String>().is_none());
+            assert_eq!(*first.meta::<&str>().unwrap(), "first");
+            assert!(first.insert().meta::<&str>().is_none());
+
+            // The metadata lives exactly as long as its priority.
+            drop(meta);
+            drop(second);
+            assert_eq!(drops.get(), 0);
+            drop(also_second);
+            assert_eq!(drops.get(), 1);
+
+            // Its key may be reused, but not its metadata.
+            let third = first.insert();
+            assert!(third.meta::<Bookkeeping>().is_none());
+
+            third.set_meta(Bookkeeping(drops.clone()));
+            third.set_meta(1_u32);
+            assert_eq!(drops.get(), 2);
+            third.clear_meta();
+            assert!(third.meta::<u32>().is_none());
+        }
+    };
+}
+
+meta_tests!(tag_range_meta, tag_range::Priority);
+meta_tests!(list_range_meta, list_range::Priority);
+
+/// Metadata may hold handles to other priorities in the same arena.
+#[test]
+fn meta_holding_priorities() {
+    let first = tag_range::Priority::new();
+    let second = first.insert();
+    second.set_meta(first.insert());
+    drop(second);
+    assert_eq!(first.arena_priorities().len(), 1);
+}
@@ -1,33 +1,28 @@
-mod common;
+#![cfg(feature = "big")]
 
-use order_maintenance::big::UniquePriority;
+mod common;
 
-macro_rules! delegate_tests {
-    () => {};
-    (fn $test_name:ident(); $($toks:tt)*) => {
-        #[test]
-        fn $test_name() {
-            common::tests::$test_name::<UniquePriority>();
-        }
-        delegate_tests!{$($toks)*}
-    };
-}
+use order_maintenance::{big::UniquePriority, om_delegate};
 
-delegate_tests! {
-    fn compare_two();
-    fn insertion();
-    fn transitive();
-    fn drop_first();
-    fn drop_middle(); // Something wrong with this
+om_delegate! {
+    UniquePriority => tests [
+        compare_two,
+        insertion,
+        transitive,
+        drop_first,
+        drop_middle, // Something wrong with this
+        default_new,
 
-    // These only work if SOME/MANY is dropped to less than 64
-    fn drop_some();
-    fn drop_random();
-    fn insert_some_begin();
-    fn insert_some_end();
-    fn insert_some_flipflop();
-    fn insert_many_begin();
-    fn insert_many_end();
-    fn insert_some_begin_many_end();
-    fn insert_many_random();
+        // These only work if SOME/MANY is dropped to less than 64
+        drop_some,
+        drop_random,
+        insert_some_begin,
+        insert_some_end,
+        insert_some_flipflop,
+        insert_many_begin,
+        insert_many_end,
+        insert_some_begin_many_end,
+        insert_many_random,
+        new_sequence,
+    ]
 }
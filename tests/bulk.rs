@@ -0,0 +1,40 @@
+//! Integration tests for bulk loading.
+
+use order_maintenance::bulk::BulkLoader;
+use order_maintenance::{list_range, tag_range, MaintainedOrd};
+use rand::{Rng, SeedableRng};
+
+/// Load random records in chunks, and check them against sorting them directly.
+fn load_random<P: MaintainedOrd>(chunk_size: usize) {
+    let mut rng = rand::rngs::StdRng::seed_from_u64(0);
+    let records: Vec<(usize, usize)> = (0..5000).map(|i| (rng.gen_range(0..1000), i)).collect();
+    let mut loader = BulkLoader::with_chunk_size(chunk_size);
+    loader.extend(records.iter().copied());
+    assert_eq!(loader.len(), records.len());
+    assert_eq!(loader.sorted_chunks(), records.len() / chunk_size);
+
+    let loaded: Vec<(usize, usize, P)> = loader.finish();
+    let mut expected = records;
+    expected.sort_by_key(|r| r.0);
+    let keys: Vec<_> = loaded.iter().map(|(k, v, _)| (*k, *v)).collect();
+    assert_eq!(keys, expected);
+    assert!(loaded.windows(2).all(|w| w[0].2 < w[1].2));
+}
+
+#[test]
+fn tag_range_bulk_load() {
+    load_random::<tag_range::Priority>(64);
+    load_random::<tag_range::Priority>(5000);
+}
+
+#[test]
+fn list_range_bulk_load() {
+    load_random::<list_range::Priority>(7);
+}
+
+#[test]
+fn empty_load() {
+    let loader = BulkLoader::<u8, ()>::new();
+    assert!(loader.is_empty());
+    assert!(loader.finish::<tag_range::Priority>().is_empty());
+}
@@ -0,0 +1,67 @@
+//! Integration tests for capability marker traits.
+
+use order_maintenance::capability::{ConstantTimeCompare, ThreadSafe};
+use order_maintenance::descending::Descending;
+use order_maintenance::policy::{CustomPriority, TagRangePolicy};
+use order_maintenance::{list_range, tag_range, MaintainedOrd};
+use std::rc::Rc;
+
+/// Count the ascending pairs among `n` priorities, for algorithms that compare cheaply.
+fn ascending_pairs<P: MaintainedOrd + ConstantTimeCompare>(n: usize) -> usize {
+    P::new_sequence(n)
+        .windows(2)
+        .filter(|w| w[0] < w[1])
+        .count()
+}
+
+/// Insert `n` priorities after the first, for algorithms that never run out of labels.
+#[cfg(feature = "big")]
+fn insert_many<P: MaintainedOrd + order_maintenance::capability::UnboundedCapacity>(
+    n: usize,
+) -> Vec<P> {
+    let first = P::new();
+    (0..n).map(|_| first.insert()).collect()
+}
+
+fn assert_thread_safe<T: ThreadSafe>() {}
+
+#[test]
+fn constant_time_compare() {
+    assert_eq!(ascending_pairs::<tag_range::Priority>(10), 9);
+    assert_eq!(ascending_pairs::<list_range::Priority>(10), 9);
+    assert_eq!(
+        ascending_pairs::<CustomPriority<TagRangePolicy<tag_range::DefaultParams>>>(10),
+        9
+    );
+    assert_eq!(ascending_pairs::<Rc<tag_range::Priority>>(10), 9);
+    assert_eq!(ascending_pairs::<Descending<list_range::Priority>>(10), 9);
+    #[cfg(feature = "naive")]
+    assert_eq!(ascending_pairs::<order_maintenance::naive::Priority>(10), 9);
+}
+
+#[cfg(feature = "big")]
+#[test]
+fn unbounded_capacity() {
+    use order_maintenance::big::Priority;
+    let ps = insert_many::<Priority>(10);
+    assert!(ps.windows(2).all(|w| w[1] < w[0]));
+    let ps = insert_many::<Descending<Rc<Priority>>>(10);
+    assert!(ps.windows(2).all(|w| w[0] < w[1]));
+}
+
+#[test]
+fn thread_safe_is_send_and_sync() {
+    // Plain data that is a priority, but does not live in an arena.
+    #[derive(PartialEq, PartialOrd)]
+    struct Plain(u32);
+    impl MaintainedOrd for Plain {
+        fn new() -> Self {
+            Self(0)
+        }
+        fn insert(&self) -> Self {
+            Self(self.0 + 1)
+        }
+    }
+    assert_thread_safe::<Plain>();
+    assert_thread_safe::<Descending<Plain>>();
+}
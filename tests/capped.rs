@@ -0,0 +1,63 @@
+//! Integration tests for tag-range priorities whose arenas hold a bounded number of priorities.
+
+use order_maintenance::capability::{CapacityErrorKind, TryInsert, TryInsertFront};
+use order_maintenance::tag_range::CappedPriority;
+
+#[test]
+fn fills_up_and_frees() {
+    type P = CappedPriority<100>;
+    let p0 = P::new();
+    let mut ps = vec![p0.clone()];
+    while let Ok(p) = p0.try_insert() {
+        ps.push(p);
+    }
+    assert_eq!(ps.len(), P::CAPACITY);
+    assert!(p0.is_full());
+    let e = p0.try_insert_front().unwrap_err();
+    assert_eq!(e.kind(), CapacityErrorKind::LiveQuota { max: 100 });
+    assert_eq!(e.live(), 100);
+
+    // Inserting right after the first priority every time orders them in reverse.
+    for w in ps[1..].windows(2) {
+        assert!(w[1] < w[0]);
+    }
+
+    ps.truncate(50);
+    assert_eq!(p0.arena_len(), 50);
+    let front = p0.try_insert_front().unwrap();
+    assert!(ps.iter().all(|p| front < *p));
+}
+
+#[test]
+fn small_label_space_never_panics() {
+    // With a threshold of 1.9, the label space only has capacity for a couple dozen priorities.
+    type P = CappedPriority<20, 19>;
+    let p0 = P::new();
+    let mut ps = vec![p0];
+    let mut state = 7usize;
+    for _ in 0..10_000 {
+        state = state
+            .wrapping_mul(6364136223846793005)
+            .wrapping_add(1442695040888963407);
+        let i = (state >> 33) % ps.len();
+        match ps[i].try_insert() {
+            Ok(p) => ps.push(p),
+            Err(_) => {
+                ps.swap_remove((state >> 13) % ps.len());
+            }
+        }
+    }
+    for (i, a) in ps.iter().enumerate() {
+        for b in &ps[i + 1..] {
+            assert_ne!(a, b);
+            assert!(a < b || b < a);
+        }
+    }
+}
+
+#[test]
+#[should_panic(expected = "Quota of 3 live priorities was reached")]
+fn insert_panics_once_full() {
+    use order_maintenance::MaintainedOrd;
+    CappedPriority::<3>::new_sequence(4);
+}
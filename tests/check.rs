@@ -0,0 +1,55 @@
+//! Integration tests for invariant checks.
+
+use order_maintenance::{list_range, tag_range, MaintainedOrd};
+use rand::{Rng, SeedableRng};
+use std::num::NonZeroU64;
+
+/// Perform random insertions and removals, calling `check` after each one.
+fn random_ops<P: MaintainedOrd + Clone>(first: P, mut check: impl FnMut(&P)) -> Vec<P> {
+    let mut rng = rand::rngs::StdRng::seed_from_u64(0);
+    let mut ps = vec![first];
+    for _ in 0..2000 {
+        let i = rng.gen_range(0..ps.len());
+        if ps.len() > 1 && rng.gen_range(0..3) == 0 {
+            ps.swap_remove(i);
+        } else {
+            let p = ps[i].insert();
+            ps.push(p);
+        }
+        check(&ps[0]);
+    }
+    ps
+}
+
+#[test]
+fn tag_range_invariants() {
+    random_ops(tag_range::Priority::new(), |p| {
+        assert_eq!(p.check_invariants(), Ok(()))
+    });
+
+    let first = tag_range::Priority::new();
+    first.set_self_check(NonZeroU64::new(10));
+    let ps = random_ops(first, |_| ());
+    let report = ps[0].self_check_report();
+    assert!(report.checks > 100);
+    assert_eq!(report.failures, 0);
+    assert_eq!(report.first_violation, None);
+}
+
+#[test]
+fn list_range_invariants() {
+    random_ops(list_range::Priority::new(), |p| {
+        assert_eq!(p.check_invariants(), Ok(()))
+    });
+
+    let first = list_range::Priority::new();
+    first.set_self_check(NonZeroU64::new(10));
+    let ps = random_ops(first, |_| ());
+    assert!(ps[0].self_check_report().checks > 100);
+    assert_eq!(ps[0].self_check_report().failures, 0);
+
+    ps[0].set_self_check(None);
+    let checks = ps[0].self_check_report().checks;
+    let _ = ps[0].insert();
+    assert_eq!(ps[0].self_check_report().checks, checks);
+}
@@ -0,0 +1,64 @@
+//! Integration tests for the `checkpoint` feature.
+#![cfg(all(feature = "checkpoint", feature = "introspect"))]
+
+use order_maintenance::checkpoint::Checkpointed;
+use order_maintenance::introspect::Introspect;
+use order_maintenance::{list_range, tag_range};
+use rand::{Rng, SeedableRng};
+use std::collections::HashMap;
+
+/// Perform random operations on an arena between checkpoints, and check that applying the changes
+/// since each checkpoint to a copy of the labels brings that copy up to date.
+fn changes_reproduce_labels<P: Checkpointed + Introspect + order_maintenance::MaintainedOrd>() {
+    let labels = |ps: &[P]| -> HashMap<usize, usize> {
+        ps.iter().map(|p| (p.change_key(), p.raw_label())).collect()
+    };
+
+    let mut rng = rand::rngs::StdRng::seed_from_u64(0);
+    let mut ps = P::new_sequence(16);
+    let mut token = ps[0].checkpoint();
+    let mut copy = labels(&ps);
+
+    for round in 0..20 {
+        for _ in 0..50 {
+            let i = rng.gen_range(0..ps.len());
+            match rng.gen_range(0..3) {
+                0 | 1 => ps.push(ps[i].insert()),
+                _ if ps.len() > 1 => drop(ps.swap_remove(i)),
+                _ => (),
+            }
+        }
+
+        let changes = ps[0].changes_since(&token).unwrap();
+        for key in &changes.removed {
+            assert!(copy.remove(key).is_some());
+        }
+        for node in changes.inserted.iter().chain(&changes.relabeled) {
+            copy.insert(node.key, node.label);
+        }
+        // List-range arenas also track their base priority, which no handle refers to.
+        copy.retain(|key, _| ps.iter().any(|p| p.change_key() == *key));
+        assert_eq!(copy, labels(&ps));
+
+        // Persist incrementally, discarding every other round's log.
+        let next = ps[0].checkpoint();
+        assert!(ps[0].changes_since(&next).unwrap().is_empty());
+        if round % 2 == 0 {
+            ps[0].discard_changes_before(&next);
+            assert!(ps[0].changes_since(&token).is_none());
+        }
+        token = next;
+    }
+
+    assert!(P::new().changes_since(&token).is_none());
+}
+
+#[test]
+fn tag_range_changes() {
+    changes_reproduce_labels::<tag_range::Priority>();
+}
+
+#[test]
+fn list_range_changes() {
+    changes_reproduce_labels::<list_range::Priority>();
+}
@@ -0,0 +1,59 @@
+//! Integration tests for MVCC commit timestamps.
+
+use order_maintenance::commit_order::CommitOrder;
+use order_maintenance::list_range;
+
+#[test]
+fn snapshot_isolation() {
+    let order: CommitOrder = CommitOrder::new();
+
+    let t1 = order.begin();
+    let w1 = t1.timestamp();
+    let t2 = order.begin();
+    let c1 = t1.commit();
+    let t3 = order.begin();
+
+    assert!(c1.is_committed());
+    assert_eq!(w1, c1);
+    assert!(!t2.sees(&w1));
+    assert!(t3.sees(&w1));
+}
+
+#[test]
+fn commit_order_is_total() {
+    let order: CommitOrder<list_range::Priority> = CommitOrder::new();
+
+    let ts: Vec<_> = (0..100).map(|_| order.begin()).collect();
+    let mut cs = vec![];
+    // Commit in reverse order of beginning.
+    for t in ts.into_iter().rev() {
+        cs.push(t.commit());
+    }
+
+    for i in 0..cs.len() - 1 {
+        assert!(cs[i] < cs[i + 1], "cs[{}] < cs[{}]", i, i + 1);
+    }
+}
+
+#[test]
+fn provisional_and_aborted() {
+    let order: CommitOrder = CommitOrder::new();
+
+    let t1 = order.begin();
+    let t2 = order.begin();
+    let t3 = order.begin();
+    let w1 = t1.timestamp();
+    let w2 = t2.timestamp();
+    let c3 = t3.commit();
+
+    assert!(c3 < w1);
+    assert_eq!(w1.partial_cmp(&w2), None);
+
+    t1.abort();
+    assert!(w1.is_aborted());
+    assert_eq!(w1.partial_cmp(&c3), None);
+
+    drop(t2);
+    assert!(w2.is_aborted());
+    assert!(!order.begin().sees(&w2));
+}
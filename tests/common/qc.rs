@@ -1,5 +1,5 @@
 #![allow(dead_code)]
-use order_maintenance::MaintainedOrd;
+use order_maintenance::{workload, MaintainedOrd};
 use quickcheck::{Arbitrary, Gen};
 use std::fmt::Debug;
 use std::rc::Rc;
@@ -7,6 +7,9 @@ use std::vec::Vec;
 
 const MAX_DECISIONS: usize = 10000;
 
+/// Differential tests compare every pair of priorities, so they make fewer decisions.
+const MAX_FEW_DECISIONS: usize = 500;
+
 #[derive(Debug, Clone, Copy)]
 pub enum Decision {
     Insert(usize),
@@ -43,13 +46,45 @@ impl Decisions {
         }
         ps
     }
+
+    /// The decisions as a workload script, which [`workload::run()`] replays identically.
+    pub fn script(&self) -> Vec<workload::Step> {
+        self.decisions.as_slice()[..self.len]
+            .iter()
+            .map(|&d| match d {
+                Decision::Insert(i) => workload::Step::Insert(i),
+                Decision::Drop(i) => workload::Step::Drop(i),
+            })
+            .collect()
+    }
+
+    /// Save the decisions to a workload file named after `name`, for turning a failure into a
+    /// regression test.
+    ///
+    /// Quickcheck shrinks a failure by retrying ever smaller decisions, each overwriting the file,
+    /// so the file ends up holding the smallest failing decisions it found.
+    fn save_failure(&self, name: &str) {
+        let name: String = name
+            .chars()
+            .map(|c| if c.is_alphanumeric() { c } else { '_' })
+            .collect();
+        let path =
+            std::path::Path::new(env!("CARGO_TARGET_TMPDIR")).join(format!("{name}.workload"));
+        match workload::save(&path, &self.script()) {
+            Ok(()) => println!("Saved failing workload to {}", path.display()),
+            Err(err) => println!(
+                "Could not save failing workload to {}: {err}",
+                path.display()
+            ),
+        }
+    }
 }
 
-impl Arbitrary for Decisions {
-    fn arbitrary(g: &mut Gen) -> Self {
+impl Decisions {
+    fn arbitrary_up_to(g: &mut Gen, max: usize) -> Self {
         let mut ds = vec![];
         let mut size: usize = 1;
-        let n: usize = usize::arbitrary(g) % MAX_DECISIONS;
+        let n: usize = usize::arbitrary(g) % max;
         // let n: usize = g.size(); // TODO: use quickcheck size rather than our own
         for _ in 0..n {
             if size > 1 && bool::arbitrary(g) {
@@ -65,6 +100,12 @@ impl Arbitrary for Decisions {
             decisions: Rc::new(ds),
         }
     }
+}
+
+impl Arbitrary for Decisions {
+    fn arbitrary(g: &mut Gen) -> Self {
+        Self::arbitrary_up_to(g, MAX_DECISIONS)
+    }
 
     fn shrink(&self) -> Box<dyn Iterator<Item = Self>> {
         let mut lens = Vec::new();
@@ -105,6 +146,48 @@ pub fn run_and_check<Priority: MaintainedOrd>(ds: Decisions) -> bool {
         }
     }
     if !success {
+        ds.save_failure(std::any::type_name::<Priority>());
+        // Makes divisions clearer
+        println!("Among set of {} priorities\n------", ps.len());
+    }
+    success
+}
+
+/// Decisions for differential tests, which are limited to `MAX_FEW_DECISIONS`.
+#[derive(Debug, Clone)]
+pub struct FewDecisions(pub Decisions);
+
+impl Arbitrary for FewDecisions {
+    fn arbitrary(g: &mut Gen) -> Self {
+        FewDecisions(Decisions::arbitrary_up_to(g, MAX_FEW_DECISIONS))
+    }
+
+    fn shrink(&self) -> Box<dyn Iterator<Item = Self>> {
+        Box::new(self.0.shrink().map(FewDecisions))
+    }
+}
+
+/// Run the same decisions against two implementations, checking that they agree on every
+/// pairwise comparison.
+pub fn run_and_compare<P: MaintainedOrd, Q: MaintainedOrd>(ds: Decisions) -> bool {
+    let ps: Vec<P> = ds.generate_priorities();
+    let qs: Vec<Q> = ds.generate_priorities();
+    let mut success = true;
+    for i in 0..ps.len() {
+        for j in 0..ps.len() {
+            let (p, q) = (ps[i].partial_cmp(&ps[j]), qs[i].partial_cmp(&qs[j]));
+            if p != q {
+                println!("Error: ps[{i}] vs ps[{j}] is {p:?}, but qs[{i}] vs qs[{j}] is {q:?}");
+                success = false;
+            }
+        }
+    }
+    if !success {
+        ds.save_failure(&format!(
+            "{}_vs_{}",
+            std::any::type_name::<P>(),
+            std::any::type_name::<Q>()
+        ));
         // Makes divisions clearer
         println!("Among set of {} priorities\n------", ps.len());
     }
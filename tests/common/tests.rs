@@ -159,3 +159,87 @@ pub fn insert_many_random<Priority: MaintainedOrd>() {
     let mut rng = StdRng::seed_from_u64(42);
     do_insert::<Priority>(MANY, |n| rng.gen_range(0..n.max(1)));
 }
+
+pub fn new_sequence<Priority: MaintainedOrd>() {
+    for n in [0, 1, 2, 3, SOME] {
+        let ps = Priority::new_sequence(n);
+        assert_eq!(ps.len(), n);
+        for i in 0..n.saturating_sub(1) {
+            assert!(ps[i] < ps[i + 1], "ps[{}] < ps[{}]", i, i + 1);
+        }
+    }
+
+    // Insertion still works after constructing a sequence
+    let mut ps = Priority::new_sequence(SOME);
+    for i in 0..SOME {
+        let p = ps[2 * i].insert();
+        ps.insert(2 * i + 1, p);
+    }
+    for i in 0..ps.len() - 1 {
+        assert!(ps[i] < ps[i + 1], "ps[{}] < ps[{}]", i, i + 1);
+    }
+}
+
+pub fn tag_in_order<Priority: MaintainedOrd + Clone>() {
+    use order_maintenance::tag_in_order;
+    use rand::{rngs::StdRng, Rng, SeedableRng};
+    let mut rng = StdRng::seed_from_u64(42);
+    let items: Vec<usize> = (0..MANY).map(|_| rng.gen_range(0..SOME)).collect();
+    let tags: Vec<Priority> = tag_in_order(items.iter(), |a, b| a.cmp(b));
+
+    for _ in 0..MANY {
+        let i = rng.gen_range(0..MANY);
+        let j = rng.gen_range(0..MANY);
+        assert_eq!(
+            tags[i].partial_cmp(&tags[j]),
+            Some(items[i].cmp(&items[j])),
+            "tags[{}] vs tags[{}]",
+            i,
+            j
+        );
+    }
+}
+
+pub fn tag_sorted<Priority: MaintainedOrd>() {
+    use order_maintenance::tag_sorted;
+    let tagged: Vec<(usize, Priority)> = tag_sorted(0..MANY);
+    assert_eq!(tagged.len(), MANY);
+    for (i, (item, _)) in tagged.iter().enumerate() {
+        assert_eq!(*item, i);
+    }
+    for w in tagged.windows(2) {
+        assert!(w[0].1 < w[1].1, "{} < {}", w[0].0, w[1].0);
+    }
+
+    let empty: Vec<(usize, Priority)> = tag_sorted(std::iter::empty());
+    assert!(empty.is_empty());
+}
+
+/// For priorities whose `Debug` output shows their arena and rank.
+pub fn debug_rank<Priority: MaintainedOrd + std::fmt::Debug>() {
+    let a = Priority::new();
+    let c = a.insert();
+    let b = a.insert();
+
+    let debug = |p: &Priority| format!("{p:?}");
+    assert!(debug(&a).ends_with("rank: 0 }"), "{}", debug(&a));
+    assert!(debug(&b).ends_with("rank: 1 }"), "{}", debug(&b));
+    assert!(debug(&c).ends_with("rank: 2 }"), "{}", debug(&c));
+
+    // Priorities in the same arena show the same arena, unlike those in another arena.
+    let arena = |p: &Priority| debug(p).split(',').next().unwrap().to_string();
+    assert!(arena(&a).starts_with("Priority { arena: 0x"));
+    assert_eq!(arena(&a), arena(&c));
+    assert_ne!(arena(&a), arena(&Priority::new()));
+
+    assert!(format!("{a:#?}").contains("\n    rank: 0,\n"));
+}
+
+/// For priorities that implement `Default`, which should behave like `new()`.
+pub fn default_new<Priority: MaintainedOrd + Default>() {
+    let a = Priority::default();
+    let b = a.insert();
+    let c = a.insert();
+    assert!(a < c);
+    assert!(c < b);
+}
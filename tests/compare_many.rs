@@ -0,0 +1,39 @@
+//! Integration tests for batch comparisons.
+
+use order_maintenance::config::PriorityConfig;
+use order_maintenance::{list_range, tag_range, MaintainedOrd};
+use rand::{Rng, SeedableRng};
+
+/// Batch comparisons agree with `partial_cmp`, including for pairs of different arenas.
+macro_rules! batch_agrees {
+    ($name:ident, $priority:ty) => {
+        #[test]
+        fn $name() {
+            let mut rng = rand::rngs::StdRng::seed_from_u64(0);
+            let mut ps = vec![<$priority>::new()];
+            for _ in 0..1000 {
+                let p = ps[rng.gen_range(0..ps.len())].insert();
+                ps.push(p);
+            }
+            let config = PriorityConfig::new().order_across_arenas();
+            let others: Vec<$priority> = vec![config.build_arena(), <$priority>::new()];
+
+            let mut pairs = Vec::new();
+            for _ in 0..5000 {
+                let a = ps[rng.gen_range(0..ps.len())].clone();
+                let b = if rng.gen_range(0..10) == 0 {
+                    others[rng.gen_range(0..others.len())].clone()
+                } else {
+                    ps[rng.gen_range(0..ps.len())].clone()
+                };
+                pairs.push((a, b));
+            }
+            let expected: Vec<_> = pairs.iter().map(|(a, b)| a.partial_cmp(b)).collect();
+            assert_eq!(<$priority>::compare_many(&pairs), expected);
+            assert!(<$priority>::compare_many(&[]).is_empty());
+        }
+    };
+}
+
+batch_agrees!(tag_range_batch, tag_range::Priority);
+batch_agrees!(list_range_batch, list_range::Priority);
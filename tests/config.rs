@@ -0,0 +1,193 @@
+//! Integration tests for arenas constructed from a `PriorityConfig`.
+
+use order_maintenance::config::PriorityConfig;
+use order_maintenance::{list_range, tag_range, MaintainedOrd};
+use std::num::{NonZeroU64, NonZeroUsize};
+
+#[test]
+fn configured_tag_range() {
+    let config = PriorityConfig::new()
+        .threshold(1.3)
+        .initial_capacity(1024)
+        .self_check(NonZeroU64::new(7).unwrap());
+    let first: tag_range::Priority = config.build_arena();
+
+    let mut ps = vec![first];
+    for i in 0..2000 {
+        let p = ps[i / 2].insert();
+        ps.push(p);
+    }
+    let report = ps[0].self_check_report();
+    assert_eq!(report.checks, 2000 / 7);
+    assert_eq!(report.failures, 0);
+
+    // A configured arena orders priorities just like any other.
+    ps.sort_by(|a, b| a.partial_cmp(b).unwrap());
+    assert!(ps.windows(2).all(|w| w[0] < w[1]));
+}
+
+#[test]
+fn configured_list_range() {
+    let first: list_range::Priority = PriorityConfig::new().threshold(1.9).build_arena();
+    let second = first.insert();
+    assert!(first < second);
+    assert_eq!(first.check_invariants(), Ok(()));
+}
+
+#[test]
+#[should_panic(expected = "threshold must be strictly between 1.0 and 2.0: 2.5")]
+fn reject_invalid_threshold() {
+    let _ = PriorityConfig::new().threshold(2.5);
+}
+
+#[test]
+// Relabeling mutates priorities internally, but never changes their relative order.
+#[allow(clippy::mutable_key_type)]
+fn ordered_across_arenas() {
+    use order_maintenance::TotalOrder;
+    use std::collections::BTreeSet;
+
+    let config = PriorityConfig::new().order_across_arenas();
+    let a: tag_range::Priority = config.build_arena();
+    let b: tag_range::Priority = config.build_arena();
+    let a2 = a.insert();
+    let b2 = b.insert();
+    assert!(a < b && a2 < b && a2 < b2);
+    assert!(b > a2 && b2 > a);
+
+    // Priorities of arenas that did not opt in remain incomparable.
+    let c = tag_range::Priority::new();
+    assert_eq!(a.partial_cmp(&c), None);
+
+    let set: BTreeSet<_> = [&b2, &a, &b, &a2, &a]
+        .into_iter()
+        .map(|p| TotalOrder(p.clone()))
+        .collect();
+    let sorted: Vec<_> = set.into_iter().map(|p| p.0).collect();
+    assert_eq!(sorted, [a, a2, b, b2]);
+}
+
+#[test]
+#[should_panic(expected = "compared priorities from different arenas")]
+fn panic_across_arenas() {
+    use order_maintenance::config::CrossArena;
+
+    let a: list_range::Priority = PriorityConfig::new()
+        .on_cross_arena(CrossArena::Panic)
+        .build_arena();
+    // Priorities within the arena compare as usual.
+    assert!(a < a.insert());
+    // Panicking takes precedence over the other arena's behavior.
+    let b: list_range::Priority = PriorityConfig::new().order_across_arenas().build_arena();
+    let _ = b.partial_cmp(&a);
+}
+
+#[test]
+fn configured_cross_arena() {
+    use order_maintenance::config::CrossArena;
+
+    let config = PriorityConfig::new().on_cross_arena(CrossArena::OrderByArena);
+    assert_eq!(config, PriorityConfig::new().order_across_arenas());
+    let (a, b): (list_range::Priority, list_range::Priority) =
+        (config.build_arena(), config.build_arena());
+    assert!(a < b);
+
+    let config = PriorityConfig::new().on_cross_arena(CrossArena::Incomparable);
+    assert_eq!(config, PriorityConfig::new());
+    let c: list_range::Priority = config.build_arena();
+    assert_eq!(a.partial_cmp(&c), None);
+}
+
+#[test]
+fn deferred_drop() {
+    use order_maintenance::InArena;
+
+    let first: tag_range::Priority = PriorityConfig::new().deferred_drop().build_arena();
+    let ps: Vec<_> = (0..100).map(|_| first.insert()).collect();
+    let kept = ps[10].clone();
+    drop(ps);
+    // Dropped priorities are only freed once collected.
+    assert_eq!(first.arena_priorities().len(), 101);
+    assert_eq!(first.gc(), 99);
+    assert_eq!(first.arena_priorities(), [first.clone(), kept.clone()]);
+    assert_eq!(first.gc(), 0);
+    assert_eq!(first.check_invariants(), Ok(()));
+
+    let first: list_range::Priority = PriorityConfig::new().deferred_drop().build_arena();
+    drop(first.insert());
+    assert_eq!(first.gc(), 1);
+    assert_eq!(first.check_invariants(), Ok(()));
+
+    // Without deferred drops, priorities are freed right away.
+    let first = tag_range::Priority::new();
+    drop(first.insert());
+    assert_eq!(first.gc(), 0);
+    assert_eq!(first.arena_priorities().len(), 1);
+}
+
+std::thread_local! {
+    static CROSSED: std::cell::RefCell<Vec<usize>> = const { std::cell::RefCell::new(Vec::new()) };
+}
+
+fn crossed(live: usize) {
+    CROSSED.with(|crossed| crossed.borrow_mut().push(live));
+}
+
+#[test]
+fn soft_cap() {
+    let first: tag_range::Priority = PriorityConfig::new().soft_cap(10, crossed).build_arena();
+    let mut ps: Vec<_> = (0..9).map(|_| first.insert()).collect();
+    assert!(!first.over_soft_cap());
+    assert!(CROSSED.with(|crossed| crossed.borrow().is_empty()));
+
+    // Crossing the cap calls back once, until the arena drops back to the cap.
+    ps.push(first.insert());
+    ps.push(first.insert());
+    assert!(first.over_soft_cap());
+    assert_eq!(CROSSED.with(|crossed| crossed.take()), [11]);
+    ps.truncate(9);
+    assert!(!first.over_soft_cap());
+    ps.push(first.insert());
+    assert_eq!(CROSSED.with(|crossed| crossed.take()), [11]);
+
+    // Arenas without a soft cap are never over it.
+    let first = list_range::Priority::new();
+    let _ps: Vec<_> = (0..100).map(|_| first.insert()).collect();
+    assert!(!first.over_soft_cap());
+}
+
+thread_local! {
+    /// Progress reported by each call to `on_yield`, on this test's thread.
+    static YIELDS: std::cell::RefCell<Vec<usize>> = const { std::cell::RefCell::new(Vec::new()) };
+}
+
+fn on_yield(done: usize) {
+    YIELDS.with(|y| y.borrow_mut().push(done));
+}
+
+macro_rules! yield_during_relabel {
+    ($name:ident, $priority:ty) => {
+        #[test]
+        fn $name() {
+            let every = NonZeroUsize::new(16).unwrap();
+            let first: $priority = PriorityConfig::new()
+                .yield_during_relabel(every, on_yield)
+                .build_arena();
+            YIELDS.with(|y| y.borrow_mut().clear());
+            let ps: Vec<$priority> = (0..2000).map(|_| first.insert()).collect();
+            let yields = YIELDS.with(|y| y.take());
+            // Only passes that relabel at least 16 priorities yield, every 16 priorities.
+            assert!(!yields.is_empty());
+            assert!(yields.iter().all(|&done| done > 0 && done % 16 == 0));
+
+            first.rebalance_all();
+            let yields = YIELDS.with(|y| y.take());
+            let total = ps.len() + 1;
+            assert_eq!(yields.last(), Some(&(total / 16 * 16)));
+            assert_eq!(yields.len(), total / 16);
+        }
+    };
+}
+
+yield_during_relabel!(tag_range_yield_during_relabel, tag_range::Priority);
+yield_during_relabel!(list_range_yield_during_relabel, list_range::Priority);
@@ -0,0 +1,52 @@
+//! Integration tests for converting arenas between implementations.
+
+use order_maintenance::{list_range, tag_range, MaintainedOrd};
+use rand::{Rng, SeedableRng};
+
+/// Build an arena of priorities by inserting after random ones, then dropping some of them.
+fn random_arena<P: MaintainedOrd>() -> Vec<P> {
+    let mut rng = rand::rngs::StdRng::seed_from_u64(0);
+    let mut ps = vec![P::new()];
+    for _ in 0..500 {
+        let i = rng.gen_range(0..ps.len());
+        let p = ps[i].insert();
+        ps.push(p);
+    }
+    for _ in 0..100 {
+        ps.swap_remove(rng.gen_range(0..ps.len()));
+    }
+    ps
+}
+
+#[test]
+fn tag_range_to_list_range_and_back() {
+    let ps: Vec<tag_range::Priority> = random_arena();
+    let to_list = ps[7].convert_arena::<list_range::Priority>();
+    assert_eq!(to_list.len(), ps.len());
+    for w in to_list.windows(2) {
+        assert!(w[0].0 < w[1].0 && w[0].1 < w[1].1);
+    }
+    for p in &ps {
+        assert!(to_list.iter().any(|(old, _)| old == p));
+    }
+
+    let back = to_list[3].1.convert_arena::<tag_range::Priority>();
+    assert_eq!(back.len(), ps.len());
+    for ((_, list), (old_list, _)) in to_list.iter().zip(&back) {
+        assert!(list == old_list);
+    }
+    assert!(back.windows(2).all(|w| w[0].1 < w[1].1));
+}
+
+#[test]
+fn list_range_to_tag_range() {
+    let ps: Vec<list_range::Priority> = random_arena();
+    let converted = ps[0].convert_arena::<tag_range::Priority>();
+    let mut sorted = ps.clone();
+    sorted.sort_by(|a, b| a.partial_cmp(b).unwrap());
+    assert_eq!(converted.len(), sorted.len());
+    for ((old, new), p) in converted.iter().zip(&sorted) {
+        assert!(old == p);
+        assert_eq!(new.check_invariants(), Ok(()));
+    }
+}
@@ -0,0 +1,53 @@
+//! Integration tests for the cost model of tag-range priorities.
+
+use order_maintenance::cost_model::{
+    levels, prefer_rebuild, relabels_per_insert, relabels_per_level,
+};
+use order_maintenance::params::Capacities;
+use order_maintenance::tag_range::{DefaultParams, FixedThreshold};
+use order_maintenance::workload::{simulate, Algorithm, Step};
+
+#[test]
+fn grows_with_arena() {
+    let costs: Vec<f64> = [10, 1_000, 100_000, 10_000_000]
+        .into_iter()
+        .map(relabels_per_insert::<DefaultParams>)
+        .collect();
+    assert!(costs.windows(2).all(|w| w[0] <= w[1]));
+    assert!(costs.iter().all(|c| c.is_finite()));
+
+    // Full arenas cannot be inserted into at all.
+    let full = FixedThreshold::<19>::max_capacity(0);
+    assert_eq!(
+        relabels_per_insert::<FixedThreshold<19>>(full),
+        f64::INFINITY
+    );
+    assert!(prefer_rebuild::<FixedThreshold<19>>(full, 1));
+}
+
+#[test]
+fn levels_hold_arena() {
+    type P = FixedThreshold<15>;
+    for n in [1, 2, 100, 12345] {
+        let level = levels::<P>(0, n);
+        assert!(P::capacity(0, level) >= n);
+        assert!(level == 0 || P::capacity(0, level - 1) < n);
+    }
+}
+
+#[test]
+fn bounds_adversarial_workload() {
+    // Inserting right after the first priority over and over is the worst case for relabeling.
+    for n in [100, 1_000, 10_000] {
+        let script = vec![Step::Insert(0); n];
+        let report = simulate(&script, Algorithm::TagRange);
+        let measured = report.relabeled as f64 / report.inserts as f64;
+        assert!(measured <= relabels_per_insert::<DefaultParams>(n + 1));
+    }
+}
+
+#[test]
+#[should_panic(expected = "thresholds must be greater than 1: 10/10")]
+fn threshold_of_one_is_rejected() {
+    relabels_per_level(10, 10);
+}
@@ -0,0 +1,87 @@
+//! Integration tests for priority types defined with `define_priority!`.
+//!
+//! Delegates to tests defined in the `common` module.
+
+mod common;
+use order_maintenance::{define_priority, om_delegate};
+
+define_priority! {
+    /// Tag-range priorities with a single threshold and 32-bit labels.
+    TagRange32: tag_range(threshold = 1.3, bits = 32)
+}
+
+define_priority! {
+    /// Tag-range priorities with a narrow range of thresholds and 16-bit labels.
+    TagRange16: tag_range(threshold = 1.1..=1.5, count = 5, bits = 16)
+}
+
+define_priority! {
+    /// Tag-range priorities with an explicit list of thresholds and 32-bit labels.
+    TagRangeList: tag_range(threshold = [1.2, 1.4, 1.6], bits = 32)
+}
+
+define_priority! {
+    /// List-range priorities with 32-bit labels.
+    ListRange32: list_range(bits = 32)
+}
+
+define_priority! {
+    /// List-range priorities with 16-bit labels.
+    ListRange16: list_range(bits = 16)
+}
+
+define_priority! {
+    /// Tag-range priorities with so few labels that they quickly run out.
+    TagRange4: tag_range(threshold = 1.3, bits = 4)
+}
+
+om_delegate! {
+    TagRange32 as "tag_range_32",
+    TagRange16 as "tag_range_16",
+    TagRangeList as "tag_range_list",
+    ListRange32 as "list_range_32",
+    ListRange16 as "list_range_16"
+    => tests [
+        compare_two,
+        insertion,
+        transitive,
+        drop_first,
+        drop_middle,
+        drop_some,
+        drop_random,
+        insert_some_begin,
+        insert_some_end,
+        insert_some_flipflop,
+        insert_many_begin,
+        insert_many_end,
+        insert_some_begin_many_end,
+        insert_many_random,
+        new_sequence,
+        tag_in_order,
+        default_new,
+    ]
+}
+
+#[test]
+fn threshold_metadata() {
+    use order_maintenance::params::Capacities;
+
+    assert_eq!(TagRange32Params::threshold(0), (13, 10));
+    let thresholds: Vec<_> = (0..TagRange16Params::THRESHOLDS)
+        .map(TagRange16Params::threshold)
+        .collect();
+    assert_eq!(thresholds, [(11, 10), (6, 5), (13, 10), (7, 5), (3, 2)]);
+    assert_eq!(
+        TagRange16Params::max_capacity(0),
+        TagRange16Params::capacity(0, 15)
+    );
+}
+
+#[test]
+#[should_panic(expected = "Too many priorities were inserted: 5 (arena: 0x")]
+fn capacity_panic_context() {
+    use order_maintenance::MaintainedOrd;
+
+    let a = TagRange4::new();
+    let _ps: Vec<_> = (0..16).map(|_| a.insert()).collect();
+}
@@ -0,0 +1,43 @@
+//! Integration tests for types deriving `MaintainedOrder`.
+
+use order_maintenance::{tag_range::Priority, MaintainedOrd, MaintainedOrder};
+
+#[derive(Debug, MaintainedOrder)]
+struct Task {
+    name: &'static str,
+    #[order]
+    priority: Priority,
+}
+
+#[derive(Debug, MaintainedOrder)]
+struct Wrapper<P>(P);
+
+#[test]
+fn delegates_to_priority() {
+    let a = Task {
+        name: "a",
+        priority: Priority::new(),
+    };
+    let c = a.insert_after("c");
+    let b = a.insert_after("b");
+
+    assert!(a < b);
+    assert!(b < c);
+    assert_eq!(c.name, "c");
+
+    // Only the priority takes part in comparisons.
+    let a2 = Task {
+        name: "a2",
+        priority: a.priority.clone(),
+    };
+    assert_eq!(a, a2);
+}
+
+#[test]
+fn generic_wrapper() {
+    let a = Wrapper(Priority::new());
+    let b = a.insert_after();
+    let c = b.insert_after();
+    assert!(a < b && b < c);
+    assert!(a < c);
+}
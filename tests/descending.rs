@@ -0,0 +1,33 @@
+//! Integration tests for descending priorities.
+
+use order_maintenance::descending::{Descending, DescendingPriority};
+use order_maintenance::{list_range, InArena, MaintainedOrd, TotalOrder};
+use std::collections::BTreeSet;
+
+type DescendingList = Descending<list_range::Priority>;
+
+#[test]
+fn insert_is_smaller() {
+    let a = DescendingList::new();
+    let b = a.insert();
+    let c = a.insert();
+    assert!(a > c && c > b);
+    assert!(a.0 < c.0 && c.0 < b.0);
+
+    let all = a.arena_priorities();
+    assert_eq!(all, [b, c, a.clone()]);
+    assert!(all.windows(2).all(|w| w[0] < w[1]));
+    assert!(a.into_inner() == all[2].0);
+}
+
+#[test]
+// Relabeling mutates priorities internally, but never changes their relative order.
+#[allow(clippy::mutable_key_type)]
+fn sequences_increase() {
+    let ps = DescendingPriority::new_sequence(100);
+    assert!(ps.windows(2).all(|w| w[0] < w[1]));
+
+    // Containers order descending priorities consistently.
+    let set: BTreeSet<_> = ps.iter().rev().cloned().map(TotalOrder).collect();
+    assert!(set.into_iter().map(|p| p.0).eq(ps));
+}
@@ -0,0 +1,34 @@
+//! Integration tests for comparing the orders of arenas.
+
+use order_maintenance::diff::order_eq;
+use order_maintenance::{list_range, tag_range, MaintainedOrd};
+
+#[test]
+fn converted_arenas_agree() {
+    let first = tag_range::Priority::new();
+    let mut ps = vec![first.clone()];
+    for _ in 0..100 {
+        ps.push(first.insert());
+    }
+    let converted = first.convert_arena::<list_range::Priority>();
+    let map = |p: &tag_range::Priority| {
+        converted
+            .iter()
+            .find(|(old, _)| old == p)
+            .map(|(_, new)| new.clone())
+    };
+    assert!(order_eq(&first, &converted[0].1, map));
+
+    // Mapping two priorities to each other's counterparts breaks the order.
+    let (a, b) = (&converted[3], &converted[4]);
+    let swapped = |p: &tag_range::Priority| match p {
+        p if *p == a.0 => Some(b.1.clone()),
+        p if *p == b.0 => Some(a.1.clone()),
+        p => map(p),
+    };
+    assert!(!order_eq(&first, &converted[0].1, swapped));
+
+    // Their arena has a priority without a counterpart in ours.
+    let extra = converted[0].1.insert();
+    assert!(!order_eq(&first, &extra, map));
+}
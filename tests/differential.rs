@@ -0,0 +1,26 @@
+//! Differential tests across implementations.
+//!
+//! Runs the same decisions against several implementations, which must agree on every pairwise
+//! comparison, so that each serves as an independent oracle for the others.
+
+mod common;
+use common::qc;
+use order_maintenance::{list_range, tag_range};
+use quickcheck_macros::quickcheck;
+
+#[quickcheck]
+fn qc_tag_range_list_range(ds: qc::FewDecisions) -> bool {
+    qc::run_and_compare::<tag_range::Priority, list_range::Priority>(ds.0)
+}
+
+#[cfg(feature = "big")]
+#[quickcheck]
+fn qc_tag_range_big(ds: qc::FewDecisions) -> bool {
+    qc::run_and_compare::<tag_range::Priority, order_maintenance::big::Priority>(ds.0)
+}
+
+#[cfg(feature = "big")]
+#[quickcheck]
+fn qc_list_range_big(ds: qc::FewDecisions) -> bool {
+    qc::run_and_compare::<list_range::Priority, order_maintenance::big::Priority>(ds.0)
+}
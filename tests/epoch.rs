@@ -0,0 +1,30 @@
+//! Integration tests for label epochs.
+
+use order_maintenance::types::Epoch;
+use order_maintenance::{list_range, tag_range, MaintainedOrd};
+
+/// Insert at the front repeatedly, checking that labels only change along with the epoch.
+macro_rules! labels_follow_epoch {
+    ($name:ident, $priority:ty) => {
+        #[test]
+        fn $name() {
+            let first = <$priority>::new();
+            assert_eq!(first.label_epoch(), Epoch::new(0));
+            let mut cached = first.label_with_epoch();
+            let mut ps = Vec::new();
+            for _ in 0..1000 {
+                ps.push(first.insert());
+                let now = first.label_with_epoch();
+                if now.is_current(cached.epoch()) {
+                    assert_eq!(now, cached);
+                }
+                assert!(now.epoch() >= cached.epoch());
+                cached = now;
+            }
+            assert!(first.label_epoch() > Epoch::new(0));
+        }
+    };
+}
+
+labels_follow_epoch!(tag_range_labels_follow_epoch, tag_range::Priority);
+labels_follow_epoch!(list_range_labels_follow_epoch, list_range::Priority);
@@ -0,0 +1,51 @@
+//! Integration tests for explaining relabeling passes.
+
+use order_maintenance::config::PriorityConfig;
+use order_maintenance::{list_range, tag_range, MaintainedOrd};
+
+/// Crowding priorities after the first one makes the arena relabel, and explain its last pass.
+macro_rules! explain_relabels {
+    ($name:ident, $priority:ty) => {
+        #[test]
+        fn $name() {
+            let first: $priority = PriorityConfig::new().explain_relabels().build_arena();
+            assert!(first.last_relabel_report().is_none());
+            let ps: Vec<$priority> = (0..1000).map(|_| first.insert()).collect();
+
+            let report = first.last_relabel_report().unwrap();
+            assert_eq!(report.epoch, first.label_epoch());
+            assert!(report.live <= ps.len() + 1);
+            let last = report.steps.last().unwrap();
+            assert!(last.accepted());
+            assert!(report.steps[..report.steps.len() - 1]
+                .iter()
+                .all(|step| !step.accepted()));
+            assert!(report.steps.windows(2).all(|w| w[0].level < w[1].level));
+            assert!(0.0 < report.density && report.density <= 1.0);
+
+            // Arenas that are not configured to explain relabels do not keep reports.
+            let plain = <$priority>::new();
+            let qs: Vec<$priority> = (0..1000).map(|_| plain.insert()).collect();
+            assert!(plain.label_epoch().get() > 0);
+            assert!(qs[0].last_relabel_report().is_none());
+        }
+    };
+}
+
+explain_relabels!(tag_range_explain, tag_range::Priority);
+explain_relabels!(list_range_explain, list_range::Priority);
+
+#[test]
+fn tag_range_steps_widen() {
+    let first: tag_range::Priority = PriorityConfig::new().explain_relabels().build_arena();
+    let _ps: Vec<tag_range::Priority> = (0..1000).map(|_| first.insert()).collect();
+
+    let report = first.last_relabel_report().unwrap();
+    assert!(report.threshold.is_some());
+    for step in &report.steps {
+        let (lo, hi) = step.window;
+        assert_eq!(hi - lo + 1, 1 << step.level);
+        assert!(step.capacity.is_some());
+    }
+    assert_eq!(report.steps.last().unwrap().count, report.relabeled);
+}
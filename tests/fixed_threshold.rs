@@ -0,0 +1,36 @@
+//! Integration tests for tag-range priorities with a threshold fixed at compile time.
+//!
+//! Delegates to tests defined in the `common` module.
+
+mod common;
+use order_maintenance::params::Capacities;
+use order_maintenance::tag_range::{FixedPriority, FixedThreshold};
+use order_maintenance::{om_delegate, self_test};
+
+type Fixed13 = FixedPriority<13>;
+
+om_delegate! {
+    Fixed13 => tests [
+        compare_two,
+        insertion,
+        transitive,
+        drop_random,
+        insert_many_begin,
+        insert_many_end,
+        insert_many_random,
+        new_sequence,
+    ]
+}
+
+#[test]
+fn fixed_capacities() {
+    type T = FixedThreshold<15>;
+    assert_eq!(T::THRESHOLDS, 1);
+    assert_eq!(T::threshold(0), (15, 10));
+    assert_eq!(T::capacity(0, 8), 9); // floor((2 / 1.5)^8)
+    assert_eq!(T::threshold_for(0), Some(0));
+    assert_eq!(T::threshold_for(usize::MAX), None);
+
+    let report = self_test::<FixedPriority<17>>();
+    assert!(report.is_ok(), "{:?}", report.problems);
+}
@@ -0,0 +1,115 @@
+//! Integration tests for frozen orders.
+
+use order_maintenance::frozen::FrozenPriority;
+use order_maintenance::id::Identified;
+use order_maintenance::types::Rank;
+use order_maintenance::{list_range, tag_range, MaintainedOrd};
+
+/// Freeze an arena after inserting at the front, then compare frozen priorities across threads.
+macro_rules! freeze_and_share {
+    ($name:ident, $priority:ty) => {
+        #[test]
+        fn $name() {
+            let first = <$priority>::new();
+            let mut ps = vec![first.clone()];
+            for _ in 0..200 {
+                ps.push(first.insert());
+            }
+            drop(ps.remove(100));
+
+            let (order, handles) = ps[0].freeze_arena();
+            assert_eq!(order.len(), ps.len());
+            let frozen: Vec<FrozenPriority> = ps
+                .iter()
+                .map(|p| handles.iter().find(|(q, _)| q == p).unwrap().1)
+                .collect();
+            for (i, (p, f)) in handles.iter().enumerate() {
+                assert_eq!(order.rank(*f), Some(Rank::new(i)));
+                assert_eq!(order.get(i), Some(*f));
+                if i > 0 {
+                    assert!(handles[i - 1].0 < *p);
+                    assert!(order.label(handles[i - 1].1) < order.label(*f));
+                }
+            }
+            assert_eq!(order.get(order.len()), None);
+
+            let first = frozen[0];
+            std::thread::spawn(move || {
+                for w in frozen[1..].windows(2) {
+                    assert!(w[0] > w[1]);
+                    assert!(first < w[0]);
+                }
+            })
+            .join()
+            .unwrap();
+
+            let (other, _) = <$priority>::new().freeze_arena();
+            let f = other.get(0).unwrap();
+            assert_eq!(f.partial_cmp(&first), None);
+            assert_eq!(order.rank(f), None);
+        }
+    };
+}
+
+freeze_and_share!(tag_range_freeze, tag_range::Priority);
+freeze_and_share!(list_range_freeze, list_range::Priority);
+
+/// Snapshot an arena, keep inserting into it, and read the snapshot on another thread.
+macro_rules! snapshot_during_mutation {
+    ($name:ident, $priority:ty) => {
+        #[test]
+        fn $name() {
+            let ps = <$priority>::new_sequence(100);
+            let snapshot = ps[0].read_snapshot();
+            assert_eq!(snapshot.order().len(), ps.len());
+            assert_eq!(snapshot.label_epoch(), ps[0].label_epoch());
+
+            // Relabel the arena thoroughly; the snapshot keeps the order it had.
+            let more: Vec<_> = (0..1000).map(|_| ps[0].insert()).collect();
+            assert!(ps[0].label_epoch() > snapshot.label_epoch());
+            let ids: Vec<_> = ps.iter().map(|p| p.id()).collect();
+            let fresh = more[0].id();
+            std::thread::spawn(move || {
+                for (i, id) in ids.iter().enumerate() {
+                    assert_eq!(
+                        snapshot.order().rank(snapshot.get(*id).unwrap()),
+                        Some(Rank::new(i))
+                    );
+                }
+                assert_eq!(snapshot.get(fresh), None);
+            })
+            .join()
+            .unwrap();
+        }
+    };
+}
+
+snapshot_during_mutation!(tag_range_snapshot, tag_range::Priority);
+snapshot_during_mutation!(list_range_snapshot, list_range::Priority);
+
+#[test]
+fn thaw_aligned_by_rank() {
+    let first = tag_range::Priority::new();
+    let mut ps = vec![first.clone()];
+    for _ in 0..100 {
+        ps.push(first.insert());
+    }
+    let (order, handles) = first.freeze_arena();
+    drop(ps);
+
+    let thawed: Vec<list_range::Priority> = order.thaw();
+    assert_eq!(thawed.len(), order.len());
+    assert!(thawed.windows(2).all(|w| w[0] < w[1]));
+    // Old handles map to their thawed counterparts through their frozen ranks.
+    for w in handles.windows(2) {
+        let (a, b) = (order.rank(w[0].1).unwrap(), order.rank(w[1].1).unwrap());
+        assert!(w[0].0 < w[1].0 && thawed[a.get()] < thawed[b.get()]);
+    }
+    assert_eq!(thawed[0].check_invariants(), Ok(()));
+
+    // Thawed arenas accept insertions again, then freeze just the same.
+    let p = thawed[50].insert();
+    assert!(thawed[50] < p && p < thawed[51]);
+    let (refrozen, _) = thawed[0].freeze_arena();
+    assert_eq!(refrozen.len(), order.len() + 1);
+}
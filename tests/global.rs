@@ -0,0 +1,43 @@
+//! Integration tests for global arenas.
+
+use order_maintenance::global::GlobalArena;
+use order_maintenance::{list_range, tag_range, InArena};
+
+static TAGS: GlobalArena = GlobalArena::new();
+static OTHER_TAGS: GlobalArena<tag_range::Priority> = GlobalArena::new();
+static LISTS: GlobalArena<list_range::Priority> = GlobalArena::new();
+
+#[test]
+fn allocated_lazily() {
+    static UNUSED: GlobalArena = GlobalArena::new();
+    assert!(!UNUSED.is_allocated());
+
+    let a = LISTS.push();
+    assert!(LISTS.is_allocated());
+    let b = LISTS.push();
+    assert!(LISTS.first() < a && a < b);
+    assert_eq!(b.arena_priorities(), [LISTS.first(), a, b.clone()]);
+    assert!(!UNUSED.is_allocated());
+}
+
+#[test]
+fn separate_statics() {
+    let a = TAGS.push();
+    let b = OTHER_TAGS.push();
+    // Arenas without cross-arena ordering leave priorities from different arenas incomparable.
+    assert_eq!(a.partial_cmp(&b), None);
+    assert!(TAGS.first() < a);
+    assert!(OTHER_TAGS.first() < b);
+}
+
+#[test]
+fn separate_threads() {
+    let here = TAGS.push();
+    std::thread::spawn(|| {
+        let there = TAGS.push();
+        assert_eq!(there.arena_priorities(), [TAGS.first(), there.clone()]);
+    })
+    .join()
+    .unwrap();
+    assert!(TAGS.first() < here);
+}
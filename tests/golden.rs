@@ -0,0 +1,78 @@
+//! Golden-file tests for the encoding of operation logs.
+//!
+//! Each `tests/golden/*.oplog` file pins down the bytes that a fixed workload encodes to, so that
+//! readers of the format outside of this crate can test against the same files. Set
+//! `UPDATE_GOLDEN=1` to rewrite them after an intentional change to the format.
+#![cfg(feature = "oplog")]
+
+use order_maintenance::oplog::{validate_snapshot, OpLog, Recorded, FORMAT_VERSION};
+use order_maintenance::{list_range, tag_range, MaintainedOrd};
+use std::path::PathBuf;
+
+fn golden_path(name: &str) -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/golden")
+        .join(format!("{name}.oplog"))
+}
+
+/// Check that `bytes` match the golden file `name`, or rewrite it if requested.
+fn check_golden(name: &str, bytes: &[u8]) {
+    let path = golden_path(name);
+    if std::env::var_os("UPDATE_GOLDEN").is_some() {
+        std::fs::write(&path, bytes).unwrap();
+    }
+    let golden = std::fs::read(&path).unwrap();
+    assert_eq!(bytes, golden, "{} is out of date", path.display());
+}
+
+/// Insert one or several, insert at the front, clone, drop, rebalance, compact, and reserve and
+/// release a region, so that every kind of operation is logged.
+fn workload<P: Recorded>(first: P) -> Vec<P> {
+    let b = first.insert();
+    let c = b.insert();
+    let d = first.insert_front();
+    let e = c.clone();
+    drop(b);
+    e.rebalance_all();
+    d.compact_labels();
+    let f = c.insert_n(2);
+    first.reserve_region_after(5).unwrap().release();
+    vec![first, c, d, e, f[0].clone()]
+}
+
+/// Check that the golden file `name` round-trips through decoding, and replays with `P`.
+fn round_trip<P: Recorded>(name: &str, live: usize) {
+    let bytes = std::fs::read(golden_path(name)).unwrap();
+    assert_eq!(OpLog::format_version(&bytes), Some(FORMAT_VERSION));
+    assert_eq!(validate_snapshot(&bytes), Ok(()));
+
+    let log = OpLog::from_bytes(&bytes).unwrap();
+    assert_eq!(log.to_bytes(), bytes);
+    let replayed: Vec<P> = log.replay();
+    assert_eq!(replayed.len(), live);
+    assert_eq!(replayed[0].op_log(), log);
+}
+
+#[test]
+fn tag_range_new() {
+    let ps = workload(tag_range::Priority::new());
+    check_golden("tag_range_new", &ps[0].op_log().to_bytes());
+    round_trip::<tag_range::Priority>("tag_range_new", 5);
+}
+
+#[test]
+fn tag_range_new_sequence() {
+    let mut ps = tag_range::Priority::new_sequence(300);
+    let first = ps.swap_remove(0);
+    drop(ps);
+    let ps = workload(first);
+    check_golden("tag_range_new_sequence", &ps[0].op_log().to_bytes());
+    round_trip::<tag_range::Priority>("tag_range_new_sequence", 5);
+}
+
+#[test]
+fn list_range_new() {
+    let ps = workload(list_range::Priority::new());
+    check_golden("list_range_new", &ps[0].op_log().to_bytes());
+    round_trip::<list_range::Priority>("list_range_new", 5);
+}
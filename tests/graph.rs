@@ -0,0 +1,85 @@
+//! Integration tests for the `petgraph` feature.
+#![cfg(feature = "petgraph")]
+
+use order_maintenance::graph::{NodeOrder, TopoOrder};
+use petgraph::algo::toposort;
+use petgraph::graph::NodeIndex;
+use petgraph::Graph;
+use rand::{Rng, SeedableRng};
+
+/// Check that every edge of `graph` goes forward in `order`.
+fn assert_topological(graph: &Graph<(), ()>, order: &TopoOrder) {
+    for a in graph.node_indices() {
+        for b in graph.neighbors(a) {
+            assert!(order.precedes(a, b), "{a:?} -> {b:?} is out of order");
+        }
+    }
+}
+
+#[test]
+fn random_dag() {
+    let mut rng = rand::rngs::StdRng::seed_from_u64(0);
+    let mut graph = Graph::<(), ()>::new();
+    let mut order: TopoOrder = TopoOrder::new();
+    for _ in 0..50 {
+        let n = graph.add_node(());
+        order.add_node(n);
+    }
+
+    let mut rejected = 0;
+    for _ in 0..300 {
+        let a = NodeIndex::new(rng.gen_range(0..graph.node_count()));
+        let b = NodeIndex::new(rng.gen_range(0..graph.node_count()));
+        match order.add_edge(&graph, a, b) {
+            Ok(()) => {
+                graph.add_edge(a, b, ());
+            }
+            Err(_) => rejected += 1,
+        }
+        assert_topological(&graph, &order);
+    }
+    assert!(rejected > 0);
+    assert!(toposort(&graph, None).is_ok());
+
+    // Removing nodes keeps the remaining ones in order.
+    for _ in 0..10 {
+        let n = NodeIndex::new(rng.gen_range(0..graph.node_count()));
+        graph.remove_node(n);
+        order.remove_node(&graph, n);
+        assert_topological(&graph, &order);
+    }
+    assert!(!order.order().contains(NodeIndex::new(graph.node_count())));
+}
+
+#[test]
+fn from_toposort() {
+    let mut graph = Graph::<(), ()>::new();
+    let nodes: Vec<_> = (0..5).map(|_| graph.add_node(())).collect();
+    graph.add_edge(nodes[3], nodes[1], ());
+    graph.add_edge(nodes[1], nodes[4], ());
+    let order: TopoOrder = TopoOrder::from_toposort(toposort(&graph, None).unwrap());
+    assert_topological(&graph, &order);
+
+    let mut sorted = vec![nodes[4], nodes[1], nodes[3]];
+    order.order().sort(&mut sorted);
+    assert_eq!(sorted, [nodes[3], nodes[1], nodes[4]]);
+}
+
+#[test]
+fn node_order_follows_removals() {
+    let mut graph = Graph::<(), ()>::new();
+    let mut order: NodeOrder = NodeOrder::new();
+    let a = graph.add_node(());
+    let b = graph.add_node(());
+    let c = graph.add_node(());
+    order.push(a);
+    order.push(c);
+    order.insert_after(b, a);
+    assert!(order.precedes(a, b) && order.precedes(b, c));
+
+    // Removing `a` moves `c` into its index.
+    graph.remove_node(a);
+    order.remove_node(&graph, a);
+    assert!(order.precedes(b, a));
+    assert!(!order.contains(c));
+}
@@ -0,0 +1,35 @@
+//! Integration tests for grouping handles in order of priority.
+
+use order_maintenance::{list_range, tag_range, MaintainedOrd};
+use rand::{Rng, SeedableRng};
+
+/// Events in priority order, tagged with the "session" they belong to, grouped back by session.
+macro_rules! group_tests {
+    ($name:ident, $priority:ty) => {
+        #[test]
+        fn $name() {
+            let mut rng = rand::rngs::StdRng::seed_from_u64(0);
+            let ps = <$priority>::new_sequence(100);
+            let session = |p: &$priority| ps.iter().position(|q| q == p).unwrap() / 10;
+
+            let mut shuffled = ps.clone();
+            for i in (1..shuffled.len()).rev() {
+                shuffled.swap(i, rng.gen_range(0..=i));
+            }
+            let groups = <$priority>::group_adjacent_by(&mut shuffled, session);
+            assert!(shuffled.iter().zip(&ps).all(|(a, b)| a == b));
+            assert_eq!(groups.len(), 10);
+            for (i, group) in groups.iter().enumerate() {
+                assert_eq!(*group, i * 10..(i + 1) * 10);
+            }
+
+            // Sessions that recur are separate groups.
+            let groups = <$priority>::group_adjacent_by(&mut shuffled, |p| session(p) % 2);
+            assert_eq!(groups.len(), 10);
+            assert!(<$priority>::group_adjacent_by(&mut [], session).is_empty());
+        }
+    };
+}
+
+group_tests!(tag_range_groups, tag_range::Priority);
+group_tests!(list_range_groups, list_range::Priority);
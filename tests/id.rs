@@ -0,0 +1,61 @@
+//! Integration tests for comparing priorities by their identifiers.
+
+use order_maintenance::id::Identified;
+use order_maintenance::view::OrderView;
+use order_maintenance::{list_range, tag_range, MaintainedOrd};
+use std::cmp::Ordering;
+
+macro_rules! id_tests {
+    ($name:ident, $priority:ty) => {
+        #[test]
+        fn $name() {
+            let ps = <$priority>::new_sequence(100);
+            let ids: Vec<_> = ps.iter().map(|p| p.id()).collect();
+            assert_eq!(
+                std::mem::size_of_val(&ids[0]),
+                2 * std::mem::size_of::<usize>()
+            );
+            {
+                let order = ps[50].id_order();
+                for (i, a) in ids.iter().enumerate() {
+                    assert!(order.contains(*a));
+                    for (j, b) in ids.iter().enumerate() {
+                        assert_eq!(order.compare(*a, *b), Some(i.cmp(&j)));
+                    }
+                }
+            }
+
+            // Identifiers of other arenas, and of dropped priorities, compare with nothing.
+            let other = <$priority>::new();
+            let order = ps[0].id_order();
+            assert_eq!(order.compare(ids[0], other.id()), None);
+            drop(order);
+            let p = ps[0].insert();
+            let id = p.id();
+            drop(p);
+            let order = ps[0].id_order();
+            assert!(!order.contains(id));
+            assert_eq!(order.compare(id, ids[0]), None);
+            drop(order);
+
+            // Views have the identifiers of the priorities they refer to.
+            let view = OrderView::of(&ps[3]);
+            assert_eq!(view.id(), ids[3]);
+            assert_eq!(
+                view.id_order().compare(view.id(), ids[4]),
+                Some(Ordering::Less)
+            );
+        }
+    };
+}
+
+id_tests!(tag_range_ids, tag_range::Priority);
+id_tests!(list_range_ids, list_range::Priority);
+
+#[test]
+#[should_panic]
+fn insert_while_borrowed() {
+    let a = tag_range::Priority::new();
+    let _order = a.id_order();
+    let _ = a.insert();
+}
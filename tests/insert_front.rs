@@ -0,0 +1,57 @@
+//! Integration tests for inserting at the front of an arena.
+
+use order_maintenance::capability::InsertFront;
+use order_maintenance::{list_range, tag_range, InArena, MaintainedOrd};
+use std::rc::Rc;
+
+/// Inserting at the front, including into a crowded front, yields ever smaller priorities.
+macro_rules! insert_front {
+    ($name:ident, $priority:ty) => {
+        #[test]
+        fn $name() {
+            let first = <$priority>::new();
+            let last = first.insert();
+            let mut ps = Vec::new();
+            for _ in 0..1000 {
+                let p = last.insert_front();
+                assert!(ps.iter().chain([&first, &last]).all(|q| p < *q));
+                ps.push(p);
+            }
+            ps.reverse();
+            ps.extend([first.clone(), last]);
+            assert!(ps.windows(2).all(|w| w[0] < w[1]));
+            assert_eq!(first.arena_priorities(), ps);
+            assert_eq!(first.check_invariants(), Ok(()));
+            assert!(first.label_epoch().get() > 0);
+        }
+    };
+}
+
+insert_front!(tag_range_insert_front, tag_range::Priority);
+insert_front!(list_range_insert_front, list_range::Priority);
+
+#[test]
+fn tag_range_lone_priority() {
+    // The least priority of a tag-range arena takes the least label, so it must be relabeled.
+    let a = tag_range::Priority::new();
+    let (label, epoch) = a.label_with_epoch().into_parts();
+    let b = a.insert_front();
+    assert!(b < a);
+    assert_eq!(b.label_with_epoch().label(), label);
+    assert!(a.label_epoch() > epoch);
+    assert_eq!(a.arena_priorities(), [b.clone(), a.clone()]);
+
+    // The new least priority is kept as the arena is emptied.
+    drop(a);
+    let c = b.insert();
+    assert!(b < c);
+    assert_eq!(b.check_invariants(), Ok(()));
+}
+
+#[test]
+fn shared_insert_front() {
+    let a = Rc::new(list_range::Priority::new());
+    let b = a.insert();
+    let c = b.insert_front();
+    assert!(c < a && a < b);
+}
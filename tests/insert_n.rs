@@ -0,0 +1,90 @@
+//! Integration tests for inserting several priorities at once.
+
+use order_maintenance::config::PriorityConfig;
+use order_maintenance::{define_priority, list_range, tag_range, MaintainedOrd};
+
+define_priority! {
+    /// Tag-range priorities with 16-bit labels.
+    TagRange16: tag_range(threshold = 1.3, bits = 16)
+}
+
+define_priority! {
+    /// List-range priorities with 16-bit labels.
+    ListRange16: list_range(bits = 16)
+}
+
+macro_rules! insert_n {
+    ($name:ident, $priority:ty, $max:expr) => {
+        mod $name {
+            use super::*;
+
+            #[test]
+            fn in_order() {
+                let first = <$priority>::new();
+                let mut ps = vec![first.clone(), first.insert()];
+                let mut state = 7usize;
+                for k in (0..).map(|round| round % 17) {
+                    if ps.len() + k > $max {
+                        break;
+                    }
+                    state = state
+                        .wrapping_mul(6364136223846793005)
+                        .wrapping_add(1442695040888963407);
+                    let at = (state >> 33) % ps.len();
+                    let epoch = ps[at].label_epoch();
+                    let inserted = ps[at].insert_n(k);
+                    assert_eq!(inserted.len(), k);
+                    assert!(ps[at].label_epoch().get() <= epoch.get() + 1);
+                    ps.splice(at + 1..at + 1, inserted);
+                    assert!(ps.windows(2).all(|w| w[0] < w[1]));
+                    first.check_invariants().unwrap();
+                }
+            }
+
+            #[test]
+            fn at_the_end() {
+                let first = <$priority>::new();
+                let mut ps = vec![first.clone()];
+                while ps.len() + 20 <= $max {
+                    let inserted = ps.last().unwrap().insert_n(20);
+                    ps.extend(inserted);
+                }
+                assert!(ps.windows(2).all(|w| w[0] < w[1]));
+                first.check_invariants().unwrap();
+            }
+
+            #[test]
+            fn none() {
+                let first = <$priority>::new();
+                assert!(first.insert_n(0).is_empty());
+                assert_eq!(first.label_epoch().get(), 0);
+            }
+        }
+    };
+}
+
+insert_n!(tag_range_priority, tag_range::Priority, 2000);
+insert_n!(list_range_priority, list_range::Priority, 2000);
+insert_n!(tag_range_16, TagRange16, 200);
+insert_n!(list_range_16, ListRange16, 200);
+
+#[test]
+fn fewer_relabels_than_one_at_a_time() {
+    let one_at_a_time = tag_range::Priority::new();
+    let mut ps = vec![one_at_a_time.clone()];
+    for _ in 0..1000 {
+        let p = ps.last().unwrap().insert();
+        ps.push(p);
+    }
+    let at_once = tag_range::Priority::new();
+    let ps = at_once.insert_n(1000);
+    assert_eq!(ps.len(), 1000);
+    assert!(at_once.label_epoch() < one_at_a_time.label_epoch());
+}
+
+#[test]
+#[should_panic(expected = "Quota of 10 live priorities was reached")]
+fn past_quota() {
+    let first: tag_range::Priority = PriorityConfig::new().max_live(10).build_arena();
+    let _ps = first.insert_n(10);
+}
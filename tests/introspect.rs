@@ -0,0 +1,103 @@
+//! Integration tests for the `introspect` feature.
+#![cfg(feature = "introspect")]
+
+use order_maintenance::introspect::Introspect;
+use order_maintenance::{list_range, tag_range, MaintainedOrd};
+
+fn neighbors_and_dumps<P: MaintainedOrd + Introspect + Clone>() {
+    let a = P::new();
+    let c = a.insert();
+    let b = a.insert();
+
+    assert_eq!(b.neighbor_labels(), (a.raw_label(), c.raw_label()));
+
+    let dump = b.dump_arena();
+    let labels = dump.labels();
+    let ia = labels.iter().position(|&l| l == a.raw_label()).unwrap();
+    assert_eq!(labels[ia + 1], b.raw_label());
+    assert_eq!(labels[ia + 2], c.raw_label());
+
+    let this: Vec<_> = dump.nodes.iter().filter(|n| n.is_self).collect();
+    assert_eq!(this.len(), 1);
+    assert_eq!(this[0].label, b.raw_label());
+    assert_eq!(this[0].ref_count, 1);
+
+    let b2 = b.clone();
+    let node = b2.dump_arena().nodes.into_iter().find(|n| n.is_self);
+    assert_eq!(node.unwrap().ref_count, 2);
+}
+
+#[test]
+fn tag_range_introspection() {
+    neighbors_and_dumps::<tag_range::Priority>();
+
+    // Tag-range priorities use the base priority, so the arena holds only what was inserted.
+    let a = tag_range::Priority::new();
+    assert_eq!(a.neighbor_labels(), (a.raw_label(), a.raw_label()));
+    assert_eq!(a.dump_arena().nodes.len(), 1);
+}
+
+#[test]
+fn list_range_introspection() {
+    neighbors_and_dumps::<list_range::Priority>();
+
+    // List-range priorities keep a separate base priority at the head of the list.
+    let a = list_range::Priority::new();
+    let dump = a.dump_arena();
+    assert_eq!(dump.nodes.len(), 2);
+    assert!(!dump.nodes[0].is_self);
+    assert!(dump.nodes[1].is_self);
+}
+
+#[test]
+fn creation_indices() {
+    let a = tag_range::Priority::new();
+    let c = a.insert();
+    let b = a.insert();
+    assert_eq!(
+        [a.creation_index(), c.creation_index(), b.creation_index()],
+        [0, 1, 2]
+    );
+
+    // Creation indices survive relabeling, and are not reused.
+    let ps: Vec<_> = (0..100).map(|_| a.insert()).collect();
+    assert_eq!(ps[99].creation_index(), 102);
+    assert_eq!(b.creation_index(), 2);
+    drop(ps);
+    assert_eq!(a.insert().creation_index(), 103);
+
+    // List-range arenas allocate their base priority first.
+    let a = list_range::Priority::new();
+    assert_eq!(a.creation_index(), 1);
+    assert_eq!(a.dump_arena().nodes[1].creation_index, 1);
+}
+
+fn gaps<P: MaintainedOrd + Introspect + Clone>() {
+    let ps = P::new_sequence(10);
+    for w in ps.windows(2) {
+        let gap = w[0].gap_to_next();
+        assert!(gap > 1);
+        assert_eq!(w[1].raw_label().wrapping_sub(w[0].raw_label()) as u64, gap);
+        // Inserting into a gap of more than one label splits it, without relabeling.
+        let p = w[0].insert();
+        assert_eq!(w[0].gap_to_next() + p.gap_to_next(), gap);
+    }
+    // The gap of the greatest priority extends to the end of the label space.
+    assert!(ps[9].gap_to_next() > 1);
+}
+
+#[test]
+fn gap_to_next() {
+    gaps::<tag_range::Priority>();
+    gaps::<list_range::Priority>();
+
+    // A lone tag-range priority spans the whole label space, which does not fit in a `u64`.
+    let a = tag_range::Priority::new();
+    assert_eq!(a.gap_to_next(), u64::MAX);
+
+    // Adjacent labels leave no room, so inserting between them relabels.
+    let b = a.insert();
+    let ps: Vec<_> = (0..64).map(|_| a.insert()).collect();
+    assert!(a.label_epoch().get() > 0);
+    assert!(ps.iter().chain([&a, &b]).all(|p| p.gap_to_next() >= 1));
+}
@@ -0,0 +1,241 @@
+//! Integration tests for the `ipc` feature.
+#![cfg(all(feature = "ipc", unix))]
+
+use order_maintenance::ipc::{
+    Client, Handle, IpcError, Request, Response, Server, ShutdownHandle, MAX_CONNECTIONS,
+};
+use std::cmp::Ordering;
+use std::io::{Read, Write};
+use std::os::unix::net::UnixStream;
+use std::path::PathBuf;
+use std::thread::JoinHandle;
+
+/// A server running on a thread of its own.
+struct Running {
+    path: PathBuf,
+    stop: ShutdownHandle,
+    serving: JoinHandle<()>,
+}
+
+/// Start a server on a fresh socket, named after `test`.
+fn start(test: &str) -> Running {
+    let path = std::env::temp_dir().join(format!("om-ipc-{test}-{}.sock", std::process::id()));
+    let _ = std::fs::remove_file(&path);
+    let server = Server::bind(&path).unwrap();
+    let stop = server.shutdown_handle();
+    let serving = std::thread::spawn(move || server.serve());
+    Running {
+        path,
+        stop,
+        serving,
+    }
+}
+
+fn stop(server: Running) {
+    let Running {
+        path,
+        stop,
+        serving,
+    } = server;
+    stop.shutdown();
+    serving.join().unwrap();
+    // The server no longer listens once it has stopped.
+    assert!(UnixStream::connect(&path).is_err());
+    std::fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn round_trip_messages() {
+    let requests = [
+        Request::Front,
+        Request::InsertAfter(Handle(7)),
+        Request::Release(Handle(u64::MAX)),
+        Request::Compare(Handle(1), Handle(2)),
+        Request::Sort(vec![Handle(3), Handle(1), Handle(2)]),
+        Request::Sort(vec![]),
+    ];
+    for request in requests {
+        assert_eq!(Request::decode(&request.encode()), Some(request));
+    }
+    let responses = [
+        Response::Handle(Handle(5)),
+        Response::Done,
+        Response::Ordering(Ordering::Less),
+        Response::Ordering(Ordering::Greater),
+        Response::Sorted(vec![Handle(2), Handle(9)]),
+        Response::UnknownHandle(Handle(4)),
+        Response::Malformed,
+        Response::Full,
+        Response::Incomparable,
+    ];
+    for response in responses {
+        assert_eq!(Response::decode(&response.encode()), Some(response));
+    }
+
+    assert_eq!(Request::decode(&[]), None);
+    assert_eq!(Request::decode(&[9]), None);
+    assert_eq!(Request::decode(&[1, 0, 0]), None);
+    assert_eq!(Request::decode(&[0, 0]), None);
+    // A list longer than the rest of the payload.
+    assert_eq!(Request::decode(&[4, 0xff, 0xff, 0xff, 0xff]), None);
+}
+
+#[test]
+fn clients_share_one_order() {
+    let server = start("share");
+    let mut alice = Client::connect(&server.path).unwrap();
+    let mut bob = Client::connect(&server.path).unwrap();
+
+    let a = alice.front().unwrap();
+    let c = bob.insert_after(a).unwrap();
+    let b = alice.insert_after(a).unwrap();
+    let z = bob.front().unwrap();
+    assert_eq!(bob.compare(a, b).unwrap(), Ordering::Less);
+    assert_eq!(alice.compare(c, b).unwrap(), Ordering::Greater);
+    assert_eq!(alice.sort(&[c, a, z, b]).unwrap(), [z, a, b, c]);
+
+    bob.release(b).unwrap();
+    assert!(matches!(alice.compare(a, b), Err(IpcError::UnknownHandle(h)) if h == b));
+    assert!(matches!(alice.release(b), Err(IpcError::UnknownHandle(_))));
+    // Handles are never reused.
+    let d = alice.insert_after(a).unwrap();
+    assert_ne!(d, b);
+    assert_eq!(bob.sort(&[d, c, a]).unwrap(), [a, d, c]);
+
+    stop(server);
+}
+
+#[test]
+fn concurrent_clients() {
+    let server = start("concurrent");
+    // Keep the client that inserted the first priority connected, so that it stays live.
+    let mut owner = Client::connect(&server.path).unwrap();
+    let first = owner.front().unwrap();
+    let workers: Vec<_> = (0..4)
+        .map(|_| {
+            let path = server.path.clone();
+            std::thread::spawn(move || {
+                let mut client = Client::connect(&path).unwrap();
+                let mut last = first;
+                let mut mine = vec![];
+                for _ in 0..50 {
+                    last = client.insert_after(last).unwrap();
+                    mine.push(last);
+                }
+                // Hand back the client too, since disconnecting would release the chain.
+                (client, mine)
+            })
+        })
+        .collect();
+    let (_workers, chains): (Vec<Client>, Vec<Vec<Handle>>) =
+        workers.into_iter().map(|w| w.join().unwrap()).unzip();
+
+    let mut client = Client::connect(&server.path).unwrap();
+    for chain in &chains {
+        // Each chain stays in the order it was inserted in, whatever the others did meanwhile.
+        assert_eq!(&client.sort(chain).unwrap(), chain);
+    }
+    let all: Vec<Handle> = chains.iter().flatten().copied().collect();
+    assert_eq!(client.sort(&all).unwrap().len(), 200);
+    drop(owner);
+
+    stop(server);
+}
+
+#[test]
+fn disconnecting_releases_handles() {
+    let server = start("disconnect");
+    let mut alice = Client::connect(&server.path).unwrap();
+    let mut bob = Client::connect(&server.path).unwrap();
+    let a = alice.front().unwrap();
+    let b = bob.insert_after(a).unwrap();
+    let c = bob.insert_after(b).unwrap();
+    bob.release(c).unwrap();
+
+    drop(bob);
+    // The server notices the disconnect eventually, but not necessarily before the next request.
+    while alice.compare(a, b).is_ok() {
+        std::thread::yield_now();
+    }
+    assert!(matches!(alice.compare(a, b), Err(IpcError::UnknownHandle(h)) if h == b));
+    // Priorities inserted after ones that were released are unaffected.
+    let d = alice.insert_after(a).unwrap();
+    assert_eq!(alice.sort(&[d, a]).unwrap(), [a, d]);
+
+    stop(server);
+}
+
+#[test]
+fn malformed_requests_are_rejected() {
+    let server = start("malformed");
+    let mut stream = UnixStream::connect(&server.path).unwrap();
+    stream.write_all(&2u32.to_le_bytes()).unwrap();
+    stream.write_all(&[42, 0]).unwrap();
+    let mut reply = [0; 5];
+    stream.read_exact(&mut reply).unwrap();
+    assert_eq!(reply, [1, 0, 0, 0, 5]);
+
+    // The connection is still usable afterwards.
+    let front = Request::Front.encode();
+    stream
+        .write_all(&(front.len() as u32).to_le_bytes())
+        .unwrap();
+    stream.write_all(&front).unwrap();
+    let mut reply = [0; 13];
+    stream.read_exact(&mut reply).unwrap();
+    assert_eq!(reply[..5], [9, 0, 0, 0, 0]);
+
+    stop(server);
+}
+
+#[test]
+fn shutdown_closes_connections() {
+    let server = start("shutdown");
+    let mut idle = Client::connect(&server.path).unwrap();
+    let first = idle.front().unwrap();
+    stop(server);
+    // The server closed the idle connection rather than leaving a thread blocked on it.
+    assert!(matches!(idle.insert_after(first), Err(IpcError::Io(_))));
+}
+
+#[test]
+fn connections_are_capped() {
+    let server = start("capped");
+    let mut clients: Vec<Client> = (0..MAX_CONNECTIONS)
+        .map(|_| Client::connect(&server.path).unwrap())
+        .collect();
+    let first = clients[0].front().unwrap();
+    for client in &mut clients {
+        client.insert_after(first).unwrap();
+    }
+    // The server closes connections beyond the cap right away.
+    let mut extra = Client::connect(&server.path).unwrap();
+    assert!(matches!(extra.insert_after(first), Err(IpcError::Io(_))));
+
+    // Once a connection closes, the server serves a new one again.
+    drop(clients.pop());
+    loop {
+        let mut client = Client::connect(&server.path).unwrap();
+        if client.insert_after(first).is_ok() {
+            break;
+        }
+        std::thread::yield_now();
+    }
+
+    stop(server);
+}
+
+#[test]
+fn clients_cannot_shut_down() {
+    let server = start("no-shutdown");
+    let mut stream = UnixStream::connect(&server.path).unwrap();
+    // Tag 5 is not a request, so the server rejects it rather than stopping.
+    stream.write_all(&1u32.to_le_bytes()).unwrap();
+    stream.write_all(&[5]).unwrap();
+    let mut reply = [0; 5];
+    stream.read_exact(&mut reply).unwrap();
+    assert_eq!(reply, [1, 0, 0, 0, 5]);
+    assert!(Client::connect(&server.path).unwrap().front().is_ok());
+
+    stop(server);
+}
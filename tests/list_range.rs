@@ -4,35 +4,31 @@
 
 mod common;
 use common::qc;
-use order_maintenance::list_range::Priority;
+use order_maintenance::{list_range::Priority, om_delegate};
 use quickcheck_macros::quickcheck;
 
-macro_rules! delegate_tests {
-    () => {};
-    (fn $test_name:ident(); $($toks:tt)*) => {
-        #[test]
-        fn $test_name() {
-            common::tests::$test_name::<Priority>();
-        }
-        delegate_tests!{$($toks)*}
-    };
-}
-
-delegate_tests! {
-    fn compare_two();
-    fn insertion();
-    fn transitive();
-    fn drop_first();
-    fn drop_middle();
-    fn drop_some();
-    fn drop_random();
-    fn insert_some_begin();
-    fn insert_some_end();
-    fn insert_some_flipflop();
-    fn insert_many_begin();
-    fn insert_many_end();
-    fn insert_some_begin_many_end();
-    fn insert_many_random();
+om_delegate! {
+    Priority => tests [
+        compare_two,
+        insertion,
+        transitive,
+        drop_first,
+        drop_middle,
+        drop_some,
+        drop_random,
+        insert_some_begin,
+        insert_some_end,
+        insert_some_flipflop,
+        insert_many_begin,
+        insert_many_end,
+        insert_some_begin_many_end,
+        insert_many_random,
+        new_sequence,
+        tag_in_order,
+        tag_sorted,
+        default_new,
+        debug_rank,
+    ]
 }
 
 #[quickcheck]
@@ -0,0 +1,81 @@
+//! Integration tests for least-recently-used caches.
+
+use order_maintenance::{list_range, lru::OmLru};
+use rand::{Rng, SeedableRng};
+use std::collections::VecDeque;
+
+/// Check the cache against a naive model, which keeps keys from least to most recently used.
+fn check_against_model(cache: &OmLru<usize, usize, list_range::Priority>, model: &VecDeque<usize>) {
+    let keys: Vec<usize> = cache
+        .iter()
+        .map(|(k, v)| {
+            assert_eq!(*v, k * 10);
+            *k
+        })
+        .collect();
+    assert_eq!(keys, model.iter().copied().collect::<Vec<_>>());
+    for (i, x) in model.iter().enumerate() {
+        for y in model.iter().skip(i + 1) {
+            assert_eq!(cache.used_after(y, x), Some(true));
+            assert_eq!(cache.used_after(x, y), Some(false));
+        }
+    }
+}
+
+#[test]
+fn random_uses() {
+    let mut rng = rand::rngs::StdRng::seed_from_u64(0);
+    let mut cache = OmLru::new(16);
+    let mut model: VecDeque<usize> = VecDeque::new();
+    for step in 0..2000 {
+        let key = rng.gen_range(0..32);
+        match rng.gen_range(0..4) {
+            0 => {
+                assert_eq!(cache.get(&key).is_some(), model.contains(&key));
+                if let Some(i) = model.iter().position(|k| *k == key) {
+                    model.remove(i);
+                    model.push_back(key);
+                }
+            }
+            1 => {
+                assert_eq!(cache.remove(&key).is_some(), model.contains(&key));
+                model.retain(|k| *k != key);
+            }
+            _ => {
+                let evicted = cache.insert(key, key * 10);
+                if let Some(i) = model.iter().position(|k| *k == key) {
+                    model.remove(i);
+                    assert_eq!(evicted, None);
+                } else if model.len() == cache.capacity() {
+                    let lru = model.pop_front().unwrap();
+                    assert_eq!(evicted, Some((lru, lru * 10)));
+                }
+                model.push_back(key);
+            }
+        }
+        assert_eq!(cache.len(), model.len());
+        if step % 100 == 0 {
+            check_against_model(&cache, &model);
+        }
+    }
+    check_against_model(&cache, &model);
+    assert_eq!(cache.used_after(&100, &model[0]), None);
+}
+
+#[test]
+fn peek_does_not_touch() {
+    let mut cache: OmLru<&str, u32> = OmLru::new(2);
+    cache.insert("a", 1);
+    cache.insert("b", 2);
+    assert_eq!(cache.peek(&"a"), Some(&1));
+    assert_eq!(cache.insert("a", 3), None);
+    assert_eq!(cache.pop_lru(), Some(("b", 2)));
+    assert_eq!(cache.pop_lru(), Some(("a", 3)));
+    assert!(cache.is_empty());
+}
+
+#[test]
+#[should_panic(expected = "capacity must be positive")]
+fn zero_capacity() {
+    let _: OmLru<u32, u32> = OmLru::new(0);
+}
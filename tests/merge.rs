@@ -0,0 +1,40 @@
+//! Integration tests for merging sequences of priorities.
+
+use order_maintenance::{list_range, tag_range, MaintainedOrd};
+use rand::{Rng, SeedableRng};
+
+/// Merging two sorted halves of an arena's priorities sorts all of them.
+macro_rules! merge_halves {
+    ($name:ident, $priority:ty) => {
+        #[test]
+        fn $name() {
+            let mut rng = rand::rngs::StdRng::seed_from_u64(0);
+            let mut ps = vec![<$priority>::new()];
+            for _ in 0..500 {
+                let p = ps[rng.gen_range(0..ps.len())].insert();
+                ps.push(p);
+            }
+            let sort = |ps: &mut Vec<$priority>| ps.sort_by(|a, b| a.partial_cmp(b).unwrap());
+            let (mut a, mut b): (Vec<_>, Vec<_>) =
+                ps.iter().cloned().partition(|_| rng.gen_range(0..2) == 0);
+            sort(&mut a);
+            sort(&mut b);
+            sort(&mut ps);
+
+            assert_eq!(<$priority>::merge(&a, &b), ps);
+            assert_eq!(<$priority>::merge(&b, &a), ps);
+            assert_eq!(<$priority>::merge(&a, &[]), a);
+            assert!(<$priority>::merge(&[], &[]).is_empty());
+        }
+    };
+}
+
+merge_halves!(tag_range_merge, tag_range::Priority);
+merge_halves!(list_range_merge, list_range::Priority);
+
+#[test]
+#[should_panic(expected = "merged priorities must belong to the same arena")]
+fn merge_across_arenas() {
+    let _ =
+        tag_range::Priority::merge(&[tag_range::Priority::new()], &[tag_range::Priority::new()]);
+}
@@ -0,0 +1,64 @@
+//! Integration tests for locating the least and greatest priorities in an arena, and for walking
+//! it from one priority to the next.
+
+use order_maintenance::{list_range, tag_range, MaintainedOrd};
+
+/// Insert in both directions, then drop the extremes, checking the least and greatest priorities.
+macro_rules! min_max_under_drops {
+    ($name:ident, $priority:ty) => {
+        #[test]
+        fn $name() {
+            let mut ps = vec![<$priority>::new()];
+            assert!(ps[0].min_in_arena() == ps[0] && ps[0].max_in_arena() == ps[0]);
+
+            for i in 0..100 {
+                let p = ps[i].insert();
+                ps.push(p);
+            }
+            ps.sort_by(|a, b| a.partial_cmp(b).unwrap());
+            while !ps.is_empty() {
+                let mid = &ps[ps.len() / 2];
+                assert!(mid.min_in_arena() == ps[0]);
+                assert!(mid.max_in_arena() == *ps.last().unwrap());
+                if ps.len() % 2 == 0 {
+                    ps.remove(0);
+                } else {
+                    ps.pop();
+                }
+            }
+        }
+    };
+}
+
+min_max_under_drops!(tag_range_min_max, tag_range::Priority);
+min_max_under_drops!(list_range_min_max, list_range::Priority);
+
+/// Walk the arena in both directions from its least and greatest priorities, under drops.
+macro_rules! neighbors_under_drops {
+    ($name:ident, $priority:ty) => {
+        #[test]
+        fn $name() {
+            let mut ps = vec![<$priority>::new()];
+            assert!(ps[0].successor().is_none() && ps[0].predecessor().is_none());
+
+            for i in 0..100 {
+                let p = ps[i / 2].insert();
+                ps.push(p);
+            }
+            ps.sort_by(|a, b| a.partial_cmp(b).unwrap());
+            while !ps.is_empty() {
+                let forward: Vec<_> =
+                    std::iter::successors(Some(ps[0].min_in_arena()), |p| p.successor()).collect();
+                assert!(forward == ps);
+                let backward: Vec<_> =
+                    std::iter::successors(Some(ps[0].max_in_arena()), |p| p.predecessor())
+                        .collect();
+                assert!(backward.iter().rev().eq(ps.iter()));
+                ps.remove(ps.len() / 3);
+            }
+        }
+    };
+}
+
+neighbors_under_drops!(tag_range_neighbors, tag_range::Priority);
+neighbors_under_drops!(list_range_neighbors, list_range::Priority);
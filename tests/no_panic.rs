@@ -0,0 +1,59 @@
+//! Integration tests asserting that sequences of operations documented not to panic do not.
+
+use order_maintenance::config::PriorityConfig;
+use order_maintenance::{list_range, tag_range, InArena, MaintainedOrd};
+use rand::{Rng, SeedableRng};
+use std::panic::{catch_unwind, AssertUnwindSafe};
+
+macro_rules! no_panic_tests {
+    ($name:ident, $P:ty) => {
+        #[test]
+        fn $name() {
+            for seed in 0..4 {
+                let result = catch_unwind(AssertUnwindSafe(|| {
+                    let mut rng = rand::rngs::StdRng::seed_from_u64(seed);
+                    let first: $P = if seed % 2 == 0 {
+                        <$P>::new()
+                    } else {
+                        PriorityConfig::new().deferred_drop().build_arena()
+                    };
+                    let other = <$P>::new();
+                    let mut ps = vec![first];
+                    for _ in 0..3000 {
+                        let i = rng.gen_range(0..ps.len());
+                        let j = rng.gen_range(0..ps.len());
+                        match rng.gen_range(0..8) {
+                            0..=2 => {
+                                let p = ps[i].insert();
+                                ps.push(p);
+                            }
+                            3 if ps.len() > 1 => drop(ps.swap_remove(i)),
+                            4 => ps.push(ps[i].clone()),
+                            5 => {
+                                let _ = ps[i].partial_cmp(&ps[j]);
+                                let _ = ps[i].partial_cmp(&other);
+                                let _ = <$P>::compare_many(&[(ps[i].clone(), ps[j].clone())]);
+                            }
+                            6 => {
+                                let _ = ps[i].label_epoch();
+                                let _ = ps[i].position_hint();
+                                let _ = format!("{:?}", ps[i]);
+                                let _ = ps[i].gc();
+                            }
+                            _ => {
+                                let _ = ps[i].min_in_arena();
+                                let _ = ps[i].max_in_arena();
+                                let _ = ps[i].arena_priorities();
+                                assert_eq!(ps[i].check_invariants(), Ok(()));
+                            }
+                        }
+                    }
+                }));
+                assert!(result.is_ok(), "panicked with seed {seed}");
+            }
+        }
+    };
+}
+
+no_panic_tests!(tag_range_no_panic, tag_range::Priority);
+no_panic_tests!(list_range_no_panic, list_range::Priority);
@@ -0,0 +1,94 @@
+//! Integration tests for the `oplog` feature.
+#![cfg(all(feature = "oplog", feature = "introspect"))]
+
+use order_maintenance::introspect::Introspect;
+use order_maintenance::oplog::{Op, OpLog, Recorded};
+use order_maintenance::{list_range, tag_range};
+use rand::{Rng, SeedableRng};
+
+/// Perform random operations on an arena, then check that replaying its log reproduces it.
+fn replay_reproduces_state<P: Recorded + Introspect>() {
+    let mut rng = rand::rngs::StdRng::seed_from_u64(0);
+    let mut ps = P::new_sequence(4);
+    let mut regions = Vec::new();
+    for _ in 0..500 {
+        let i = rng.gen_range(0..ps.len());
+        match rng.gen_range(0..50) {
+            0..=19 => ps.push(ps[i].insert()),
+            20..=29 => ps.push(ps[i].clone()),
+            30..=39 => ps.push(ps[i].insert_front()),
+            40 => {
+                ps[i].rebalance_all();
+            }
+            41 => {
+                ps[i].compact_labels();
+            }
+            42 | 43 => ps.extend(ps[i].insert_n(3)),
+            44 => regions.extend(ps[i].reserve_region_after(3)),
+            45 => drop(regions.pop()),
+            _ if ps.len() > 1 => drop(ps.swap_remove(i)),
+            _ => (),
+        }
+    }
+
+    drop(regions);
+    ps.sort_by(|a, b| a.partial_cmp(b).unwrap());
+    let log = ps[0].op_log();
+    assert_eq!(log.ops()[0], Op::NewSequence(4));
+
+    let replayed: Vec<P> = OpLog::from_bytes(&log.to_bytes()).unwrap().replay();
+    assert_eq!(replayed.len(), ps.len());
+    assert_eq!(replayed[0].dump_arena(), ps[0].dump_arena());
+    assert_eq!(replayed[0].op_log(), log);
+}
+
+#[test]
+fn tag_range_replay() {
+    replay_reproduces_state::<tag_range::Priority>();
+}
+
+#[test]
+fn list_range_replay() {
+    replay_reproduces_state::<list_range::Priority>();
+}
+
+#[test]
+fn historical_order() {
+    use order_maintenance::oplog::HistoricalOrder;
+    use order_maintenance::MaintainedOrd;
+    use std::cmp::Ordering;
+
+    // b starts out behind c, then gets ahead of it once c is moved behind it.
+    let a = tag_range::Priority::new();
+    let b = a.insert();
+    let c = a.insert();
+    let (b_key, c_key) = (b.op_key(), c.op_key());
+    let history = HistoricalOrder::<tag_range::Priority>::new(a.op_log());
+    assert_eq!(history.at(2).compare(c_key, b_key), Some(Ordering::Less));
+    drop(c);
+    let c = b.insert();
+    let c_key = c.op_key();
+
+    let history = HistoricalOrder::<tag_range::Priority>::new(a.op_log());
+    assert_eq!(history.len(), 5);
+    assert_eq!(history.at(0).keys(), [a.op_key()]);
+    assert_eq!(history.at(1).compare(b_key, c_key), None);
+    let ahead = history.bisect(|order| order.compare(b_key, c_key) == Some(Ordering::Less));
+    assert_eq!(ahead, Some(4));
+    assert_eq!(history.at(4).keys(), [a.op_key(), b_key, c_key]);
+    assert_eq!(history.bisect(|_| false), None);
+}
+
+#[test]
+fn clone_while_borrowed() {
+    use order_maintenance::id::Identified;
+    use order_maintenance::MaintainedOrd;
+
+    // Logging a clone must not borrow the arena, which the `IdOrder` holds on to.
+    let a = tag_range::Priority::new();
+    let order = a.id_order();
+    let b = a.clone();
+    assert!(order.contains(b.id()));
+    drop(order);
+    assert_eq!(a.op_log().ops().last(), Some(&Op::Clone(a.op_key())));
+}
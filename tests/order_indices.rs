@@ -0,0 +1,42 @@
+//! Integration tests for sorting handles by priority.
+
+use order_maintenance::{list_range, tag_range, MaintainedOrd};
+use rand::{Rng, SeedableRng};
+
+/// The permutation from `order_indices()` agrees with sorting by comparison.
+macro_rules! order_indices_sorts {
+    ($name:ident, $priority:ty) => {
+        #[test]
+        fn $name() {
+            let mut rng = rand::rngs::StdRng::seed_from_u64(0);
+            let mut ps = vec![<$priority>::new()];
+            for _ in 0..500 {
+                let i = rng.gen_range(0..ps.len());
+                // Include duplicate handles, which must stay in their original order.
+                let p = if rng.gen_range(0..5) == 0 {
+                    ps[i].clone()
+                } else {
+                    ps[i].insert()
+                };
+                ps.push(p);
+            }
+
+            let mut expected: Vec<usize> = (0..ps.len()).collect();
+            expected.sort_by(|&i, &j| ps[i].partial_cmp(&ps[j]).unwrap());
+            assert_eq!(<$priority>::order_indices(&ps), expected);
+            assert!(<$priority>::order_indices(&[]).is_empty());
+        }
+    };
+}
+
+order_indices_sorts!(tag_range_order_indices, tag_range::Priority);
+order_indices_sorts!(list_range_order_indices, list_range::Priority);
+
+#[test]
+#[should_panic(expected = "ordered priorities must belong to the same arena")]
+fn order_indices_across_arenas() {
+    let _ = list_range::Priority::order_indices(&[
+        list_range::Priority::new(),
+        list_range::Priority::new(),
+    ]);
+}
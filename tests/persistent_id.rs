@@ -0,0 +1,99 @@
+//! Integration tests for the `persistent-id` feature.
+#![cfg(feature = "persistent-id")]
+
+use order_maintenance::config::PriorityConfig;
+use order_maintenance::persistent_id::PersistentId;
+use order_maintenance::{list_range, tag_range, MaintainedOrd};
+
+macro_rules! persistent_ids {
+    ($name:ident, $priority:ty) => {
+        #[test]
+        fn $name() {
+            let first = <$priority>::new();
+            let ps: Vec<$priority> = (0..500).map(|_| first.insert()).collect();
+            let ids: Vec<PersistentId> = ps.iter().map(|p| p.persistent_id()).collect();
+            assert!(first.label_epoch().get() > 0);
+
+            // Identifiers are distinct within an arena, and share the arena's identifier.
+            let mut sorted = ids.clone();
+            sorted.sort();
+            sorted.dedup();
+            assert_eq!(sorted.len(), ids.len());
+            assert!(ids.iter().all(|id| id.arena() == ids[0].arena()));
+
+            // Every identifier resolves to its priority, through any handle in the arena.
+            for (p, id) in ps.iter().zip(&ids) {
+                assert!(ps[0].find(*id).as_ref() == Some(p));
+                assert_eq!(PersistentId::from_u128(id.as_u128()), *id);
+            }
+
+            // Other arenas resolve nothing.
+            let other = <$priority>::new();
+            assert_ne!(other.persistent_id().arena(), ids[0].arena());
+            assert!(other.find(ids[0]).is_none());
+        }
+    };
+}
+
+persistent_ids!(tag_range_persistent_ids, tag_range::Priority);
+persistent_ids!(list_range_persistent_ids, list_range::Priority);
+
+#[test]
+fn list_range_base_is_hidden() {
+    let first = list_range::Priority::new();
+    // The base priority is allocated first, before any handle.
+    let base = PersistentId::from_u128(first.persistent_id().as_u128() - 1);
+    assert_eq!(base.index(), 0);
+    assert!(first.find(base).is_none());
+}
+
+#[test]
+fn deferred_drops_are_found_until_collected() {
+    let first: tag_range::Priority = PriorityConfig::new().deferred_drop().build_arena();
+    let p = first.insert();
+    let id = p.persistent_id();
+    drop(p);
+    let found = first.find(id).unwrap();
+    assert_eq!(first.gc(), 0);
+    drop(found);
+    assert_eq!(first.gc(), 1);
+    assert!(first.find(id).is_none());
+}
+
+#[test]
+fn displayed_in_hex() {
+    let id = tag_range::Priority::new().persistent_id();
+    let shown = id.to_string();
+    assert_eq!(shown.len(), 32);
+    assert_eq!(u128::from_str_radix(&shown, 16).unwrap(), id.as_u128());
+}
+
+#[test]
+fn iter_with_secondary_maps() {
+    use std::collections::{BTreeMap, HashMap};
+
+    let first = list_range::Priority::new();
+    let mut ps = vec![first.clone()];
+    for i in 0..100 {
+        let p = ps[i / 3].insert();
+        ps.push(p);
+    }
+    ps.sort_by(|a, b| a.partial_cmp(b).unwrap());
+
+    // Only every other priority has data.
+    let data: HashMap<PersistentId, usize> = ps
+        .iter()
+        .enumerate()
+        .filter(|(i, _)| i % 2 == 0)
+        .map(|(i, p)| (p.persistent_id(), i))
+        .collect();
+    let zipped: Vec<_> = first.iter_with(&data).collect();
+    assert_eq!(zipped.len(), 51);
+    for (p, &i) in zipped {
+        assert!(p == ps[i]);
+    }
+
+    let data: BTreeMap<PersistentId, usize> = data.into_iter().collect();
+    let order: Vec<usize> = ps[7].iter_with(&data).map(|(_, &i)| i).collect();
+    assert!(order.windows(2).all(|w| w[0] < w[1]));
+}
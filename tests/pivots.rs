@@ -0,0 +1,39 @@
+//! Integration tests for inserting evenly spread pivots.
+
+use order_maintenance::{list_range, tag_range, InArena};
+
+/// Count how many of `ps` are less than each of `pivots`.
+fn ranks<P: PartialOrd>(ps: &[P], pivots: &[P]) -> Vec<usize> {
+    pivots
+        .iter()
+        .map(|pivot| ps.iter().filter(|p| *p < pivot).count())
+        .collect()
+}
+
+fn pivots_spread_evenly<P: InArena>() {
+    let ps = P::new_sequence(100);
+    let pivots = ps[42].pivots(4);
+    assert_eq!(ranks(&ps, &pivots), [25, 50, 75, 100]);
+    assert_eq!(ps[0].arena_priorities().len(), 104);
+
+    let ps = P::new_sequence(100);
+    let pivots = ps[0].pivots(3);
+    assert_eq!(ranks(&ps, &pivots), [34, 67, 100]);
+
+    // More pivots than priorities.
+    let few = P::new_sequence(2);
+    let pivots = few[0].pivots(5);
+    assert_eq!(ranks(&few, &pivots), [1, 1, 2, 2, 2]);
+    assert!(pivots.windows(2).all(|w| w[0] < w[1]));
+    assert!(few[0].pivots(0).is_empty());
+}
+
+#[test]
+fn tag_range_pivots() {
+    pivots_spread_evenly::<tag_range::Priority>();
+}
+
+#[test]
+fn list_range_pivots() {
+    pivots_spread_evenly::<list_range::Priority>();
+}
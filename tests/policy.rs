@@ -0,0 +1,35 @@
+//! Integration tests for priorities with pluggable relabeling policies.
+//!
+//! Delegates to tests defined in the `common` module.
+
+mod common;
+use order_maintenance::om_delegate;
+use order_maintenance::policy::{CustomPriority, ListRangePolicy, TagRangePolicy};
+
+type TagRange = CustomPriority<TagRangePolicy<order_maintenance::tag_range::DefaultParams>>;
+type ListRange = CustomPriority<ListRangePolicy<order_maintenance::list_range::DefaultParams>>;
+
+om_delegate! {
+    TagRange as "tag_range",
+    ListRange as "list_range"
+    => tests [
+        compare_two,
+        insertion,
+        transitive,
+        drop_first,
+        drop_middle,
+        drop_some,
+        drop_random,
+        insert_some_begin,
+        insert_some_end,
+        insert_some_flipflop,
+        insert_many_begin,
+        insert_many_end,
+        insert_some_begin_many_end,
+        insert_many_random,
+        new_sequence,
+        tag_in_order,
+        default_new,
+        debug_rank,
+    ]
+}
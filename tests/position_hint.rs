@@ -0,0 +1,21 @@
+//! Integration tests for approximate positions of priorities.
+
+use order_maintenance::{list_range, tag_range, MaintainedOrd};
+
+/// Position hints stay within range, and follow the order of priorities.
+macro_rules! hints_follow_order {
+    ($name:ident, $priority:ty) => {
+        #[test]
+        fn $name() {
+            let ps = <$priority>::new_sequence(1000);
+            let hints: Vec<f64> = ps.iter().map(|p| p.position_hint()).collect();
+            assert!(hints.iter().all(|h| (0.0..=1.0).contains(h)));
+            assert!(hints.windows(2).all(|w| w[0] < w[1]));
+            // Evenly spaced labels give a good approximation of the rank.
+            assert!((hints[500] - 0.5).abs() < 0.01);
+        }
+    };
+}
+
+hints_follow_order!(tag_range_hints, tag_range::Priority);
+hints_follow_order!(list_range_hints, list_range::Priority);
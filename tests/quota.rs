@@ -0,0 +1,95 @@
+//! Integration tests for per-arena quotas.
+
+use order_maintenance::capability::{CapacityErrorKind, TryInsert};
+use order_maintenance::config::PriorityConfig;
+use order_maintenance::{list_range, tag_range, MaintainedOrd};
+
+macro_rules! quotas {
+    ($live:ident, $relabel:ident, $priority:ty) => {
+        #[test]
+        fn $live() {
+            let first: $priority = PriorityConfig::new().max_live(10).build_arena();
+            let mut ps = vec![first.clone()];
+            let error = loop {
+                match ps[ps.len() / 2].try_insert() {
+                    Ok(p) => ps.push(p),
+                    Err(e) => break e,
+                }
+            };
+            assert_eq!(error.kind(), CapacityErrorKind::LiveQuota { max: 10 });
+            assert_eq!(error.live(), 10);
+            assert!(error.to_string().starts_with("Quota of 10 live priorities"));
+
+            // The arena was left as is, and takes priorities again once some are dropped.
+            assert_eq!(first.try_insert().unwrap_err(), error);
+            ps.pop();
+            assert!(first < first.try_insert().unwrap());
+        }
+
+        #[test]
+        fn $relabel() {
+            let first: $priority = PriorityConfig::new()
+                .max_relabeled_per_sec(64)
+                .build_arena();
+            let mut ps = vec![first.clone()];
+            // Inserting at the same spot relabels ever more often, soon exceeding the quota.
+            let error = loop {
+                match ps.last().unwrap().try_insert() {
+                    Ok(p) => ps.push(p),
+                    Err(e) => break e,
+                }
+                assert!(ps.len() < 100_000, "quota was never reached");
+            };
+            assert_eq!(
+                error.kind(),
+                CapacityErrorKind::RelabelQuota { max_per_sec: 64 }
+            );
+            assert!(first.label_epoch().get() > 0);
+            assert!(ps.windows(2).all(|w| w[0] < w[1]));
+        }
+    };
+}
+
+quotas!(
+    tag_range_live_quota,
+    tag_range_relabel_quota,
+    tag_range::Priority
+);
+quotas!(
+    list_range_live_quota,
+    list_range_relabel_quota,
+    list_range::Priority
+);
+
+#[test]
+#[should_panic(expected = "Quota of 2 live priorities was reached")]
+fn insert_past_quota() {
+    let first: tag_range::Priority = PriorityConfig::new().max_live(2).build_arena();
+    let _second = first.insert();
+    let _third = first.insert();
+}
+
+#[test]
+fn without_quota() {
+    let first = tag_range::Priority::new();
+    let ps: Vec<_> = (0..1000).map(|_| first.try_insert().unwrap()).collect();
+    assert!(ps.windows(2).all(|w| w[0] > w[1]));
+}
+
+/// Commit reservations taken within the quota, then check that the last one is refused.
+macro_rules! reservations_past_quota {
+    ($name:ident, $priority:ty) => {
+        #[test]
+        #[should_panic(expected = "Quota of 3 live priorities was reached")]
+        fn $name() {
+            let a: $priority = PriorityConfig::new().max_live(3).build_arena();
+            let b = a.insert();
+            let (ra, rb) = (a.reserve_after(), b.reserve_after());
+            let _c = ra.commit();
+            let _d = rb.commit();
+        }
+    };
+}
+
+reservations_past_quota!(tag_range_reservations_past_quota, tag_range::Priority);
+reservations_past_quota!(list_range_reservations_past_quota, list_range::Priority);
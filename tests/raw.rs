@@ -0,0 +1,58 @@
+//! Integration tests for the low-level arena API.
+
+use order_maintenance::check::Violation;
+use order_maintenance::raw::RawPriority;
+
+/// Label a new node halfway between this one and the next, with 8-bit labels and no relabeling.
+fn halfway(p: &RawPriority) -> RawPriority {
+    p.insert_with(|at| {
+        let next = if at.next_label() == 0 {
+            256
+        } else {
+            at.next_label()
+        };
+        (at.label() + next) / 2
+    })
+}
+
+#[test]
+fn insert_and_drop() {
+    let first = RawPriority::new(128);
+    let mut ps = vec![first.clone()];
+    for _ in 0..6 {
+        let p = halfway(ps.last().unwrap());
+        ps.push(p);
+    }
+    let labels: Vec<usize> = ps.iter().map(RawPriority::label).collect();
+    assert_eq!(labels, [128, 192, 224, 240, 248, 252, 254]);
+    assert!(ps.iter().all(|p| p.same_arena(&first)));
+    assert_eq!(ps[3].position(), 4);
+    assert_eq!(first.check_invariants(8), Ok(()));
+
+    ps.remove(3);
+    assert_eq!(ps[3].position(), 4);
+    assert_eq!(first.check_invariants(8), Ok(()));
+    assert!(!first.same_arena(&RawPriority::new(128)));
+}
+
+#[test]
+fn relabel_through_cursor() {
+    let first = RawPriority::new(1);
+    let second = first.insert_with(|at| {
+        // Relabel this node before labeling the new one after it.
+        at.set_label(0x10);
+        at.label() + 1
+    });
+    assert_eq!((first.label(), second.label()), (0x10, 0x11));
+    assert_eq!(first.check_invariants(8), Ok(()));
+}
+
+#[test]
+fn detects_mislabeling() {
+    let first = RawPriority::new(128);
+    let _second = first.insert_with(|_| 64);
+    assert!(matches!(
+        first.check_invariants(8),
+        Err(Violation::Unordered { .. })
+    ));
+}
@@ -0,0 +1,69 @@
+//! Integration tests for rebalancing the labels of an arena.
+
+use order_maintenance::{list_range, tag_range, MaintainedOrd};
+
+/// Rebalancing an arena crowded at one end preserves its order and spreads its labels evenly.
+///
+/// `hidden` is the number of priorities in the arena that precede every handle, i.e., list-range's
+/// base priority.
+macro_rules! rebalance_all {
+    ($name:ident, $priority:ty, $hidden:expr) => {
+        #[test]
+        fn $name() {
+            let first = <$priority>::new();
+            let mut ps: Vec<$priority> = (0..1000).map(|_| first.insert()).collect();
+            ps.push(first.clone());
+            ps.sort_by(|a, b| a.partial_cmp(b).unwrap());
+
+            let epoch = first.label_epoch();
+            assert_eq!(first.rebalance_all(), ps.len() + $hidden);
+            assert_eq!(first.label_epoch(), epoch.next());
+            assert_eq!(first.check_invariants(), Ok(()));
+            assert!(ps.windows(2).all(|w| w[0] < w[1]));
+            for (i, p) in ps.iter().enumerate() {
+                let expected = (i + $hidden) as f64 / (ps.len() + $hidden) as f64;
+                assert!((p.position_hint() - expected).abs() < 1e-9);
+            }
+        }
+    };
+}
+
+rebalance_all!(tag_range_rebalance_all, tag_range::Priority, 0);
+rebalance_all!(list_range_rebalance_all, list_range::Priority, 1);
+
+/// Compacting a sparse arena numbers its labels consecutively, and it keeps working afterwards.
+macro_rules! compact_labels {
+    ($name:ident, $priority:ty, $hidden:expr) => {
+        #[test]
+        fn $name() {
+            let mut ps = <$priority>::new_sequence(1000);
+            ps.retain({
+                let mut i = 0;
+                move |_| {
+                    i += 1;
+                    i % 10 == 1
+                }
+            });
+
+            let epoch = ps[0].label_epoch();
+            assert_eq!(ps[0].compact_labels(), ps.len() + $hidden);
+            assert_eq!(ps[0].label_epoch(), epoch.next());
+            assert_eq!(ps[0].check_invariants(), Ok(()));
+            let base = ps[0].label_with_epoch().label() - $hidden;
+            for (i, p) in ps.iter().enumerate() {
+                assert_eq!(p.label_with_epoch().label(), base + i + $hidden);
+            }
+
+            // Inserting between compacted priorities relabels them, but keeps their order.
+            for i in 0..ps.len() {
+                let p = ps[i].insert();
+                assert!(ps[i] < p);
+                assert!(ps.get(i + 1).map_or(true, |q| p < *q));
+            }
+            assert_eq!(ps[0].check_invariants(), Ok(()));
+        }
+    };
+}
+
+compact_labels!(tag_range_compact_labels, tag_range::Priority, 0);
+compact_labels!(list_range_compact_labels, list_range::Priority, 1);
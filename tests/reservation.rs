@@ -0,0 +1,126 @@
+//! Integration tests for two-phase insertion.
+
+use order_maintenance::capability::CapacityErrorKind;
+use order_maintenance::{define_priority, list_range, tag_range, InArena, MaintainedOrd};
+
+macro_rules! reservations {
+    ($name:ident, $priority:ty) => {
+        mod $name {
+            use super::*;
+
+            #[test]
+            fn commit_without_relabeling() {
+                let a = <$priority>::new();
+                let b = a.insert();
+                let mut ps = vec![b];
+                for _ in 0..200 {
+                    // Inserting right after `a` over and over runs out of labels there, but only
+                    // reserving relabels.
+                    let r = a.reserve_after();
+                    assert!(*r.anchor() == a);
+                    let epoch = a.label_epoch();
+                    let p = r.commit();
+                    assert_eq!(a.label_epoch(), epoch);
+                    assert!(a < p && p < ps[ps.len() - 1]);
+                    ps.push(p);
+                }
+                assert!(a.label_epoch().get() > 0);
+                assert_eq!(a.check_invariants(), Ok(()));
+            }
+
+            #[test]
+            fn stale_reservation() {
+                let a = <$priority>::new();
+                let b = a.insert();
+                let r = a.reserve_after();
+                // Takes the reserved label.
+                let q = a.insert();
+                let p = r.commit();
+                assert!(a < p && p < q && q < b);
+                assert_eq!(a.check_invariants(), Ok(()));
+            }
+
+            #[test]
+            fn abort() {
+                let a = <$priority>::new();
+                let b = a.insert();
+                let before = a.arena_priorities().len();
+                a.reserve_after().abort();
+                assert_eq!(a.arena_priorities().len(), before);
+                assert!(a.reserve_after().commit() < b);
+            }
+
+            /// Number of labels from `p` up to the next priority in `ps`, in order.
+            fn gap_after(ps: &[$priority], p: &$priority) -> u128 {
+                let i = ps.iter().position(|q| q == p).unwrap();
+                let label = |q: &$priority| q.label_with_epoch().label();
+                label(&ps[i + 1]).wrapping_sub(label(p)) as u128
+            }
+
+            #[test]
+            fn region_kept_free() {
+                let first = <$priority>::new();
+                let mut ps = vec![first.clone()];
+                for _ in 0..100 {
+                    let p = ps.last().unwrap().insert();
+                    ps.push(p);
+                }
+                let anchor = ps[50].clone();
+                let region = anchor.reserve_region_after(34).unwrap();
+                assert!(*region.anchor() == anchor);
+                assert_eq!(region.room(), 34);
+
+                // Crowd the priorities around the anchor, relabeling them over and over.
+                let mut state = 5usize;
+                for i in 0..2000 {
+                    state = state
+                        .wrapping_mul(6364136223846793005)
+                        .wrapping_add(1442695040888963407);
+                    let at = ps.iter().position(|q| *q == anchor).unwrap() + (state >> 33) % 7 - 3;
+                    if ps[at] == anchor {
+                        continue;
+                    }
+                    let p = ps[at].insert();
+                    ps.insert(at + 1, p);
+                    if i % 500 == 0 {
+                        first.rebalance_all();
+                    }
+                }
+                assert!(ps.windows(2).all(|w| w[0] < w[1]));
+                assert_eq!(first.check_invariants(), Ok(()));
+
+                // Rebalancing leaves room for the region's priorities right after the anchor.
+                first.rebalance_all();
+                let room = region.room() as u128;
+                assert!(gap_after(&ps, &anchor) >= room * gap_after(&ps, &ps[1]));
+
+                region.release();
+                first.rebalance_all();
+                assert!(gap_after(&ps, &anchor) <= gap_after(&ps, &ps[1]) + 1);
+                assert_eq!(first.check_invariants(), Ok(()));
+            }
+        }
+    };
+}
+
+define_priority! {
+    /// Tag-range priorities with 16-bit labels.
+    TagRange16: tag_range(threshold = 1.3, bits = 16)
+}
+
+#[test]
+fn region_too_wide() {
+    let a = TagRange16::new();
+    let b = a.insert();
+    let epoch = a.label_epoch();
+    // Room for two thousand priorities is more than the arena holds.
+    let error = a.reserve_region_after(2000).unwrap_err();
+    assert_eq!(error.kind(), CapacityErrorKind::Labels);
+    assert_eq!(a.label_epoch(), epoch);
+    let region = a.reserve_region_after(1).unwrap();
+    assert!(a < b);
+    drop(region);
+}
+
+reservations!(tag_range_reservations, tag_range::Priority);
+reservations!(list_range_reservations, list_range::Priority);
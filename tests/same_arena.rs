@@ -0,0 +1,33 @@
+//! Integration tests for comparisons through `SameArena` witnesses.
+
+use order_maintenance::{list_range, tag_range, MaintainedOrd};
+use std::cmp::Ordering;
+
+/// Witnessed comparisons agree with `partial_cmp`, even across relabeling.
+macro_rules! witnessed_compare {
+    ($name:ident, $priority:ty) => {
+        #[test]
+        fn $name() {
+            let a = <$priority>::new();
+            let b = a.insert();
+            let same = <$priority>::witness_same_arena(&a, &b).unwrap();
+            assert_eq!(same.compare(), Ordering::Less);
+
+            // Relabeling does not invalidate the witness.
+            let ps: Vec<_> = (0..1000).map(|_| a.insert()).collect();
+            assert_eq!(same.compare(), Ordering::Less);
+            for p in &ps {
+                let w = <$priority>::witness_same_arena(p, &b).unwrap();
+                assert_eq!(Some(w.compare()), p.partial_cmp(&b));
+                let w = <$priority>::witness_same_arena(p, p).unwrap();
+                assert_eq!(w.compare(), Ordering::Equal);
+            }
+
+            let other = <$priority>::new();
+            assert!(<$priority>::witness_same_arena(&a, &other).is_none());
+        }
+    };
+}
+
+witnessed_compare!(tag_range_witnessed_compare, tag_range::Priority);
+witnessed_compare!(list_range_witnessed_compare, list_range::Priority);
@@ -0,0 +1,32 @@
+use order_maintenance::{define_priority, list_range, self_test, tag_range};
+
+define_priority! {
+    /// Tag-range priorities with few enough labels to exercise relabeling of the whole arena.
+    TagRange8: tag_range(threshold = 1.3, bits = 8)
+}
+
+define_priority! {
+    /// List-range priorities with 16-bit labels.
+    ListRange16: list_range(bits = 16)
+}
+
+#[test]
+fn self_test_tag_range() {
+    let report = self_test::<tag_range::Priority>();
+    assert!(report.is_ok(), "{:?}", report.problems);
+    assert!(report.checks > 1);
+}
+
+#[test]
+fn self_test_list_range() {
+    let report = self_test::<list_range::Priority>();
+    assert!(report.is_ok(), "{:?}", report.problems);
+}
+
+#[test]
+fn self_test_narrow() {
+    let report = self_test::<TagRange8>();
+    assert!(report.is_ok(), "{:?}", report.problems);
+    let report = self_test::<ListRange16>();
+    assert!(report.is_ok(), "{:?}", report.problems);
+}
@@ -0,0 +1,30 @@
+use order_maintenance::{list_range, sentinel::Bounded, tag_range, MaintainedOrd};
+
+macro_rules! sentinel_tests {
+    ($name:ident, $P:ty) => {
+        #[test]
+        fn $name() {
+            let ps: Vec<Bounded<$P>> = <$P>::new_sequence(20)
+                .into_iter()
+                .map(Bounded::from)
+                .collect();
+            let other = Bounded::from(<$P>::new());
+            for p in ps.iter().chain([&other]) {
+                assert!(<$P>::MIN_SENTINEL < *p);
+                assert!(*p < <$P>::MAX_SENTINEL);
+                assert!(!p.is_sentinel());
+            }
+            assert!(ps.windows(2).all(|w| w[0] < w[1]));
+            assert_eq!(ps[0].partial_cmp(&other), None);
+
+            assert!(<$P>::MIN_SENTINEL < <$P>::MAX_SENTINEL);
+            assert_eq!(<$P>::MIN_SENTINEL, Bounded::Min);
+            assert!(<$P>::MAX_SENTINEL.is_sentinel());
+            assert_eq!(<$P>::MAX_SENTINEL.priority(), None);
+            assert_eq!(ps[3].clone().into_priority().as_ref(), ps[3].priority());
+        }
+    };
+}
+
+sentinel_tests!(tag_range_sentinels, tag_range::Priority);
+sentinel_tests!(list_range_sentinels, list_range::Priority);
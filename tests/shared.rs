@@ -0,0 +1,46 @@
+mod common;
+
+use order_maintenance::{om_delegate, sort_priorities, tag_range, InArena, MaintainedOrd};
+use std::rc::Rc;
+
+type SharedPriority = Rc<tag_range::Priority>;
+
+om_delegate! {
+    SharedPriority => tests [
+        compare_two,
+        insertion,
+        transitive,
+        drop_first,
+        drop_middle,
+        insert_some_begin,
+        insert_some_end,
+        new_sequence,
+        tag_in_order,
+    ]
+}
+
+#[cfg(feature = "naive")]
+#[test]
+fn shared_unique_priorities() {
+    use order_maintenance::naive::UniquePriority;
+
+    let a: Rc<UniquePriority> = MaintainedOrd::new();
+    let c = a.insert();
+    let b = a.insert();
+    let shared = [c.clone(), a.clone(), b.clone()];
+    assert!(a < b && b < c);
+    assert!(shared[0] == c);
+}
+
+#[test]
+fn sort_references_and_shared() {
+    let ps = tag_range::Priority::new_sequence(10);
+    let mut refs: Vec<&tag_range::Priority> = ps.iter().rev().collect();
+    sort_priorities(&mut refs);
+    assert!(refs.iter().zip(&ps).all(|(r, p)| *r == p));
+
+    let mut shared: Vec<SharedPriority> = ps.iter().rev().cloned().map(Rc::new).collect();
+    sort_priorities(&mut shared);
+    assert!(shared.iter().zip(&ps).all(|(s, p)| **s == *p));
+    assert_eq!(shared[3].arena_priorities().len(), 10);
+}
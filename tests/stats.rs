@@ -0,0 +1,60 @@
+//! Integration tests for the `stats` feature.
+#![cfg(feature = "stats")]
+
+use order_maintenance::{list_range, tag_range, MaintainedOrd};
+
+#[test]
+fn tag_range_stats() {
+    let a = tag_range::Priority::new();
+    assert_eq!(a.relabel_stats().passes(), 0);
+
+    // Always inserting after the first priority soon runs out of labels in between.
+    let ps: Vec<_> = (0..1000).map(|_| a.insert()).collect();
+    let stats = ps[0].relabel_stats();
+    assert!(stats.passes() > 0);
+    assert!(stats.p50() <= stats.p95());
+    assert!(stats.p95() <= stats.max());
+    assert!(stats.max() <= 1001);
+    assert_eq!(stats.buckets().iter().sum::<u64>(), stats.passes());
+
+    // Stats are per arena.
+    assert_eq!(tag_range::Priority::new().relabel_stats().passes(), 0);
+}
+
+#[test]
+fn list_range_stats() {
+    let a = list_range::Priority::new();
+    let ps: Vec<_> = (0..1000).map(|_| a.insert()).collect();
+    let stats = ps[999].relabel_stats();
+    assert!(stats.passes() > 0);
+    assert!(stats.relabeled() >= stats.passes());
+    assert_eq!(stats, a.relabel_stats());
+}
+
+#[test]
+fn poll_and_reset() {
+    let a = tag_range::Priority::new();
+    let mut ps: Vec<_> = (0..1000).map(|_| a.insert()).collect();
+    let first = a.poll_relabel_stats();
+    assert!(first.passes() > 0);
+    assert_eq!(first, a.relabel_stats());
+
+    // Polling starts a new window, but keeps the overall statistics.
+    assert_eq!(a.poll_relabel_stats().passes(), 0);
+    ps.extend((0..1000).map(|_| a.insert()));
+    let second = ps[0].poll_relabel_stats();
+    assert!(second.passes() > 0);
+    assert_eq!(first.passes() + second.passes(), a.relabel_stats().passes());
+
+    // Resetting forgets both.
+    ps.extend((0..1000).map(|_| a.insert()));
+    a.reset_relabel_stats();
+    assert_eq!(a.relabel_stats().passes(), 0);
+    assert_eq!(a.poll_relabel_stats().passes(), 0);
+
+    let l = list_range::Priority::new();
+    let ls: Vec<_> = (0..1000).map(|_| l.insert()).collect();
+    assert_eq!(ls[0].poll_relabel_stats(), l.relabel_stats());
+    l.reset_relabel_stats();
+    assert_eq!(l.relabel_stats().passes(), 0);
+}
@@ -0,0 +1,52 @@
+//! Integration tests for switching the relabeling algorithm of an arena.
+
+use order_maintenance::switchable::SwitchablePriority;
+use order_maintenance::workload::Algorithm;
+use order_maintenance::{InArena, MaintainedOrd};
+use rand::{Rng, SeedableRng};
+
+/// Insert after random priorities, checking the order against a model after each insertion.
+fn insert_randomly(ps: &mut Vec<SwitchablePriority>, rng: &mut impl Rng, n: usize) {
+    for _ in 0..n {
+        let i = rng.gen_range(0..ps.len());
+        let p = ps[i].insert();
+        ps.insert(i + 1, p);
+    }
+    assert!(ps.windows(2).all(|w| w[0] < w[1]));
+}
+
+#[test]
+fn switch_keeps_handles() {
+    let mut rng = rand::rngs::StdRng::seed_from_u64(0);
+    let mut ps = vec![SwitchablePriority::new()];
+    assert_eq!(ps[0].algorithm(), Algorithm::TagRange);
+    insert_randomly(&mut ps, &mut rng, 500);
+
+    let relabeled = ps[7].switch_algorithm(Algorithm::ListRange);
+    assert_eq!(relabeled, ps.len() + 1);
+    assert!(ps.iter().all(|p| p.algorithm() == Algorithm::ListRange));
+    assert!(ps.windows(2).all(|w| w[0] < w[1]));
+    insert_randomly(&mut ps, &mut rng, 500);
+
+    assert!(ps[0].switch_algorithm(Algorithm::TagRange) > 0);
+    insert_randomly(&mut ps, &mut rng, 500);
+    assert_eq!(ps[0].arena_priorities().len(), ps.len());
+}
+
+#[test]
+fn switch_to_same_algorithm() {
+    let a = SwitchablePriority::with_algorithm(Algorithm::ListRange);
+    let b = a.insert();
+    assert_eq!(a.switch_algorithm(Algorithm::ListRange), 0);
+    assert!(a < b);
+}
+
+#[test]
+fn arenas_switch_independently() {
+    let a = SwitchablePriority::new();
+    let b = SwitchablePriority::new();
+    a.switch_algorithm(Algorithm::ListRange);
+    assert_eq!(a.insert().algorithm(), Algorithm::ListRange);
+    assert_eq!(b.insert().algorithm(), Algorithm::TagRange);
+    assert_eq!(a.partial_cmp(&b), None);
+}
@@ -0,0 +1,103 @@
+//! Integration tests for append-optimized timelines.
+
+use order_maintenance::{list_range, timeline::Timeline};
+
+#[test]
+fn append_only() {
+    let log: Timeline = Timeline::new();
+    let es: Vec<_> = (0..1000).map(|_| log.append()).collect();
+    assert_eq!(log.pending(), 1000);
+
+    for i in 0..es.len() - 1 {
+        assert!(es[i] < es[i + 1], "es[{}] < es[{}]", i, i + 1);
+    }
+}
+
+#[test]
+fn insert_into_run() {
+    let log: Timeline = Timeline::new();
+    let mut es: Vec<_> = (0..100).map(|_| log.append()).collect();
+
+    let e = log.insert_after(&es[49]);
+    es.insert(50, e);
+    assert_eq!(log.pending(), 0);
+
+    for _ in 0..100 {
+        es.push(log.append());
+    }
+
+    for i in 0..es.len() - 1 {
+        assert!(es[i] < es[i + 1], "es[{}] < es[{}]", i, i + 1);
+    }
+}
+
+#[test]
+fn insert_after_tail_keeps_run() {
+    let log: Timeline = Timeline::new();
+    let e0 = log.insert_front();
+    let es: Vec<_> = (0..100).map(|_| log.append()).collect();
+
+    // The new entry goes between the tail and the run, without labeling the run.
+    let e1 = log.insert_after(&e0);
+    assert_eq!(log.pending(), 100);
+    assert!(e0 < e1 && e1 < es[0]);
+
+    // Labeling the run later still places it after the new entry.
+    let e = log.insert_after(&es[49]);
+    assert_eq!(log.pending(), 0);
+    assert!(e1 < es[0] && es[49] < e && e < es[50]);
+    for i in 0..es.len() - 1 {
+        assert!(es[i] < es[i + 1], "es[{}] < es[{}]", i, i + 1);
+    }
+}
+
+#[test]
+fn insert_front() {
+    let log: Timeline<list_range::Priority> = Timeline::new();
+    let e1 = log.append();
+    let e0 = log.insert_front();
+    let e2 = log.append();
+    let e3 = log.insert_after(&e2);
+
+    assert!(e0 < e1);
+    assert!(e1 < e2);
+    assert!(e2 < e3);
+}
+
+#[test]
+fn mixed_with_drops() {
+    use rand::{rngs::StdRng, Rng, SeedableRng};
+    let mut rng = StdRng::seed_from_u64(42);
+    let log: Timeline = Timeline::new();
+    let mut es = vec![log.append()];
+
+    for _ in 0..2000 {
+        if rng.gen_bool(0.9) {
+            es.push(log.append());
+        } else if rng.gen_bool(0.5) {
+            es.remove(rng.gen_range(0..es.len()));
+            if es.is_empty() {
+                es.push(log.append());
+            }
+        } else {
+            let i = rng.gen_range(0..es.len());
+            let e = log.insert_after(&es[i]);
+            es.insert(i + 1, e);
+        }
+    }
+
+    for i in 0..es.len() - 1 {
+        assert!(es[i] < es[i + 1], "es[{}] < es[{}]", i, i + 1);
+    }
+}
+
+#[test]
+fn different_timelines() {
+    let l1: Timeline = Timeline::new();
+    let l2: Timeline = Timeline::new();
+    let e1 = l1.append();
+    let e2 = l2.append();
+
+    assert_eq!(e1.partial_cmp(&e2), None);
+    assert_eq!(e1, e1.clone());
+}
@@ -0,0 +1,45 @@
+//! Integration tests for the `track-alloc` feature.
+#![cfg(feature = "track-alloc")]
+
+use order_maintenance::track_alloc::{set_alloc_hook, AllocEvent};
+use order_maintenance::{tag_range::Priority, MaintainedOrd};
+use std::cell::RefCell;
+
+std::thread_local! {
+    static EVENTS: RefCell<Vec<AllocEvent>> = const { RefCell::new(Vec::new()) };
+}
+
+fn record(event: AllocEvent) {
+    EVENTS.with(|events| events.borrow_mut().push(event));
+}
+
+#[test]
+fn reports_allocations() {
+    set_alloc_hook(Some(record));
+    {
+        let a = Priority::new();
+        let ps: Vec<_> = (0..10).map(|_| a.insert()).collect();
+        drop(ps);
+    }
+    set_alloc_hook(None);
+    let _ = Priority::new();
+
+    let events = EVENTS.with(|events| events.take());
+    let count = |e: AllocEvent| events.iter().filter(|&&x| x == e).count();
+    assert_eq!(count(AllocEvent::NodeAlloc), 11);
+    assert_eq!(count(AllocEvent::NodeFree), 11);
+
+    let grows: Vec<_> = events
+        .iter()
+        .filter_map(|e| match *e {
+            AllocEvent::SlabGrow {
+                old_capacity,
+                new_capacity,
+            } => Some((old_capacity, new_capacity)),
+            _ => None,
+        })
+        .collect();
+    assert!(!grows.is_empty());
+    assert!(grows.iter().all(|(old, new)| old < new));
+    assert!(grows.windows(2).all(|w| w[0].1 == w[1].0));
+}
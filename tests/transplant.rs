@@ -0,0 +1,53 @@
+use order_maintenance::{list_range, tag_range, MaintainedOrd};
+
+macro_rules! transplant_tests {
+    ($name:ident, $P:ty, $Q:ty) => {
+        #[test]
+        fn $name() {
+            let ps = <$P>::new_sequence(100);
+
+            // Migrate the upper half of the arena, one priority at a time.
+            let mut anchor = (ps[50].clone(), <$Q>::new());
+            let mut migrated = vec![anchor.1.clone()];
+            for p in &ps[51..] {
+                let q = p.try_clone_into(&anchor.0, &anchor.1).unwrap();
+                anchor = (p.clone(), q.clone());
+                migrated.push(q);
+            }
+            assert_eq!(migrated.len(), 50);
+            assert!(migrated.windows(2).all(|w| w[0] < w[1]));
+
+            // The correspondence point must not come after the transplanted priority.
+            let q = ps[0].try_clone_into(&ps[0], &migrated[3]).unwrap();
+            assert!(q == migrated[3]);
+            assert!(ps[0].try_clone_into(&ps[1], &migrated[0]).is_none());
+            assert!(ps[0].try_clone_into(&<$P>::new(), &migrated[0]).is_none());
+
+            // Priorities several places after the correspondence point keep their distance from
+            // it, as far as the other arena allows.
+            let qs = <$Q>::new_sequence(10);
+            let q = ps[3].try_clone_into(&ps[0], &qs[1]).unwrap();
+            assert!(qs[3] < q && q < qs[4]);
+            let q = ps[6].try_clone_into(&ps[2], &qs[4]).unwrap();
+            assert!(qs[7] < q && q < qs[8]);
+            let q = ps[60].try_clone_into(&ps[2], &qs[5]).unwrap();
+            assert!(qs[9] < q);
+        }
+    };
+}
+
+transplant_tests!(
+    tag_range_transplant,
+    tag_range::Priority,
+    tag_range::Priority
+);
+transplant_tests!(
+    list_range_transplant,
+    list_range::Priority,
+    list_range::Priority
+);
+transplant_tests!(
+    tag_to_list_transplant,
+    tag_range::Priority,
+    list_range::Priority
+);
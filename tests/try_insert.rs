@@ -0,0 +1,93 @@
+//! Integration tests for fallible insertion.
+
+use order_maintenance::capability::{TryInsert, TryInsertFront};
+use order_maintenance::{define_priority, list_range, tag_range, MaintainedOrd};
+use std::rc::Rc;
+
+define_priority! {
+    /// Tag-range priorities with 6-bit labels.
+    TagRange6: tag_range(threshold = 1.3, bits = 6)
+}
+
+define_priority! {
+    /// List-range priorities with 6-bit labels.
+    ListRange6: list_range(bits = 6)
+}
+
+/// Insert after random priorities until the arena runs out of labels, then check that the arena
+/// was left as is, and still accepts insertions once priorities are dropped.
+macro_rules! exhaust {
+    ($name:ident, $priority:ty) => {
+        #[test]
+        fn $name() {
+            let first = <$priority>::new();
+            let mut ps = vec![first.clone()];
+            let mut state = 3usize;
+            let error = loop {
+                state = state
+                    .wrapping_mul(6364136223846793005)
+                    .wrapping_add(1442695040888963407);
+                match ps[(state >> 33) % ps.len()].try_insert() {
+                    Ok(p) => ps.push(p),
+                    Err(e) => break e,
+                }
+            };
+            assert!(error.live() >= ps.len());
+            assert!(error
+                .to_string()
+                .starts_with("Too many priorities were inserted"));
+            assert_eq!(first.check_invariants(), Ok(()));
+            ps.sort_by(|a, b| a.partial_cmp(b).unwrap());
+            assert!(ps.windows(2).all(|w| w[0] < w[1]));
+
+            ps.truncate(ps.len() / 2);
+            let p = ps[0].try_insert().unwrap();
+            assert!(ps[0] < p);
+        }
+    };
+}
+
+exhaust!(tag_range_exhaust, TagRange6);
+exhaust!(list_range_exhaust, ListRange6);
+
+/// Insert at the front until the arena runs out of labels, then check that the arena was left as
+/// is.
+macro_rules! exhaust_front {
+    ($name:ident, $priority:ty) => {
+        #[test]
+        fn $name() {
+            let last = <$priority>::new();
+            let mut ps = vec![last.clone()];
+            let error = loop {
+                match last.try_insert_front() {
+                    Ok(p) => ps.push(p),
+                    Err(e) => break e,
+                }
+            };
+            assert!(error.live() >= ps.len());
+            assert_eq!(last.check_invariants(), Ok(()));
+            assert!(ps.windows(2).all(|w| w[1] < w[0]));
+        }
+    };
+}
+
+exhaust_front!(tag_range_exhaust_front, TagRange6);
+exhaust_front!(list_range_exhaust_front, ListRange6);
+
+#[test]
+fn ample_capacity() {
+    let a = tag_range::Priority::new();
+    let b = a.try_insert().unwrap();
+    assert!(a < b);
+
+    let a = Rc::new(list_range::Priority::new());
+    let b = a.try_insert().unwrap();
+    assert!(a < b);
+}
+
+#[test]
+#[should_panic(expected = "Too many priorities were inserted")]
+fn list_range_insert_panics() {
+    let first = ListRange6::new();
+    let _ps: Vec<_> = (0..64).map(|_| first.insert()).collect();
+}
@@ -0,0 +1,34 @@
+//! Integration tests for read-only views of priorities.
+
+use order_maintenance::view::OrderView;
+use order_maintenance::{list_range, tag_range, InArena, MaintainedOrd};
+
+/// A "plugin" that can only query the order it is given.
+fn rank<P: InArena>(view: &OrderView<P>) -> usize {
+    view.arena_views().iter().filter(|v| *v < view).count()
+}
+
+macro_rules! view_tests {
+    ($name:ident, $priority:ty) => {
+        #[test]
+        fn $name() {
+            let ps = <$priority>::new_sequence(10);
+            let views: Vec<_> = ps.iter().map(OrderView::of).collect();
+            for (i, (p, v)) in ps.iter().zip(&views).enumerate() {
+                assert_eq!(rank(v), i);
+                assert!(v.is(p));
+                assert_eq!(v.partial_cmp_priority(&ps[5]), i.partial_cmp(&5));
+            }
+
+            // Views keep the priorities they refer to alive, and only those.
+            let kept = views[3].clone();
+            drop(views);
+            drop(ps);
+            assert_eq!(kept.arena_views().len(), 1);
+            assert!(kept.arena_views()[0] == kept);
+        }
+    };
+}
+
+view_tests!(tag_range_views, tag_range::Priority);
+view_tests!(list_range_views, list_range::Priority);
@@ -0,0 +1,136 @@
+use order_maintenance::workload::{
+    load, minimize, parse, run, save, simulate, Algorithm, ParseError, Step,
+};
+use order_maintenance::{define_priority, list_range, tag_range};
+use std::panic::{catch_unwind, AssertUnwindSafe};
+
+define_priority! {
+    /// Tag-range priorities with so few labels that they quickly run out.
+    TagRange4: tag_range(threshold = 1.3, bits = 4)
+}
+
+#[test]
+fn run_skips_invalid_steps() {
+    let script = [
+        Step::Insert(0),
+        Step::Insert(5),
+        Step::Drop(0),
+        Step::Drop(0),
+        Step::Drop(3),
+    ];
+    let ps = run::<tag_range::Priority>(&script);
+    assert_eq!(ps.len(), 1);
+}
+
+#[test]
+fn minimize_to_culprits() {
+    let script: Vec<usize> = (0..100).collect();
+    let fails = |s: &[usize]| s.contains(&3) && s.contains(&71);
+    assert_eq!(minimize(&script, fails), [3, 71]);
+
+    // Scripts that do not fail are left as is.
+    assert_eq!(minimize(&script, |s| s.len() > 100), script);
+    // Scripts that fail even without any steps minimize to no steps.
+    assert_eq!(minimize(&script, |_| true), []);
+}
+
+#[test]
+fn minimize_capacity_panic() {
+    let script: Vec<Step> = (0..200)
+        .map(|i| match i % 3 {
+            2 => Step::Drop(i % 5),
+            _ => Step::Insert(i % 7),
+        })
+        .collect();
+    let fails = |s: &[Step]| catch_unwind(AssertUnwindSafe(|| run::<TagRange4>(s))).is_err();
+    assert!(fails(&script));
+
+    let minimal = minimize(&script, fails);
+    assert!(fails(&minimal));
+    // Deleting any single step avoids the panic.
+    for i in 0..minimal.len() {
+        let mut shorter = minimal.clone();
+        shorter.remove(i);
+        assert!(!fails(&shorter));
+    }
+}
+
+#[test]
+fn simulate_matches_priorities() {
+    let script: Vec<Step> = (0..5000)
+        .map(|i| match i % 4 {
+            3 => Step::Drop(i % 11),
+            _ => Step::Insert(i % 13),
+        })
+        .collect();
+    // Count the priorities alive after each step, skipping invalid steps like `run()`.
+    let (mut live, mut inserts, mut peak_live) = (1, 0, 1);
+    for &step in &script {
+        match step {
+            Step::Insert(i) if i < live => {
+                live += 1;
+                inserts += 1;
+            }
+            Step::Drop(i) if i < live && live > 1 => live -= 1,
+            _ => (),
+        }
+        peak_live = peak_live.max(live);
+    }
+
+    let report = simulate(&script, Algorithm::TagRange);
+    let ps = run::<tag_range::Priority>(&script);
+    assert_eq!(ps.len(), live);
+    assert_eq!(report.relabels, ps[0].label_epoch().get());
+    assert_eq!(report.inserts, inserts);
+    assert_eq!(report.peak_live, peak_live);
+    assert!(report.relabeled >= report.relabels);
+    assert!(report.peak_bytes > 0);
+
+    let report = simulate(&script, Algorithm::ListRange);
+    let ps = run::<list_range::Priority>(&script);
+    assert_eq!(report.relabels, ps[0].label_epoch().get());
+    assert_eq!(report.peak_live, peak_live);
+}
+
+#[test]
+fn save_and_load() {
+    let script = [
+        Step::Insert(0),
+        Step::Insert(1),
+        Step::Drop(0),
+        Step::Insert(7),
+    ];
+    let path = std::path::Path::new(env!("CARGO_TARGET_TMPDIR")).join("save_and_load.workload");
+    save(&path, &script).unwrap();
+    assert_eq!(load(&path).unwrap(), script);
+
+    std::fs::write(&path, "insert 0\n# fine\nremove 0\n").unwrap();
+    let err = load(&path).unwrap_err();
+    assert_eq!(err.kind(), std::io::ErrorKind::InvalidData);
+    assert_eq!(
+        parse("insert 0\n# fine\nremove 0\n"),
+        Err(ParseError { line: 3 })
+    );
+    assert_eq!(parse("drop -1"), Err(ParseError { line: 1 }));
+    assert_eq!(parse("insert"), Err(ParseError { line: 1 }));
+}
+
+#[test]
+fn replay_regressions() {
+    let dir = std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/workloads");
+    let mut replayed = 0;
+    for entry in std::fs::read_dir(dir).unwrap() {
+        let path = entry.unwrap().path();
+        if path.extension().is_none_or(|ext| ext != "workload") {
+            continue;
+        }
+        let script = load(&path).unwrap();
+        let tags = run::<tag_range::Priority>(&script);
+        let lists = run::<list_range::Priority>(&script);
+        assert!(tags.windows(2).all(|w| w[0] < w[1]), "{}", path.display());
+        assert!(lists.windows(2).all(|w| w[0] < w[1]), "{}", path.display());
+        assert_eq!(tags.len(), lists.len());
+        replayed += 1;
+    }
+    assert!(replayed > 0);
+}